@@ -0,0 +1,299 @@
+use crate::{write_grid, MatrixError};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// A grid like `DataGrid`, but with its dimensions fixed at compile time and its values stored in
+/// arrays, so that creating, copying, and updating it never allocates.
+///
+/// `W` is the width (number of columns) and `H` is the height (number of rows). Both must be at
+/// least 1.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct FixedGrid<T, const W: usize, const H: usize>
+where
+    T: Copy,
+{
+    values: [[T; W]; H],
+}
+
+impl<T: Copy, const W: usize, const H: usize> FixedGrid<T, W, H> {
+    /// Stops grids without any cells from compiling, matching `DataGrid`, which can't be empty.
+    const NOT_EMPTY: () = assert!(W > 0 && H > 0, "a grid must be at least 1x1");
+
+    /// Creates a new matrix and initializes all elements with the given initial value.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_value` - The initial value to fill the matrix with.
+    pub fn new(initial_value: T) -> FixedGrid<T, W, H> {
+        #[allow(clippy::let_unit_value)] // evaluating the constant is what performs the check
+        let () = Self::NOT_EMPTY;
+        FixedGrid {
+            values: [[initial_value; W]; H],
+        }
+    }
+
+    /// Gets a row from the matrix by its index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the row to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option<[T; W]>` containing the row's elements, or `None` if the index is out of bounds.
+    pub fn get_row(&self, index: usize) -> Option<[T; W]> {
+        self.values.get(index).copied()
+    }
+
+    /// Gets a column from the matrix by its index. The item in the top row of the matrix is in the
+    /// front of the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the column to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option<[T; H]>` containing the column's elements, or `None` if the index is out of bounds.
+    pub fn get_column(&self, index: usize) -> Option<[T; H]> {
+        (index < W).then(|| self.values.map(|row| row[index]))
+    }
+
+    /// Updates a row in the matrix with the provided data.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the row to update.
+    /// * `data` - The new data to replace the row with.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the update was successful, or an `Err(MatrixError)` with a description of the error otherwise.
+    pub fn update_row(&mut self, index: usize, data: [T; W]) -> Result<(), MatrixError> {
+        *self
+            .values
+            .get_mut(index)
+            .ok_or(MatrixError::IndexNotFound)? = data;
+        Ok(())
+    }
+
+    /// Updates a column in the matrix with the provided data.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the column to update.
+    /// * `data` - The new data to replace the column with, top first.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the update was successful, or an `Err(MatrixError)` with a description of the error otherwise.
+    pub fn update_column(&mut self, index: usize, data: [T; H]) -> Result<(), MatrixError> {
+        if index >= W {
+            return Err(MatrixError::IndexNotFound);
+        }
+
+        for (row, value) in self.values.iter_mut().zip(data) {
+            row[index] = value;
+        }
+
+        Ok(())
+    }
+
+    /// Updates a single position in the matrix with the provided value.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row index of the position to update.
+    /// * `column` - The column index of the position to update.
+    /// * `value` - The new value to set at the specified position.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the update was successful, or an `Err(MatrixError)` with a description of the error otherwise.
+    pub fn update_single_position(
+        &mut self,
+        row: usize,
+        column: usize,
+        value: T,
+    ) -> Result<(), MatrixError> {
+        *(self
+            .values
+            .get_mut(row)
+            .ok_or(MatrixError::IndexNotFound)?
+            .get_mut(column)
+            .ok_or(MatrixError::IndexNotFound)?) = value;
+        Ok(())
+    }
+
+    /// Transpose the FixedGrid, converting columns into rows.
+    ///
+    /// # Returns
+    ///
+    /// A new FixedGrid representing the transposed data, with the width and height swapped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use data_grid::FixedGrid;
+    /// let grid: FixedGrid<i32, 3, 2> = FixedGrid::try_from(vec![vec![1, 2, 3], vec![1, 2, 3]]).unwrap();
+    /// let transposed_grid: FixedGrid<i32, 2, 3> = grid.transpose();
+    ///
+    /// assert!(transposed_grid == FixedGrid::try_from(vec![vec![1, 1], vec![2, 2], vec![3, 3]]).unwrap());
+    /// ```
+    pub fn transpose(&self) -> FixedGrid<T, H, W> {
+        FixedGrid {
+            values: std::array::from_fn(|column| self.values.map(|row| row[column])),
+        }
+    }
+
+    /// Returns an immutable iterator over the rows in the FixedGrid.
+    ///
+    /// To iterate over columns, call `grid.transpose().iter_rows()`.
+    ///
+    /// # Returns
+    ///
+    /// An iterator that yields references to rows as `&[T; W]`.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T; W]> {
+        self.values.iter()
+    }
+
+    /// Gets the height (number of rows) of the matrix.
+    ///
+    /// # Returns
+    ///
+    /// Returns the height of the matrix as a `usize` value.
+    pub const fn get_height(&self) -> usize {
+        H
+    }
+
+    /// Gets the width (number of columns) of the matrix.
+    ///
+    /// # Returns
+    ///
+    /// Returns the width of the matrix as a `usize` value.
+    pub const fn get_width(&self) -> usize {
+        W
+    }
+
+    // get data in grid immutably, like DataGrid::get_values
+    pub fn get_values(&self) -> &[[T; W]; H] {
+        &self.values
+    }
+}
+
+impl<T: Copy, const W: usize, const H: usize> TryFrom<Vec<Vec<T>>> for FixedGrid<T, W, H> {
+    type Error = MatrixError;
+
+    fn try_from(value: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+        #[allow(clippy::let_unit_value)] // evaluating the constant is what performs the check
+        let () = Self::NOT_EMPTY;
+        if value.len() != H {
+            return Err(MatrixError::InvalidDataLength(
+                "Input data length is not equal to matrix height!".to_string(),
+            ));
+        }
+        if value.iter().any(|row| row.len() != W) {
+            return Err(MatrixError::InvalidDataLength(
+                "Input data length is not equal to matrix width!".to_string(),
+            ));
+        }
+
+        Ok(FixedGrid {
+            values: std::array::from_fn(|y| std::array::from_fn(|x| value[y][x])),
+        })
+    }
+}
+
+impl<T: Copy, const W: usize, const H: usize> From<[[T; W]; H]> for FixedGrid<T, W, H> {
+    fn from(values: [[T; W]; H]) -> Self {
+        #[allow(clippy::let_unit_value)] // evaluating the constant is what performs the check
+        let () = Self::NOT_EMPTY;
+        FixedGrid { values }
+    }
+}
+
+impl<T, const W: usize, const H: usize> Display for FixedGrid<T, W, H>
+where
+    T: Copy,
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_grid(f, &self.values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataGrid;
+
+    #[test]
+    fn create_new() {
+        let expected = FixedGrid {
+            values: [[0; 4]; 4],
+        };
+        let actual = FixedGrid::new(0);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn get_row_and_column() {
+        let grid: FixedGrid<i32, 3, 2> = FixedGrid::from([[1, 2, 3], [4, 5, 6]]);
+
+        assert_eq!(Some([4, 5, 6]), grid.get_row(1));
+        assert_eq!(None, grid.get_row(2));
+        assert_eq!(Some([2, 5]), grid.get_column(1));
+        assert_eq!(None, grid.get_column(3));
+    }
+
+    #[test]
+    fn update_row_and_column() {
+        let mut grid: FixedGrid<i32, 3, 3> = FixedGrid::new(0);
+
+        assert_eq!(Ok(()), grid.update_row(1, [1, 1, 1]));
+        assert_eq!(Ok(()), grid.update_column(2, [2, 2, 2]));
+        assert_eq!(
+            Err(MatrixError::IndexNotFound),
+            grid.update_row(3, [1, 1, 1])
+        );
+        assert_eq!(
+            Err(MatrixError::IndexNotFound),
+            grid.update_column(3, [1, 1, 1])
+        );
+        assert_eq!(&[[0, 0, 2], [1, 1, 2], [0, 0, 2]], grid.get_values());
+    }
+
+    #[test]
+    fn update_single_position_out_of_bounds() {
+        let mut grid: FixedGrid<i32, 2, 2> = FixedGrid::new(0);
+
+        assert_eq!(Ok(()), grid.update_single_position(1, 0, 5));
+        assert_eq!(
+            Err(MatrixError::IndexNotFound),
+            grid.update_single_position(0, 2, 5)
+        );
+        assert_eq!(Some([5, 0]), grid.get_row(1));
+    }
+
+    #[test]
+    fn try_from_checks_dimensions() {
+        assert!(FixedGrid::<i32, 2, 2>::try_from(vec![vec![1, 2], vec![3, 4]]).is_ok());
+        assert!(matches!(
+            FixedGrid::<i32, 2, 2>::try_from(vec![vec![1, 2]]),
+            Err(MatrixError::InvalidDataLength(_))
+        ));
+        assert!(matches!(
+            FixedGrid::<i32, 2, 2>::try_from(vec![vec![1, 2], vec![3]]),
+            Err(MatrixError::InvalidDataLength(_))
+        ));
+    }
+
+    #[test]
+    fn displays_like_data_grid() {
+        let rows = vec![vec![1, 20], vec![300, 4]];
+        let fixed: FixedGrid<i32, 2, 2> = FixedGrid::try_from(rows.clone()).unwrap();
+        let dynamic = DataGrid::try_from(rows).unwrap();
+
+        assert_eq!(dynamic.to_string(), fixed.to_string());
+    }
+}
@@ -2,6 +2,12 @@ use std::cmp;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
+mod fixed;
+#[cfg(feature = "rayon")]
+mod parallel;
+
+pub use fixed::FixedGrid;
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct DataGrid<T>
 where
@@ -164,10 +170,7 @@ impl<T: Clone> DataGrid<T> {
         // By getting each column, we can store those as the rows in the new data grid, getting transposition for free.
         // See the implementation of get_column for context.
         let rows: Vec<Vec<T>> = (0..self.get_width())
-            .map(|col_index| match self.get_column(col_index) {
-                Some(item) => item,
-                None => Vec::new(),
-            })
+            .map(|col_index| self.get_column(col_index).unwrap_or_default())
             .collect();
 
         DataGrid { values: rows }
@@ -253,137 +256,135 @@ where
     T: Display,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let max_item_length = self.values.iter().fold(0usize, |max_row_len, vec| {
-            cmp::max(
-                max_row_len,
-                vec.iter().fold(0usize, |max_item_len, item| {
-                    cmp::max(max_item_len, item.to_string().len())
-                }),
-            )
-        });
+        write_grid(f, &self.values)
+    }
+}
 
-        let cell_width = max_item_length + 2; // add two for a space on each side
-        let grid_width = self.values[0].len();
+/// Draws a grid's rows as a table with box-drawing borders, for the grids' `Display` impls.
+///
+/// # Arguments
+///
+/// * `f` - The formatter to write to.
+/// * `rows` - The rows of the grid. There must be at least one, and they must all be the same
+///   length.
+///
+/// # Returns
+///
+/// Returns a `fmt::Result` that indicates success or failure.
+fn write_grid<T: Display, R: AsRef<[T]>>(f: &mut Formatter<'_>, rows: &[R]) -> fmt::Result {
+    let max_item_length = rows.iter().fold(0usize, |max_row_len, row| {
+        cmp::max(
+            max_row_len,
+            row.as_ref().iter().fold(0usize, |max_item_len, item| {
+                cmp::max(max_item_len, item.to_string().len())
+            }),
+        )
+    });
 
-        // write top border
-        write!(
-            f,
-            "{}",
-            DataGrid::<T>::create_constant_row(grid_width, cell_width, '┌', '┬', '┐', '─').as_str()
-        )?;
+    let cell_width = max_item_length + 2; // add two for a space on each side
+    let grid_width = rows[0].as_ref().len();
 
-        let inner_rows = self
-            .values
-            .iter()
-            .map(|current_row| {
-                // write blank lines above row
-                // let num_blank_lines_above = (cell_width - 1) / 2; // subtract 1 for row where text is
-                let num_blank_lines_above = 1;
-
-                let mut string = "".to_string();
-
-                for _ in 0..num_blank_lines_above {
-                    string += DataGrid::<T>::create_constant_row(
-                        grid_width, cell_width, '│', '│', '│', ' ',
-                    )
-                    .as_str()
-                }
-
-                // write row
-                string += format!(
-                    "│{}│\n",
-                    current_row
-                        .iter()
-                        .map(|item| {
-                            let spaces_before = (cell_width - item.to_string().len()) / 2;
-                            let spaces_after =
-                                (cell_width - item.to_string().len()) - spaces_before; // subtract here because spaces_before and spaces_after aren't equal if cell_width - item length is odd, and want all cells to be consistent width
-                            format!(
-                                "{}{}{}",
-                                " ".repeat(spaces_before),
-                                item,
-                                " ".repeat(spaces_after)
-                            )
-                        })
-                        .collect::<Vec<String>>()
-                        .join("│")
-                )
-                .as_str();
-
-                // write blank lines below row
-                // let num_blank_lines_below = (cell_width - 1) - num_blank_lines_above; // subtract here for the same reason as above
-                let num_blank_lines_below = 1;
-                for _ in 0..num_blank_lines_below {
-                    string += DataGrid::<T>::create_constant_row(
-                        grid_width, cell_width, '│', '│', '│', ' ',
-                    )
-                    .as_str()
-                }
-
-                string
-            })
-            .collect::<Vec<String>>()
-            .join(
-                DataGrid::<T>::create_constant_row(grid_width, cell_width, '├', '┼', '┤', '─')
-                    .as_str(),
-            );
+    // write top border
+    write!(
+        f,
+        "{}",
+        create_constant_row(grid_width, cell_width, '┌', '┬', '┐', '─').as_str()
+    )?;
 
-        write!(f, "{}", inner_rows)?;
+    let inner_rows = rows
+        .iter()
+        .map(|current_row| {
+            // write blank lines above row
+            // let num_blank_lines_above = (cell_width - 1) / 2; // subtract 1 for row where text is
+            let num_blank_lines_above = 1;
 
-        // write bottom border
-        write!(
-            f,
-            "{}",
-            DataGrid::<T>::create_constant_row(grid_width, cell_width, '└', '┴', '┘', '─')
-        )?;
+            let mut string = "".to_string();
 
-        Ok(())
-    }
+            for _ in 0..num_blank_lines_above {
+                string += create_constant_row(grid_width, cell_width, '│', '│', '│', ' ').as_str()
+            }
+
+            // write row
+            string += format!(
+                "│{}│\n",
+                current_row
+                    .as_ref()
+                    .iter()
+                    .map(|item| {
+                        let spaces_before = (cell_width - item.to_string().len()) / 2;
+                        let spaces_after = (cell_width - item.to_string().len()) - spaces_before; // subtract here because spaces_before and spaces_after aren't equal if cell_width - item length is odd, and want all cells to be consistent width
+                        format!(
+                            "{}{}{}",
+                            " ".repeat(spaces_before),
+                            item,
+                            " ".repeat(spaces_after)
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join("│")
+            )
+            .as_str();
+
+            // write blank lines below row
+            // let num_blank_lines_below = (cell_width - 1) - num_blank_lines_above; // subtract here for the same reason as above
+            let num_blank_lines_below = 1;
+            for _ in 0..num_blank_lines_below {
+                string += create_constant_row(grid_width, cell_width, '│', '│', '│', ' ').as_str()
+            }
+
+            string
+        })
+        .collect::<Vec<String>>()
+        .join(create_constant_row(grid_width, cell_width, '├', '┼', '┤', '─').as_str());
+
+    write!(f, "{}", inner_rows)?;
+
+    // write bottom border
+    write!(
+        f,
+        "{}",
+        create_constant_row(grid_width, cell_width, '└', '┴', '┘', '─')
+    )?;
+
+    Ok(())
 }
 
-// utility functions for display trait
-impl<T> DataGrid<T>
-where
-    T: Clone,
-    T: Display,
-{
-    /// Creates a constant row of text for the grid with specified formatting.
-    ///
-    /// This function generates a row of text with a specified number of cells, each cell having a
-    /// specified width and containing the same filler character. The row is formatted with opening,
-    /// joining, and closing characters.
-    ///
-    /// # Arguments
-    ///
-    /// - `number_of_cells`: The number of cells in the row.
-    /// - `cell_width`: The width of each cell, including spaces.
-    /// - `opening_char`: The character used at the beginning of the row.
-    /// - `joining_char`: The character used to join cells within the row.
-    /// - `closing_char`: The character used at the end of the row.
-    /// - `filler_char`: The character used to fill each cell.
-    ///
-    /// # Returns
-    ///
-    /// A `String` containing the generated row of text.
-    ///
-    fn create_constant_row(
-        number_of_cells: usize,
-        cell_width: usize,
-        opening_char: char,
-        joining_char: char,
-        closing_char: char,
-        filler_char: char,
-    ) -> String {
-        format!(
-            "{}{}{}\n",
-            opening_char,
-            (0..number_of_cells)
-                .map(|_| filler_char.to_string().repeat(cell_width))
-                .collect::<Vec<String>>()
-                .join(joining_char.to_string().as_str()),
-            closing_char
-        )
-    }
+/// Creates a constant row of text for the grid with specified formatting.
+///
+/// This function generates a row of text with a specified number of cells, each cell having a
+/// specified width and containing the same filler character. The row is formatted with opening,
+/// joining, and closing characters.
+///
+/// # Arguments
+///
+/// - `number_of_cells`: The number of cells in the row.
+/// - `cell_width`: The width of each cell, including spaces.
+/// - `opening_char`: The character used at the beginning of the row.
+/// - `joining_char`: The character used to join cells within the row.
+/// - `closing_char`: The character used at the end of the row.
+/// - `filler_char`: The character used to fill each cell.
+///
+/// # Returns
+///
+/// A `String` containing the generated row of text.
+///
+fn create_constant_row(
+    number_of_cells: usize,
+    cell_width: usize,
+    opening_char: char,
+    joining_char: char,
+    closing_char: char,
+    filler_char: char,
+) -> String {
+    format!(
+        "{}{}{}\n",
+        opening_char,
+        (0..number_of_cells)
+            .map(|_| filler_char.to_string().repeat(cell_width))
+            .collect::<Vec<String>>()
+            .join(joining_char.to_string().as_str()),
+        closing_char
+    )
 }
 
 #[cfg(test)]
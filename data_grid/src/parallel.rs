@@ -0,0 +1,104 @@
+use crate::DataGrid;
+use rayon::prelude::*;
+
+impl<T: Clone + Send + Sync> DataGrid<T> {
+    /// Returns a parallel iterator over the rows in the DataGrid, like `iter_rows` but spread
+    /// across rayon's thread pool.
+    ///
+    /// # Returns
+    ///
+    /// A parallel iterator that yields references to rows as `&Vec<T>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use data_grid::DataGrid;
+    /// use rayon::prelude::*;
+    /// let grid: DataGrid<i32> = DataGrid::try_from(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    /// let row_sums: Vec<i32> = grid.par_iter_rows().map(|row| row.iter().sum()).collect();
+    ///
+    /// assert_eq!(vec![6, 15], row_sums);
+    /// ```
+    pub fn par_iter_rows(&self) -> impl IndexedParallelIterator<Item = &Vec<T>> {
+        self.values.par_iter()
+    }
+
+    /// Creates a new grid with the same dimensions by applying a function to every element, with
+    /// the rows processed in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function to apply to each element.
+    ///
+    /// # Returns
+    ///
+    /// A new DataGrid holding the results, in the same positions as their inputs.
+    pub fn par_map<U, F>(&self, f: F) -> DataGrid<U>
+    where
+        U: Clone + Send,
+        F: Fn(&T) -> U + Sync,
+    {
+        DataGrid {
+            values: self
+                .values
+                .par_iter()
+                .map(|row| row.iter().map(&f).collect())
+                .collect(),
+        }
+    }
+
+    /// Transposes the DataGrid like `transpose`, building the new rows in parallel.
+    ///
+    /// # Returns
+    ///
+    /// A new DataGrid representing the transposed data.
+    pub fn par_transpose(&self) -> DataGrid<T> {
+        DataGrid {
+            values: (0..self.get_width())
+                .into_par_iter()
+                .map(|column| self.values.iter().map(|row| row[column].clone()).collect())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn large_grid() -> DataGrid<usize> {
+        DataGrid::try_from(
+            (0..1000)
+                .map(|y| (0..1000).map(|x| y * 1000 + x).collect())
+                .collect::<Vec<Vec<usize>>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn par_transpose_matches_transpose() {
+        let grid = large_grid();
+
+        assert_eq!(grid.transpose(), grid.par_transpose());
+    }
+
+    #[test]
+    fn par_map_keeps_positions() {
+        let doubled = large_grid().par_map(|value| value * 2);
+
+        assert_eq!(
+            Some(vec![2000, 2002, 2004]),
+            doubled.get_row(1).map(|row| row[..3].to_vec())
+        );
+        assert_eq!(1000, doubled.get_height());
+        assert_eq!(1000, doubled.get_width());
+    }
+
+    #[test]
+    fn par_iter_rows_visits_rows_in_order() {
+        let grid = large_grid();
+        let firsts: Vec<usize> = grid.par_iter_rows().map(|row| row[0]).collect();
+
+        assert_eq!((0..1000).map(|y| y * 1000).collect::<Vec<usize>>(), firsts);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::board::TileType;
+use crate::game::{Game, GameUpdate, WINNING_TILE};
+use crate::merge::Variant;
+use crate::persistence;
+use crate::persistence::PersistenceError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// A milestone the player can unlock once, e.g. making a 512 for the first time.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    First512,
+    First2048,
+    /// Win a game without undoing a single move.
+    WinWithoutUndo,
+    /// Make a 512 on a 3x3 board, where 2048 won't fit.
+    WinOn3x3,
+    /// Score 20,000 points in one game.
+    Score20000,
+    /// Keep playing after winning and make a 4096.
+    Beyond2048,
+}
+
+impl Achievement {
+    /// Every achievement, in the order they're listed.
+    pub const ALL: [Achievement; 6] = [
+        Achievement::First512,
+        Achievement::First2048,
+        Achievement::WinWithoutUndo,
+        Achievement::WinOn3x3,
+        Achievement::Score20000,
+        Achievement::Beyond2048,
+    ];
+
+    /// Gets the name of the achievement shown to the player.
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::First512 => "First 512",
+            Achievement::First2048 => "First 2048",
+            Achievement::WinWithoutUndo => "Win without Undo",
+            Achievement::WinOn3x3 => "Win on 3x3",
+            Achievement::Score20000 => "20,000 Club",
+            Achievement::Beyond2048 => "Beyond 2048",
+        }
+    }
+
+    /// Gets what the player has to do to unlock the achievement.
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::First512 => "Make a 512 tile",
+            Achievement::First2048 => "Make a 2048 tile",
+            Achievement::WinWithoutUndo => "Win a game to 2048 without undoing a move",
+            Achievement::WinOn3x3 => "Make a 512 on a 3x3 board",
+            Achievement::Score20000 => "Score 20,000 points in one game",
+            Achievement::Beyond2048 => "Keep playing after winning and make a 4096",
+        }
+    }
+
+    /// Gets whether a game has done what the achievement asks for.
+    fn is_met_by(self, game: &Game) -> bool {
+        let rules = game.rules();
+        // the tile achievements count powers of two, which a tile of another variant isn't
+        let reached =
+            |tile: TileType| rules.variant == Variant::Classic && game.highest_tile() >= tile;
+        match self {
+            Achievement::First512 => reached(9),
+            Achievement::First2048 => reached(WINNING_TILE),
+            Achievement::WinWithoutUndo => {
+                game.has_won() && rules.win_exponent >= WINNING_TILE && game.stats().undos == 0
+            }
+            Achievement::WinOn3x3 => rules.board_size == 3 && reached(9),
+            Achievement::Score20000 => game.score() >= 20_000,
+            Achievement::Beyond2048 => reached(WINNING_TILE + 1),
+        }
+    }
+}
+
+/// The achievements the player has unlocked, kept in `achievements.json` in the data directory.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    /// When each unlocked achievement was unlocked, in seconds since the Unix epoch.
+    pub unlocked: BTreeMap<Achievement, u64>,
+}
+
+impl Achievements {
+    const FILE_NAME: &'static str = "achievements.json";
+
+    /// Loads the unlocked achievements, starting from none if they don't exist or can't be read.
+    ///
+    /// # Returns
+    ///
+    /// The loaded `Achievements`.
+    pub fn load() -> Achievements {
+        persistence::data_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(Achievements::FILE_NAME)).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the unlocked achievements to the data directory. Another instance may have
+    /// unlocked some since these were read, so the file is read again first and the achievements
+    /// in either are kept, all while holding the data directory's lock. These achievements then
+    /// become the merged ones.
+    ///
+    /// # Returns
+    ///
+    /// The path of the achievements file, or a `PersistenceError` if it couldn't be written.
+    pub fn save(&mut self) -> Result<PathBuf, PersistenceError> {
+        let mut merged = None;
+        let path = persistence::update_data_file(Achievements::FILE_NAME, |contents| {
+            let on_disk: Achievements = contents
+                .and_then(|contents| serde_json::from_str(&contents).ok())
+                .unwrap_or_default();
+            let achievements = self.merged_into(on_disk);
+            let contents = serde_json::to_string_pretty(&achievements)
+                .map_err(|e| PersistenceError::Io(e.into()))?;
+            merged = Some(achievements);
+            Ok(contents)
+        })?;
+        if let Some(merged) = merged {
+            *self = merged;
+        }
+        Ok(path)
+    }
+
+    /// Merges these achievements into the ones in the file, keeping every achievement unlocked
+    /// in either, with the earlier of the times it was unlocked.
+    fn merged_into(&self, mut on_disk: Achievements) -> Achievements {
+        for (&achievement, &unlocked_at) in &self.unlocked {
+            let earliest = on_disk.unlocked.entry(achievement).or_insert(unlocked_at);
+            *earliest = (*earliest).min(unlocked_at);
+        }
+        on_disk
+    }
+
+    /// Unlocks the achievements a game has earned with the event it just handled. Games played
+    /// with help, or with adaptive odds, don't unlock anything, like they don't make the best
+    /// scores.
+    ///
+    /// # Arguments
+    ///
+    /// * `update` - What the event did, with the game after it.
+    /// * `now` - The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// The achievements that were unlocked just now, in `Achievement::ALL` order.
+    pub fn check(&mut self, update: &GameUpdate, now: u64) -> Vec<Achievement> {
+        let game = &update.game;
+        if game.is_assisted() || game.rules().adaptive {
+            return Vec::new();
+        }
+        let unlocked: Vec<Achievement> = Achievement::ALL
+            .into_iter()
+            .filter(|achievement| !self.unlocked.contains_key(achievement))
+            .filter(|achievement| achievement.is_met_by(game))
+            .collect();
+        for &achievement in &unlocked {
+            self.unlocked.insert(achievement, now);
+        }
+        unlocked
+    }
+
+    /// Gets when an achievement was unlocked, or `None` if it's still locked.
+    pub fn unlocked_at(&self, achievement: Achievement) -> Option<u64> {
+        self.unlocked.get(&achievement).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::game::GameEvent;
+
+    fn unaided(notation: &str) -> Game {
+        Game::from_board(Board::from_notation(notation).unwrap(), 0)
+            .unwrap()
+            .with_assisted(false)
+    }
+
+    #[test]
+    fn winning_on_a_small_board_unlocks_its_achievements_once() {
+        let mut achievements = Achievements::default();
+        let update = unaided("1024,1024,./.,.,./.,.,.")
+            .handle_event(GameEvent::SwipeLeft)
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                Achievement::First512,
+                Achievement::First2048,
+                Achievement::WinWithoutUndo,
+                Achievement::WinOn3x3,
+            ],
+            achievements.check(&update, 1_000)
+        );
+        assert_eq!(Some(1_000), achievements.unlocked_at(Achievement::WinOn3x3));
+        assert_eq!(None, achievements.unlocked_at(Achievement::Score20000));
+        assert!(achievements.check(&update, 2_000).is_empty());
+        assert_eq!(Some(1_000), achievements.unlocked_at(Achievement::First512));
+    }
+
+    #[test]
+    fn saving_keeps_what_another_instance_unlocked() {
+        let ours = Achievements {
+            unlocked: BTreeMap::from([
+                (Achievement::First512, 2_000),
+                (Achievement::WinOn3x3, 3_000),
+            ]),
+        };
+        let theirs = Achievements {
+            unlocked: BTreeMap::from([
+                (Achievement::First512, 1_000),
+                (Achievement::Score20000, 4_000),
+            ]),
+        };
+
+        assert_eq!(
+            BTreeMap::from([
+                (Achievement::First512, 1_000),
+                (Achievement::WinOn3x3, 3_000),
+                (Achievement::Score20000, 4_000),
+            ]),
+            ours.merged_into(theirs).unlocked
+        );
+    }
+
+    #[test]
+    fn assisted_games_unlock_nothing() {
+        let mut achievements = Achievements::default();
+        let update = unaided("1024,1024,./.,.,./.,.,.")
+            .with_assisted(true)
+            .handle_event(GameEvent::SwipeLeft)
+            .unwrap();
+
+        assert!(achievements.check(&update, 1_000).is_empty());
+        assert!(achievements.unlocked.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::board::{Direction, TileType};
+use crate::game::{Game, GameError, GameEvent};
+use crate::solver::{EvalWeights, Solver};
+use rand::seq::SliceRandom;
+
+/// Something that picks moves for a game without a human, e.g. for tournaments.
+pub trait Strategy {
+    /// Chooses the next move to make.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game to choose a move for.
+    /// * `moves` - The moves that would change the board. This is never empty.
+    ///
+    /// # Returns
+    ///
+    /// One of the directions in `moves`.
+    fn choose_move(&mut self, game: &Game, moves: &[Direction]) -> Direction;
+}
+
+/// The built-in strategies, which can be selected by name on the command line.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StrategyKind {
+    /// Picks a random move.
+    Random,
+    /// Picks the move that scores the most points, breaking ties by the number of empty cells
+    /// left afterwards.
+    Greedy,
+    /// Keeps the biggest tiles in the bottom left corner by preferring down, then left, then
+    /// right, and only going up when nothing else is possible.
+    Corner,
+    /// Looks one move ahead with the expectimax solver, using the weights saved by `rs2048 tune`.
+    Expectimax,
+}
+
+impl StrategyKind {
+    pub const ALL: [StrategyKind; 4] = [
+        StrategyKind::Random,
+        StrategyKind::Greedy,
+        StrategyKind::Corner,
+        StrategyKind::Expectimax,
+    ];
+
+    /// Gets the name used for this strategy on the command line and in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StrategyKind::Random => "random",
+            StrategyKind::Greedy => "greedy",
+            StrategyKind::Corner => "corner",
+            StrategyKind::Expectimax => "expectimax",
+        }
+    }
+
+    /// Looks up a strategy by the name returned from `name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the strategy.
+    ///
+    /// # Returns
+    ///
+    /// The matching `StrategyKind`, or `None` if there isn't one.
+    pub fn from_name(name: &str) -> Option<StrategyKind> {
+        StrategyKind::ALL
+            .iter()
+            .find(|kind| kind.name() == name)
+            .copied()
+    }
+
+    /// Creates a new instance of this strategy.
+    pub fn create(&self) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Random => Box::new(RandomStrategy),
+            StrategyKind::Greedy => Box::new(GreedyStrategy),
+            StrategyKind::Corner => Box::new(CornerStrategy),
+            StrategyKind::Expectimax => {
+                Box::new(SolverStrategy::new(Solver::new(EvalWeights::load(), 1)))
+            }
+        }
+    }
+}
+
+struct RandomStrategy;
+
+impl Strategy for RandomStrategy {
+    fn choose_move(&mut self, _game: &Game, moves: &[Direction]) -> Direction {
+        *moves.choose(&mut rand::thread_rng()).unwrap()
+    }
+}
+
+struct GreedyStrategy;
+
+impl Strategy for GreedyStrategy {
+    fn choose_move(&mut self, game: &Game, moves: &[Direction]) -> Direction {
+        game.legal_moves()
+            .filter(|(direction, _)| moves.contains(direction))
+            .max_by_key(|(_, outcome)| (outcome.points, outcome.board.empty_positions().len()))
+            .map_or(moves[0], |(direction, _)| direction)
+    }
+}
+
+struct CornerStrategy;
+
+impl Strategy for CornerStrategy {
+    fn choose_move(&mut self, _game: &Game, moves: &[Direction]) -> Direction {
+        [
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+            Direction::Up,
+        ]
+        .into_iter()
+        .find(|direction| moves.contains(direction))
+        .unwrap()
+    }
+}
+
+/// Lets a `Solver` play games, e.g. to try out a set of weights while tuning.
+pub struct SolverStrategy {
+    solver: Solver,
+}
+
+impl SolverStrategy {
+    pub fn new(solver: Solver) -> SolverStrategy {
+        SolverStrategy { solver }
+    }
+}
+
+impl Strategy for SolverStrategy {
+    fn choose_move(&mut self, game: &Game, moves: &[Direction]) -> Direction {
+        self.solver
+            .best_legal_move(game)
+            .filter(|direction| moves.contains(direction))
+            .unwrap_or(moves[0])
+    }
+}
+
+/// The outcome of a game played to the end by a `Strategy`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct GameResult {
+    pub score: u32,
+    /// The exponent of the biggest tile on the final board.
+    pub highest_tile: TileType,
+    pub moves: u32,
+}
+
+/// Plays a new game until no more moves are possible.
+///
+/// # Arguments
+///
+/// * `strategy` - The strategy that picks every move.
+///
+/// # Returns
+///
+/// The `GameResult` of the finished game, or a `GameError` if the game couldn't continue.
+pub fn play_game(strategy: &mut dyn Strategy) -> Result<GameResult, GameError> {
+    play_from(Game::start_new_game()?, strategy)
+}
+
+/// Plays an existing game, e.g. one loaded from a save slot, until no more moves are possible.
+/// Strategies play on past the winning tile.
+///
+/// # Arguments
+///
+/// * `game` - The game to continue.
+/// * `strategy` - The strategy that picks every move.
+///
+/// # Returns
+///
+/// The `GameResult` of the finished game, counting only the moves made here, or a `GameError` if
+/// the game couldn't continue.
+pub fn play_from(mut game: Game, strategy: &mut dyn Strategy) -> Result<GameResult, GameError> {
+    let mut moves = 0;
+
+    loop {
+        game = game.handle_event(GameEvent::KeepPlaying)?.game;
+        let available = game.available_moves();
+        if available.is_empty() {
+            break;
+        }
+        let direction = strategy.choose_move(&game, &available);
+        game = game.handle_event(direction.into())?.game;
+        moves += 1;
+    }
+
+    Ok(GameResult {
+        score: game.score(),
+        highest_tile: game.highest_tile(),
+        moves,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strategy_names_round_trip() {
+        for kind in StrategyKind::ALL {
+            assert_eq!(Some(kind), StrategyKind::from_name(kind.name()));
+        }
+    }
+
+    #[test]
+    fn unknown_strategy_name() {
+        assert_eq!(None, StrategyKind::from_name("minimax"));
+    }
+
+    #[test]
+    fn play_game_until_stuck() {
+        for kind in StrategyKind::ALL {
+            let result = play_game(kind.create().as_mut()).unwrap();
+            assert!(result.moves > 0);
+            assert!(result.highest_tile >= 2);
+        }
+    }
+}
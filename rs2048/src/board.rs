@@ -1,5 +1,11 @@
+use crate::merge::{merge_line, trace_line, ClassicRule, LineMove, LineOutcome, MergeRule};
+use crate::number_format::NumberFormat;
+use crate::spawn::SpawnPolicy;
+use crate::tile;
 use data_grid::{DataGrid, MatrixError};
 use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
 pub type TileType = u8;
@@ -9,11 +15,108 @@ pub struct Board {
     board: DataGrid<TileType>, // items are stored as their power of 2 - if 3 is in the grid, that means 8 is shown in game because 2^3=8
 }
 
+/// A direction the player can swipe in.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// Gets the name of the direction used on the command line, over JSON-RPC, and in position
+    /// files, e.g. `"up"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        }
+    }
+
+    /// Looks up a direction by the name `name` gives it.
+    pub fn from_name(name: &str) -> Option<Direction> {
+        Direction::ALL
+            .into_iter()
+            .find(|direction| direction.name() == name)
+    }
+}
+
 #[derive(Debug)]
 pub enum BoardError {
     AddRandomTileError,
 }
 
+/// Where a tile goes when the board is swiped, e.g. to animate the move.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TileMovement {
+    /// The row and column of the tile before the move.
+    pub from: (usize, usize),
+    /// The row and column the tile ends up in.
+    pub to: (usize, usize),
+    /// The tile that moved, as it was before merging.
+    pub tile: TileType,
+    /// Whether the tile merged with another one, which moves to the same cell.
+    pub merged: bool,
+}
+
+/// A `MergeRule` under which no tiles merge, for sliding them without merging, e.g. for gravity.
+struct NoMerges;
+
+impl MergeRule for NoMerges {
+    fn merge(&self, _front: TileType, _back: TileType) -> Option<TileType> {
+        None
+    }
+
+    fn points(&self, _merged: TileType) -> u32 {
+        0
+    }
+}
+
+/// Why a string couldn't be read as a board by `Board::from_notation`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NotationError {
+    /// The notation has no rows or a row has no cells.
+    Empty,
+    /// A cell is neither `.` nor a tile value, e.g. "3" or "x".
+    InvalidTile(String),
+    /// A row has a different number of cells than the first row.
+    UnevenRows { row: usize },
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotationError::Empty => write!(f, "the board has no tiles"),
+            NotationError::InvalidTile(tile) => write!(f, "'{}' isn't a tile value", tile),
+            NotationError::UnevenRows { row } => write!(
+                f,
+                "row {} has a different number of cells than the first row",
+                row + 1
+            ),
+        }
+    }
+}
+
+/// Whether a position made by `Board::random_position` can still be played.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PositionKind {
+    /// At least one swipe changes the board.
+    Playable,
+    /// The board is full and no two neighbouring tiles are equal, so the game is over.
+    GameOver,
+}
+
 impl Board {
     /// Creates a new `Board` with the specified size and initializes all cells with zero values.
     ///
@@ -30,111 +133,386 @@ impl Board {
         }
     }
 
-    /// Places an item with the specified value at the given column and row on the board.
+    /// Places an item with the specified value at the given row and column on the board.
     ///
     /// # Arguments
     ///
-    /// * `column` - The column index where the item will be placed.
     /// * `row` - The row index where the item will be placed.
+    /// * `column` - The column index where the item will be placed.
     /// * `value` - The value of the item to be placed on the board.
     ///
     /// # Returns
     ///
     /// Returns `Ok(())` if the placement was successful, or an `Err(MatrixError)` with a description of the error otherwise.
-    fn place_item_in_board(
+    pub fn place_item_in_board(
         &mut self,
-        column: usize,
         row: usize,
+        column: usize,
         value: TileType,
     ) -> Result<(), MatrixError> {
-        self.board.update_single_position(column, row, value)
+        self.board.update_single_position(row, column, value)
     }
 
-    /// Merges the cells in the board by moving tiles upwards as if the user had swiped up.
-    pub fn merge_up(&mut self) {
-        for i in 0..self.board.get_width() {
-            let column = self.board.get_column(i).unwrap();
-            self.board
-                .update_column(i, Board::merge_tiles(&column))
-                .unwrap();
+    /// Merges the cells in the board by moving tiles in a direction as if the user had swiped.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction the tiles move in.
+    /// * `wrap_around` - Whether tiles sliding off one edge come back in from the opposite edge,
+    ///   as implemented by `merge_wrapped_tiles`.
+    /// * `rule` - Decides which tiles merge, e.g. `ClassicRule`.
+    ///
+    /// # Returns
+    ///
+    /// A `LineOutcome` for the whole board: whether any tile moved, and the merges and points of
+    /// every line, the points being the sum of the values of the merged tiles.
+    #[tracing::instrument(level = "trace", skip_all, fields(?direction))]
+    pub fn merge(
+        &mut self,
+        direction: Direction,
+        wrap_around: bool,
+        rule: &impl MergeRule,
+    ) -> LineOutcome {
+        self.update_lines(direction, |line| {
+            if wrap_around {
+                Board::merge_wrapped_tiles(line, rule)
+            } else {
+                Board::merge_tiles(line, rule)
+            }
+        })
+    }
+
+    /// Slides every tile as far as it goes in a direction without merging any, e.g. for gravity
+    /// pulling the tiles down after a move.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction the tiles slide in.
+    ///
+    /// # Returns
+    ///
+    /// Whether any tile moved.
+    pub fn compact(&mut self, direction: Direction) -> bool {
+        self.update_lines(direction, |line| {
+            merge_line(line.iter().copied(), &NoMerges)
+        })
+        .moved
+    }
+
+    /// Works out where every tile goes when the board is merged by `merge`, without merging it.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction the tiles move in.
+    /// * `wrap_around` - Whether tiles sliding off one edge come back in from the opposite edge.
+    /// * `rule` - Decides which tiles merge.
+    ///
+    /// # Returns
+    ///
+    /// A `TileMovement` for every tile on the board, including those that stay where they are.
+    pub fn trace_merge(
+        &self,
+        direction: Direction,
+        wrap_around: bool,
+        rule: &impl MergeRule,
+    ) -> Vec<TileMovement> {
+        self.trace_lines(direction, |line| {
+            // joined lines are merged starting from their first gap, like in `merge_wrapped_tiles`
+            let start = if wrap_around {
+                line.iter().position(|&tile| tile == 0).unwrap_or(0)
+            } else {
+                0
+            };
+            let length = line.len();
+            let rotated = line.iter().copied().cycle().skip(start).take(length);
+            trace_line(rotated, rule)
+                .into_iter()
+                .map(|line_move| LineMove {
+                    from: (line_move.from + start) % length,
+                    to: (line_move.to + start) % length,
+                    merged: line_move.merged,
+                })
+                .collect()
+        })
+    }
+
+    /// Works out where every tile goes when the board is compacted by `compact`, without
+    /// compacting it.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction the tiles slide in.
+    ///
+    /// # Returns
+    ///
+    /// A `TileMovement` for every tile on the board, including those that stay where they are.
+    pub fn trace_compact(&self, direction: Direction) -> Vec<TileMovement> {
+        self.trace_lines(direction, |line| {
+            trace_line(line.iter().copied(), &NoMerges)
+        })
+    }
+
+    /// Follows the tiles of every row or column that runs in a direction, like `update_lines`.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction of motion.
+    /// * `trace` - Works out where the tiles of a line go, given the line ordered like for
+    ///   `update_lines`.
+    ///
+    /// # Returns
+    ///
+    /// The movements of the tiles of all the lines, with their cells on the board.
+    fn trace_lines(
+        &self,
+        direction: Direction,
+        trace: impl Fn(&[TileType]) -> Vec<LineMove>,
+    ) -> Vec<TileMovement> {
+        let columns = matches!(direction, Direction::Up | Direction::Down);
+        let backwards = matches!(direction, Direction::Down | Direction::Right);
+        let mut movements = Vec::new();
+        for i in 0..self.line_count(direction) {
+            let line = self.read_line(direction, i);
+            let cell = |position: usize| {
+                let position = if backwards {
+                    line.len() - 1 - position
+                } else {
+                    position
+                };
+                if columns {
+                    (position, i)
+                } else {
+                    (i, position)
+                }
+            };
+            movements.extend(trace(&line).into_iter().map(|line_move| TileMovement {
+                from: cell(line_move.from),
+                to: cell(line_move.to),
+                tile: line[line_move.from],
+                merged: line_move.merged,
+            }));
         }
+        movements
     }
 
-    /// Merges the cells in the board by moving tiles downwards as if the user had swiped down.
-    pub fn merge_down(&mut self) {
-        for i in 0..self.board.get_width() {
-            let mut column = self.board.get_column(i).unwrap();
-            column.reverse();
-            let mut merged = Board::merge_tiles(&column);
-            merged.reverse();
-            self.board.update_column(i, merged).unwrap();
+    /// Gets how many rows or columns run in a direction, e.g. the number of columns for `Up`.
+    fn line_count(&self, direction: Direction) -> usize {
+        match direction {
+            Direction::Up | Direction::Down => self.board.get_width(),
+            Direction::Left | Direction::Right => self.board.get_height(),
         }
     }
 
-    /// Merges the cells in the board by moving tiles to the left as if the user had swiped left.
-    pub fn merge_left(&mut self) {
-        for i in 0..self.board.get_height() {
-            let row = self.board.get_row(i).unwrap();
-            self.board.update_row(i, Board::merge_tiles(&row)).unwrap();
+    /// Gets a row or column ordered so that motion in a direction is from the back of the line
+    /// to the front, e.g. a column from the bottom up for `Down`.
+    fn read_line(&self, direction: Direction, index: usize) -> Vec<TileType> {
+        let mut line = match direction {
+            Direction::Up | Direction::Down => self.board.get_column(index),
+            Direction::Left | Direction::Right => self.board.get_row(index),
+        }
+        .unwrap();
+        if matches!(direction, Direction::Down | Direction::Right) {
+            line.reverse();
         }
+        line
     }
 
-    /// Merges the cells in the board by moving tiles to the right as if the user had swiped right.
-    pub fn merge_right(&mut self) {
-        for i in 0..self.board.get_height() {
-            let mut row = self.board.get_row(i).unwrap();
-            row.reverse();
-            let mut merged = Board::merge_tiles(&row);
-            merged.reverse();
-            self.board.update_row(i, merged).unwrap();
+    /// Replaces every row or column that runs in a direction, e.g. every column for `Up`.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction of motion. Each line is given to `update` ordered so that
+    ///   motion is from the back of the line to the front, and put back the same way.
+    /// * `update` - Works out the new line, and what happened to it.
+    ///
+    /// # Returns
+    ///
+    /// What happened to all the lines together.
+    fn update_lines(
+        &mut self,
+        direction: Direction,
+        mut update: impl FnMut(&[TileType]) -> (Vec<TileType>, LineOutcome),
+    ) -> LineOutcome {
+        let dimensions = (self.board.get_width(), self.board.get_height());
+        let columns = matches!(direction, Direction::Up | Direction::Down);
+        let backwards = matches!(direction, Direction::Down | Direction::Right);
+        let mut outcome = LineOutcome::default();
+        for i in 0..self.line_count(direction) {
+            let line = self.read_line(direction, i);
+            let (mut updated, line_outcome) = update(&line);
+            if backwards {
+                updated.reverse();
+            }
+            if columns {
+                self.board.update_column(i, updated).unwrap();
+            } else {
+                self.board.update_row(i, updated).unwrap();
+            }
+            outcome.moved |= line_outcome.moved;
+            outcome.merges += line_outcome.merges;
+            outcome.points += line_outcome.points;
         }
+        self.debug_check_dimensions(dimensions);
+        outcome
+    }
+
+    /// Merges the cells in the board by moving tiles upwards as if the user had swiped up.
+    ///
+    /// # Returns
+    ///
+    /// The points scored by the merges, i.e. the sum of the values of the merged tiles.
+    #[deprecated(note = "use `Board::merge` with `Direction::Up`")]
+    #[allow(dead_code)]
+    pub fn merge_up(&mut self) -> u32 {
+        self.merge(Direction::Up, false, &ClassicRule).points
+    }
+
+    /// Merges the cells in the board by moving tiles downwards as if the user had swiped down.
+    ///
+    /// # Returns
+    ///
+    /// The points scored by the merges, i.e. the sum of the values of the merged tiles.
+    #[deprecated(note = "use `Board::merge` with `Direction::Down`")]
+    #[allow(dead_code)]
+    pub fn merge_down(&mut self) -> u32 {
+        self.merge(Direction::Down, false, &ClassicRule).points
+    }
+
+    /// Merges the cells in the board by moving tiles to the left as if the user had swiped left.
+    ///
+    /// # Returns
+    ///
+    /// The points scored by the merges, i.e. the sum of the values of the merged tiles.
+    #[deprecated(note = "use `Board::merge` with `Direction::Left`")]
+    #[allow(dead_code)]
+    pub fn merge_left(&mut self) -> u32 {
+        self.merge(Direction::Left, false, &ClassicRule).points
+    }
+
+    /// Merges the cells in the board by moving tiles to the right as if the user had swiped right.
+    ///
+    /// # Returns
+    ///
+    /// The points scored by the merges, i.e. the sum of the values of the merged tiles.
+    #[deprecated(note = "use `Board::merge` with `Direction::Right`")]
+    #[allow(dead_code)]
+    pub fn merge_right(&mut self) -> u32 {
+        self.merge(Direction::Right, false, &ClassicRule).points
     }
 
     /// Merges the tiles in a single row or column as if motion is from the back of the vector to the front.
     ///
     /// This function takes a vector representing a row or column of the game board and merges it according to
-    /// the rules of the 2048 game.
+    /// the rules of the 2048 game, as implemented by `merge_line`.
     ///
     /// # Arguments
     ///
     /// * `tiles` - A reference to a vector containing the tiles to be merged.
+    /// * `rule` - Decides which tiles merge.
     ///
     /// # Returns
     ///
-    /// Returns a new vector with the merged tiles.
-    fn merge_tiles(tiles: &[TileType]) -> Vec<TileType> {
-        if tiles.is_empty() {
-            return vec![];
-        }
+    /// Returns a new vector with the merged tiles, and a `LineOutcome` describing what changed.
+    fn merge_tiles(tiles: &[TileType], rule: &impl MergeRule) -> (Vec<TileType>, LineOutcome) {
+        let (merged, outcome) = merge_line(tiles.iter().copied(), rule);
+        Board::debug_check_merged_line(tiles, &merged, outcome, rule);
+        (merged, outcome)
+    }
 
-        let mut last_seen_tile: TileType = tiles[0];
-        let mut result: Vec<TileType> = Vec::with_capacity(tiles.len());
+    /// Merges the tiles in a single row or column whose ends are joined, so that a tile sliding
+    /// off the front comes back in at the back.
+    ///
+    /// A joined line has no wall for the tiles to stop at, so one is put at the front of its first
+    /// gap: the line is rotated to start at its first empty cell, merged like a normal line, and
+    /// rotated back. So `[2, 0, 0, 2]` becomes `[0, 4, 0, 0]`, the two tiles meeting across the
+    /// edge. A full line has no gap and is merged like a normal one.
+    ///
+    /// # Arguments
+    ///
+    /// * `tiles` - The tiles to be merged, in the same order as for `merge_tiles`.
+    /// * `rule` - Decides which tiles merge.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new vector with the merged tiles, and a `LineOutcome` describing what changed.
+    /// Rotating doesn't change whether the line moved.
+    fn merge_wrapped_tiles(
+        tiles: &[TileType],
+        rule: &impl MergeRule,
+    ) -> (Vec<TileType>, LineOutcome) {
+        let first_gap = tiles.iter().position(|&tile| tile == 0).unwrap_or(0);
+        let mut line = tiles.to_vec();
+        line.rotate_left(first_gap);
+        let (mut merged, outcome) = Board::merge_tiles(&line, rule);
+        merged.rotate_right(first_gap);
+        (merged, outcome)
+    }
 
-        for &tile in tiles.iter().skip(1) {
-            if tile == 0 {
-                continue;
-            }
+    /// Checks, in debug builds only, that merging a line kept its length, that each merge turned
+    /// two tiles into one, and that no value was created or lost. This catches mistakes in the
+    /// merge rules early, e.g. while working on variants.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - The line before merging.
+    /// * `after` - The line after merging.
+    /// * `outcome` - What `merge_line` reported about the merge.
+    /// * `rule` - The rule the line was merged by, which gives the values of the tiles.
+    fn debug_check_merged_line(
+        before: &[TileType],
+        after: &[TileType],
+        outcome: LineOutcome,
+        rule: &impl MergeRule,
+    ) {
+        let count = |line: &[TileType]| line.iter().filter(|&&tile| tile != 0).count();
+        let total = |line: &[TileType]| {
+            line.iter()
+                .filter(|&&tile| tile != 0)
+                .map(|&tile| rule.value(tile))
+                .sum::<u64>()
+        };
+        debug_assert_eq!(
+            before.len(),
+            after.len(),
+            "merging changed the line's length"
+        );
+        debug_assert_eq!(
+            count(before),
+            count(after) + outcome.merges as usize * (rule.arity() - 1),
+            "each merge should turn {} tiles into one: {:?} became {:?}",
+            rule.arity(),
+            before,
+            after
+        );
+        debug_assert_eq!(
+            total(before),
+            total(after),
+            "merging changed the total of the tiles: {:?} became {:?}",
+            before,
+            after
+        );
+    }
 
-            if tile == last_seen_tile {
-                result.push(tile + 1);
-                last_seen_tile = 0;
-            } else {
-                if last_seen_tile != 0 {
-                    result.push(last_seen_tile);
-                }
-                last_seen_tile = tile;
-            }
-        }
-        result.push(last_seen_tile);
-        result.extend([0].repeat(tiles.len() - result.len()));
-        result
+    /// Checks, in debug builds only, that a swipe didn't change the size of the board.
+    fn debug_check_dimensions(&self, before: (usize, usize)) {
+        debug_assert_eq!(
+            before,
+            (self.board.get_width(), self.board.get_height()),
+            "merging changed the board's dimensions: {}",
+            self.to_ascii()
+        );
     }
 
-    /// Adds a new tile with a random value to a random empty position on the board.
+    /// Adds a new tile to an empty position on the board, chosen by a spawn policy.
+    ///
+    /// With `UniformSpawn`, the tile lands in any empty position with the same chance and is
+    /// either a 2 or a 4 based on a weighted choice, by default a 3:1 ratio for 2's and 4's.
+    ///
+    /// # Arguments
     ///
-    /// The function searches for empty positions on the board and randomly selects one
-    /// to place a new tile. The new tile is assigned a value of either 2 or 4 based on
-    /// a weighted choice (3:1 ratio for 2's and 4's).
+    /// * `policy` - Decides where the tile appears and what it is.
+    /// * `turn` - How many moves have been made, i.e. `0` for the tile the game starts with.
+    /// * `rng` - Where the randomness comes from, so that games can be replayed from a seed.
     ///
     /// # Errors
     ///
@@ -143,18 +521,152 @@ impl Board {
     ///
     /// # Returns
     ///
-    /// - `Ok(())` if a new tile is successfully added.
+    /// - `Ok((row, column))` of the new tile if it is successfully added.
     /// - An error variant of `BoardError` if the operation fails.
     ///
     /// # Example
     ///
     /// ```
     /// let mut board = Board::new(4);
-    /// board.add_random_tile().unwrap();
+    /// let policy = UniformSpawn {
+    ///     weights: SpawnWeights(vec![(1, 9), (2, 1)]),
+    /// };
+    /// board.add_random_tile(&policy, 0, &mut rand::thread_rng()).unwrap();
     /// ```
-    pub fn add_random_tile(&mut self) -> Result<(), BoardError> {
-        let empty_positions: Vec<(usize, usize)> = self
-            .board
+    pub fn add_random_tile(
+        &mut self,
+        policy: &dyn SpawnPolicy,
+        turn: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<(usize, usize), BoardError> {
+        let ((x, y), tile) = policy
+            .choose_spawn(self, turn, rng)
+            .ok_or(BoardError::AddRandomTileError)?; // nowhere to insert tile
+        self.place_item_in_board(y, x, tile).unwrap();
+        Ok((y, x))
+    }
+
+    /// Generates a random mid or late game position, e.g. for benchmarks, fuzzing, or puzzles.
+    ///
+    /// Like a real game, small tiles are more common than big ones, and the biggest tile sits in a
+    /// corner.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Where the randomness comes from. Use a seeded RNG for repeatable positions.
+    /// * `size` - The width and height of the board.
+    /// * `fill_ratio` - The fraction of cells that have a tile, from `0.0` to `1.0`. At least one
+    ///   cell is always filled. Ignored for `PositionKind::GameOver`, which fills every cell.
+    /// * `max_exponent` - The exponent of the biggest tile, e.g. `11` for 2048. Must be at least 3
+    ///   for `PositionKind::GameOver`.
+    /// * `kind` - Whether the position must be playable or must be game over.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `Board` instance.
+    pub fn random_position<R: Rng + ?Sized>(
+        rng: &mut R,
+        size: usize,
+        fill_ratio: f64,
+        max_exponent: TileType,
+        kind: PositionKind,
+    ) -> Board {
+        assert!(
+            kind == PositionKind::Playable || max_exponent >= 3,
+            "a game over position needs at least three different tiles"
+        );
+        let max_exponent = max_exponent.max(1);
+        // smaller tiles are proportionally more likely, like on a real board
+        let exponents: Vec<TileType> = (1..=max_exponent).collect();
+        let random_tile = |rng: &mut R, excluded: &[TileType]| -> TileType {
+            let allowed: Vec<TileType> = exponents
+                .iter()
+                .copied()
+                .filter(|tile| !excluded.contains(tile))
+                .collect();
+            *allowed
+                .choose_weighted(rng, |&tile| max_exponent - tile + 1)
+                .unwrap()
+        };
+
+        let cell_count = size * size;
+        let mut rows = vec![vec![0 as TileType; size]; size];
+        // the biggest tile goes in the top left corner, which is rotated to a random corner below
+        rows[0][0] = max_exponent;
+        match kind {
+            PositionKind::Playable => {
+                let filled = ((fill_ratio.clamp(0.0, 1.0) * cell_count as f64).round() as usize)
+                    .clamp(1, cell_count);
+                let mut cells: Vec<usize> = (1..cell_count).collect();
+                cells.shuffle(rng);
+                for &cell in cells.iter().take(filled - 1) {
+                    rows[cell / size][cell % size] = random_tile(rng, &[]);
+                }
+
+                // a full board needs one pair of equal neighbours to stay playable
+                if filled == cell_count && size > 1 {
+                    let board = Board::try_from(rows.clone()).unwrap();
+                    if !board.has_available_move(&ClassicRule) {
+                        let cell = *cells.choose(rng).unwrap();
+                        let (y, x) = (cell / size, cell % size);
+                        let neighbour = if x + 1 < size { (y, x + 1) } else { (y, x - 1) };
+                        rows[y][x] = rows[neighbour.0][neighbour.1];
+                    }
+                }
+            }
+            PositionKind::GameOver => {
+                for y in 0..size {
+                    for x in 0..size {
+                        if (y, x) == (0, 0) {
+                            continue;
+                        }
+                        // three or more exponents always leave one that matches neither neighbour
+                        let left = if x > 0 { rows[y][x - 1] } else { 0 };
+                        let above = if y > 0 { rows[y - 1][x] } else { 0 };
+                        rows[y][x] = random_tile(rng, &[left, above]);
+                    }
+                }
+            }
+        }
+        let mut board = Board::try_from(rows).unwrap();
+        for _ in 0..rng.gen_range(0..4) {
+            board.rotate_clockwise();
+        }
+        board
+    }
+
+    /// Rotates the board a quarter turn clockwise.
+    fn rotate_clockwise(&mut self) {
+        let rows = self.board.get_values();
+        let size = rows.len();
+        let rotated: Vec<Vec<TileType>> = (0..size)
+            .map(|y| (0..size).map(|x| rows[size - 1 - x][y]).collect())
+            .collect();
+        self.board = DataGrid::try_from(rotated).unwrap();
+    }
+
+    /// Finds the biggest tile on the board.
+    ///
+    /// # Returns
+    ///
+    /// The exponent of the biggest tile, or `0` if the board is empty.
+    pub fn highest_tile(&self) -> TileType {
+        self.board
+            .get_values()
+            .iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Finds every empty cell on the board.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of `(x, y)` positions, i.e. `(column, row)`, ordered row by row.
+    pub fn empty_positions(&self) -> Vec<(usize, usize)> {
+        self.board
             .iter_rows()
             .enumerate()
             .flat_map(|(y_index, vec)| {
@@ -164,59 +676,370 @@ impl Board {
                     .map(|(x_index, _item)| (x_index, y_index))
                     .collect::<Vec<(usize, usize)>>()
             })
-            .collect();
+            .collect()
+    }
 
-        if let Some(pos) = empty_positions.choose(&mut rand::thread_rng()) {
-            let value_to_add = [1 as TileType, 2]
-                .choose_weighted(
-                    &mut rand::thread_rng(),
-                    |item| if *item == 1 { 3 } else { 1 },
-                )
-                .unwrap();
-            self.place_item_in_board(pos.1, pos.0, *value_to_add)
-                .unwrap();
-        } else {
-            return Err(BoardError::AddRandomTileError); // nowhere to insert tile
+    /// Checks whether any swipe would change the board, i.e. whether there is an empty cell or two
+    /// tiles next to each other that merge, e.g. two equal tiles under `ClassicRule`. For rules
+    /// that merge more tiles at once, a whole group has to line up in a row or column.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - Decides which tiles merge.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the game can go on, or `false` if it's over.
+    pub fn has_available_move(&self, rule: &impl MergeRule) -> bool {
+        if rule.arity() > 2 {
+            let merges = |grid: &DataGrid<TileType>| {
+                grid.iter_rows()
+                    .any(|row| merge_line(row.iter().copied(), rule).1.merges > 0)
+            };
+            return !self.empty_positions().is_empty()
+                || merges(&self.board)
+                || merges(&self.board.transpose());
         }
+        let rows = self.board.get_values();
+        let merges = |a: TileType, b: TileType| {
+            b != 0 && (rule.merge(a, b).is_some() || rule.merge(b, a).is_some())
+        };
+        rows.iter().enumerate().any(|(y, row)| {
+            row.iter().enumerate().any(|(x, &tile)| {
+                tile == 0
+                    || row.get(x + 1).is_some_and(|&right| merges(tile, right))
+                    || rows.get(y + 1).is_some_and(|below| merges(tile, below[x]))
+            })
+        })
+    }
 
-        Ok(())
+    /// Estimates how many moves are left before the board locks up, assuming that spawns never
+    /// line up into new merges: every move fills an empty cell, and every merge that's possible
+    /// right now frees `MergeRule::arity` less one. Merges along rows and along columns can't all be made, so only the
+    /// larger of the two counts.
+    ///
+    /// This is cheap enough to check after every move, e.g. to warn the player before a sudden
+    /// game over.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - Decides which tiles merge.
+    ///
+    /// # Returns
+    ///
+    /// The estimated number of moves left, which is `0` when no move is possible.
+    pub fn min_moves_until_stuck_upper_bound(&self, rule: &impl MergeRule) -> u32 {
+        let merges = |grid: &DataGrid<TileType>| {
+            grid.iter_rows()
+                .map(|row| merge_line(row.iter().copied(), rule).1.merges)
+                .sum::<u32>()
+                * (rule.arity() as u32 - 1)
+        };
+        let empty_cells = self.empty_positions().len() as u32;
+        empty_cells + merges(&self.board).max(merges(&self.board.transpose()))
     }
 
     pub fn get_data_for_display(&self) -> &Vec<Vec<TileType>> {
         self.board.get_values()
     }
+
+    /// Reads a board from its notation, which describes a position on one line, e.g. in tests and
+    /// bug reports.
+    ///
+    /// Rows are separated by `/` and each cell is either `.` for an empty cell or the value shown
+    /// on the tile. Two tiles next to each other are separated by `,`, so "2.4,4/..../8..." is a
+    /// board of 4 columns and 3 rows. Spaces are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `notation` - The board's notation, as written by `to_notation`.
+    ///
+    /// # Returns
+    ///
+    /// The `Board`, or a `NotationError` if the notation isn't a valid board.
+    pub fn from_notation(notation: &str) -> Result<Board, NotationError> {
+        let rows = notation
+            .split('/')
+            .map(Board::parse_notation_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        if rows[0].is_empty() {
+            return Err(NotationError::Empty);
+        }
+        if let Some(row) = rows.iter().position(|row| row.len() != rows[0].len()) {
+            return Err(NotationError::UnevenRows { row });
+        }
+        // the rows were checked above, so this can't fail
+        Board::try_from(rows).map_err(|_| NotationError::UnevenRows { row: 0 })
+    }
+
+    /// Reads one row of a board's notation into tile exponents.
+    fn parse_notation_row(row: &str) -> Result<Vec<TileType>, NotationError> {
+        /// Adds the tile whose value has been read so far, if any.
+        fn finish_value(
+            value: &mut String,
+            tiles: &mut Vec<TileType>,
+        ) -> Result<(), NotationError> {
+            if value.is_empty() {
+                return Ok(());
+            }
+            let tile = value
+                .parse::<u64>()
+                .ok()
+                .filter(|&number| number >= 2 && number.is_power_of_two())
+                .and_then(|number| TileType::try_from(number.trailing_zeros()).ok())
+                .ok_or_else(|| NotationError::InvalidTile(value.clone()))?;
+            tiles.push(tile);
+            value.clear();
+            Ok(())
+        }
+
+        let mut tiles = Vec::new();
+        let mut value = String::new();
+        for character in row.chars().filter(|character| !character.is_whitespace()) {
+            match character {
+                '.' => {
+                    finish_value(&mut value, &mut tiles)?;
+                    tiles.push(0);
+                }
+                ',' => finish_value(&mut value, &mut tiles)?,
+                '0'..='9' => value.push(character),
+                _ => return Err(NotationError::InvalidTile(character.to_string())),
+            }
+        }
+        finish_value(&mut value, &mut tiles)?;
+        Ok(tiles)
+    }
+
+    /// Writes the board in the notation read by `from_notation`, e.g. "2.4,4/..../8...".
+    pub fn to_notation(&self) -> String {
+        self.board
+            .get_values()
+            .iter()
+            .map(|row| {
+                let mut notation = String::new();
+                let mut after_tile = false;
+                for &tile in row {
+                    if tile == 0 {
+                        notation.push('.');
+                        after_tile = false;
+                    } else {
+                        if after_tile {
+                            notation.push(',');
+                        }
+                        notation.push_str(&tile::value(tile).to_string());
+                        after_tile = true;
+                    }
+                }
+                notation
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Writes the board on one line without colours, one character per cell, for logs and error
+    /// messages that should be easy to grep, e.g. "1..2/..../...b/....".
+    ///
+    /// Each tile is written as its exponent in hexadecimal, so `b` is 2048, carrying on through
+    /// the alphabet for tiles beyond 32768. Empty cells are `.` and rows are separated by `/`.
+    pub fn to_ascii(&self) -> String {
+        self.board
+            .get_values()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&tile| match tile {
+                        0 => '.',
+                        _ => char::from_digit(tile as u32, 36).unwrap_or('+'),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl TryFrom<Vec<Vec<TileType>>> for Board {
+    type Error = MatrixError;
+
+    /// Creates a board from its rows of tiles, e.g. when loading a saved game.
+    fn try_from(rows: Vec<Vec<TileType>>) -> Result<Self, Self::Error> {
+        Ok(Board {
+            board: DataGrid::try_from(rows)?,
+        })
+    }
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.board.to_string().replace(" 0 ", "   "))
+        let values: Vec<Vec<String>> = self
+            .board
+            .iter_rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&tile| tile::display_value(tile, NumberFormat::None))
+                    .collect()
+            })
+            .collect();
+        let values = DataGrid::try_from(values).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", values)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::merge::{FibonacciRule, PowersOfThreeRule};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "each merge should turn 2 tiles into one")]
+    fn merge_check_catches_lost_tiles() {
+        let outcome = LineOutcome {
+            moved: true,
+            merges: 0,
+            points: 0,
+        };
+        Board::debug_check_merged_line(&[1, 1, 0, 0], &[2, 0, 0, 0], outcome, &ClassicRule);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "merging changed the total of the tiles")]
+    fn merge_check_catches_wrong_values() {
+        let outcome = LineOutcome {
+            moved: true,
+            merges: 1,
+            points: 8,
+        };
+        Board::debug_check_merged_line(&[1, 1, 0, 0], &[3, 0, 0, 0], outcome, &ClassicRule);
+    }
+
+    #[test]
+    fn merging_returns_the_points_scored() {
+        let mut board = Board::from_notation("2,2,4,4/2,2,2,./..../8,8,..").unwrap();
+
+        let outcome = board.merge(Direction::Left, false, &ClassicRule);
+        assert_eq!(4 + 8 + 4 + 16, outcome.points);
+        assert_eq!(4, outcome.merges);
+        assert_eq!("4,8../4,2../..../16...", board.to_notation());
+        assert_eq!(
+            LineOutcome::default(),
+            board.merge(Direction::Left, false, &ClassicRule)
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn direction_wrappers_merge_like_merge() {
+        let board = Board::from_notation("2,2,4,4/2,2,2,./..../8,8,..").unwrap();
+        let merged = |direction| {
+            let mut board = board.clone();
+            let points = board.merge(direction, false, &ClassicRule).points;
+            (board, points)
+        };
+        let wrapped = |wrapper: fn(&mut Board) -> u32| {
+            let mut board = board.clone();
+            let points = wrapper(&mut board);
+            (board, points)
+        };
+
+        assert_eq!(merged(Direction::Up), wrapped(Board::merge_up));
+        assert_eq!(merged(Direction::Down), wrapped(Board::merge_down));
+        assert_eq!(merged(Direction::Left), wrapped(Board::merge_left));
+        assert_eq!(merged(Direction::Right), wrapped(Board::merge_right));
+    }
+
+    #[test]
+    fn wrapped_lines_merge_across_the_edge() {
+        let merge = |tiles: &[TileType]| {
+            let (merged, outcome) = Board::merge_wrapped_tiles(tiles, &ClassicRule);
+            (merged, outcome.points)
+        };
+        assert_eq!((vec![0, 2, 0, 0], 4), merge(&[1, 0, 0, 1]));
+        assert_eq!((vec![2, 0, 3, 1], 0), merge(&[1, 2, 0, 3]));
+        assert_eq!((vec![2, 0, 0, 2], 4), merge(&[1, 1, 2, 0]));
+        assert_eq!((vec![2, 3, 1, 0], 4), merge(&[1, 1, 3, 1]));
+    }
+
+    #[test]
+    fn wrapped_board_moves_every_line() {
+        let mut board = Board::from_notation("2..2/4.../..../.8,8.").unwrap();
+
+        assert_eq!(
+            4 + 16,
+            board.merge(Direction::Right, true, &ClassicRule).points
+        );
+        assert_eq!("..4./...4/..../...16", board.to_notation());
+    }
+
+    #[test]
+    fn traced_tiles_land_where_the_merge_puts_them() {
+        let board = Board::from_notation("2..2/4.../..../.8,8.").unwrap();
+        for wrap_around in [false, true] {
+            for direction in Direction::ALL {
+                let mut merged = board.clone();
+                merged.merge(direction, wrap_around, &ClassicRule);
+
+                let mut traced = Board::new(4);
+                for movement in board.trace_merge(direction, wrap_around, &ClassicRule) {
+                    let (row, column) = movement.to;
+                    let tile = movement.tile + movement.merged as TileType;
+                    traced.place_item_in_board(row, column, tile).unwrap();
+                }
+                assert_eq!(merged, traced, "{:?}, wrapping: {}", direction, wrap_around);
+            }
+        }
+
+        let movements = board.trace_merge(Direction::Right, true, &ClassicRule);
+        let first_row: Vec<_> = movements.iter().filter(|m| m.from.0 == 0).collect();
+        assert_eq!(2, first_row.len());
+        assert!(first_row.iter().all(|m| m.to == (0, 2) && m.merged));
+    }
+
+    #[test]
+    fn compacting_never_merges() {
+        let mut board = Board::from_notation("2.../2.4./..../.4..").unwrap();
+        assert!(board.compact(Direction::Down));
+
+        assert_eq!("..../..../2.../2,4,4.", board.to_notation());
+        assert!(!board.compact(Direction::Down));
+    }
+
+    #[test]
+    fn merging_reports_whether_tiles_moved() {
+        let mut board = Board::from_notation("2,4../8.../..../....").unwrap();
+        assert!(!board.merge(Direction::Left, false, &ClassicRule).moved);
+        assert!(!board.merge(Direction::Up, false, &ClassicRule).moved);
+
+        let outcome = board.merge(Direction::Right, false, &ClassicRule);
+        assert!(outcome.moved);
+        assert_eq!(0, outcome.points);
+        assert_eq!("..2,4/...8/..../....", board.to_notation());
+        assert!(board.merge(Direction::Left, true, &ClassicRule).moved);
+    }
 
     // single row merge tests
     #[test]
     fn merge_simple() {
         let input = vec![2 as TileType, 2, 0, 0];
         let expected = vec![3 as TileType, 0, 0, 0];
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
     #[test]
     fn merge_with_spaces() {
         let input = vec![2 as TileType, 0, 2, 0];
         let expected = vec![3 as TileType, 0, 0, 0];
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
     #[test]
     fn merge_but_cant() {
         let input = vec![2 as TileType, 3, 2, 3];
         let expected = vec![2 as TileType, 3, 2, 3];
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
 
@@ -224,7 +1047,7 @@ mod tests {
     fn merge_all_same() {
         let input = vec![2 as TileType, 2, 2, 2];
         let expected = vec![3 as TileType, 3, 0, 0];
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
 
@@ -232,7 +1055,7 @@ mod tests {
     fn dont_merge_twice_at_once() {
         let input = vec![1 as TileType, 1, 2, 0];
         let expected = vec![2 as TileType, 2, 0, 0];
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
 
@@ -240,7 +1063,7 @@ mod tests {
     fn dont_merge_twice_at_once_reverse() {
         let input = vec![2 as TileType, 1, 1, 0];
         let expected = vec![2 as TileType, 2, 0, 0];
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
 
@@ -248,7 +1071,7 @@ mod tests {
     fn merge_empty_input() {
         let input = vec![];
         let expected: Vec<TileType> = vec![];
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
 
@@ -256,7 +1079,7 @@ mod tests {
     fn merge_single_element() {
         let input = vec![2 as TileType];
         let expected = vec![2 as TileType];
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
 
@@ -265,7 +1088,7 @@ mod tests {
         let input = vec![2 as TileType; 1000];
         let mut expected = vec![3 as TileType; 500];
         expected.extend(vec![0 as TileType; 500]);
-        let actual = Board::merge_tiles(&input);
+        let (actual, _) = Board::merge_tiles(&input, &ClassicRule);
         assert_eq!(expected, actual);
     }
 
@@ -294,7 +1117,7 @@ mod tests {
         };
 
         let mut actual = input.clone();
-        actual.merge_up();
+        actual.merge(Direction::Up, false, &ClassicRule);
 
         assert_eq!(expected, actual);
     }
@@ -322,7 +1145,7 @@ mod tests {
         };
 
         let mut actual = input.clone();
-        actual.merge_up();
+        actual.merge(Direction::Up, false, &ClassicRule);
 
         assert_eq!(expected, actual);
     }
@@ -350,7 +1173,7 @@ mod tests {
         };
 
         let mut actual = input.clone();
-        actual.merge_up();
+        actual.merge(Direction::Up, false, &ClassicRule);
 
         assert_eq!(expected, actual);
     }
@@ -377,7 +1200,7 @@ mod tests {
         };
 
         let mut actual = input.clone();
-        actual.merge_left();
+        actual.merge(Direction::Left, false, &ClassicRule);
 
         assert_eq!(expected, actual);
     }
@@ -404,7 +1227,7 @@ mod tests {
         };
 
         let mut actual = input.clone();
-        actual.merge_right();
+        actual.merge(Direction::Right, false, &ClassicRule);
 
         assert_eq!(expected, actual);
     }
@@ -431,7 +1254,7 @@ mod tests {
         };
 
         let mut actual = input.clone();
-        actual.merge_down();
+        actual.merge(Direction::Down, false, &ClassicRule);
 
         assert_eq!(expected, actual);
     }
@@ -449,8 +1272,235 @@ mod tests {
         };
 
         let mut actual = input.clone();
-        actual.merge_up();
+        actual.merge(Direction::Up, false, &ClassicRule);
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn available_move_with_empty_cell() {
+        let board = Board {
+            board: DataGrid::try_from(vec![vec![1, 2 as TileType], vec![2, 0 as TileType]])
+                .unwrap(),
+        };
+        assert!(board.has_available_move(&ClassicRule));
+    }
+
+    #[test]
+    fn available_moves_follow_the_merge_rule() {
+        // 5 and 8 are neighbours in the Fibonacci sequence, but not equal
+        let fibonacci = Board::try_from(vec![vec![1, 3], vec![4, 5]]).unwrap();
+        assert!(!fibonacci.has_available_move(&ClassicRule));
+        assert!(fibonacci.has_available_move(&FibonacciRule));
+        let stuck = Board::try_from(vec![vec![1, 3], vec![5, 1]]).unwrap();
+        assert!(!stuck.has_available_move(&FibonacciRule));
+    }
+
+    #[test]
+    fn available_move_with_equal_neighbours() {
+        let side_by_side = Board {
+            board: DataGrid::try_from(vec![vec![1, 2 as TileType], vec![3, 3 as TileType]])
+                .unwrap(),
+        };
+        let stacked = Board {
+            board: DataGrid::try_from(vec![vec![1, 3 as TileType], vec![2, 3 as TileType]])
+                .unwrap(),
+        };
+        assert!(side_by_side.has_available_move(&ClassicRule));
+        assert!(stacked.has_available_move(&ClassicRule));
+    }
+
+    #[test]
+    fn no_available_move() {
+        let board = Board {
+            board: DataGrid::try_from(vec![vec![1, 2 as TileType], vec![2, 1 as TileType]])
+                .unwrap(),
+        };
+        assert!(!board.has_available_move(&ClassicRule));
+    }
+
+    // random position tests
+    fn tiles(board: &Board) -> Vec<TileType> {
+        board
+            .get_data_for_display()
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn random_playable_positions() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for fill_ratio in [0.0, 0.5, 0.8, 1.0] {
+            for _ in 0..50 {
+                let board =
+                    Board::random_position(&mut rng, 4, fill_ratio, 11, PositionKind::Playable);
+                let tiles = tiles(&board);
+                let filled = tiles.iter().filter(|&&tile| tile != 0).count();
+
+                assert!(board.has_available_move(&ClassicRule));
+                assert_eq!(((fill_ratio * 16.0).round() as usize).max(1), filled);
+                assert_eq!(Some(&11), tiles.iter().max());
+            }
+        }
+    }
+
+    #[test]
+    fn biggest_tile_is_in_a_corner() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let board = Board::random_position(&mut rng, 4, 0.6, 9, PositionKind::Playable);
+            let rows = board.get_data_for_display();
+            let corners = [rows[0][0], rows[0][3], rows[3][0], rows[3][3]];
+            assert!(corners.contains(&9));
+        }
+    }
+
+    #[test]
+    fn random_game_over_positions() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for max_exponent in [3, 4, 11] {
+            for _ in 0..50 {
+                let board =
+                    Board::random_position(&mut rng, 4, 0.5, max_exponent, PositionKind::GameOver);
+                let tiles = tiles(&board);
+
+                assert!(!board.has_available_move(&ClassicRule));
+                assert!(!tiles.contains(&0));
+                assert_eq!(Some(&max_exponent), tiles.iter().max());
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_position() {
+        let position = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            Board::random_position(&mut rng, 4, 0.7, 10, PositionKind::Playable)
+        };
+        assert_eq!(
+            position(3).get_data_for_display(),
+            position(3).get_data_for_display()
+        );
+    }
+
+    #[test]
+    fn moves_until_stuck() {
+        let full_without_merges = Board::try_from(vec![vec![1, 2], vec![2, 1]]).unwrap();
+        let one_empty_cell = Board::try_from(vec![vec![1, 2], vec![2, 0]]).unwrap();
+        let merges_both_ways = Board::try_from(vec![
+            vec![1, 1, 1, 0],
+            vec![2, 3, 2, 3],
+            vec![2, 4, 5, 6],
+            vec![7, 8, 9, 10],
+        ])
+        .unwrap();
+
+        assert_eq!(
+            0,
+            full_without_merges.min_moves_until_stuck_upper_bound(&ClassicRule)
+        );
+        assert_eq!(
+            1,
+            one_empty_cell.min_moves_until_stuck_upper_bound(&ClassicRule)
+        );
+        // one empty cell, and one merge along rows or one along columns
+        assert_eq!(
+            2,
+            merges_both_ways.min_moves_until_stuck_upper_bound(&ClassicRule)
+        );
+    }
+
+    #[test]
+    fn powers_of_three_need_three_in_a_row_to_move() {
+        let pairs_only =
+            Board::try_from(vec![vec![1, 1, 2], vec![2, 3, 3], vec![1, 2, 1]]).unwrap();
+        let three_in_a_column =
+            Board::try_from(vec![vec![1, 2, 1], vec![1, 3, 2], vec![1, 2, 1]]).unwrap();
+
+        assert!(pairs_only.has_available_move(&ClassicRule));
+        assert!(!pairs_only.has_available_move(&PowersOfThreeRule));
+        assert!(three_in_a_column.has_available_move(&PowersOfThreeRule));
+        // one merge of three tiles frees two cells
+        assert_eq!(
+            2,
+            three_in_a_column.min_moves_until_stuck_upper_bound(&PowersOfThreeRule)
+        );
+
+        let mut board = three_in_a_column;
+        let outcome = board.merge(Direction::Up, false, &PowersOfThreeRule);
+        assert!(outcome.moved);
+        assert_eq!(1, outcome.merges);
+        assert_eq!(&vec![2, 2, 1], &board.get_data_for_display()[0]);
+    }
+
+    #[test]
+    fn notation_round_trip() {
+        let board = Board::from_notation("2.4,4/..../8.../..2048,2").unwrap();
+
+        assert_eq!(
+            &vec![
+                vec![1, 0, 2, 2],
+                vec![0, 0, 0, 0],
+                vec![3, 0, 0, 0],
+                vec![0, 0, 11, 1],
+            ],
+            board.get_data_for_display()
+        );
+        assert_eq!("2.4,4/..../8.../..2048,2", board.to_notation());
+        assert_eq!(
+            board,
+            Board::from_notation(" 2 . 4,4 / .... / 8... / ..2048,2 ").unwrap()
+        );
+    }
+
+    #[test]
+    fn displays_tile_values() {
+        let board = Board::try_from(vec![vec![1, 0], vec![0, 11]]).unwrap();
+        let text = board.to_string();
+
+        assert!(text.contains("│  2   │      │"), "{text}");
+        assert!(text.contains("│      │ 2048 │"), "{text}");
+    }
+
+    #[test]
+    fn ascii_has_one_character_per_cell() {
+        let board = Board::try_from(vec![
+            vec![1, 0, 0, 11],
+            vec![0, 15, 16, 0],
+            vec![0, 0, 0, 0],
+            vec![36, 0, 0, 2],
+        ])
+        .unwrap();
+
+        assert_eq!("1..b/.fg./..../+..2", board.to_ascii());
+    }
+
+    #[test]
+    fn notation_rejects_invalid_boards() {
+        assert_eq!(
+            Err(NotationError::InvalidTile("3".to_string())),
+            Board::from_notation("3./..")
+        );
+        assert_eq!(
+            Err(NotationError::InvalidTile("x".to_string())),
+            Board::from_notation("x./..")
+        );
+        assert_eq!(
+            Err(NotationError::UnevenRows { row: 1 }),
+            Board::from_notation("2./...")
+        );
+        assert_eq!(Err(NotationError::Empty), Board::from_notation(""));
+    }
+
+    #[test]
+    fn notation_describes_stuck_boards() {
+        assert!(!Board::from_notation("2,4/4,2")
+            .unwrap()
+            .has_available_move(&ClassicRule));
+        assert!(Board::from_notation("2,4/2,8")
+            .unwrap()
+            .has_available_move(&ClassicRule));
+    }
 }
@@ -0,0 +1,606 @@
+use crate::ai;
+use crate::ai::StrategyKind;
+use crate::board::{Board, PositionKind};
+use crate::config::Config;
+use crate::diagnostics::Diagnostics;
+use crate::eval;
+use crate::game::Game;
+use crate::persistence;
+use crate::rpc;
+use crate::save;
+use crate::solver::{EvalWeights, Solver};
+use crate::tile;
+use crate::tournament;
+use crate::tournament::OutputFormat;
+use crate::tune;
+use crate::tune::SearchMethod;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::Instant;
+use std::{fs, io, thread};
+
+const USAGE: &str = "\
+Usage: rs2048 [--json-errors] [--ascii] [--practice <BOARD>] [-v|-vv|-vvv] [COMMAND]
+
+Starts the game when no command is given. With --json-errors, a failing command or --practice
+prints its error to stderr as a JSON object with the error's kind, exit code, and message. With --ascii, the game
+draws its borders with ASCII characters for terminals that can't show box-drawing ones. With
+--practice, the game starts from a position to practice, given as a board like
+\"2.4,4/..../8.../....\" or a file with one; practice games don't count as best scores. With -v,
+the time taken by each frame is logged to trace.log in the data directory, with -vv the moves and
+the solver's searches too, and with -vvv every merge; this needs the `trace` feature.
+
+Commands:
+  tournament  Play the AI strategies against each other and report the results
+      --games <N>            games per strategy (default 100)
+      --threads <N>          games played at once (default: number of CPUs)
+      --strategies <A,B,..>  strategies to compare (default: all)
+      --format <FORMAT>      csv, json, or markdown (default markdown)
+  tune        Search for the best solver evaluation weights and save them
+      --method <METHOD>      grid or random (default random)
+      --samples <N>          sets of weights to try (default 27)
+      --games <N>            games per set of weights (default 20)
+      --depth <N>            moves the solver looks ahead (default 1)
+      --threads <N>          games played at once (default: number of CPUs)
+  serve       Let other programs play games over JSON-RPC, one request per line
+      --port <PORT>          listen on this local TCP port instead of stdin/stdout
+                             (needs the `net` feature)
+  bench       Time how long the solver takes to choose a move on random positions
+      --positions <N>        positions to solve (default 50)
+      --depth <N>            moves the solver looks ahead (default 2)
+      --seed <N>             seed for generating the positions (default 0)
+      --game-over <BOOL>     solve positions with no moves left (default false)
+  eval        Evaluate positions from a file and print the solver's view of each as CSV
+      --positions <FILE>     one board per line, e.g. \"2.4,4/..../8.../....\", optionally
+                             followed by \"-> left|up\" to check the best move; # for comments
+      --depth <N>            moves the solver looks ahead (default 2)
+  autoplay    Let a strategy play a game to the end, exiting with 10 if it won and 11 if it lost
+      --strategy <NAME>      strategy to play with (default expectimax)
+      --slot <N>             continue the game saved in this slot instead of a new game
+      --board <NOTATION>     start from this position instead, e.g. \"2.4,4/..../8.../....\"
+  doctor      Show details of the terminal, where files are kept, and the saved positions, for
+              bug reports
+
+Exit codes:
+  0   success
+  1   the command failed, e.g. the server couldn't listen on its port
+  2   invalid arguments
+  3   the save slot is empty or damaged
+  4   the game engine failed
+  10  autoplay won the game
+  11  autoplay lost the game";
+
+/// What went wrong with a command, which decides the process's exit code.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorKind {
+    /// The command failed for another reason, e.g. the server couldn't listen on its port.
+    Failed,
+    /// The command or its flags weren't understood.
+    InvalidArguments,
+    /// The requested save slot is empty or damaged.
+    SaveNotFound,
+    /// A game couldn't continue, e.g. because a tile couldn't be added.
+    EngineFailure,
+}
+
+impl ErrorKind {
+    /// Gets the exit code for this kind of error.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Failed => 1,
+            ErrorKind::InvalidArguments => 2,
+            ErrorKind::SaveNotFound => 3,
+            ErrorKind::EngineFailure => 4,
+        }
+    }
+
+    /// Gets the name of this kind of error in `--json-errors` output.
+    fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Failed => "failed",
+            ErrorKind::InvalidArguments => "invalid_arguments",
+            ErrorKind::SaveNotFound => "save_not_found",
+            ErrorKind::EngineFailure => "engine_failure",
+        }
+    }
+}
+
+/// An error from a command, with a message for the user.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CliError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl CliError {
+    fn new(kind: ErrorKind, message: impl Into<String>) -> CliError {
+        CliError {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Describes the error as a single line of JSON, for scripts that run the commands.
+    pub fn to_json(&self) -> String {
+        json!({
+            "error": {
+                "kind": self.kind.name(),
+                "code": self.kind.exit_code(),
+                "message": self.message,
+            }
+        })
+        .to_string()
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Most errors come from checking the command line, so plain messages count as invalid arguments.
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::new(ErrorKind::InvalidArguments, message)
+    }
+}
+
+/// How a command that ran to the end finished, which decides the process's exit code.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Outcome {
+    Success,
+    /// `autoplay` reached the winning tile.
+    GameWon,
+    /// `autoplay` ran out of moves before reaching the winning tile.
+    GameLost,
+}
+
+impl Outcome {
+    /// Gets the exit code for this outcome.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Success => 0,
+            Outcome::GameWon => 10,
+            Outcome::GameLost => 11,
+        }
+    }
+}
+
+/// Runs the command line, reporting errors the way `--json-errors` asks for.
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments, excluding the program name and the flags `main.rs`
+///   handles. Must not be empty.
+/// * `json_errors` - Whether `--json-errors` was given.
+///
+/// # Returns
+///
+/// The exit code for the process.
+pub fn main(args: &[String], json_errors: bool) -> i32 {
+    match run(args) {
+        Ok(outcome) => outcome.exit_code(),
+        Err(e) => report(&e, json_errors),
+    }
+}
+
+/// Prints an error to stderr, as JSON with `--json-errors` and as its message otherwise.
+///
+/// # Arguments
+///
+/// * `error` - The error to print.
+/// * `json_errors` - Whether `--json-errors` was given.
+///
+/// # Returns
+///
+/// The exit code for the process.
+pub fn report(error: &CliError, json_errors: bool) -> i32 {
+    if json_errors {
+        eprintln!("{}", error.to_json());
+    } else {
+        eprintln!("{}", error);
+    }
+    error.kind.exit_code()
+}
+
+/// Runs a command given on the command line instead of starting the game.
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments, excluding the program name and `--json-errors`.
+///
+/// # Returns
+///
+/// How the command finished, or a `CliError` to show the user if it failed.
+pub fn run(args: &[String]) -> Result<Outcome, CliError> {
+    let Some(command) = args.first() else {
+        return Err(format!("Missing command\n\n{}", USAGE).into());
+    };
+    let flags = parse_flags(&args[1..])?;
+    match command.as_str() {
+        "tournament" => run_tournament(&flags),
+        "tune" => run_tune(&flags),
+        "serve" => run_serve(&flags),
+        "bench" => run_bench(&flags),
+        "eval" => run_eval(&flags),
+        "autoplay" => return run_autoplay(&flags),
+        "doctor" => {
+            run_doctor();
+            Ok(())
+        }
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        command => Err(format!("Unknown command '{}'\n\n{}", command, USAGE).into()),
+    }
+    .map(|()| Outcome::Success)
+}
+
+/// Parses `--name value` pairs.
+///
+/// # Arguments
+///
+/// * `args` - The arguments following the command.
+///
+/// # Returns
+///
+/// A map from flag names (without the dashes) to their values, or an error message if the
+/// arguments aren't all `--name value` pairs.
+fn parse_flags(args: &[String]) -> Result<HashMap<String, String>, String> {
+    let mut flags = HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let name = arg
+            .strip_prefix("--")
+            .ok_or_else(|| format!("Unexpected argument '{}'", arg))?;
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for --{}", name))?;
+        flags.insert(name.to_string(), value.clone());
+    }
+    Ok(flags)
+}
+
+/// Reads the position a practice game starts from, given on the command line either as a board
+/// in notation or as the path of a file with one. The file's first line that isn't blank or a
+/// `#` comment is the board, as in the positions files for `eval`.
+///
+/// # Arguments
+///
+/// * `source` - The board, or the path of the file.
+///
+/// # Returns
+///
+/// The practice `Game`, or a `CliError` if the board can't be read or isn't valid.
+pub fn practice_game(source: &str) -> Result<Game, CliError> {
+    let notation = match fs::read_to_string(source) {
+        Ok(contents) => contents
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .ok_or_else(|| format!("{} doesn't have a board in it", source))?,
+        Err(_) => source.to_string(),
+    };
+    let board = Board::from_notation(&notation)
+        .map_err(|e| format!("--practice isn't a valid board: {}", e))?;
+    Game::from_board(board, 0)
+        .map_err(|e| format!("--practice isn't a valid board: {:?}", e).into())
+}
+
+/// Parses a numeric flag, falling back to a default if it wasn't given.
+fn parse_number_flag(
+    flags: &HashMap<String, String>,
+    name: &str,
+    default: usize,
+) -> Result<usize, String> {
+    match flags.get(name) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("--{} must be a number, got '{}'", name, value)),
+        None => Ok(default),
+    }
+}
+
+fn run_tournament(flags: &HashMap<String, String>) -> Result<(), CliError> {
+    let games = parse_number_flag(flags, "games", 100)?;
+    if games == 0 {
+        return Err("--games must be at least 1".to_string().into());
+    }
+    let default_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let threads = parse_number_flag(flags, "threads", default_threads)?;
+
+    let strategies = match flags.get("strategies") {
+        Some(names) => names
+            .split(',')
+            .map(|name| {
+                StrategyKind::from_name(name).ok_or_else(|| format!("Unknown strategy '{}'", name))
+            })
+            .collect::<Result<Vec<StrategyKind>, String>>()?,
+        None => StrategyKind::ALL.to_vec(),
+    };
+
+    let format = match flags.get("format") {
+        Some(name) => {
+            OutputFormat::from_name(name).ok_or_else(|| format!("Unknown format '{}'", name))?
+        }
+        None => OutputFormat::Markdown,
+    };
+
+    let results = tournament::run(&strategies, games, threads)
+        .map_err(|e| CliError::new(ErrorKind::EngineFailure, e))?;
+    print!("{}", tournament::format_results(&results, format));
+    Ok(())
+}
+
+fn run_tune(flags: &HashMap<String, String>) -> Result<(), CliError> {
+    let method = match flags.get("method") {
+        Some(name) => {
+            SearchMethod::from_name(name).ok_or_else(|| format!("Unknown method '{}'", name))?
+        }
+        None => SearchMethod::Random,
+    };
+    let samples = parse_number_flag(flags, "samples", 27)?;
+    let games = parse_number_flag(flags, "games", 20)?;
+    if samples == 0 || games == 0 {
+        return Err("--samples and --games must be at least 1"
+            .to_string()
+            .into());
+    }
+    let depth = parse_number_flag(flags, "depth", 1)?;
+    let default_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let threads = parse_number_flag(flags, "threads", default_threads)?;
+
+    let weights = tune::candidates(method, samples);
+    println!(
+        "Trying {} sets of weights, {} games each...",
+        weights.len(),
+        games
+    );
+    let ranked = tune::run(&weights, games, depth as u32, threads)
+        .map_err(|e| CliError::new(ErrorKind::EngineFailure, e))?;
+
+    println!(
+        "| monotonicity | smoothness | empty_cells | mean score | mean moves | mean highest tile |"
+    );
+    println!("|---|---|---|---|---|---|");
+    for candidate in ranked.iter().take(5) {
+        println!(
+            "| {:.3} | {:.3} | {:.3} | {:.1} | {:.1} | {:.1} |",
+            candidate.weights.monotonicity,
+            candidate.weights.smoothness,
+            candidate.weights.empty_cells,
+            candidate.mean_score,
+            candidate.mean_moves,
+            candidate.mean_highest_tile
+        );
+    }
+
+    let path = ranked[0].weights.save().map_err(|e| {
+        CliError::new(
+            ErrorKind::Failed,
+            format!("Couldn't save the weights: {}", e),
+        )
+    })?;
+    println!("Saved the best weights to {}", path.display());
+    Ok(())
+}
+
+fn run_serve(flags: &HashMap<String, String>) -> Result<(), CliError> {
+    let result = match flags.get("port") {
+        #[cfg(feature = "net")]
+        Some(port) => {
+            let port = port
+                .parse()
+                .map_err(|_| format!("--port must be a port number, got '{}'", port))?;
+            rpc::serve_tcp(port)
+        }
+        #[cfg(not(feature = "net"))]
+        Some(_) => {
+            return Err("--port needs rs2048 to be built with the `net` feature"
+                .to_string()
+                .into())
+        }
+        None => rpc::serve(io::stdin().lock(), io::stdout().lock()),
+    };
+    result.map_err(|e| CliError::new(ErrorKind::Failed, format!("Server failed: {}", e)))
+}
+
+fn run_bench(flags: &HashMap<String, String>) -> Result<(), CliError> {
+    let positions = parse_number_flag(flags, "positions", 50)?;
+    if positions == 0 {
+        return Err("--positions must be at least 1".to_string().into());
+    }
+    let depth = parse_number_flag(flags, "depth", 2)?;
+    let seed = parse_number_flag(flags, "seed", 0)?;
+    let kind = match flags.get("game-over").map(String::as_str) {
+        Some("true") => PositionKind::GameOver,
+        Some("false") | None => PositionKind::Playable,
+        Some(value) => {
+            return Err(format!("--game-over must be true or false, got '{}'", value).into())
+        }
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+    let boards: Vec<Board> = (0..positions)
+        .map(|_| {
+            let fill_ratio = rng.gen_range(0.4..0.9);
+            let max_exponent = rng.gen_range(7..=12);
+            Board::random_position(&mut rng, 4, fill_ratio, max_exponent, kind)
+        })
+        .collect();
+
+    let solver = Solver::new(EvalWeights::load(), depth as u32);
+    let started = Instant::now();
+    for board in &boards {
+        solver.best_move(board);
+    }
+    let elapsed = started.elapsed();
+
+    println!(
+        "Solved {} positions at depth {} in {:.2?} ({:.3} ms per move)",
+        positions,
+        depth,
+        elapsed,
+        elapsed.as_secs_f64() * 1000.0 / positions as f64
+    );
+    Ok(())
+}
+
+fn run_eval(flags: &HashMap<String, String>) -> Result<(), CliError> {
+    let path = flags
+        .get("positions")
+        .ok_or_else(|| "--positions is required".to_string())?;
+    let depth = parse_number_flag(flags, "depth", eval::DEFAULT_DEPTH as usize)?;
+    let contents = fs::read_to_string(path).map_err(|e| {
+        CliError::new(
+            ErrorKind::Failed,
+            format!("Couldn't read '{}': {}", path, e),
+        )
+    })?;
+    let positions = eval::read_positions(&contents)?;
+
+    let results = eval::evaluate_positions(positions, EvalWeights::load(), depth as u32);
+    print!("{}", eval::to_csv(&results));
+    Ok(())
+}
+
+fn run_autoplay(flags: &HashMap<String, String>) -> Result<Outcome, CliError> {
+    let strategy = match flags.get("strategy") {
+        Some(name) => {
+            StrategyKind::from_name(name).ok_or_else(|| format!("Unknown strategy '{}'", name))?
+        }
+        None => StrategyKind::Expectimax,
+    };
+
+    let game = match (flags.get("slot"), flags.get("board")) {
+        (Some(_), Some(_)) => {
+            return Err("--slot and --board can't be used together"
+                .to_string()
+                .into())
+        }
+        (None, Some(notation)) => {
+            let board = Board::from_notation(notation)
+                .map_err(|e| format!("--board isn't a valid board: {}", e))?;
+            Game::from_board(board, 0)
+                .map_err(|e| format!("--board isn't a valid board: {:?}", e))?
+        }
+        (Some(_), None) => {
+            let slot = parse_number_flag(flags, "slot", 0)?;
+            if !(1..=save::SLOT_COUNT).contains(&slot) {
+                return Err(format!("--slot must be from 1 to {}", save::SLOT_COUNT).into());
+            }
+            save::read_slots()
+                .swap_remove(slot - 1)
+                .and_then(|saved| saved.to_game())
+                .ok_or_else(|| {
+                    CliError::new(
+                        ErrorKind::SaveNotFound,
+                        format!("Slot {} is empty or damaged", slot),
+                    )
+                })?
+        }
+        (None, None) => Game::start_new_game()
+            .map_err(|e| CliError::new(ErrorKind::EngineFailure, format!("{:?}", e)))?,
+    };
+
+    let win_exponent = game.rules().win_exponent;
+    let result = ai::play_from(game, strategy.create().as_mut())
+        .map_err(|e| CliError::new(ErrorKind::EngineFailure, format!("{:?}", e)))?;
+    let won = result.highest_tile >= win_exponent;
+    println!(
+        "{} with a score of {} after {} moves, highest tile {}",
+        if won { "Won" } else { "Lost" },
+        result.score,
+        result.moves,
+        tile::value(result.highest_tile)
+    );
+    Ok(if won {
+        Outcome::GameWon
+    } else {
+        Outcome::GameLost
+    })
+}
+
+fn run_doctor() {
+    let describe = |path: Option<PathBuf>| {
+        path.map_or("unknown".to_string(), |path| path.display().to_string())
+    };
+    println!("{}", Diagnostics::gather());
+    println!("Config file: {}", describe(Config::path()));
+    println!("Data directory: {}", describe(persistence::data_dir()));
+    for (index, saved) in save::read_slots().into_iter().enumerate() {
+        let position = saved
+            .and_then(|saved| saved.to_game())
+            .map_or_else(|| "empty".to_string(), |game| game.board().to_notation());
+        println!("Slot {}: {}", index + 1, position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_flag_pairs() {
+        let flags = parse_flags(&to_args(&["--games", "10", "--format", "csv"])).unwrap();
+
+        assert_eq!(Some(&"10".to_string()), flags.get("games"));
+        assert_eq!(Some(&"csv".to_string()), flags.get("format"));
+    }
+
+    #[test]
+    fn practice_boards_have_to_be_square() {
+        assert!(practice_game("2,.,.,./4,.,.,.").is_err());
+        assert!(practice_game("2,./4,.").is_ok());
+    }
+
+    #[test]
+    fn parse_flag_without_value() {
+        assert!(parse_flags(&to_args(&["--games"])).is_err());
+    }
+
+    #[test]
+    fn parse_positional_argument() {
+        assert!(parse_flags(&to_args(&["10"])).is_err());
+    }
+
+    #[test]
+    fn unknown_tune_method() {
+        assert!(run(&to_args(&["tune", "--method", "annealing"])).is_err());
+    }
+
+    #[test]
+    fn unknown_command() {
+        assert_eq!(
+            Some(ErrorKind::InvalidArguments),
+            run(&to_args(&["frobnicate"])).err().map(|e| e.kind)
+        );
+    }
+
+    #[test]
+    fn json_errors() {
+        let error = CliError::new(ErrorKind::SaveNotFound, "Slot 2 is empty or damaged");
+
+        assert_eq!(
+            r#"{"error":{"code":3,"kind":"save_not_found","message":"Slot 2 is empty or damaged"}}"#,
+            error.to_json()
+        );
+    }
+
+    #[test]
+    fn json_errors_keep_the_exit_code() {
+        assert_eq!(2, main(&to_args(&["bench", "--positions", "0"]), true));
+        assert_eq!(2, main(&to_args(&["bench", "--positions", "0"]), false));
+    }
+}
@@ -0,0 +1,380 @@
+use crate::board::{Board, Direction, TileType};
+use crate::game::Game;
+use crate::merge::{MergeRule, Variant};
+use crate::solver::{apply_move, EvalWeights, MoveRules, Solver};
+use serde::{Deserialize, Serialize};
+
+/// How much the coach says about the player's moves, set with `coaching` in the config file.
+/// The levels are ordered from quietest to most talkative.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoachingLevel {
+    /// The coach stays quiet.
+    #[default]
+    Off,
+    /// Only blunders are pointed out.
+    Quiet,
+    /// Every move that's noticeably worse than the best one is pointed out.
+    Normal,
+    /// Good moves are acknowledged too.
+    Chatty,
+}
+
+impl CoachingLevel {
+    const ALL: [CoachingLevel; 4] = [
+        CoachingLevel::Off,
+        CoachingLevel::Quiet,
+        CoachingLevel::Normal,
+        CoachingLevel::Chatty,
+    ];
+
+    /// Gets the name of the level shown in the settings screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            CoachingLevel::Off => "Off",
+            CoachingLevel::Quiet => "Quiet",
+            CoachingLevel::Normal => "Normal",
+            CoachingLevel::Chatty => "Chatty",
+        }
+    }
+
+    /// Gets the next level, wrapping around from the most talkative back to off.
+    pub fn next(self) -> CoachingLevel {
+        let index = CoachingLevel::ALL
+            .iter()
+            .position(|&level| level == self)
+            .unwrap_or(0);
+        CoachingLevel::ALL[(index + 1) % CoachingLevel::ALL.len()]
+    }
+}
+
+/// What the coach thinks of a move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verdict {
+    /// The move was the best one, or close enough.
+    Fine,
+    /// A noticeably better move was available.
+    Mistake { better: Direction },
+    /// A much better move was available.
+    Blunder { better: Direction },
+    /// The move took the biggest tile out of its corner when the better move kept it there.
+    LeftCorner { tile: TileType, better: Direction },
+}
+
+impl Verdict {
+    /// How much worse than the best move, relative to it, a move has to be to count as a mistake.
+    const MISTAKE_THRESHOLD: f64 = 0.05;
+    /// How much worse than the best move, relative to it, a move has to be to count as a blunder.
+    const BLUNDER_THRESHOLD: f64 = 0.25;
+
+    /// Judges a move by comparing it with the solver's evaluation of every possible move.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The board before the move.
+    /// * `evaluations` - The solver's evaluation of every move that changes the board.
+    /// * `played` - The move that was played.
+    /// * `rules` - How the tiles move in the game.
+    ///
+    /// # Returns
+    ///
+    /// The `Verdict`, or `None` if the move doesn't change the board.
+    fn judge(
+        board: &Board,
+        evaluations: &[(Direction, f64)],
+        played: Direction,
+        rules: MoveRules,
+    ) -> Option<Verdict> {
+        let (better, loss) = loss_against_best(evaluations, played)?;
+        if better == played || loss < Verdict::MISTAKE_THRESHOLD {
+            return Some(Verdict::Fine);
+        }
+
+        let corner_before = biggest_tile_in_corner(board);
+        let corner_after = |direction| {
+            apply_move(board, direction, rules).and_then(|after| biggest_tile_in_corner(&after))
+        };
+        if let Some(tile) = corner_before {
+            if corner_after(played).is_none() && corner_after(better).is_some() {
+                return Some(Verdict::LeftCorner { tile, better });
+            }
+        }
+
+        Some(if loss < Verdict::BLUNDER_THRESHOLD {
+            Verdict::Mistake { better }
+        } else {
+            Verdict::Blunder { better }
+        })
+    }
+
+    /// Gets what the coach says about the move at a coaching level.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - How much the coach should say.
+    /// * `variant` - The variant of the game, which decides the values of the tiles.
+    ///
+    /// # Returns
+    ///
+    /// The comment, or `None` if the level is too quiet for this verdict.
+    fn comment(self, level: CoachingLevel, variant: Variant) -> Option<String> {
+        let minimum_level = match self {
+            Verdict::Fine => CoachingLevel::Chatty,
+            Verdict::Mistake { .. } => CoachingLevel::Normal,
+            Verdict::Blunder { .. } | Verdict::LeftCorner { .. } => CoachingLevel::Quiet,
+        };
+        if level < minimum_level {
+            return None;
+        }
+
+        Some(match self {
+            Verdict::Fine => "Fine.".to_string(),
+            Verdict::Mistake { better } => format!("Better was {}.", direction_name(better)),
+            Verdict::Blunder { better } => {
+                format!(
+                    "That hurts your position. Better was {}.",
+                    direction_name(better)
+                )
+            }
+            Verdict::LeftCorner { tile, better } => format!(
+                "That traps your {}. Better was {}.",
+                variant.value(tile),
+                direction_name(better)
+            ),
+        })
+    }
+}
+
+/// Works out how much worse a move is than the best one.
+///
+/// # Arguments
+///
+/// * `evaluations` - The solver's evaluation of every move that changes the board.
+/// * `played` - The move that was played.
+///
+/// # Returns
+///
+/// The best move and how much worse than it the played move is, relative to the best move's
+/// evaluation, or `None` if the played move doesn't change the board.
+fn loss_against_best(
+    evaluations: &[(Direction, f64)],
+    played: Direction,
+) -> Option<(Direction, f64)> {
+    let &(_, played_value) = evaluations
+        .iter()
+        .find(|&&(direction, _)| direction == played)?;
+    let &(better, best_value) = evaluations.iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+    Some((
+        better,
+        (best_value - played_value) / best_value.abs().max(1.0),
+    ))
+}
+
+/// What the coach made of one of the player's moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    /// What the coach says about the move, if anything at the coaching level.
+    pub comment: Option<String>,
+    /// Whether the move was the one the coach would have made.
+    pub followed_hint: bool,
+    /// How much worse the move was than the coach's, relative to it, e.g. `0.1` for 10% worse.
+    pub loss: f64,
+}
+
+/// Comments on the player's moves, using the solver to find out whether a better move was
+/// available.
+pub struct Coach {
+    solver: Solver,
+}
+
+impl Coach {
+    /// How many moves ahead the coach looks. This is kept low so that the comment appears as soon
+    /// as the move is made.
+    const DEPTH: u32 = 2;
+
+    pub fn new() -> Coach {
+        Coach {
+            solver: Solver::new(EvalWeights::load(), Coach::DEPTH),
+        }
+    }
+
+    /// Reviews a move the player is about to make.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - How much the coach should say.
+    /// * `game` - The game before the move.
+    /// * `played` - The move that is being made.
+    ///
+    /// # Returns
+    ///
+    /// The `Review`, or `None` if coaching is off or the move isn't legal.
+    pub fn review(&self, level: CoachingLevel, game: &Game, played: Direction) -> Option<Review> {
+        if level == CoachingLevel::Off {
+            return None;
+        }
+        let board = game.board();
+        let rules = MoveRules::of(game.rules());
+        let evaluations = self.solver.evaluate_legal_moves(game);
+        let (better, loss) = loss_against_best(&evaluations, played)?;
+        Some(Review {
+            comment: Verdict::judge(board, &evaluations, played, rules)?
+                .comment(level, rules.variant),
+            followed_hint: better == played || loss <= 0.0,
+            loss: loss.max(0.0),
+        })
+    }
+}
+
+/// Gets the biggest tile on a board if it sits in a corner.
+fn biggest_tile_in_corner(board: &Board) -> Option<TileType> {
+    let rows = board.get_data_for_display();
+    let biggest = rows.iter().flatten().copied().max()?;
+    let last_row = rows.len() - 1;
+    let last_column = rows[0].len() - 1;
+    let corners = [
+        rows[0][0],
+        rows[0][last_column],
+        rows[last_row][0],
+        rows[last_row][last_column],
+    ];
+    (biggest > 0 && corners.contains(&biggest)).then_some(biggest)
+}
+
+/// Gets the name of a direction as the coach says it.
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "Up",
+        Direction::Down => "Down",
+        Direction::Left => "Left",
+        Direction::Right => "Right",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+
+    fn board(rows: [[TileType; 4]; 4]) -> Board {
+        Board::try_from(rows.map(Vec::from).to_vec()).unwrap()
+    }
+
+    #[test]
+    fn judges_by_the_loss_against_the_best_move() {
+        let board = board([[1, 2, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]]);
+        let evaluations = [
+            (Direction::Down, 60.0),
+            (Direction::Left, 98.0),
+            (Direction::Right, 100.0),
+        ];
+        let classic = MoveRules::default();
+
+        assert_eq!(
+            Some(Verdict::Fine),
+            Verdict::judge(&board, &evaluations, Direction::Left, classic)
+        );
+        assert_eq!(
+            Some(Verdict::Blunder {
+                better: Direction::Right
+            }),
+            Verdict::judge(&board, &evaluations, Direction::Down, classic)
+        );
+        assert_eq!(
+            None,
+            Verdict::judge(&board, &evaluations, Direction::Up, classic)
+        );
+    }
+
+    #[test]
+    fn reviews_say_whether_the_hint_was_followed() {
+        let board = board([[1, 1, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [2, 0, 0, 0]]);
+        let game = Game::restore(
+            board,
+            0,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        let coach = Coach::new();
+        let best = coach.solver.best_legal_move(&game).unwrap();
+
+        let followed = coach.review(CoachingLevel::Quiet, &game, best).unwrap();
+        assert!(followed.followed_hint);
+        assert_eq!(0.0, followed.loss);
+        assert!(coach.review(CoachingLevel::Off, &game, best).is_none());
+        assert!(coach
+            .review(CoachingLevel::Quiet, &game, Direction::Down)
+            .is_some_and(|review| review.loss >= 0.0));
+    }
+
+    #[test]
+    fn notices_the_biggest_tile_leaving_its_corner() {
+        let board = board([[9, 2, 0, 0], [0, 0, 0, 1], [0, 0, 0, 0], [0, 0, 0, 0]]);
+        let evaluations = [(Direction::Left, 100.0), (Direction::Right, 50.0)];
+
+        assert_eq!(
+            Some(Verdict::LeftCorner {
+                tile: 9,
+                better: Direction::Left
+            }),
+            Verdict::judge(&board, &evaluations, Direction::Right, MoveRules::default())
+        );
+    }
+
+    #[test]
+    fn names_trapped_tiles_by_the_games_variant() {
+        let trapped = Verdict::LeftCorner {
+            tile: 9,
+            better: Direction::Left,
+        };
+
+        assert_eq!(
+            Some("That traps your 512. Better was Left.".to_string()),
+            trapped.comment(CoachingLevel::Quiet, Variant::Classic)
+        );
+        assert_eq!(
+            Some("That traps your 55. Better was Left.".to_string()),
+            trapped.comment(CoachingLevel::Quiet, Variant::Fibonacci)
+        );
+    }
+
+    #[test]
+    fn quieter_levels_say_less() {
+        let mistake = Verdict::Mistake {
+            better: Direction::Up,
+        };
+
+        assert_eq!(
+            None,
+            Verdict::Fine.comment(CoachingLevel::Normal, Variant::Classic)
+        );
+        assert_eq!(
+            Some("Fine.".to_string()),
+            Verdict::Fine.comment(CoachingLevel::Chatty, Variant::Classic)
+        );
+        assert_eq!(
+            None,
+            mistake.comment(CoachingLevel::Quiet, Variant::Classic)
+        );
+        assert_eq!(
+            Some("Better was Up.".to_string()),
+            mistake.comment(CoachingLevel::Normal, Variant::Classic)
+        );
+        assert_eq!(
+            None,
+            Verdict::Blunder {
+                better: Direction::Up
+            }
+            .comment(CoachingLevel::Off, Variant::Classic)
+        );
+    }
+
+    #[test]
+    fn levels_cycle() {
+        assert_eq!(CoachingLevel::Quiet, CoachingLevel::Off.next());
+        assert_eq!(CoachingLevel::Off, CoachingLevel::Chatty.next());
+    }
+}
@@ -0,0 +1,375 @@
+use crate::coach::CoachingLevel;
+use crate::focus::FocusIndicator;
+use crate::history::HistoryLimits;
+use crate::keymap::KeyBindings;
+use crate::number_format::NumberFormat;
+use crate::persistence;
+use crate::persistence::PersistenceError;
+use crate::rules::Rules;
+use crate::theme::Theme;
+use crate::transform::DisplayTransform;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use std::{fs, io};
+
+/// User settings, read from `config.toml` in the platform's config directory
+/// (e.g. `~/.config/rs2048/config.toml` on Linux).
+///
+/// Every field has a default, so a partial or missing file is valid.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// How thousands are separated when showing scores and tile values.
+    pub number_format: NumberFormat,
+    /// Whether the main menu offers to check crates.io for a newer release. Only has an effect
+    /// when the game is built with the `net` feature.
+    pub check_for_updates: bool,
+    /// A URL that the score, seed, rules and version of every finished game are posted to as
+    /// JSON, e.g. for a leaderboard among friends. Nothing is sent unless this is set, and it
+    /// only has an effect when the game is built with the `net` feature.
+    pub score_webhook: Option<String>,
+    /// Whether screens like the win banner are animated. Turn this off on slow terminals or if
+    /// the motion is distracting.
+    pub animations: bool,
+    /// Whether borders are drawn with ASCII characters (`+`, `-` and `|`) instead of box-drawing
+    /// ones, for terminals or fonts that can't show them. They're also used when the locale isn't
+    /// UTF-8, or with the `--ascii` command line flag.
+    pub ascii_borders: bool,
+    /// Whether a line of details for finding problems is shown in the top left corner, e.g. how
+    /// long the terminal takes to take each frame and whether the screens draw less because of it.
+    pub debug_overlay: bool,
+    /// Whether a summary of the last session is shown for a moment when the app starts.
+    pub startup_summary: bool,
+    /// The name of the colour theme for the tiles and the rest of the interface: `classic`,
+    /// `mono`, `light` for terminals with a light background, or the name of a theme file in
+    /// `Theme::themes_dir`.
+    pub theme: String,
+    /// How the selected option in menus and lists is marked: `inverted`, `arrow`, `underline`,
+    /// or `color` (yellow text only).
+    pub focus_indicator: FocusIndicator,
+    /// How the board is turned and flipped on the screen, e.g.
+    /// `board_view = { rotation = 90, mirrored = false }`. The arrow keys follow the board, so Up
+    /// still moves the tiles up on the screen.
+    pub board_view: DisplayTransform,
+    /// Which key does what while playing, e.g. `move_up = "w"` in the `[keys]` table.
+    pub keys: KeyBindings,
+    /// How much the coach comments on each move while playing: `off`, `quiet` (blunders only),
+    /// `normal`, or `chatty` (good moves too).
+    pub coaching: CoachingLevel,
+    /// Whether moves made while the score preview is shown count as help, like the coach's
+    /// hints, keeping the game off the best scores. The preview only shows the points of the
+    /// next move, so it's fair game by default.
+    pub score_preview_is_help: bool,
+    /// The rules new games are played by, in a `[rules]` table.
+    ///
+    /// `board_size` is how many cells wide and tall the board is, from 2 to 8, and
+    /// `starting_tiles` is how many tiles it starts with, 2 by default. `win_exponent` is the
+    /// exponent of the tile that wins, e.g. 10 to win at 1024.
+    /// `spawn` decides where new tiles appear. The normal game spawns them anywhere; practice
+    /// rules spawn them more often away from or toward a corner, e.g.
+    /// `spawn = { policy = "corner_bias", corner = "bottom_left", toward = false }`, always in the
+    /// worst place (`policy = "adversarial"`), or in a fixed sequence (`policy = "scripted"` with
+    /// a list of `tiles`). `spawn_weights = [[1, 9], [2, 1]]` sets which tiles spawn, as
+    /// exponents, and how often, 3:1 for 2s and 4s by default. `move_limit` and `time_limit` (in seconds) end the game early,
+    /// `undo_limit` caps how many moves can be undone, and `wrap_around = true` joins opposite
+    /// edges of the board. With `gravity = true` the tiles fall down after every sideways move,
+    /// and with `fair_spawns = true` a new tile never ends the game if another tile wouldn't.
+    /// With `zen = true` the game can't be lost: when the board locks up, the oldest of the
+    /// smallest tiles is taken off. With `adaptive = true` the bigger tiles spawn more often while
+    /// the board is kept clear and less often while it fills up; those games stay off the high
+    /// scores.
+    /// `variant = "fibonacci"` plays 2584, where tiles count through the Fibonacci sequence and
+    /// neighbours in it merge, e.g. 2 and 3 make 5. `variant = "threes"` plays like Threes!,
+    /// where a 1 and a 2 make 3, equal tiles from 3 up merge, and swipes shift tiles by one cell;
+    /// it plays best with `spawn_weights = [[1, 1], [2, 1]]`. `variant = "powers_of_three"` plays
+    /// with powers of 3, where three equal tiles in a row make the next one, e.g. three 9s a 27.
+    /// The variant can also be picked when starting a new game.
+    pub rules: Rules,
+    /// How much of a game's history is kept in memory, in a `[history]` table, e.g.
+    /// `undo = 500` positions to undo and `moves = 10000` moves and scores. Moves past the cap
+    /// are written to the data directory unless `spill_moves = false`.
+    pub history: HistoryLimits,
+    /// Extra segments shown on the status bar while playing, by name, e.g.
+    /// `status_hooks = ["scoring_streak"]` to count the moves in a row that scored, or
+    /// `"organization"` for how well sorted the rows and columns are. Names that aren't known are
+    /// left out.
+    pub status_hooks: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            number_format: NumberFormat::default(),
+            check_for_updates: false,
+            score_webhook: None,
+            animations: true,
+            ascii_borders: false,
+            debug_overlay: false,
+            startup_summary: true,
+            theme: "classic".to_string(),
+            focus_indicator: FocusIndicator::default(),
+            board_view: DisplayTransform::default(),
+            keys: KeyBindings::default(),
+            coaching: CoachingLevel::default(),
+            score_preview_is_help: false,
+            rules: Rules::default(),
+            history: HistoryLimits::default(),
+            status_hooks: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to the defaults if it doesn't exist or can't be parsed.
+    ///
+    /// # Returns
+    ///
+    /// The loaded `Config`.
+    pub fn load() -> Config {
+        Config::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the settings back to the config file, e.g. after they were changed in the settings
+    /// screen.
+    ///
+    /// # Returns
+    ///
+    /// The path of the config file, or a `PersistenceError` if it couldn't be written.
+    pub fn save(&self) -> Result<PathBuf, PersistenceError> {
+        let path = Config::path().ok_or(PersistenceError::NoConfigDirectory)?;
+        let contents =
+            toml::to_string(self).map_err(|e| PersistenceError::Io(io::Error::other(e)))?;
+        let dir = path.parent().expect("the config file is in a directory");
+        persistence::write_locked(dir, "config.toml", &contents)
+    }
+
+    /// Gets the location of the config file.
+    ///
+    /// # Returns
+    ///
+    /// The path to `config.toml`, or `None` if the platform has no config directory.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rs2048").join("config.toml"))
+    }
+}
+
+/// Settings given on the command line, which win over the config file's for as long as the app
+/// runs, including after the file is reloaded.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Overrides {
+    /// Whether `--ascii` was given.
+    pub ascii_borders: bool,
+}
+
+impl Overrides {
+    /// Applies the command line's settings to a config loaded from the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The config to change.
+    pub fn apply_to(self, config: &mut Config) {
+        config.ascii_borders |= self.ascii_borders;
+    }
+}
+
+/// Notices when files in the config directory or the themes directory are added, removed or
+/// edited, so that the config and the theme files can be reloaded while the app runs.
+pub struct ConfigWatcher {
+    dirs: Vec<PathBuf>,
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    last_checked: Instant,
+}
+
+impl ConfigWatcher {
+    /// How often the directories are looked at.
+    pub const INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Creates a watcher that treats the files as they are now as unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `dirs` - The directories to watch. They don't have to exist yet.
+    ///
+    /// # Returns
+    ///
+    /// The new `ConfigWatcher`.
+    pub fn new(dirs: Vec<PathBuf>) -> ConfigWatcher {
+        let files = ConfigWatcher::list_files(&dirs);
+        ConfigWatcher {
+            dirs,
+            files,
+            last_checked: Instant::now(),
+        }
+    }
+
+    /// Creates a watcher for the config file and the theme files.
+    ///
+    /// # Returns
+    ///
+    /// The new `ConfigWatcher`.
+    pub fn for_config() -> ConfigWatcher {
+        let config_dir = Config::path().and_then(|path| path.parent().map(PathBuf::from));
+        ConfigWatcher::new(config_dir.into_iter().chain(Theme::themes_dir()).collect())
+    }
+
+    /// Checks whether any watched file changed since the last time a change was reported. The
+    /// directories are looked at no more than once per `INTERVAL`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a file was added, removed or modified.
+    pub fn has_changed(&mut self) -> bool {
+        if self.last_checked.elapsed() < ConfigWatcher::INTERVAL {
+            return false;
+        }
+        self.last_checked = Instant::now();
+        let files = ConfigWatcher::list_files(&self.dirs);
+        let changed = files != self.files;
+        self.files = files;
+        changed
+    }
+
+    /// Lists the files in the directories with when they were last modified, in a stable order.
+    fn list_files(dirs: &[PathBuf]) -> Vec<(PathBuf, Option<SystemTime>)> {
+        let mut files: Vec<(PathBuf, Option<SystemTime>)> = dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                metadata
+                    .is_file()
+                    .then(|| (entry.path(), metadata.modified().ok()))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::{Action, Key};
+    use crate::spawn::{Corner, SpawnRule, SpawnWeights};
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn watcher_notices_new_and_edited_files() {
+        let dir = std::env::temp_dir().join(format!("rs2048-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut watcher = ConfigWatcher::new(vec![dir.clone()]);
+        watcher.last_checked -= ConfigWatcher::INTERVAL;
+        assert!(!watcher.has_changed());
+
+        fs::write(dir.join("paper.toml"), "tiles = []").unwrap();
+        assert!(!watcher.has_changed(), "checked again too soon");
+        watcher.last_checked -= ConfigWatcher::INTERVAL;
+        assert!(watcher.has_changed());
+        watcher.last_checked -= ConfigWatcher::INTERVAL;
+        assert!(!watcher.has_changed());
+
+        fs::remove_file(dir.join("paper.toml")).unwrap();
+        watcher.last_checked -= ConfigWatcher::INTERVAL;
+        assert!(watcher.has_changed());
+    }
+
+    #[test]
+    fn ascii_flag_wins_over_the_file() {
+        let mut config: Config = toml::from_str("ascii_borders = false").unwrap();
+        Overrides::default().apply_to(&mut config);
+        assert!(!config.ascii_borders);
+
+        Overrides {
+            ascii_borders: true,
+        }
+        .apply_to(&mut config);
+        assert!(config.ascii_borders);
+    }
+
+    #[test]
+    fn parse_empty_config() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(Config::default(), config);
+    }
+
+    #[test]
+    fn parse_number_format() {
+        let config: Config = toml::from_str("number_format = \"period\"").unwrap();
+        assert_eq!(NumberFormat::Period, config.number_format);
+    }
+
+    #[test]
+    fn animations_are_on_by_default() {
+        assert!(Config::default().animations);
+
+        let config: Config = toml::from_str("animations = false").unwrap();
+        assert!(!config.animations);
+    }
+
+    #[test]
+    fn parse_history_limits() {
+        let config: Config = toml::from_str("[history]\nmoves = 5000").unwrap();
+        assert_eq!(Some(5000), config.history.moves);
+        assert_eq!(HistoryLimits::default().undo, config.history.undo);
+        assert!(config.history.spill_moves);
+    }
+
+    #[test]
+    fn update_check_is_opt_in() {
+        assert!(!Config::default().check_for_updates);
+    }
+
+    #[test]
+    fn score_webhook_is_opt_in() {
+        assert_eq!(None, Config::default().score_webhook);
+        let config: Config = toml::from_str("score_webhook = \"http://example.com\"").unwrap();
+        assert_eq!(Some("http://example.com"), config.score_webhook.as_deref());
+    }
+
+    #[test]
+    fn round_trip_with_key_bindings() {
+        let mut config = Config {
+            theme: "mono".to_string(),
+            ..Config::default()
+        };
+        config
+            .keys
+            .bind(Action::Undo, Key(KeyCode::Char('z')))
+            .unwrap();
+
+        let contents = toml::to_string(&config).unwrap();
+        assert!(contents.contains("[keys]"));
+        assert_eq!(config, toml::from_str(&contents).unwrap());
+    }
+
+    #[test]
+    fn parse_rules() {
+        let config: Config = toml::from_str(
+            "[rules]\nspawn = { policy = \"corner_bias\", corner = \"bottom_left\", toward = false }\ntime_limit = 300\nspawn_weights = [[1, 6], [2, 3], [3, 1]]",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Rules {
+                spawn: SpawnRule::CornerBias {
+                    corner: Corner::BottomLeft,
+                    toward: false
+                },
+                time_limit: Some(300),
+                spawn_weights: SpawnWeights(vec![(1, 6), (2, 3), (3, 1)]),
+                ..Rules::default()
+            },
+            config.rules
+        );
+        assert_eq!(
+            config,
+            toml::from_str(&toml::to_string(&config).unwrap()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use crate::board::Board;
+use crate::persistence;
+use crate::persistence::PersistenceError;
+use crossterm::terminal;
+use std::backtrace::Backtrace;
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many colours the terminal can show, as far as its environment variables tell.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ColorSupport {
+    /// `TERM` is `dumb`, so colours shouldn't be used at all.
+    None,
+    /// The 16 basic ANSI colours.
+    Basic,
+    /// The 256 colour palette.
+    Ansi256,
+    /// Any RGB colour.
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Guesses how many colours a terminal can show from its environment variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `term` - The value of `TERM`, if it is set.
+    /// * `colorterm` - The value of `COLORTERM`, if it is set.
+    ///
+    /// # Returns
+    ///
+    /// The `ColorSupport` the variables suggest.
+    pub fn detect(term: Option<&str>, colorterm: Option<&str>) -> ColorSupport {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            ColorSupport::TrueColor
+        } else if term == Some("dumb") {
+            ColorSupport::None
+        } else if term.is_some_and(|term| term.contains("256color")) {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Basic
+        }
+    }
+
+    /// Guesses how many colours the terminal the program runs in can show, from `TERM` and
+    /// `COLORTERM`.
+    pub fn from_env() -> ColorSupport {
+        ColorSupport::detect(
+            env::var("TERM").ok().as_deref(),
+            env::var("COLORTERM").ok().as_deref(),
+        )
+    }
+}
+
+impl Display for ColorSupport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ColorSupport::None => "none",
+                ColorSupport::Basic => "16 colors",
+                ColorSupport::Ansi256 => "256 colors",
+                ColorSupport::TrueColor => "true color",
+            }
+        )
+    }
+}
+
+/// Guesses whether a terminal can show box-drawing characters from its locale. A locale that
+/// isn't UTF-8 can't show them; without a locale, e.g. on Windows, they're assumed to work.
+///
+/// # Arguments
+///
+/// * `locale` - The first of `LC_ALL`, `LC_CTYPE` and `LANG` that is set, if any.
+pub fn supports_box_drawing(locale: Option<&str>) -> bool {
+    locale.is_none_or(|locale| {
+        let locale = locale.to_ascii_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    })
+}
+
+/// Guesses whether the terminal the program runs in can show box-drawing characters, from the
+/// locale set in its environment, like `supports_box_drawing`.
+pub fn supports_box_drawing_from_env() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()));
+    supports_box_drawing(locale.as_deref())
+}
+
+/// Facts about the program and the terminal it runs in, for making rendering bugs reproducible.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostics {
+    pub version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// The terminal's size in columns and rows, or `None` if it couldn't be read, e.g. because
+    /// output isn't going to a terminal.
+    pub terminal_size: Option<(u16, u16)>,
+    pub term: Option<String>,
+    pub colorterm: Option<String>,
+    pub color_support: ColorSupport,
+}
+
+impl Diagnostics {
+    /// Gathers diagnostics about the running program.
+    pub fn gather() -> Diagnostics {
+        let term = env::var("TERM").ok();
+        let colorterm = env::var("COLORTERM").ok();
+        Diagnostics {
+            version: env!("CARGO_PKG_VERSION"),
+            os: env::consts::OS,
+            arch: env::consts::ARCH,
+            terminal_size: terminal::size().ok(),
+            color_support: ColorSupport::detect(term.as_deref(), colorterm.as_deref()),
+            term,
+            colorterm,
+        }
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let or_unset = |value: &Option<String>| value.clone().unwrap_or("(not set)".to_string());
+        writeln!(f, "rs2048 {}", self.version)?;
+        writeln!(f, "OS: {} ({})", self.os, self.arch)?;
+        match self.terminal_size {
+            Some((columns, rows)) => writeln!(f, "Terminal size: {}x{}", columns, rows)?,
+            None => writeln!(f, "Terminal size: unknown")?,
+        }
+        writeln!(f, "TERM: {}", or_unset(&self.term))?;
+        writeln!(f, "COLORTERM: {}", or_unset(&self.colorterm))?;
+        write!(f, "Colors: {}", self.color_support)
+    }
+}
+
+/// Saves a report about a panic, along with the diagnostics, in the data directory.
+///
+/// # Arguments
+///
+/// * `panic_message` - What panicked and where, as given by the panic hook.
+/// * `diagnostics` - The diagnostics to include.
+/// * `board` - The board of the game in progress, if there is one, written with
+///   `Board::to_ascii`.
+///
+/// # Returns
+///
+/// The path of the report, or a `PersistenceError` if it couldn't be written.
+pub fn write_crash_report(
+    panic_message: &str,
+    diagnostics: &Diagnostics,
+    board: Option<&Board>,
+) -> Result<PathBuf, PersistenceError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let board = board.map_or("none".to_string(), Board::to_ascii);
+    let report = format!(
+        "{}\n\n{}\nBoard: {}\n\nBacktrace:\n{}\n",
+        panic_message,
+        diagnostics,
+        board,
+        Backtrace::force_capture()
+    );
+    persistence::write_data_file(&format!("crash-reports/crash-{}.txt", now), &report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_color_support() {
+        assert_eq!(
+            ColorSupport::TrueColor,
+            ColorSupport::detect(Some("xterm-256color"), Some("truecolor"))
+        );
+        assert_eq!(
+            ColorSupport::Ansi256,
+            ColorSupport::detect(Some("screen-256color"), None)
+        );
+        assert_eq!(
+            ColorSupport::Basic,
+            ColorSupport::detect(Some("xterm"), None)
+        );
+        assert_eq!(ColorSupport::Basic, ColorSupport::detect(None, None));
+        assert_eq!(ColorSupport::None, ColorSupport::detect(Some("dumb"), None));
+    }
+
+    #[test]
+    fn box_drawing_needs_a_utf8_locale() {
+        assert!(supports_box_drawing(Some("en_US.UTF-8")));
+        assert!(supports_box_drawing(Some("de_DE.utf8")));
+        assert!(supports_box_drawing(None));
+        assert!(!supports_box_drawing(Some("C")));
+        assert!(!supports_box_drawing(Some("POSIX")));
+    }
+
+    #[test]
+    fn report_lists_everything() {
+        let diagnostics = Diagnostics {
+            version: "1.2.3",
+            os: "linux",
+            arch: "x86_64",
+            terminal_size: Some((80, 24)),
+            term: Some("xterm-256color".to_string()),
+            colorterm: None,
+            color_support: ColorSupport::Ansi256,
+        };
+
+        assert_eq!(
+            "rs2048 1.2.3\n\
+             OS: linux (x86_64)\n\
+             Terminal size: 80x24\n\
+             TERM: xterm-256color\n\
+             COLORTERM: (not set)\n\
+             Colors: 256 colors",
+            diagnostics.to_string()
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::board::{Board, Direction};
+use crate::solver;
+use crate::solver::{EvalWeights, Solver};
+
+/// A position read from a positions file, with where it came from so that results and errors
+/// can point back to it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Position {
+    /// The line of the file the position is on, counting from 1.
+    pub line: usize,
+    pub board: Board,
+    /// The moves the position's author considers best, or empty if the file doesn't say.
+    pub expected: Vec<Direction>,
+}
+
+/// What the solver made of one position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionResult {
+    pub position: Position,
+    /// The evaluation function's value of the board as it is.
+    pub evaluation: f64,
+    /// The expected evaluation of each move that changes the board, in `Direction::ALL` order.
+    pub moves: Vec<(Direction, f64)>,
+    /// The move the solver would play, or `None` if no move changes the board.
+    pub best_move: Option<Direction>,
+}
+
+impl PositionResult {
+    /// Whether the solver chose one of the expected moves.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the position has no expected moves, otherwise whether the best move is one of
+    /// them.
+    pub fn as_expected(&self) -> Option<bool> {
+        if self.position.expected.is_empty() {
+            return None;
+        }
+        Some(
+            self.best_move
+                .is_some_and(|best_move| self.position.expected.contains(&best_move)),
+        )
+    }
+}
+
+/// How many moves the solver looks ahead when evaluating positions unless told otherwise. The
+/// positions in `test_data/classic_positions.txt` are checked at this depth.
+pub const DEFAULT_DEPTH: u32 = 2;
+
+/// Reads a positions file, which has one board per line in the notation read by
+/// `Board::from_notation`, optionally followed by `->` and the moves expected to be best
+/// separated by `|`, e.g. `2,2/.. -> left|right`. Blank lines and everything after a `#` are
+/// ignored, so positions can be grouped and commented.
+///
+/// # Arguments
+///
+/// * `contents` - The contents of the file.
+///
+/// # Returns
+///
+/// The positions in the order they appear, or an error message naming the first line that isn't
+/// a valid board.
+pub fn read_positions(contents: &str) -> Result<Vec<Position>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.split('#').next().unwrap_or_default().trim()))
+        .filter(|(_, text)| !text.is_empty())
+        .map(|(line, text)| {
+            let (notation, expected) = text.split_once("->").unwrap_or((text, ""));
+            let board = Board::from_notation(notation)
+                .map_err(|e| format!("Line {} isn't a valid board: {}", line, e))?;
+            let expected = expected
+                .split('|')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    Direction::from_name(name)
+                        .ok_or_else(|| format!("Line {} has an unknown move '{}'", line, name))
+                })
+                .collect::<Result<Vec<Direction>, String>>()?;
+            Ok(Position {
+                line,
+                board,
+                expected,
+            })
+        })
+        .collect()
+}
+
+/// Runs the evaluation function and the best-move search on every position.
+///
+/// # Arguments
+///
+/// * `positions` - The positions to evaluate.
+/// * `weights` - How boards are evaluated.
+/// * `depth` - How many moves the solver looks ahead.
+///
+/// # Returns
+///
+/// A result for each position, in the same order.
+pub fn evaluate_positions(
+    positions: Vec<Position>,
+    weights: EvalWeights,
+    depth: u32,
+) -> Vec<PositionResult> {
+    let solver = Solver::new(weights, depth);
+    positions
+        .into_iter()
+        .map(|position| {
+            let moves = solver.evaluate_moves(&position.board);
+            let best_move = moves
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|&(direction, _)| direction);
+            PositionResult {
+                evaluation: solver::evaluate(&position.board, &weights),
+                moves,
+                best_move,
+                position,
+            }
+        })
+        .collect()
+}
+
+/// Writes the results as CSV with a header row, one row per position. Moves that don't change
+/// the board, positions with no best move, and positions without expected moves have empty
+/// cells.
+pub fn to_csv(results: &[PositionResult]) -> String {
+    let mut output = String::from(
+        "line,position,evaluation,up,down,left,right,best_move,expected,as_expected\n",
+    );
+    for result in results {
+        let move_values = Direction::ALL.map(|direction| {
+            result
+                .moves
+                .iter()
+                .find(|&&(candidate, _)| candidate == direction)
+                .map_or(String::new(), |(_, value)| format!("{:.3}", value))
+        });
+        let expected: Vec<&str> = result
+            .position
+            .expected
+            .iter()
+            .map(|direction| direction.name())
+            .collect();
+        output += &format!(
+            "{},\"{}\",{:.3},{},{},{},{}\n",
+            result.position.line,
+            result.position.board.to_notation(),
+            result.evaluation,
+            move_values.join(","),
+            result.best_move.map_or("", Direction::name),
+            expected.join("|"),
+            result
+                .as_expected()
+                .map_or(String::new(), |as_expected| as_expected.to_string())
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_positions_skipping_comments() {
+        let positions =
+            read_positions("# corner stacking\n2,4/.. # almost stuck\n\n  8./.2\n").unwrap();
+
+        assert_eq!(
+            vec![2, 4],
+            positions.iter().map(|p| p.line).collect::<Vec<_>>()
+        );
+        assert_eq!("8./.2", positions[1].board.to_notation());
+    }
+
+    #[test]
+    fn names_the_bad_line() {
+        let error = read_positions("2./..\n3./..\n").unwrap_err();
+
+        assert!(error.starts_with("Line 2 "), "{error}");
+    }
+
+    #[test]
+    fn csv_has_a_row_per_position() {
+        let positions = read_positions("2,4/4,2\n2.../..../..../...2\n").unwrap();
+        let csv = to_csv(&evaluate_positions(positions, EvalWeights::default(), 1));
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(3, lines.len());
+        assert!(lines[1].starts_with("1,\"2,4/4,2\","));
+        assert!(
+            lines[1].ends_with(",,,,,,,"),
+            "a stuck board has no moves: {}",
+            lines[1]
+        );
+        assert!(
+            lines[2].ends_with(",,") && !lines[2].ends_with(",,,"),
+            "a best move but no expected moves: {}",
+            lines[2]
+        );
+    }
+
+    #[test]
+    fn reads_expected_moves() {
+        let positions = read_positions("2,2/.. -> left | right\n2./.. -> sideways\n");
+
+        assert_eq!(
+            Err("Line 2 has an unknown move 'sideways'".to_string()),
+            positions
+        );
+        let positions = read_positions("2,2/.. -> left | right").unwrap();
+        assert_eq!(
+            vec![Direction::Left, Direction::Right],
+            positions[0].expected
+        );
+    }
+
+    #[test]
+    fn solver_finds_the_classic_best_moves() {
+        let positions = read_positions(include_str!("../test_data/classic_positions.txt")).unwrap();
+        assert!(positions
+            .iter()
+            .all(|position| !position.expected.is_empty()));
+
+        for result in evaluate_positions(positions, EvalWeights::default(), DEFAULT_DEPTH) {
+            assert_eq!(
+                Some(true),
+                result.as_expected(),
+                "line {}: chose {:?}, expected one of {:?}",
+                result.position.line,
+                result.best_move,
+                result.position.expected
+            );
+        }
+    }
+}
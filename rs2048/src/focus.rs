@@ -0,0 +1,138 @@
+use crate::theme::UiColors;
+use crossterm::style::{StyledContent, Stylize};
+use serde::{Deserialize, Serialize};
+
+/// How the selected option in menus and lists is marked, set with `focus_indicator` in the config
+/// file.
+///
+/// Only `Color` relies on colour alone. The others stay visible on terminals with a remapped
+/// palette and to players who can't tell the theme's highlight colour from its text.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusIndicator {
+    /// The selected line is drawn with the text and background colours swapped.
+    #[default]
+    Inverted,
+    /// The selected line starts with "▶ ".
+    Arrow,
+    /// The selected line is underlined.
+    Underline,
+    /// The selected line is drawn in the theme's highlight colour.
+    Color,
+}
+
+impl FocusIndicator {
+    const ALL: [FocusIndicator; 4] = [
+        FocusIndicator::Inverted,
+        FocusIndicator::Arrow,
+        FocusIndicator::Underline,
+        FocusIndicator::Color,
+    ];
+
+    /// What goes in front of the selected line when using `Arrow`.
+    const ARROW: &'static str = "▶ ";
+
+    /// Gets the name of the indicator shown in the settings screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            FocusIndicator::Inverted => "Inverted",
+            FocusIndicator::Arrow => "Arrow",
+            FocusIndicator::Underline => "Underline",
+            FocusIndicator::Color => "Color",
+        }
+    }
+
+    /// Gets the next indicator, wrapping around from the last back to the first.
+    pub fn next(self) -> FocusIndicator {
+        let index = FocusIndicator::ALL
+            .iter()
+            .position(|&indicator| indicator == self)
+            .unwrap_or(0);
+        FocusIndicator::ALL[(index + 1) % FocusIndicator::ALL.len()]
+    }
+
+    /// Gets how many columns `prefix` adds to every line.
+    pub fn prefix_width(self) -> usize {
+        match self {
+            FocusIndicator::Arrow => FocusIndicator::ARROW.chars().count(),
+            _ => 0,
+        }
+    }
+
+    /// Puts the arrow in front of the selected line when using `Arrow`, and the same amount of
+    /// space in front of the other lines so that they stay lined up.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the line.
+    /// * `selected` - Whether the line is the selected one.
+    ///
+    /// # Returns
+    ///
+    /// The text with its prefix, which is `text` unchanged for the other indicators.
+    pub fn prefix(self, text: &str, selected: bool) -> String {
+        match (self, selected) {
+            (FocusIndicator::Arrow, true) => format!("{}{}", FocusIndicator::ARROW, text),
+            (FocusIndicator::Arrow, false) => format!("  {}", text),
+            _ => text.to_string(),
+        }
+    }
+
+    /// Styles a line of a menu or list. Lines that aren't selected are drawn in the text colour.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The line, already prefixed and padded.
+    /// * `selected` - Whether the line is the selected one.
+    /// * `colors` - The theme's interface colours.
+    ///
+    /// # Returns
+    ///
+    /// The styled line, ready to be printed.
+    pub fn style(self, text: String, selected: bool, colors: &UiColors) -> StyledContent<String> {
+        if !selected {
+            return text.with(colors.text.foreground);
+        }
+        match self {
+            FocusIndicator::Inverted => {
+                text.with(colors.text.background).on(colors.text.foreground)
+            }
+            FocusIndicator::Arrow => text.with(colors.highlight),
+            FocusIndicator::Underline => text.with(colors.highlight).underlined(),
+            FocusIndicator::Color => text.with(colors.highlight),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+
+    #[test]
+    fn arrow_keeps_lines_lined_up() {
+        let indicator = FocusIndicator::Arrow;
+
+        assert_eq!("▶ New Game", indicator.prefix("New Game", true));
+        assert_eq!("  Quit", indicator.prefix("Quit", false));
+        assert_eq!(2, indicator.prefix_width());
+        assert_eq!("Quit", FocusIndicator::Underline.prefix("Quit", true));
+    }
+
+    #[test]
+    fn only_color_relies_on_color() {
+        for indicator in FocusIndicator::ALL {
+            let styled = indicator.style("Quit".to_string(), true, &Theme::classic().ui);
+            let marked = indicator.prefix("Quit", true) != "Quit"
+                || styled.style().background_color.is_some()
+                || !styled.style().attributes.is_empty();
+            assert_eq!(indicator != FocusIndicator::Color, marked, "{indicator:?}");
+        }
+    }
+
+    #[test]
+    fn indicators_cycle() {
+        assert_eq!(FocusIndicator::Arrow, FocusIndicator::Inverted.next());
+        assert_eq!(FocusIndicator::Inverted, FocusIndicator::Color.next());
+    }
+}
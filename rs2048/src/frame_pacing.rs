@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Watches how long the terminal takes to take in each frame, and decides when it can't keep up,
+/// e.g. a Windows console or a slow ssh connection. The screens then draw less: animations are
+/// skipped, and the game screen only redraws its clock when nothing else changed.
+///
+/// The decision is only changed once the terminal has been slow or fast for a few frames in a
+/// row, and the thresholds for throttling and for going back to full frames are apart, so a
+/// single slow frame doesn't make the screens flicker between the two.
+#[derive(Debug, Clone, Default)]
+pub struct FramePacer {
+    /// How long the latest frames took to flush, oldest first.
+    latencies: VecDeque<Duration>,
+    throttled: bool,
+}
+
+impl FramePacer {
+    /// How many of the latest frames the decision is based on.
+    const WINDOW: usize = 8;
+    /// How long frames may take to flush on average before the screens draw less. Slides are
+    /// shown for 40 ms a step, so slower terminals can't show them anyway.
+    const SLOW: Duration = Duration::from_millis(25);
+    /// How quickly frames have to flush on average before the screens draw everything again.
+    const FAST: Duration = Duration::from_millis(10);
+
+    pub fn new() -> FramePacer {
+        FramePacer::default()
+    }
+
+    /// Records how long a frame took to flush to the terminal.
+    ///
+    /// # Arguments
+    ///
+    /// * `latency` - The time from starting to write the frame until it was flushed.
+    ///
+    /// # Returns
+    ///
+    /// Whether this changed the decision to throttle.
+    pub fn record(&mut self, latency: Duration) -> bool {
+        if self.latencies.len() == FramePacer::WINDOW {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+        if self.latencies.len() < FramePacer::WINDOW {
+            return false;
+        }
+        let average = self.average();
+        let throttled = if self.throttled {
+            average > FramePacer::FAST
+        } else {
+            average > FramePacer::SLOW
+        };
+        let changed = throttled != self.throttled;
+        self.throttled = throttled;
+        changed
+    }
+
+    /// Gets how long the latest frames took to flush on average, or zero before any frame.
+    pub fn average(&self) -> Duration {
+        let total: Duration = self.latencies.iter().sum();
+        total
+            .checked_div(self.latencies.len() as u32)
+            .unwrap_or_default()
+    }
+
+    /// Gets whether the terminal can't keep up, so the screens should draw less.
+    pub fn is_throttled(&self) -> bool {
+        self.throttled
+    }
+
+    /// Describes the latest frames and the decision, for the debug overlay, e.g.
+    /// "Flush: 3.2 ms, full frames".
+    pub fn describe(&self) -> String {
+        format!(
+            "Flush: {:.1} ms, {}",
+            self.average().as_secs_f64() * 1000.0,
+            if self.throttled {
+                "throttled"
+            } else {
+                "full frames"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_many(pacer: &mut FramePacer, millis: u64, frames: usize) -> bool {
+        (0..frames).fold(false, |changed, _| {
+            pacer.record(Duration::from_millis(millis)) || changed
+        })
+    }
+
+    #[test]
+    fn fast_terminals_get_full_frames() {
+        let mut pacer = FramePacer::new();
+
+        assert!(!record_many(&mut pacer, 2, 20));
+        assert!(!pacer.is_throttled());
+        assert_eq!("Flush: 2.0 ms, full frames", pacer.describe());
+    }
+
+    #[test]
+    fn slow_terminals_are_throttled_until_they_catch_up() {
+        let mut pacer = FramePacer::new();
+
+        // a single slow frame isn't enough
+        record_many(&mut pacer, 2, 7);
+        assert!(!pacer.record(Duration::from_millis(100)));
+        assert!(!pacer.is_throttled());
+
+        assert!(record_many(&mut pacer, 40, 8));
+        assert!(pacer.is_throttled());
+        // between the thresholds the decision stays
+        assert!(!record_many(&mut pacer, 15, 8));
+        assert!(pacer.is_throttled());
+        assert!(record_many(&mut pacer, 5, 8));
+        assert!(!pacer.is_throttled());
+    }
+}
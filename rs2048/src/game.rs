@@ -1,69 +1,441 @@
-use crate::board::{Board, TileType};
+use crate::board::{Board, Direction, TileMovement, TileType};
 use crate::game::GameError::AddRandomTileError;
+use crate::game_stats::GameStats;
+use crate::history::{History, HistoryLimits, SpillFile};
+use crate::rules;
+use crate::rules::Rules;
+use crate::spawn::SpawnWeights;
+use crate::tile;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{io, iter};
 
 #[derive(Debug, Clone)]
 pub struct Game {
     board: Board,
     score: u32,
-    is_game_over: bool,
-    game_over_reason: Option<String>,
+    /// Why the game ended, or `None` while it's still going.
+    game_over_reason: Option<GameOverReason>,
+    /// Whether the winning tile has been made, and if so whether the player chose to go on.
+    win_state: WinState,
+    /// Decides where new tiles appear, so that the same moves from the same seed always give the
+    /// same board, even after undoing.
+    seed: u64,
+    /// The rules the game is played by, e.g. how the seed is used to place new tiles and how long
+    /// the game may last.
+    rules: Rules,
+    /// How many more moves may be undone under the rules' undo limit, or `None` if there's no
+    /// limit.
+    undos_left: Option<u32>,
+    /// The score after each move, oldest first.
+    score_history: History<u32>,
+    /// The board and score before each move, most recent last.
+    undo_stack: History<(Board, u32)>,
+    /// The moves that led to the board, oldest first. Replaying them from the seed gives the same
+    /// game.
+    moves: History<MoveRecord>,
+    /// Where the moves forgotten by `moves` are written, if anywhere.
+    move_spill: Option<Arc<SpillFile>>,
+    /// When the first move was made, in milliseconds since the Unix epoch, even if it's forgotten.
+    first_move_at: Option<u64>,
+    /// How long the game had been played when the first move was made, in milliseconds, not
+    /// counting pauses. The time limit's clock starts then, and `None` means it hasn't started.
+    time_limit_started: Option<u64>,
+    /// The moves that were undone, most recently undone last, so that they can be redone until a
+    /// new move is made. It can't grow past the undo stack.
+    redo_stack: Vec<MoveRecord>,
+    /// The exponent of the biggest tile made in this game, which undoing doesn't take back.
+    highest_tile: TileType,
+    /// How long the game was played before the clock was last paused, in milliseconds.
+    play_time: u64,
+    /// When the clock was last started, in milliseconds since the Unix epoch, or `None` while
+    /// it's stopped, i.e. while the game is paused or over.
+    resumed_at: Option<u64>,
+    /// Whether the player paused the game, which freezes it until `GameEvent::Resume`.
+    paused: bool,
+    /// Whether the player had help: a move undone under rules that don't limit undoing, a hint
+    /// from the coach, or a move made by auto-play. It keeps the game off the best scores.
+    assisted: bool,
+    /// Whether the game has been counted in the statistics, which happens once however often it
+    /// ends, e.g. when a lost game is undone and lost again.
+    recorded: bool,
+    /// The move each tile appeared in, by row and column, so that zen mode takes off the oldest
+    /// tiles first. It's only kept in zen mode, and starts over when a move is undone or the game
+    /// is restored, since those don't say how old the tiles are.
+    tile_ages: Vec<Vec<usize>>,
+    /// What has happened over the game, for its summary.
+    stats: GameStats,
 }
 
+/// A move that changed the board, and when it was made.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MoveRecord {
+    pub direction: Direction,
+    /// When the move was made, in milliseconds since the Unix epoch.
+    pub played_at: u64,
+}
+
+/// A position in a replay, as given by `Replay`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReplayPosition {
+    pub board: Board,
+    pub score: u32,
+    /// The move that led to the position, or `None` for the position the game started from.
+    pub last_move: Option<MoveRecord>,
+}
+
+/// Why a game ended.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GameOverReason {
+    /// The board is full and no two neighbouring tiles can merge.
+    NoMovesLeft,
+    /// The game's time limit passed.
+    TimeExpired {
+        /// The time limit, in seconds.
+        limit: u64,
+    },
+    /// The game reached its move limit.
+    MoveLimitReached { limit: u32 },
+}
+
+impl Display for GameOverReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameOverReason::NoMovesLeft => write!(f, "The board is full and nothing can merge"),
+            GameOverReason::TimeExpired { limit } => {
+                write!(f, "Time's up after {}", rules::format_duration(*limit))
+            }
+            GameOverReason::MoveLimitReached { limit } => {
+                write!(f, "The limit of {} moves was reached", limit)
+            }
+        }
+    }
+}
+
+/// Where a game stands, as given by `Game::state`, e.g. for the UI to decide what to show.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameState {
+    /// Moves can be made, and the winning tile hasn't been made yet.
+    Playing,
+    /// The winning tile was made. Until the player chooses to go on, `continued` is `false`
+    /// and swipes are ignored; afterwards the game goes on in endless mode.
+    Won { continued: bool },
+    /// The game is over and can only be undone or replaced.
+    Lost(GameOverReason),
+    /// The player paused the game, which ignores moves and stops the clock until it's resumed.
+    Paused,
+}
+
+/// The exponent of the tile that wins the game under the normal rules, i.e. 2048.
+pub const WINNING_TILE: TileType = 11;
+
+/// How far a game is from being won.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WinState {
+    /// No winning tile, `Rules::win_exponent`, is on the board.
+    #[default]
+    Playing,
+    /// A winning tile was just made. Swipes are ignored until the player chooses to keep
+    /// playing or undoes the winning move.
+    Won,
+    /// The game was won and goes on in endless mode.
+    KeepPlaying,
+}
+
+#[allow(dead_code)] // saving and loading aren't implemented yet
 pub enum GameEvent {
     SwipeUp,
     SwipeDown,
     SwipeLeft,
     SwipeRight,
     Undo,
+    /// Makes the last undone move again, if no other move was made since.
+    Redo,
+    /// Goes on playing after winning, with no further win to reach.
+    KeepPlaying,
+    /// Stops the clock and ignores moves, undos and redos until `GameEvent::Resume`.
+    Pause,
+    /// Goes on with a paused game.
+    Resume,
     SaveGame,
     LoadGame,
     NewGame,
 }
 
+impl From<Direction> for GameEvent {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => GameEvent::SwipeUp,
+            Direction::Down => GameEvent::SwipeDown,
+            Direction::Left => GameEvent::SwipeLeft,
+            Direction::Right => GameEvent::SwipeRight,
+        }
+    }
+}
+
+/// What a move does to the board, before a new tile appears.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MergeOutcome {
+    /// The board after the move.
+    pub board: Board,
+    /// The points the move scores.
+    pub points: u32,
+}
+
+/// What `Game::handle_event` did, so that the move can be animated instead of the board
+/// snapping to where it ends up.
+#[derive(Debug)]
+pub struct GameUpdate {
+    /// The game after the event.
+    pub game: Game,
+    /// Where each tile went, if the event was a move that changed the board. Tiles that stayed
+    /// where they were are included too.
+    pub movements: Vec<TileMovement>,
+    /// The row and column of the tile that appeared after the move, if one did.
+    pub spawned: Option<(usize, usize)>,
+}
+
+impl From<Game> for GameUpdate {
+    /// Wraps a game whose board didn't move, e.g. after an undo or a refused move.
+    fn from(game: Game) -> Self {
+        GameUpdate {
+            game,
+            movements: Vec::new(),
+            spawned: None,
+        }
+    }
+}
+
+impl GameUpdate {
+    /// Gets the boards to show while the tiles slide, one step further for each. A tile moves
+    /// at most one cell per step, straight to where it ends up, so tiles that wrapped around the
+    /// edge cross the board. Merged tiles keep their old values until the last board, which is
+    /// the board of the game without the new tile, so that it appears after everything else.
+    ///
+    /// # Returns
+    ///
+    /// The rows of each board between the one before the move and the one after, which is empty
+    /// if the event wasn't a move.
+    pub fn slide_frames(&self) -> Vec<Vec<Vec<TileType>>> {
+        let Some((spawn_row, spawn_column)) = self.spawned else {
+            return Vec::new();
+        };
+        let size = self.game.read_board_state().len();
+        let distance = |from: usize, to: usize| from.abs_diff(to);
+        let steps = self
+            .movements
+            .iter()
+            .map(|movement| {
+                distance(movement.from.0, movement.to.0)
+                    .max(distance(movement.from.1, movement.to.1))
+            })
+            .max()
+            .unwrap_or(0);
+        let step_towards = |from: usize, to: usize, step: usize| {
+            if from < to {
+                from + step.min(to - from)
+            } else {
+                from - step.min(from - to)
+            }
+        };
+        let mut frames: Vec<Vec<Vec<TileType>>> = (1..steps)
+            .map(|step| {
+                let mut frame = vec![vec![0; size]; size];
+                for movement in &self.movements {
+                    let row = step_towards(movement.from.0, movement.to.0, step);
+                    let column = step_towards(movement.from.1, movement.to.1, step);
+                    frame[row][column] = movement.tile;
+                }
+                frame
+            })
+            .collect();
+        let mut landed = self.game.read_board_state().clone();
+        landed[spawn_row][spawn_column] = 0;
+        frames.push(landed);
+        frames
+    }
+}
+
+/// What a move would do if the player made it now, from `Game::peek_move`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct MovePreview {
+    /// Whether the move would change the board. It doesn't when nothing can slide or merge, or
+    /// when the game is ignoring moves, e.g. because it's paused or over.
+    pub changes_board: bool,
+    /// The points the move would score.
+    pub points: u32,
+}
+
 #[derive(Debug)]
 pub enum GameError {
     AddRandomTileError,
+    /// The rules asked for a board smaller than `Rules::MIN_BOARD_SIZE` or bigger than
+    /// `Rules::MAX_BOARD_SIZE`.
+    InvalidBoardSize(usize),
+}
+
+/// A copy of what can be seen of a game at one moment, for code that shows or sends a game but
+/// mustn't change it, like the renderers and the JSON-RPC server.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct GameView {
+    /// The rows of the board. Tiles are stored as exponents like on a `Board`, but serialized as
+    /// their values, with `0` for empty cells.
+    #[serde(serialize_with = "serialize_tile_values")]
+    pub board: Vec<Vec<TileType>>,
+    pub score: u32,
+    /// How many moves led to the board.
+    pub moves: usize,
+    #[serde(rename = "won")]
+    pub has_won: bool,
+    /// Whether the game is going on, won, lost or paused.
+    pub state: GameState,
+    /// How many more moves may be undone, or `None` if there's no undo limit.
+    pub undos_left: Option<u32>,
+}
+
+/// Gets the current time in milliseconds since the Unix epoch, as moves and the clock record it.
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+fn serialize_tile_values<S: serde::Serializer>(
+    board: &[Vec<TileType>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let values: Vec<Vec<u64>> = board
+        .iter()
+        .map(|row| row.iter().map(|&tile| tile::value(tile)).collect())
+        .collect();
+    values.serialize(serializer)
 }
 
 impl Game {
-    // Game is intended to be immutable. This function will consume the Game and return a new one.
-    pub fn handle_event(mut self, event: GameEvent) -> Result<Game, GameError> {
+    // Game is intended to be immutable. This function will consume the Game and return a new one,
+    // together with what happened to the tiles.
+    #[tracing::instrument(level = "debug", name = "move", skip_all)]
+    pub fn handle_event(mut self, event: GameEvent) -> Result<GameUpdate, GameError> {
+        let is_swipe = matches!(
+            event,
+            GameEvent::SwipeUp
+                | GameEvent::SwipeDown
+                | GameEvent::SwipeLeft
+                | GameEvent::SwipeRight
+        );
+        // a limit can end the game while moves are still possible, so don't rely on the board
+        if is_swipe && (self.is_game_over() || self.win_state == WinState::Won) {
+            return Ok(self.into());
+        }
+        let changes_board = is_swipe || matches!(event, GameEvent::Undo | GameEvent::Redo);
+        if changes_board && self.paused {
+            return Ok(self.into());
+        }
         match event {
-            GameEvent::SwipeUp => {
-                let before = self.clone();
-                self.board.merge_up();
-                if self.board != before.board {
-                    self.board.add_random_tile().or(Err(AddRandomTileError))?;
-                }
-                Ok(self)
+            GameEvent::SwipeUp
+            | GameEvent::SwipeDown
+            | GameEvent::SwipeLeft
+            | GameEvent::SwipeRight => {
+                let direction = match event {
+                    GameEvent::SwipeUp => Direction::Up,
+                    GameEvent::SwipeDown => Direction::Down,
+                    GameEvent::SwipeLeft => Direction::Left,
+                    _ => Direction::Right,
+                };
+                let Some(outcome) = self.simulate_move(direction) else {
+                    return Ok(self.into());
+                };
+                let movements = self.trace_move(direction);
+                let before = (
+                    std::mem::replace(&mut self.board, outcome.board),
+                    self.score,
+                );
+                self.score += outcome.points;
+                // each merge moves its tiles into one cell, which holds the tile it made
+                let mut merged_into: Vec<(usize, usize)> = movements
+                    .iter()
+                    .filter(|movement| movement.merged)
+                    .map(|movement| movement.to)
+                    .collect();
+                merged_into.sort_unstable();
+                merged_into.dedup();
+                let rows = self.board.get_data_for_display();
+                self.stats.record_move(
+                    direction,
+                    outcome.points,
+                    merged_into
+                        .into_iter()
+                        .map(|(row, column)| rows[row][column])
+                        .filter(|&tile| tile != 0),
+                );
+                let spawned = self.finish_move(before, direction, &movements)?;
+                Ok(GameUpdate {
+                    game: self,
+                    movements,
+                    spawned: Some(spawned),
+                })
             }
-            GameEvent::SwipeDown => {
-                let before = self.clone();
-                self.board.merge_down();
-                if self.board != before.board {
-                    self.board.add_random_tile().or(Err(AddRandomTileError))?;
+            GameEvent::Undo => {
+                if !self.can_undo() {
+                    return Ok(self.into());
                 }
-                Ok(self)
+                if let Some((board, score)) = self.undo_stack.pop() {
+                    self.undos_left = self.undos_left.map(|undos_left| undos_left - 1);
+                    self.stats.record_undo();
+                    self.assisted |= self.rules.undo_assists();
+                    self.board = board;
+                    self.score = score;
+                    self.score_history.pop();
+                    self.redo_stack.extend(self.moves.pop());
+                    self.tile_ages.clear();
+                    if self.moves.total_len() == 0 {
+                        self.first_move_at = None;
+                        self.time_limit_started = None;
+                    }
+                    // there was a possible move from every position before the last one, and
+                    // undoing brings the game back under its move limit, but not back in time
+                    self.game_over_reason = None;
+                    self.start_clock(now_millis());
+                    self.check_win();
+                }
+                Ok(self.into())
             }
-            GameEvent::SwipeLeft => {
-                let before = self.clone();
-                self.board.merge_left();
-                if self.board != before.board {
-                    self.board.add_random_tile().or(Err(AddRandomTileError))?;
+            GameEvent::Redo => {
+                let Some(record) = self.redo_stack.pop() else {
+                    return Ok(self.into());
+                };
+                // making the move clears the redo stack, so keep the rest of it aside
+                let mut redo_stack = std::mem::take(&mut self.redo_stack);
+                let moves = self.moves.total_len();
+                let mut update = self.handle_event(record.direction.into())?;
+                if update.game.moves.total_len() == moves {
+                    // the move was refused, e.g. because the game is over
+                    redo_stack.push(record);
                 }
-                Ok(self)
+                update.game.redo_stack = redo_stack;
+                Ok(update)
             }
-            GameEvent::SwipeRight => {
-                let before = self.clone();
-                self.board.merge_right();
-                if self.board != before.board {
-                    self.board.add_random_tile().or(Err(AddRandomTileError))?;
+            GameEvent::KeepPlaying => {
+                if self.win_state == WinState::Won {
+                    self.win_state = WinState::KeepPlaying;
                 }
-                Ok(self)
+                Ok(self.into())
             }
-            GameEvent::Undo => {
-                todo!()
+            GameEvent::Pause => {
+                self.pause(now_millis());
+                Ok(self.into())
+            }
+            GameEvent::Resume => {
+                self.resume(now_millis());
+                Ok(self.into())
             }
             GameEvent::SaveGame => {
                 todo!()
@@ -71,23 +443,965 @@ impl Game {
             GameEvent::LoadGame => {
                 todo!()
             }
-            GameEvent::NewGame => Game::start_new_game(),
+            GameEvent::NewGame => Game::start_new_game_with(self.rules).map(GameUpdate::from),
         }
     }
     pub fn start_new_game() -> Result<Game, GameError> {
+        Game::start_new_game_with(Rules::default())
+    }
+
+    /// Starts a new game with rules other than the normal ones, e.g. for practice or a timed game.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The rules the game is played by.
+    ///
+    /// # Returns
+    ///
+    /// The new `Game`, or `GameError::InvalidBoardSize` if the rules' board size isn't supported.
+    pub fn start_new_game_with(rules: Rules) -> Result<Game, GameError> {
+        Game::start_seeded_game(rand::random(), rules)
+    }
+
+    /// Starts a new game whose tiles are decided by a given seed, e.g. so that two players race
+    /// on the same tiles.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed that decides where new tiles appear.
+    /// * `rules` - The rules the game is played by.
+    ///
+    /// # Returns
+    ///
+    /// The new `Game`, or `GameError::InvalidBoardSize` if the rules' board size isn't supported.
+    pub fn start_seeded_game(seed: u64, rules: Rules) -> Result<Game, GameError> {
+        if !(Rules::MIN_BOARD_SIZE..=Rules::MAX_BOARD_SIZE).contains(&rules.board_size) {
+            return Err(GameError::InvalidBoardSize(rules.board_size));
+        }
+        Ok(Game::with_seed(seed, rules))
+    }
+
+    /// Starts a new game under the normal rules on a board of another size, e.g. 5x5.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - How many cells wide and tall the board is.
+    ///
+    /// # Returns
+    ///
+    /// The new `Game`, or `GameError::InvalidBoardSize` if the size isn't supported.
+    pub fn start_new_game_with_size(size: usize) -> Result<Game, GameError> {
+        Game::start_new_game_with(Rules {
+            board_size: size,
+            ..Rules::default()
+        })
+    }
+
+    /// Starts a practice game from a chosen position, e.g. to practice an endgame, played by the
+    /// normal rules on a board of the position's size. The position wasn't reached by play, so the
+    /// game is marked assisted and kept off the best scores.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The tiles to start with.
+    /// * `score` - The score to start with.
+    ///
+    /// # Returns
+    ///
+    /// The new `Game`, or `GameError::InvalidBoardSize` if the board isn't square or its size
+    /// isn't supported.
+    pub fn from_board(board: Board, score: u32) -> Result<Game, GameError> {
+        let rows = board.get_data_for_display();
+        let rules = Rules {
+            board_size: rows.len(),
+            ..Rules::default()
+        };
+        // the moves and their slides assume as many columns as rows
+        let square = rows.iter().all(|row| row.len() == rows.len());
+        if !square || !(Rules::MIN_BOARD_SIZE..=Rules::MAX_BOARD_SIZE).contains(&rules.board_size) {
+            return Err(GameError::InvalidBoardSize(rules.board_size));
+        }
+        let game = Game::restore(
+            board,
+            score,
+            rand::random(),
+            rules,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        Ok(game.with_assisted(true))
+    }
+
+    /// Starts a new game whose tiles are decided by `seed`, played by `rules`.
+    fn with_seed(seed: u64, rules: Rules) -> Game {
         let mut game = Game {
-            board: Board::new(4),
+            board: Board::new(rules.board_size),
             score: 0,
-            is_game_over: false,
             game_over_reason: None,
+            win_state: WinState::Playing,
+            seed,
+            undos_left: rules.undo_limit,
+            rules,
+            score_history: History::default(),
+            undo_stack: History::default(),
+            moves: History::default(),
+            move_spill: None,
+            first_move_at: None,
+            time_limit_started: None,
+            redo_stack: Vec::new(),
+            highest_tile: 0,
+            play_time: 0,
+            resumed_at: Some(now_millis()),
+            paused: false,
+            assisted: false,
+            recorded: false,
+            tile_ages: Vec::new(),
+            stats: GameStats::default(),
+        };
+        game.undo_stack.set_cap(HistoryLimits::default().undo_cap());
+        let mut rng = game.tile_rng();
+        let policy = game.rules.spawn_policy();
+        for turn in 0..game.rules.starting_tile_count() {
+            game.board
+                .add_random_tile(&*policy, turn, &mut rng)
+                .unwrap();
+        }
+        drop(policy);
+        game.highest_tile = game.board.highest_tile();
+        game
+    }
+
+    /// Recreates a game exactly as it was left, e.g. from a save file.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The tiles on the board.
+    /// * `score` - The score so far.
+    /// * `seed` - The seed that decides where new tiles appear.
+    /// * `rules` - The rules the game is played by.
+    /// * `score_history` - The score after each move, oldest first.
+    /// * `undo_stack` - The board and score before each move, most recent last.
+    /// * `moves` - The moves that led to the board, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// The restored `Game`, which is already over if no move is possible on `board` or it has
+    /// reached its move limit, and already won if `board` has the rules' winning tile. Every undo allowed
+    /// by the rules is left; use `with_undos_left` to restore how many were used,
+    /// `with_forgotten_moves` if the history is incomplete, and `GameEvent::KeepPlaying` to carry
+    /// on past a win.
+    pub fn restore(
+        board: Board,
+        score: u32,
+        seed: u64,
+        rules: Rules,
+        score_history: Vec<u32>,
+        undo_stack: Vec<(Board, u32)>,
+        moves: Vec<MoveRecord>,
+    ) -> Game {
+        let highest_tile = undo_stack
+            .iter()
+            .map(|(board, _)| board.highest_tile())
+            .fold(board.highest_tile(), TileType::max);
+        let mut game = Game {
+            board,
+            score,
+            game_over_reason: None,
+            win_state: WinState::Playing,
+            seed,
+            undos_left: rules.undo_limit,
+            rules,
+            score_history: History::new(score_history, 0),
+            undo_stack: History::new(undo_stack, 0),
+            first_move_at: moves.first().map(|record| record.played_at),
+            // how long the game had been played by its first move isn't saved, so a restored
+            // game's time limit counts the time before it too
+            time_limit_started: moves.first().map(|_| 0),
+            moves: History::new(moves, 0),
+            move_spill: None,
+            redo_stack: Vec::new(),
+            highest_tile,
+            play_time: 0,
+            resumed_at: Some(now_millis()),
+            paused: false,
+            assisted: false,
+            recorded: false,
+            tile_ages: Vec::new(),
+            stats: GameStats::default(),
         };
-        game.board.add_random_tile().unwrap();
-        Ok(game)
+        game.undo_stack.set_cap(HistoryLimits::default().undo_cap());
+        game.check_game_over();
+        game.check_win();
+        game
+    }
+
+    /// Sets how many more moves may be undone, e.g. when loading a saved game that used some.
+    ///
+    /// # Arguments
+    ///
+    /// * `undos_left` - How many undos are left. It's capped at the rules' undo limit, and ignored
+    ///   if there's no limit.
+    pub fn with_undos_left(mut self, undos_left: u32) -> Game {
+        self.undos_left = self.rules.undo_limit.map(|limit| undos_left.min(limit));
+        self
+    }
+
+    /// Marks a restored game as assisted, e.g. when loading a saved game that undid moves.
+    ///
+    /// # Arguments
+    ///
+    /// * `assisted` - Whether the player had help, as for `is_assisted`.
+    pub fn with_assisted(mut self, assisted: bool) -> Game {
+        self.assisted = assisted;
+        self
+    }
+
+    /// Marks a restored game as counted in the statistics, e.g. when loading a saved game that
+    /// was.
+    ///
+    /// # Arguments
+    ///
+    /// * `recorded` - Whether the game was counted, as for `is_recorded`.
+    pub fn with_recorded(mut self, recorded: bool) -> Game {
+        self.recorded = recorded;
+        self
+    }
+
+    /// Sets what had happened over a restored game, e.g. when loading a saved game.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The game's statistics when it was saved.
+    pub fn with_stats(mut self, stats: GameStats) -> Game {
+        self.stats = stats;
+        self
+    }
+
+    /// Sets how long a restored game had been played, e.g. when loading a saved game, so that
+    /// `elapsed` carries on from there.
+    ///
+    /// # Arguments
+    ///
+    /// * `play_time` - How long the game was played, in milliseconds, not counting pauses.
+    pub fn with_play_time(mut self, play_time: u64) -> Game {
+        self.play_time = play_time;
+        self
+    }
+
+    /// Tells a restored game that its oldest moves and scores were forgotten, e.g. when loading a
+    /// saved game whose history was capped, so that the moves are counted and the clock of the
+    /// time limit runs from the right move.
+    ///
+    /// # Arguments
+    ///
+    /// * `forgotten` - How many moves were made before the oldest one in the move history.
+    /// * `first_move_at` - When the first move was made, in milliseconds since the Unix epoch.
+    pub fn with_forgotten_moves(mut self, forgotten: usize, first_move_at: Option<u64>) -> Game {
+        if forgotten == 0 {
+            return self;
+        }
+        self.score_history = History::new(self.score_history.as_slice().to_vec(), forgotten);
+        self.moves = History::new(self.moves.as_slice().to_vec(), forgotten);
+        self.first_move_at = first_move_at;
+        if first_move_at.is_some() {
+            self.time_limit_started.get_or_insert(0);
+        }
+        self.check_game_over();
+        self
+    }
+
+    /// Caps how much of the game's history is kept in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The caps.
+    /// * `spill_dir` - Where to write the moves that no longer fit, so that the game can still be
+    ///   replayed, or `None` to forget them.
+    pub fn with_history_limits(mut self, limits: &HistoryLimits, spill_dir: Option<&Path>) -> Game {
+        if limits.moves.is_some() && self.moves.forgotten() == 0 {
+            self.move_spill = spill_dir
+                .and_then(|dir| SpillFile::create(dir, &self.seed.to_string()).ok())
+                .map(Arc::new);
+        }
+        let forgotten = self.moves.forgotten();
+        for (index, record) in self.moves.set_cap(limits.moves).iter().enumerate() {
+            self.spill_move(forgotten + index, record);
+        }
+        self.score_history.set_cap(limits.moves);
+        self.undo_stack.set_cap(limits.undo_cap());
+        self
+    }
+
+    /// Takes a snapshot of the game that can be kept or sent without holding on to the game.
+    pub fn view(&self) -> GameView {
+        GameView {
+            board: self.read_board_state().clone(),
+            score: self.score,
+            moves: self.moves.total_len(),
+            has_won: self.has_won(),
+            state: self.state(),
+            undos_left: self.undos_left,
+        }
     }
 
     pub fn read_board_state(&self) -> &Vec<Vec<TileType>> {
         self.board.get_data_for_display()
     }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Gets the seed that decides where new tiles appear.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Gets the rules the game is played by.
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Gets what has happened over the game, e.g. for its summary.
+    pub fn stats(&self) -> &GameStats {
+        &self.stats
+    }
+
+    /// Gets the score after each move that is still remembered, oldest first.
+    pub fn score_history(&self) -> &[u32] {
+        self.score_history.as_slice()
+    }
+
+    /// Gets the board and score before each move that can be undone, most recent last.
+    pub fn undo_stack(&self) -> &[(Board, u32)] {
+        self.undo_stack.as_slice()
+    }
+
+    /// Gets the odds the next tile spawns with, which follow how well the game has been going
+    /// under adaptive rules. See `Rules::adaptive_spawn_weights`.
+    pub fn spawn_weights(&self) -> SpawnWeights {
+        let recent = std::iter::once(&self.board).chain(
+            self.undo_stack
+                .as_slice()
+                .iter()
+                .rev()
+                .map(|(board, _)| board),
+        );
+        self.rules.adaptive_spawn_weights(recent)
+    }
+
+    /// Gets how many more moves may be undone, or `None` if there's no undo limit.
+    pub fn undos_left(&self) -> Option<u32> {
+        self.undos_left
+    }
+
+    /// Gets whether the rules let a move be undone now: not once their undos are used up, nor
+    /// once the time limit is up, since undoing doesn't give the time back.
+    pub fn can_undo(&self) -> bool {
+        self.undos_left != Some(0)
+            && !matches!(
+                self.game_over_reason,
+                Some(GameOverReason::TimeExpired { .. })
+            )
+    }
+
+    /// Gets whether the player had help: a move undone under rules that don't limit undoing, a
+    /// hint from the coach, or a move made by auto-play. It keeps the game off the best scores.
+    pub fn is_assisted(&self) -> bool {
+        self.assisted
+    }
+
+    /// Marks the game as assisted by help the game itself doesn't see, e.g. the coach's hints.
+    pub fn mark_assisted(&mut self) {
+        self.assisted = true;
+    }
+
+    /// Gets whether the game has been counted in the statistics.
+    pub fn is_recorded(&self) -> bool {
+        self.recorded
+    }
+
+    /// Marks the game as counted in the statistics, so that it isn't counted again.
+    pub fn mark_recorded(&mut self) {
+        self.recorded = true;
+    }
+
+    /// Gets whether undoing now would mark the game as assisted, so that the player can be asked
+    /// first.
+    pub fn undo_would_assist(&self) -> bool {
+        self.rules.undo_assists()
+            && self.can_undo()
+            && !self.assisted
+            && !self.paused
+            && !self.undo_stack.as_slice().is_empty()
+    }
+
+    /// Gets the moves that led to the board and are still remembered, oldest first.
+    pub fn moves(&self) -> &[MoveRecord] {
+        self.moves.as_slice()
+    }
+
+    /// Gets how many moves were made before the oldest one in `moves`.
+    pub fn forgotten_moves(&self) -> usize {
+        self.moves.forgotten()
+    }
+
+    /// Gets when the first move was made, in milliseconds since the Unix epoch, or `None` if no
+    /// move was made yet.
+    pub fn first_move_at(&self) -> Option<u64> {
+        self.first_move_at
+    }
+
+    /// Streams every move that led to the board, including forgotten ones that were written to
+    /// the spill file, reading them as they're needed rather than all at once.
+    ///
+    /// # Returns
+    ///
+    /// The moves, oldest first, each of which is `None` if it couldn't be read back, or `None` if
+    /// some were forgotten and weren't written to the spill file.
+    pub fn stream_moves(&self) -> Option<impl Iterator<Item = Option<MoveRecord>> + '_> {
+        let kept = self.moves.as_slice().iter().cloned().map(Some);
+        Some(self.spilled_moves()?.map(Result::ok).chain(kept))
+    }
+
+    /// Reads back the forgotten moves from the spill file, one at a time.
+    ///
+    /// # Returns
+    ///
+    /// The forgotten moves, oldest first, or `None` if they weren't written to the spill file.
+    fn spilled_moves(&self) -> Option<Box<dyn Iterator<Item = io::Result<MoveRecord>>>> {
+        match &self.move_spill {
+            _ if self.moves.forgotten() == 0 => Some(Box::new(iter::empty())),
+            Some(spill) if spill.entries_written() == self.moves.forgotten() => {
+                Some(Box::new(spill.entries::<MoveRecord>().ok()?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Plays the game's moves again from its seed and rules, one position at a time as they're
+    /// asked for, so that a long game's positions don't all have to be in memory at once.
+    ///
+    /// # Returns
+    ///
+    /// The `Replay`, or `None` if some moves were forgotten and can't be read back.
+    pub fn replay(&self) -> Option<Replay> {
+        Replay::start(self.clone())
+    }
+
+    /// Whether the game has ended, because no move can change the board or a limit was reached.
+    pub fn is_game_over(&self) -> bool {
+        self.game_over_reason.is_some()
+    }
+
+    /// Ends the game if its time limit has passed, e.g. while the player is thinking.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in milliseconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// Whether this ended the game.
+    pub fn check_time_limit(&mut self, now: u64) -> bool {
+        let (Some(limit), Some(played)) = (self.rules.time_limit, self.time_limit_clock(now))
+        else {
+            return false;
+        };
+        if self.is_game_over() || played < limit * 1000 {
+            return false;
+        }
+        self.game_over_reason = Some(GameOverReason::TimeExpired { limit });
+        self.stop_clock(now);
+        true
+    }
+
+    /// Pauses the game, e.g. while the pause menu is open: the clock stops, so the time doesn't
+    /// count towards `elapsed`, and moves, undos and redos are ignored until `resume`.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in milliseconds since the Unix epoch.
+    pub fn pause(&mut self, now: u64) {
+        self.paused = true;
+        self.stop_clock(now);
+    }
+
+    /// Goes on with a paused game, starting the clock again unless the game is over. Resuming a
+    /// game that isn't paused does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in milliseconds since the Unix epoch.
+    pub fn resume(&mut self, now: u64) {
+        self.paused = false;
+        self.start_clock(now);
+    }
+
+    /// Gets where the game stands. A game over comes before a pause, and a pause before a win.
+    pub fn state(&self) -> GameState {
+        match (self.game_over_reason, self.win_state) {
+            (Some(reason), _) => GameState::Lost(reason),
+            _ if self.paused => GameState::Paused,
+            (None, WinState::Playing) => GameState::Playing,
+            (None, WinState::Won) => GameState::Won { continued: false },
+            (None, WinState::KeepPlaying) => GameState::Won { continued: true },
+        }
+    }
+
+    /// Gets how long the game has been played, not counting pauses, e.g. for the timer next to
+    /// the board. The clock starts when the game is started or loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in milliseconds since the Unix epoch.
+    pub fn elapsed(&self, now: u64) -> Duration {
+        let running = self
+            .resumed_at
+            .map_or(0, |resumed_at| now.saturating_sub(resumed_at));
+        Duration::from_millis(self.play_time + running)
+    }
+
+    /// Gets how long the game has been played since its first move, not counting pauses, which
+    /// is what the time limit is measured against.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in milliseconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// The time in milliseconds, or `None` before the first move.
+    fn time_limit_clock(&self, now: u64) -> Option<u64> {
+        let played = self.elapsed(now).as_millis() as u64;
+        self.time_limit_started
+            .map(|started| played.saturating_sub(started))
+    }
+
+    /// Stops the clock, e.g. when the game ends, adding the time since it was started to the play
+    /// time.
+    fn stop_clock(&mut self, now: u64) {
+        if let Some(resumed_at) = self.resumed_at.take() {
+            self.play_time += now.saturating_sub(resumed_at);
+        }
+    }
+
+    /// Starts the clock if it's stopped, unless the game is paused or over.
+    fn start_clock(&mut self, now: u64) {
+        if self.resumed_at.is_none() && !self.paused && !self.is_game_over() {
+            self.resumed_at = Some(now);
+        }
+    }
+
+    /// Gets how much of the game's limits is left, for showing next to the board.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in milliseconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// The moves left under the move limit and the seconds left under the time limit, each
+    /// `None` if there is no such limit. The clock starts with the first move.
+    pub fn limits_left(&self, now: u64) -> (Option<u32>, Option<u64>) {
+        let moves_left = self
+            .rules
+            .move_limit
+            .map(|limit| limit.saturating_sub(self.moves.total_len() as u32));
+        let seconds_left = self.rules.time_limit.map(|limit| {
+            let elapsed = self.time_limit_clock(now).map_or(0, |played| played / 1000);
+            limit.saturating_sub(elapsed)
+        });
+        (moves_left, seconds_left)
+    }
+
+    /// Whether the rules' winning tile has been made. The game can go on after winning.
+    pub fn has_won(&self) -> bool {
+        self.read_board_state()
+            .iter()
+            .flatten()
+            .any(|&tile| tile >= self.rules.win_exponent)
+    }
+
+    /// Gets the biggest tile made in this game, even if the move that made it was undone.
+    ///
+    /// # Returns
+    ///
+    /// The exponent of the tile, e.g. `10` for 1024.
+    pub fn highest_tile(&self) -> TileType {
+        self.highest_tile
+    }
+
+    /// Gets whether the game has just been won, e.g. to show a victory banner until the player
+    /// sends `GameEvent::KeepPlaying`.
+    pub fn win_state(&self) -> WinState {
+        self.win_state
+    }
+
+    /// Gets every move that would change the board under the game's rules, e.g. wrapping around
+    /// and gravity. This is the one place that decides which moves do anything: the game is over
+    /// when there are none, and the solver and coach only consider these.
+    ///
+    /// # Returns
+    ///
+    /// Each legal move with what it would do, in `Direction::ALL` order.
+    pub fn legal_moves(&self) -> impl Iterator<Item = (Direction, MergeOutcome)> + '_ {
+        Direction::ALL.into_iter().filter_map(|direction| {
+            self.simulate_move(direction)
+                .map(|outcome| (direction, outcome))
+        })
+    }
+
+    /// Previews a move without making it, so callers don't need to clone the whole game and
+    /// play the move to find out whether it does anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The move to preview.
+    ///
+    /// # Returns
+    ///
+    /// A `MovePreview` that agrees with what `handle_event` would do, apart from the new tile.
+    pub fn peek_move(&self, direction: Direction) -> MovePreview {
+        let accepts_moves = !self.is_game_over() && self.win_state != WinState::Won && !self.paused;
+        match self.simulate_move(direction).filter(|_| accepts_moves) {
+            Some(outcome) => MovePreview {
+                changes_board: true,
+                points: outcome.points,
+            },
+            None => MovePreview {
+                changes_board: false,
+                points: 0,
+            },
+        }
+    }
+
+    /// Finds the moves that would change the board, e.g. to grey out the others.
+    ///
+    /// # Returns
+    ///
+    /// The directions for which `peek_move` says the board changes, in `Direction::ALL` order.
+    /// This is empty when the game is over or is ignoring moves.
+    pub fn available_moves(&self) -> Vec<Direction> {
+        Direction::ALL
+            .into_iter()
+            .filter(|&direction| self.peek_move(direction).changes_board)
+            .collect()
+    }
+
+    /// Estimates how many moves are left before the board locks up, for the danger meter.
+    ///
+    /// # Returns
+    ///
+    /// `Board::min_moves_until_stuck_upper_bound`, or `0` whenever no move is legal.
+    pub fn moves_until_stuck(&self) -> u32 {
+        match self.legal_moves().next() {
+            Some(_) => self
+                .board
+                .min_moves_until_stuck_upper_bound(&self.rules.variant),
+            None => 0,
+        }
+    }
+
+    /// Works out what a move would do, without spawning a tile afterwards.
+    ///
+    /// # Returns
+    ///
+    /// The `MergeOutcome`, or `None` if the move doesn't change the board.
+    fn simulate_move(&self, direction: Direction) -> Option<MergeOutcome> {
+        let mut board = self.board.clone();
+        let outcome = board.merge(direction, self.rules.wrap_around, &self.rules.variant);
+        let mut moved = outcome.moved;
+        if self.rules.gravity && matches!(direction, Direction::Left | Direction::Right) {
+            moved |= board.compact(Direction::Down);
+        }
+        moved.then_some(MergeOutcome {
+            board,
+            points: outcome.points,
+        })
+    }
+
+    /// Works out where the tiles go in a move, following them when they fall after it.
+    fn trace_move(&self, direction: Direction) -> Vec<TileMovement> {
+        let mut movements =
+            self.board
+                .trace_merge(direction, self.rules.wrap_around, &self.rules.variant);
+        if self.rules.gravity && matches!(direction, Direction::Left | Direction::Right) {
+            let mut merged = self.board.clone();
+            merged.merge(direction, self.rules.wrap_around, &self.rules.variant);
+            let falls = merged.trace_compact(Direction::Down);
+            for movement in &mut movements {
+                if let Some(fall) = falls.iter().find(|fall| fall.from == movement.to) {
+                    movement.to = fall.to;
+                }
+            }
+        }
+        movements
+    }
+
+    /// Finishes a move that changed the board by remembering the position before it, adding a
+    /// new tile, and checking whether the game is over.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - The board and score before the move, for the undo stack.
+    /// * `direction` - The direction of the move.
+    /// * `movements` - Where the tiles went in the move, to keep track of how old they are.
+    ///
+    /// # Returns
+    ///
+    /// The row and column of the new tile, or `AddRandomTileError` if there was nowhere to put it.
+    fn finish_move(
+        &mut self,
+        before: (Board, u32),
+        direction: Direction,
+        movements: &[TileMovement],
+    ) -> Result<(usize, usize), GameError> {
+        self.undo_stack.push(before);
+        self.redo_stack.clear();
+        self.score_history.push(self.score);
+        let played_at = now_millis();
+        self.first_move_at.get_or_insert(played_at);
+        if self.time_limit_started.is_none() {
+            self.time_limit_started = Some(self.elapsed(played_at).as_millis() as u64);
+        }
+        if let Some(forgotten) = self.moves.push(MoveRecord {
+            direction,
+            played_at,
+        }) {
+            self.spill_move(self.moves.forgotten() - 1, &forgotten);
+        }
+        let mut rng = self.tile_rng();
+        let spawned = self
+            .board
+            .add_random_tile(
+                &*self.rules.spawn_policy_with(self.spawn_weights()),
+                self.rules.starting_tile_count() + self.score_history.total_len() - 1,
+                &mut rng,
+            )
+            .or(Err(AddRandomTileError))?;
+        if self.rules.zen {
+            self.age_tiles(movements, spawned);
+        }
+        self.highest_tile = self.highest_tile.max(self.board.highest_tile());
+        self.check_game_over();
+        self.check_win();
+        self.check_time_limit(played_at);
+        if self.is_game_over() {
+            self.stop_clock(played_at);
+        }
+        Ok(spawned)
+    }
+
+    /// Writes a move forgotten by the move history to the spill file, if there is one. Failing
+    /// to write it only means the game can't be replayed.
+    fn spill_move(&self, index: usize, record: &MoveRecord) {
+        if let Some(spill) = &self.move_spill {
+            let _ = spill.append(index, record);
+        }
+    }
+
+    /// Creates the random number generator for the next new tile. It depends only on the seed and
+    /// how many moves have been made, so redoing an undone move gives the same tile.
+    fn tile_rng(&self) -> StdRng {
+        let move_number = self.score_history.total_len() as u64;
+        StdRng::seed_from_u64(self.seed ^ move_number.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    /// Follows the tiles to where a move took them, giving the tiles it made the number of the
+    /// move. Tiles whose age isn't known stay as old as the first tiles.
+    ///
+    /// # Arguments
+    ///
+    /// * `movements` - Where the tiles went in the move.
+    /// * `spawned` - The row and column of the new tile.
+    fn age_tiles(&mut self, movements: &[TileMovement], spawned: (usize, usize)) {
+        let move_number = self.moves.total_len();
+        let age = |ages: &[Vec<usize>], (row, column): (usize, usize)| {
+            ages.get(row).and_then(|ages| ages.get(column)).copied()
+        };
+        let size = self.board.get_data_for_display().len();
+        let mut ages = vec![vec![0; size]; size];
+        for movement in movements {
+            ages[movement.to.0][movement.to.1] = if movement.merged {
+                move_number
+            } else {
+                age(&self.tile_ages, movement.from).unwrap_or(0)
+            };
+        }
+        ages[spawned.0][spawned.1] = move_number;
+        self.tile_ages = ages;
+    }
+
+    /// Takes the oldest of the smallest tiles off the board, for zen mode. Tiles of the same age
+    /// are taken in reading order.
+    fn clear_oldest_smallest_tile(&mut self) {
+        let ages = &self.tile_ages;
+        let oldest_smallest = self
+            .board
+            .get_data_for_display()
+            .iter()
+            .enumerate()
+            .flat_map(|(row, tiles)| {
+                tiles.iter().enumerate().map(move |(column, &tile)| {
+                    let age = ages.get(row).and_then(|ages| ages.get(column));
+                    (tile, age.copied().unwrap_or(0), row, column)
+                })
+            })
+            .filter(|&(tile, ..)| tile != 0)
+            .min_by_key(|&(tile, age, ..)| (tile, age));
+        if let Some((_, _, row, column)) = oldest_smallest {
+            self.board.place_item_in_board(row, column, 0).unwrap();
+        }
+    }
+
+    fn check_game_over(&mut self) {
+        if self.rules.zen && self.legal_moves().next().is_none() {
+            self.clear_oldest_smallest_tile();
+        }
+        if self.legal_moves().next().is_none() {
+            self.game_over_reason = Some(GameOverReason::NoMovesLeft);
+        } else if let Some(limit) = self.rules.move_limit {
+            if self.moves.total_len() >= limit as usize {
+                self.game_over_reason = Some(GameOverReason::MoveLimitReached { limit });
+            }
+        }
+    }
+
+    /// Moves to `WinState::Won` when the winning tile first appears, and back to
+    /// `WinState::Playing` if it's undone, so that remaking it wins again.
+    fn check_win(&mut self) {
+        if !self.has_won() {
+            self.win_state = WinState::Playing;
+        } else if self.win_state == WinState::Playing {
+            self.win_state = WinState::Won;
+        }
+    }
+}
+
+/// Plays a game's moves again from its seed and rules, as given by `Game::replay`. It gives the
+/// position the game started from and then the position after each move, each of which is `None`
+/// if the moves stop leading to the game's board, e.g. because the game was saved before moves
+/// were recorded, after which the replay ends.
+pub struct Replay {
+    /// The game being replayed.
+    source: Game,
+    /// The game the moves are played again in, or `None` before the starting position was given.
+    game: Option<Game>,
+    /// The forgotten moves that weren't played again yet.
+    spilled: Box<dyn Iterator<Item = io::Result<MoveRecord>>>,
+    /// How many of the moves that are still remembered were played again.
+    kept_played: usize,
+    finished: bool,
+}
+
+impl Replay {
+    /// Starts replaying a game from the position it started from.
+    ///
+    /// # Returns
+    ///
+    /// The `Replay`, or `None` if some moves were forgotten and can't be read back.
+    fn start(source: Game) -> Option<Replay> {
+        Some(Replay {
+            spilled: source.spilled_moves()?,
+            source,
+            game: None,
+            kept_played: 0,
+            finished: false,
+        })
+    }
+
+    /// Goes back to the position the game started from, e.g. to step back further than the
+    /// positions that were kept.
+    ///
+    /// # Returns
+    ///
+    /// Whether the replay could start over, which it can't if the spill file can no longer be
+    /// read.
+    pub fn rewind(&mut self) -> bool {
+        let Some(spilled) = self.source.spilled_moves() else {
+            return false;
+        };
+        self.spilled = spilled;
+        self.game = None;
+        self.kept_played = 0;
+        self.finished = false;
+        true
+    }
+
+    /// Gets how many moves the game being replayed has, which is how many positions follow the
+    /// first one when the replay works.
+    pub fn move_count(&self) -> usize {
+        self.source.moves.total_len()
+    }
+
+    /// Gets the next move to play again, from the spill file and then from the moves that are
+    /// still remembered.
+    ///
+    /// # Returns
+    ///
+    /// The move, which is `None` if it couldn't be read back, or `None` after the last move.
+    fn next_move(&mut self) -> Option<Option<MoveRecord>> {
+        if let Some(record) = self.spilled.next() {
+            return Some(record.ok());
+        }
+        let record = self.source.moves.as_slice().get(self.kept_played)?;
+        self.kept_played += 1;
+        Some(Some(*record))
+    }
+}
+
+impl Iterator for Replay {
+    type Item = Option<ReplayPosition>;
+
+    fn next(&mut self) -> Option<Option<ReplayPosition>> {
+        if self.finished {
+            return None;
+        }
+        let Some(game) = self.game.take() else {
+            let game = Game::with_seed(self.source.seed, self.source.rules.clone());
+            let position = ReplayPosition {
+                board: game.board.clone(),
+                score: game.score,
+                last_move: None,
+            };
+            self.game = Some(game);
+            return Some(Some(position));
+        };
+
+        let Some(record) = self.next_move() else {
+            self.finished = true;
+            return (game.board != self.source.board).then_some(None);
+        };
+        let played = record.and_then(|record| {
+            // the moves after a win were made in endless mode
+            let game = game.handle_event(GameEvent::KeepPlaying).ok()?.game;
+            Some((
+                game.handle_event(record.direction.into()).ok()?.game,
+                record,
+            ))
+        });
+        match played {
+            Some((game, record)) => {
+                let position = ReplayPosition {
+                    board: game.board.clone(),
+                    score: game.score,
+                    last_move: Some(record),
+                };
+                self.game = Some(game);
+                Some(Some(position))
+            }
+            None => {
+                self.finished = true;
+                Some(None)
+            }
+        }
+    }
 }
 
 impl Display for Game {
@@ -95,3 +1409,1003 @@ impl Display for Game {
         write!(f, "{}", self.board)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn::{Corner, ScriptedTile, SpawnRule};
+
+    fn play(game: Game, events: Vec<GameEvent>) -> Game {
+        events
+            .into_iter()
+            .fold(game, |game, event| game.handle_event(event).unwrap().game)
+    }
+
+    /// Replays a whole game, or gets `None` if it can't be replayed.
+    fn replayed(game: &Game) -> Option<Vec<ReplayPosition>> {
+        game.replay()?.collect()
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let moves = || {
+            vec![
+                GameEvent::SwipeLeft,
+                GameEvent::SwipeUp,
+                GameEvent::SwipeRight,
+                GameEvent::SwipeDown,
+            ]
+        };
+        let first = play(Game::with_seed(42, Rules::default()), moves());
+        let second = play(Game::with_seed(42, Rules::default()), moves());
+
+        assert_eq!(first.board(), second.board());
+    }
+
+    #[test]
+    fn undo_restores_the_previous_position() {
+        let game = play(
+            Game::with_seed(7, Rules::default()),
+            vec![GameEvent::SwipeLeft, GameEvent::SwipeUp],
+        );
+        let before = game.clone();
+        let moved = play(game, vec![GameEvent::SwipeRight, GameEvent::SwipeDown]);
+        let undone = play(moved.clone(), vec![GameEvent::Undo, GameEvent::Undo]);
+
+        assert_eq!(before.board(), undone.board());
+        assert_eq!(before.score_history(), undone.score_history());
+        assert_eq!(before.undo_stack().len(), undone.undo_stack().len());
+    }
+
+    #[test]
+    fn unlimited_undos_assist_the_game() {
+        let game = play(
+            Game::with_seed(7, Rules::default()),
+            vec![GameEvent::SwipeLeft, GameEvent::SwipeUp],
+        );
+        assert!(game.undo_would_assist());
+        let undone = play(game, vec![GameEvent::Undo]);
+        assert!(undone.is_assisted());
+        assert!(!undone.undo_would_assist(), "the player was already asked");
+
+        let limited = Rules {
+            undo_limit: Some(3),
+            ..Rules::default()
+        };
+        let game = play(Game::with_seed(7, limited), vec![GameEvent::SwipeLeft]);
+        assert!(!game.undo_would_assist());
+        assert!(!play(game, vec![GameEvent::Undo]).is_assisted());
+    }
+
+    #[test]
+    fn redoing_an_undone_move_gives_the_same_tile() {
+        let game = play(
+            Game::with_seed(3, Rules::default()),
+            vec![GameEvent::SwipeLeft],
+        );
+        let moved = play(game.clone(), vec![GameEvent::SwipeRight]);
+        let redone = play(moved.clone(), vec![GameEvent::Undo, GameEvent::SwipeRight]);
+
+        assert_eq!(moved.board(), redone.board());
+    }
+
+    #[test]
+    fn capped_history_plays_the_same_game() {
+        let limits = HistoryLimits {
+            undo: Some(5),
+            moves: Some(3),
+            spill_moves: true,
+        };
+        let dir = std::env::temp_dir().join(format!("rs2048-history-{}", std::process::id()));
+        let full = long_game(11, SpawnRule::Uniform);
+        let capped = play(
+            Game::with_seed(11, Rules::default()).with_history_limits(&limits, Some(&dir)),
+            long_game_events(),
+        );
+
+        assert_eq!(full.board(), capped.board());
+        assert_eq!(full.view().moves, capped.view().moves);
+        assert_eq!(3, capped.moves().len());
+        assert_eq!(
+            3,
+            capped.undo_stack().len(),
+            "undo can't go past the move history"
+        );
+        assert_eq!(
+            full.first_move_at().is_some(),
+            capped.first_move_at().is_some()
+        );
+        let directions = |game: &Game| {
+            game.stream_moves()
+                .unwrap()
+                .map(|record| record.unwrap().direction)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(directions(&full), directions(&capped));
+        assert_eq!(
+            capped.board(),
+            &replayed(&capped).unwrap().last().unwrap().board
+        );
+    }
+
+    #[test]
+    fn forgotten_moves_without_a_spill_file_cant_be_replayed() {
+        let limits = HistoryLimits {
+            undo: None,
+            moves: Some(3),
+            spill_moves: false,
+        };
+        let game = play(
+            Game::with_seed(11, Rules::default()).with_history_limits(&limits, None),
+            long_game_events(),
+        );
+
+        assert!(game.forgotten_moves() > 0);
+        assert!(game.stream_moves().is_none());
+        assert!(game.replay().is_none());
+    }
+
+    #[test]
+    fn starts_with_the_rules_starting_tiles() {
+        let tiles = |starting_tiles, board_size| {
+            let rules = Rules {
+                starting_tiles,
+                board_size,
+                ..Rules::default()
+            };
+            let game = Game::with_seed(5, rules.clone());
+            assert_eq!(game.board(), Game::with_seed(5, rules).board());
+            board_size * board_size - game.board().empty_positions().len()
+        };
+
+        assert_eq!(2, tiles(2, 4));
+        assert_eq!(1, tiles(1, 4));
+        assert_eq!(5, tiles(5, 4));
+        assert_eq!(1, tiles(0, 4));
+        assert_eq!(9, tiles(100, 3));
+        assert_eq!(
+            14,
+            Game::start_new_game()
+                .unwrap()
+                .board()
+                .empty_positions()
+                .len()
+        );
+    }
+
+    #[test]
+    fn boards_can_have_other_sizes() {
+        for size in [3, 6] {
+            let game = Game::start_new_game_with_size(size).unwrap();
+            assert_eq!(size, game.read_board_state().len());
+            assert_eq!(size * size - 2, game.board().empty_positions().len());
+
+            let game = play(game, long_game_events());
+            assert_eq!(
+                game.board(),
+                &replayed(&game).unwrap().last().unwrap().board
+            );
+        }
+        assert!(matches!(
+            Game::start_new_game_with_size(1),
+            Err(GameError::InvalidBoardSize(1))
+        ));
+        assert!(Game::start_new_game_with_size(9).is_err());
+    }
+
+    #[test]
+    fn redo_makes_undone_moves_again() {
+        let game = play(
+            Game::with_seed(5, Rules::default()),
+            vec![
+                GameEvent::SwipeLeft,
+                GameEvent::SwipeUp,
+                GameEvent::SwipeRight,
+            ],
+        );
+        let undone = play(game.clone(), vec![GameEvent::Undo, GameEvent::Undo]);
+        let redone = play(undone.clone(), vec![GameEvent::Redo, GameEvent::Redo]);
+
+        assert_eq!(game.board(), redone.board());
+        assert_eq!(game.score_history(), redone.score_history());
+        assert_eq!(
+            game.board(),
+            play(redone, vec![GameEvent::Redo]).board(),
+            "nothing is left to redo"
+        );
+
+        let moved = play(undone, vec![GameEvent::SwipeDown]);
+        assert_eq!(
+            moved.board(),
+            play(moved.clone(), vec![GameEvent::Redo]).board(),
+            "a new move clears what could be redone"
+        );
+    }
+
+    #[test]
+    fn moves_that_change_nothing_are_not_recorded() {
+        let board = Board::try_from(vec![vec![1, 0], vec![0, 0]]).unwrap();
+        let game = Game::restore(
+            board,
+            0,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        let game = play(game, vec![GameEvent::SwipeLeft, GameEvent::SwipeUp]);
+
+        assert!(game.score_history().is_empty());
+        assert!(game.undo_stack().is_empty());
+    }
+
+    #[test]
+    fn merges_add_to_the_score() {
+        let board = Board::from_notation("2,2,4,4/..../..../2...").unwrap();
+        let game = Game::restore(
+            board,
+            10,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        let game = play(game, vec![GameEvent::SwipeLeft]);
+
+        assert_eq!(10 + 4 + 8, game.score());
+        assert_eq!(&[22], game.score_history());
+        assert_eq!(10, play(game, vec![GameEvent::Undo]).score());
+    }
+
+    #[test]
+    fn winning_waits_for_keep_playing() {
+        let board = Board::from_notation("1024,1024../..../..../....").unwrap();
+        let game = Game::restore(
+            board,
+            0,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        let won = play(game, vec![GameEvent::SwipeLeft]);
+        assert_eq!(WinState::Won, won.win_state());
+
+        let ignored = play(won.clone(), vec![GameEvent::SwipeRight]);
+        assert_eq!(won.board(), ignored.board());
+
+        let endless = play(
+            won.clone(),
+            vec![GameEvent::KeepPlaying, GameEvent::SwipeRight],
+        );
+        assert_eq!(WinState::KeepPlaying, endless.win_state());
+        assert_ne!(won.board(), endless.board());
+
+        let undone = play(won, vec![GameEvent::Undo]);
+        assert_eq!(WinState::Playing, undone.win_state());
+        assert_eq!(
+            WinState::Playing,
+            play(undone, vec![GameEvent::KeepPlaying]).win_state()
+        );
+    }
+
+    #[test]
+    fn rules_choose_the_winning_tile() {
+        let restore = |win_exponent| {
+            Game::restore(
+                Board::from_notation("512,512../..../..../....").unwrap(),
+                0,
+                1,
+                Rules {
+                    win_exponent,
+                    ..Rules::default()
+                },
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+            )
+        };
+
+        let game = play(restore(10), vec![GameEvent::SwipeLeft]);
+        assert_eq!(WinState::Won, game.win_state());
+        let game = play(restore(WINNING_TILE), vec![GameEvent::SwipeLeft]);
+        assert_eq!(WinState::Playing, game.win_state());
+    }
+
+    #[test]
+    fn wrap_around_rules_join_the_edges() {
+        let play_left = |wrap_around| {
+            let board = Board::from_notation("2,4.2/..../..../....").unwrap();
+            let rules = Rules {
+                wrap_around,
+                ..Rules::default()
+            };
+            let game = Game::restore(board, 0, 1, rules, Vec::new(), Vec::new(), Vec::new());
+            play(game, vec![GameEvent::SwipeLeft]).score()
+        };
+
+        assert_eq!(0, play_left(false));
+        assert_eq!(4, play_left(true));
+    }
+
+    #[test]
+    fn elapsed_time_leaves_out_pauses() {
+        let mut game = Game::start_new_game().unwrap();
+        // stop the clock started by the real time, at no time played
+        game.pause(0);
+        assert_eq!(Duration::ZERO, game.elapsed(5_000));
+
+        game.resume(1_000);
+        game.resume(2_000);
+        game.pause(4_000);
+        assert_eq!(Duration::from_secs(3), game.elapsed(10_000));
+        game.resume(20_000);
+        assert_eq!(Duration::from_millis(4_500), game.elapsed(21_500));
+    }
+
+    #[test]
+    fn paused_games_are_frozen() {
+        let game = Game::start_new_game().unwrap();
+        let paused = play(game.clone(), vec![GameEvent::Pause]);
+        assert_eq!(GameState::Paused, paused.state());
+        assert_eq!(GameState::Paused, paused.view().state);
+
+        let frozen = play(
+            paused.clone(),
+            vec![
+                GameEvent::SwipeUp,
+                GameEvent::SwipeDown,
+                GameEvent::SwipeLeft,
+                GameEvent::SwipeRight,
+            ],
+        );
+        assert_eq!(0, frozen.view().moves);
+        assert_eq!(paused.elapsed(u64::MAX), frozen.elapsed(u64::MAX));
+
+        let resumed = play(frozen, vec![GameEvent::Resume]);
+        assert_eq!(GameState::Playing, resumed.state());
+        assert_eq!(game.board(), resumed.board());
+        assert_eq!(
+            GameState::Playing,
+            play(paused, vec![GameEvent::NewGame]).state()
+        );
+    }
+
+    #[test]
+    fn highest_tile_outlasts_undo() {
+        let board = Board::from_notation("256,256../..../..../....").unwrap();
+        let game = Game::restore(
+            board,
+            0,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        assert_eq!(8, game.highest_tile());
+
+        let game = play(game, vec![GameEvent::SwipeLeft]);
+        assert_eq!(9, game.highest_tile());
+        let game = play(game, vec![GameEvent::Undo]);
+        assert_eq!(8, game.board().highest_tile());
+        assert_eq!(9, game.highest_tile());
+    }
+
+    #[test]
+    fn legal_moves_follow_the_rules() {
+        let restore = |gravity| {
+            let board = Board::from_notation("2.../..../..../....").unwrap();
+            let rules = Rules {
+                gravity,
+                ..Rules::default()
+            };
+            Game::restore(board, 0, 1, rules, Vec::new(), Vec::new(), Vec::new())
+        };
+        let directions = |game: &Game| {
+            game.legal_moves()
+                .map(|(direction, _)| direction)
+                .collect::<Vec<_>>()
+        };
+
+        let classic = restore(false);
+        assert_eq!(
+            vec![Direction::Down, Direction::Right],
+            directions(&classic)
+        );
+        // the tile can't go any further left, but then it falls
+        let falling = restore(true);
+        assert_eq!(
+            vec![Direction::Down, Direction::Left, Direction::Right],
+            directions(&falling)
+        );
+        let (_, outcome) = falling.legal_moves().nth(1).unwrap();
+        assert_eq!(
+            Board::from_notation("..../..../..../2...").unwrap(),
+            outcome.board
+        );
+        assert_eq!(0, outcome.points);
+
+        let stuck = Board::from_notation("2,4,2,4/4,2,4,2/2,4,2,4/4,2,4,2").unwrap();
+        let stuck = Game::restore(
+            stuck,
+            0,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        assert_eq!(0, stuck.legal_moves().count());
+        assert_eq!(0, stuck.moves_until_stuck());
+        assert!(stuck.is_game_over());
+    }
+
+    #[test]
+    fn peek_move_agrees_with_playing_the_move() {
+        let board = Board::from_notation("2,2,4./..../..../....").unwrap();
+        let game = Game::restore(
+            board,
+            0,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        for direction in Direction::ALL {
+            let preview = game.peek_move(direction);
+            let after = game.clone().handle_event(direction.into()).unwrap().game;
+            assert_eq!(preview.changes_board, after.board() != game.board());
+            assert_eq!(preview.points, after.score() - game.score());
+        }
+        assert_eq!(
+            MovePreview {
+                changes_board: true,
+                points: 4,
+            },
+            game.peek_move(Direction::Left)
+        );
+        assert!(!game.peek_move(Direction::Up).changes_board);
+
+        let paused = play(game, vec![GameEvent::Pause]);
+        assert!(!paused.peek_move(Direction::Left).changes_board);
+    }
+
+    #[test]
+    fn updates_follow_the_tiles() {
+        let restore = |notation, gravity| {
+            let rules = Rules {
+                gravity,
+                ..Rules::default()
+            };
+            let board = Board::from_notation(notation).unwrap();
+            Game::restore(board, 0, 1, rules, Vec::new(), Vec::new(), Vec::new())
+        };
+
+        let update = restore("2..2/..../..../....", false)
+            .handle_event(GameEvent::SwipeLeft)
+            .unwrap();
+        assert_eq!(
+            vec![
+                TileMovement {
+                    from: (0, 0),
+                    to: (0, 0),
+                    tile: 1,
+                    merged: true,
+                },
+                TileMovement {
+                    from: (0, 3),
+                    to: (0, 0),
+                    tile: 1,
+                    merged: true,
+                },
+            ],
+            update.movements
+        );
+        let (row, column) = update.spawned.unwrap();
+        assert_ne!(0, update.game.read_board_state()[row][column]);
+
+        let frames = update.slide_frames();
+        assert_eq!(3, frames.len());
+        assert_eq!(vec![1, 0, 1, 0], frames[0][0]);
+        assert_eq!(vec![1, 1, 0, 0], frames[1][0]);
+        let mut landed = update.game.read_board_state().clone();
+        landed[row][column] = 0;
+        assert_eq!(landed, frames[2]);
+
+        let undone = update.game.handle_event(GameEvent::Undo).unwrap();
+        assert!(undone.movements.is_empty());
+        assert!(undone.slide_frames().is_empty());
+
+        // the tile slides nowhere, then falls
+        let update = restore("2.../..../..../....", true)
+            .handle_event(GameEvent::SwipeLeft)
+            .unwrap();
+        assert_eq!((3, 0), update.movements[0].to);
+    }
+
+    #[test]
+    fn available_moves_leave_out_blocked_directions() {
+        let board = Board::from_notation("2.../4.../..../....").unwrap();
+        let game = Game::restore(
+            board,
+            0,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        assert_eq!(
+            vec![Direction::Down, Direction::Right],
+            game.available_moves()
+        );
+        assert!(play(game, vec![GameEvent::Pause])
+            .available_moves()
+            .is_empty());
+    }
+
+    #[test]
+    fn gravity_pulls_tiles_down_after_sideways_moves() {
+        let board = Board::from_notation("2,4../..../2.../....").unwrap();
+        let rules = Rules {
+            gravity: true,
+            ..Rules::default()
+        };
+        let game = Game::restore(board, 0, 1, rules, Vec::new(), Vec::new(), Vec::new());
+        let game = play(game, vec![GameEvent::SwipeRight]);
+        let rows = game.read_board_state();
+
+        // the new tile appears after gravity, so it can be anywhere else
+        assert_eq!(2, rows[2][3]);
+        assert_eq!(&[1, 1], &rows[3][2..]);
+        assert_eq!(4, rows.iter().flatten().filter(|&&tile| tile != 0).count());
+    }
+
+    fn long_game_events() -> Vec<GameEvent> {
+        (0..40)
+            .map(|index| match index % 5 {
+                0 => GameEvent::SwipeLeft,
+                1 => GameEvent::SwipeUp,
+                2 => GameEvent::Undo,
+                3 => GameEvent::SwipeDown,
+                _ => GameEvent::SwipeRight,
+            })
+            .collect()
+    }
+
+    fn long_game(seed: u64, spawn: SpawnRule) -> Game {
+        play(
+            Game::with_seed(
+                seed,
+                Rules {
+                    spawn,
+                    ..Rules::default()
+                },
+            ),
+            long_game_events(),
+        )
+    }
+
+    #[test]
+    fn replay_reaches_the_same_board() {
+        let game = long_game(11, SpawnRule::Uniform);
+        let positions = replayed(&game).unwrap();
+
+        assert!(!game.moves().is_empty());
+        assert_eq!(game.undo_stack().len(), game.moves().len());
+        assert_eq!(game.moves().len() + 1, positions.len());
+        assert_eq!(game.moves().len(), game.replay().unwrap().move_count());
+        assert_eq!(game.board(), &positions.last().unwrap().board);
+        assert!(game.score() > 0);
+        assert_eq!(game.score(), positions.last().unwrap().score);
+        assert_eq!(None, positions[0].last_move);
+        assert_eq!(
+            game.moves().last(),
+            positions.last().unwrap().last_move.as_ref()
+        );
+    }
+
+    #[test]
+    fn replay_can_start_over() {
+        let game = long_game(11, SpawnRule::Uniform);
+        let mut replay = game.replay().unwrap();
+        let first = replay.next().unwrap();
+        replay.nth(5);
+
+        assert!(replay.rewind());
+        assert_eq!(first, replay.next().unwrap());
+        assert_eq!(game.moves().len(), replay.count());
+    }
+
+    #[test]
+    fn replay_keeps_the_spawn_rule() {
+        let spawn = SpawnRule::CornerBias {
+            corner: Corner::BottomRight,
+            toward: true,
+        };
+        let game = long_game(11, spawn.clone());
+
+        assert_eq!(
+            game.board(),
+            &replayed(&game).unwrap().last().unwrap().board
+        );
+        assert_ne!(
+            game.board(),
+            long_game(11, SpawnRule::Uniform).board(),
+            "the rule should change where tiles appear"
+        );
+    }
+
+    #[test]
+    fn scripted_games_follow_the_script() {
+        let tiles = vec![
+            ScriptedTile {
+                x: 0,
+                y: 0,
+                tile: 1,
+            },
+            ScriptedTile {
+                x: 3,
+                y: 3,
+                tile: 2,
+            },
+        ];
+        let rules = Rules {
+            spawn: SpawnRule::Scripted { tiles },
+            starting_tiles: 1,
+            ..Rules::default()
+        };
+        let game = Game::with_seed(1, rules.clone());
+        let game = play(game, vec![GameEvent::SwipeRight]);
+
+        assert_eq!(
+            &vec![vec![0, 0, 0, 1], vec![0; 4], vec![0; 4], vec![0, 0, 0, 2]],
+            game.read_board_state()
+        );
+        // with two starting tiles, both come from the script
+        let game = Game::with_seed(
+            1,
+            Rules {
+                starting_tiles: 2,
+                ..rules
+            },
+        );
+        assert_eq!(
+            &vec![vec![1, 0, 0, 0], vec![0; 4], vec![0; 4], vec![0, 0, 0, 2]],
+            game.read_board_state()
+        );
+    }
+
+    /// Plays a whole game on a 2x2 board, from its first tiles to game over, so that merging,
+    /// scoring, spawning and game over are checked working together.
+    #[test]
+    fn full_scripted_game() {
+        let spawns = [(0, 0, 1), (1, 1, 1), (1, 0, 2), (0, 1, 1), (1, 0, 1)]
+            .into_iter()
+            .chain([(1, 0, 2), (0, 1, 3), (0, 1, 1), (0, 1, 2)]);
+        let rules = Rules {
+            board_size: 2,
+            spawn: SpawnRule::Scripted {
+                tiles: spawns
+                    .map(|(x, y, tile)| ScriptedTile { x, y, tile })
+                    .collect(),
+            },
+            ..Rules::default()
+        };
+        let game = Game::with_seed(1, rules);
+        assert_eq!(&vec![vec![1, 0], vec![0, 1]], game.read_board_state());
+
+        let game = play(
+            game,
+            vec![
+                GameEvent::SwipeLeft,
+                GameEvent::SwipeUp,
+                GameEvent::SwipeLeft,
+                // nothing moves, so no tile spawns and the script waits
+                GameEvent::SwipeLeft,
+                GameEvent::SwipeDown,
+                GameEvent::SwipeRight,
+                GameEvent::SwipeUp,
+                GameEvent::SwipeRight,
+            ],
+        );
+
+        assert_eq!(&vec![vec![4, 3], vec![2, 1]], game.read_board_state());
+        assert_eq!(40, game.score());
+        assert_eq!(&[0, 4, 12, 12, 16, 40, 40], game.score_history());
+        assert_eq!(4, game.highest_tile());
+        assert_eq!(0, game.legal_moves().count());
+        assert_eq!(GameState::Lost(GameOverReason::NoMovesLeft), game.state());
+        assert_eq!(WinState::Playing, game.win_state());
+        assert_eq!(8, replayed(&game).unwrap().len());
+    }
+
+    #[test]
+    fn replay_without_moves_fails() {
+        let game = long_game(11, SpawnRule::Uniform);
+        let game = Game::restore(
+            game.board().clone(),
+            game.score(),
+            game.seed(),
+            game.rules().clone(),
+            game.score_history().to_vec(),
+            game.undo_stack().to_vec(),
+            Vec::new(),
+        );
+        // the starting position is there, but the moves don't lead to the board
+        let mut replay = game.replay().unwrap();
+        assert!(replay.next().unwrap().is_some());
+        assert_eq!(Some(None), replay.next());
+        assert_eq!(None, replay.next());
+        assert!(replayed(&game).is_none());
+    }
+
+    #[test]
+    fn view_serializes_tile_values() {
+        let board = Board::try_from(vec![vec![1, 2], vec![0, 11]]).unwrap();
+        let view = Game::restore(
+            board,
+            12,
+            1,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        )
+        .view();
+
+        assert_eq!(
+            serde_json::json!({
+                "board": [[2, 4], [0, 2048]],
+                "score": 12,
+                "moves": 0,
+                "won": true,
+                "state": {"won": {"continued": false}},
+                "undos_left": null,
+            }),
+            serde_json::to_value(&view).unwrap()
+        );
+    }
+
+    #[test]
+    fn undo_with_nothing_to_undo() {
+        let game = Game::with_seed(1, Rules::default());
+        let undone = play(game.clone(), vec![GameEvent::Undo]);
+        assert_eq!(game.board(), undone.board());
+    }
+
+    #[test]
+    fn move_limit_ends_the_game() {
+        let rules = Rules {
+            move_limit: Some(2),
+            ..Rules::default()
+        };
+        let game = play(
+            Game::with_seed(5, rules),
+            vec![GameEvent::SwipeLeft, GameEvent::SwipeRight],
+        );
+        let board = game.board().clone();
+        let game = play(game, vec![GameEvent::SwipeUp, GameEvent::SwipeDown]);
+
+        assert_eq!(
+            GameState::Lost(GameOverReason::MoveLimitReached { limit: 2 }),
+            game.state()
+        );
+        assert_eq!(&board, game.board(), "no moves after the game is over");
+        assert!(!play(game, vec![GameEvent::Undo]).is_game_over());
+    }
+
+    #[test]
+    fn full_board_ends_the_game() {
+        let board = Board::try_from(vec![vec![1, 2], vec![0, 3]]).unwrap();
+        let game = Game::restore(
+            board,
+            0,
+            1,
+            Rules {
+                // the first tile is the one a new game starts with
+                starting_tiles: 1,
+                spawn: SpawnRule::Scripted {
+                    tiles: vec![
+                        ScriptedTile {
+                            x: 0,
+                            y: 0,
+                            tile: 1,
+                        },
+                        ScriptedTile {
+                            x: 1,
+                            y: 1,
+                            tile: 4,
+                        },
+                    ],
+                },
+                ..Rules::default()
+            },
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        let game = play(game, vec![GameEvent::SwipeLeft]);
+
+        assert_eq!(GameState::Lost(GameOverReason::NoMovesLeft), game.state());
+    }
+
+    #[test]
+    fn stats_count_merges_moves_and_undos() {
+        let board = Board::from_notation("2,2,4,4/..../..../....").unwrap();
+        let game = Game::restore(
+            board,
+            0,
+            7,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        let game = play(game, vec![GameEvent::SwipeLeft, GameEvent::Undo]);
+
+        let stats = game.stats();
+        assert_eq!(
+            std::collections::BTreeMap::from([(2, 1), (3, 1)]),
+            stats.merges
+        );
+        assert_eq!(12, stats.best_move_points);
+        assert_eq!(1, stats.undos);
+        assert_eq!([0, 0, 1, 0], stats.directions);
+    }
+
+    #[test]
+    fn practice_games_start_from_the_given_board() {
+        let board = Board::from_notation("2,4,8,16/32,64,128,256/..../....").unwrap();
+        let game = Game::from_board(board.clone(), 1000).unwrap();
+
+        assert_eq!(&board, game.board());
+        assert_eq!(1000, game.score());
+        assert_eq!(4, game.rules().board_size);
+        assert!(game.is_assisted());
+        assert!(!game.is_game_over());
+
+        let tiny = Board::from_notation("2").unwrap();
+        assert!(matches!(
+            Game::from_board(tiny, 0),
+            Err(GameError::InvalidBoardSize(1))
+        ));
+        let rectangle = Board::from_notation("2,.,.,./4,.,.,.").unwrap();
+        assert!(matches!(
+            Game::from_board(rectangle, 0),
+            Err(GameError::InvalidBoardSize(2))
+        ));
+    }
+
+    #[test]
+    fn zen_mode_takes_off_the_oldest_smallest_tile() {
+        let board = Board::try_from(vec![vec![3, 0], vec![4, 2]]).unwrap();
+        let game = Game::restore(
+            board,
+            0,
+            1,
+            Rules {
+                starting_tiles: 1,
+                spawn: SpawnRule::Scripted {
+                    tiles: vec![
+                        ScriptedTile {
+                            x: 1,
+                            y: 1,
+                            tile: 2,
+                        },
+                        ScriptedTile {
+                            x: 0,
+                            y: 0,
+                            tile: 2,
+                        },
+                    ],
+                },
+                zen: true,
+                ..Rules::default()
+            },
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        // the new 2 locks the board up, and the 2 that was already there goes
+        let game = play(game, vec![GameEvent::SwipeRight]);
+
+        assert!(!game.is_game_over());
+        assert_eq!(&vec![vec![2, 3], vec![4, 0]], game.read_board_state());
+    }
+
+    #[test]
+    fn time_limit_counts_from_the_first_move() {
+        let rules = Rules {
+            time_limit: Some(60),
+            ..Rules::default()
+        };
+        let mut game = Game::with_seed(5, rules);
+        assert!(!game.check_time_limit(u64::MAX), "the clock hasn't started");
+
+        let mut game = play(game, vec![GameEvent::SwipeLeft]);
+        let started = game.moves()[0].played_at;
+        assert_eq!((None, Some(50)), game.limits_left(started + 10_000));
+        assert!(!game.check_time_limit(started + 59_999));
+        assert!(game.check_time_limit(started + 60_000));
+        assert_eq!(
+            GameState::Lost(GameOverReason::TimeExpired { limit: 60 }),
+            game.state()
+        );
+        assert_eq!(
+            "Time's up after 1:00",
+            GameOverReason::TimeExpired { limit: 60 }.to_string()
+        );
+    }
+
+    #[test]
+    fn time_limit_cant_be_undone() {
+        let rules = Rules {
+            time_limit: Some(60),
+            ..Rules::default()
+        };
+        let mut game = play(Game::with_seed(5, rules), vec![GameEvent::SwipeLeft]);
+        let started = game.moves()[0].played_at;
+        assert!(game.can_undo());
+        assert!(game.check_time_limit(started + 60_000));
+        assert!(!game.can_undo());
+
+        let mut game = play(game, vec![GameEvent::Undo]);
+        assert_eq!(1, game.moves().len());
+        assert_eq!(
+            GameState::Lost(GameOverReason::TimeExpired { limit: 60 }),
+            game.state()
+        );
+        assert!(!game.check_time_limit(u64::MAX), "the game only ends once");
+    }
+
+    #[test]
+    fn time_limit_stops_while_paused() {
+        let rules = Rules {
+            time_limit: Some(60),
+            ..Rules::default()
+        };
+        let mut game = play(Game::with_seed(5, rules), vec![GameEvent::SwipeLeft]);
+        let started = game.moves()[0].played_at;
+
+        game.pause(started + 10_000);
+        let resumed = started + 600_000;
+        assert!(!game.check_time_limit(resumed));
+        game.resume(resumed);
+        assert_eq!((None, Some(50)), game.limits_left(resumed));
+        assert!(!game.check_time_limit(resumed + 49_000));
+
+        let direction = game.available_moves()[0];
+        let mut game = play(game, vec![direction.into()]);
+        assert_eq!(2, game.moves().len());
+        assert_eq!(GameState::Playing, game.state());
+        assert!(game.check_time_limit(resumed + 50_000));
+    }
+
+    #[test]
+    fn undo_limit_is_used_up() {
+        let rules = Rules {
+            undo_limit: Some(1),
+            ..Rules::default()
+        };
+        let game = play(
+            Game::with_seed(9, rules),
+            vec![GameEvent::SwipeLeft, GameEvent::SwipeRight, GameEvent::Undo],
+        );
+        assert_eq!(Some(0), game.undos_left());
+        assert_eq!(1, game.moves().len());
+
+        let game = play(game, vec![GameEvent::Undo]);
+        assert_eq!(1, game.moves().len(), "no undos were left");
+        assert_eq!(
+            Some(1),
+            play(game, vec![GameEvent::NewGame]).undos_left(),
+            "a new game gets its undos back"
+        );
+    }
+}
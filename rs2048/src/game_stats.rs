@@ -0,0 +1,105 @@
+use crate::board::{Direction, TileType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What happened over one game, for the summary at its end. Moves that were undone still count,
+/// since they were played.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameStats {
+    /// How many tiles merges made of each exponent, e.g. `{3: 12}` for twelve 8s.
+    pub merges: BTreeMap<TileType, u32>,
+    /// The most points scored by a single move.
+    pub best_move_points: u32,
+    /// How many moves were undone.
+    pub undos: u32,
+    /// How many moves went each way, in `Direction::ALL` order.
+    pub directions: [u32; 4],
+}
+
+impl GameStats {
+    /// Counts a move that changed the board.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Which way the board was swiped.
+    /// * `points` - The points the move scored.
+    /// * `made` - The exponents of the tiles the move's merges made.
+    pub fn record_move(
+        &mut self,
+        direction: Direction,
+        points: u32,
+        made: impl IntoIterator<Item = TileType>,
+    ) {
+        for tile in made {
+            *self.merges.entry(tile).or_default() += 1;
+        }
+        self.best_move_points = self.best_move_points.max(points);
+        if let Some(index) = Direction::ALL.iter().position(|&way| way == direction) {
+            self.directions[index] += 1;
+        }
+    }
+
+    /// Counts a move that was undone.
+    pub fn record_undo(&mut self) {
+        self.undos += 1;
+    }
+
+    /// Gets how many merges there were in the game.
+    pub fn total_merges(&self) -> u32 {
+        self.merges.values().sum()
+    }
+
+    /// Gets how many moves went each way, most used first.
+    ///
+    /// # Returns
+    ///
+    /// Each direction with its count, in `Direction::ALL` order between directions used as often.
+    pub fn directions_by_use(&self) -> Vec<(Direction, u32)> {
+        let mut directions: Vec<(Direction, u32)> =
+            Direction::ALL.into_iter().zip(self.directions).collect();
+        directions.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        directions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_are_counted() {
+        let mut stats = GameStats::default();
+        stats.record_move(Direction::Left, 12, [2, 3]);
+        stats.record_move(Direction::Left, 4, [2]);
+        stats.record_move(Direction::Down, 0, []);
+        stats.record_undo();
+
+        assert_eq!(BTreeMap::from([(2, 2), (3, 1)]), stats.merges);
+        assert_eq!(3, stats.total_merges());
+        assert_eq!(12, stats.best_move_points);
+        assert_eq!(1, stats.undos);
+        assert_eq!(
+            vec![
+                (Direction::Left, 2),
+                (Direction::Down, 1),
+                (Direction::Up, 0),
+                (Direction::Right, 0)
+            ],
+            stats.directions_by_use()
+        );
+    }
+
+    #[test]
+    fn reads_stats_saved_without_every_field() {
+        let stats: GameStats = serde_json::from_str(r#"{"undos": 2}"#).unwrap();
+
+        assert_eq!(
+            GameStats {
+                undos: 2,
+                ..GameStats::default()
+            },
+            stats
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::persistence;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Caps on how much of a game's history is kept in memory, set in a `[history]` table in the
+/// config file, so that very long games don't keep growing.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryLimits {
+    /// How many positions are kept for undoing, or `None` to keep them all. It's never more than
+    /// `moves`, since undoing a move takes it off the move history.
+    pub undo: Option<usize>,
+    /// How many moves and scores are kept, or `None` to keep them all.
+    pub moves: Option<usize>,
+    /// Whether moves that no longer fit are written to a file in the data directory instead of
+    /// being forgotten, so that the game can still be replayed when it ends.
+    pub spill_moves: bool,
+}
+
+impl Default for HistoryLimits {
+    fn default() -> Self {
+        HistoryLimits {
+            undo: Some(1000),
+            moves: None,
+            spill_moves: true,
+        }
+    }
+}
+
+impl HistoryLimits {
+    /// Gets how many positions are kept for undoing, taking the move cap into account.
+    pub fn undo_cap(&self) -> Option<usize> {
+        match (self.undo, self.moves) {
+            (Some(undo), Some(moves)) => Some(undo.min(moves)),
+            (undo, moves) => undo.or(moves),
+        }
+    }
+
+    /// Gets where moves that no longer fit are written.
+    ///
+    /// # Returns
+    ///
+    /// The directory for spill files, or `None` if moves aren't spilled or the platform has no
+    /// data directory.
+    pub fn spill_dir(&self) -> Option<PathBuf> {
+        if !self.spill_moves {
+            return None;
+        }
+        persistence::data_dir().map(|dir| dir.join("history"))
+    }
+}
+
+/// A list of entries, oldest first, that forgets its oldest entries when it grows past its cap.
+/// It remembers how many entries it forgot, so that e.g. the number of moves made stays right.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct History<T> {
+    entries: Vec<T>,
+    cap: Option<usize>,
+    forgotten: usize,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        History::new(Vec::new(), 0)
+    }
+}
+
+impl<T> History<T> {
+    /// Creates a history without a cap.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - The entries that are kept, oldest first.
+    /// * `forgotten` - How many older entries were already forgotten.
+    pub fn new(entries: Vec<T>, forgotten: usize) -> History<T> {
+        History {
+            entries,
+            cap: None,
+            forgotten,
+        }
+    }
+
+    /// Changes how many entries are kept, forgetting the oldest ones if there are too many.
+    ///
+    /// # Arguments
+    ///
+    /// * `cap` - The most entries to keep, or `None` to keep them all.
+    ///
+    /// # Returns
+    ///
+    /// The entries that were forgotten, oldest first.
+    pub fn set_cap(&mut self, cap: Option<usize>) -> Vec<T> {
+        self.cap = cap;
+        let excess = cap.map_or(0, |cap| self.entries.len().saturating_sub(cap));
+        self.forgotten += excess;
+        self.entries.drain(..excess).collect()
+    }
+
+    /// Adds an entry, forgetting the oldest one if the history is full.
+    ///
+    /// # Returns
+    ///
+    /// The forgotten entry, if one was.
+    pub fn push(&mut self, entry: T) -> Option<T> {
+        self.entries.push(entry);
+        if self.cap.is_some_and(|cap| self.entries.len() > cap) {
+            self.forgotten += 1;
+            return Some(self.entries.remove(0));
+        }
+        None
+    }
+
+    /// Takes off the newest entry. Forgotten entries can't be taken off.
+    pub fn pop(&mut self) -> Option<T> {
+        self.entries.pop()
+    }
+
+    /// Gets the entries that are kept, oldest first.
+    pub fn as_slice(&self) -> &[T] {
+        &self.entries
+    }
+
+    /// Gets how many entries were forgotten.
+    pub fn forgotten(&self) -> usize {
+        self.forgotten
+    }
+
+    /// Gets how many entries were ever added and not taken off, including forgotten ones.
+    pub fn total_len(&self) -> usize {
+        self.forgotten + self.entries.len()
+    }
+}
+
+/// Hands out a different number to every spill file of this process, so that two games with
+/// the same seed never share one.
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// A file the entries forgotten by a `History` are written to, one JSON line each, so that they
+/// can still be read back. It's deleted when dropped.
+#[derive(Debug)]
+pub struct SpillFile {
+    path: PathBuf,
+    /// How many entries are in the file.
+    written: Mutex<usize>,
+}
+
+impl SpillFile {
+    /// Creates an empty spill file.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to create the file in. It's created if it doesn't exist.
+    /// * `name` - What the file is for, e.g. the game's seed, to make its name easier to read.
+    ///
+    /// # Returns
+    ///
+    /// The new `SpillFile`, or an `io::Error` if it couldn't be created.
+    pub fn create(dir: &Path, name: &str) -> io::Result<SpillFile> {
+        fs::create_dir_all(dir)?;
+        let number = SPILL_FILES.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("{}-{}-{}.jsonl", name, std::process::id(), number));
+        File::create(&path)?;
+        Ok(SpillFile {
+            path,
+            written: Mutex::new(0),
+        })
+    }
+
+    /// Writes a forgotten entry to the end of the file. Games that were cloned share their spill
+    /// file, and forget the same entries in the same order, so an entry is only written the
+    /// first time its index comes up.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Where the entry was in its history, counting forgotten entries.
+    /// * `entry` - The forgotten entry.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `io::Result` that indicates success or failure.
+    pub fn append<T: Serialize>(&self, index: usize, entry: &T) -> io::Result<()> {
+        let mut written = self.written.lock().unwrap_or_else(|e| e.into_inner());
+        if index != *written {
+            return Ok(());
+        }
+        let mut line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        line.push('\n');
+        OpenOptions::new()
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())?;
+        *written += 1;
+        Ok(())
+    }
+
+    /// Reads back the entries written to the file, oldest first, one line at a time so that a
+    /// long game's moves don't have to be in memory at once.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the entries, each of which is an `io::Error` if it couldn't be read or
+    /// parsed, or an `io::Error` if the file couldn't be opened.
+    pub fn entries<T: DeserializeOwned>(&self) -> io::Result<impl Iterator<Item = io::Result<T>>> {
+        Ok(BufReader::new(File::open(&self.path)?)
+            .lines()
+            .map(|line| serde_json::from_str(&line?).map_err(io::Error::other)))
+    }
+
+    /// Gets how many entries were written to the file, without reading it.
+    pub fn entries_written(&self) -> usize {
+        *self.written.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_the_oldest_entries() {
+        let mut history = History::new(vec![1, 2, 3], 0);
+        assert_eq!(vec![1], history.set_cap(Some(2)));
+        assert_eq!(Some(2), history.push(4));
+        assert_eq!(None, History::new(vec![1], 0).push(2));
+
+        assert_eq!(&[3, 4], history.as_slice());
+        assert_eq!(2, history.forgotten());
+        assert_eq!(4, history.total_len());
+        assert_eq!(Some(4), history.pop());
+        assert_eq!(3, history.total_len());
+    }
+
+    #[test]
+    fn undo_cap_fits_in_the_move_cap() {
+        let limits = |undo, moves| HistoryLimits {
+            undo,
+            moves,
+            spill_moves: false,
+        };
+        assert_eq!(Some(10), limits(Some(50), Some(10)).undo_cap());
+        assert_eq!(Some(10), limits(None, Some(10)).undo_cap());
+        assert_eq!(Some(50), limits(Some(50), None).undo_cap());
+        assert_eq!(None, limits(None, None).undo_cap());
+        assert_eq!(None, limits(None, None).spill_dir());
+    }
+
+    #[test]
+    fn spilled_entries_read_back_once() {
+        let dir = std::env::temp_dir().join(format!("rs2048-spill-{}", std::process::id()));
+        let spill = SpillFile::create(&dir, "test").unwrap();
+        spill.append(0, &"first").unwrap();
+        spill.append(0, &"first again").unwrap();
+        spill.append(1, &"second").unwrap();
+
+        let entries: io::Result<Vec<String>> = spill.entries().unwrap().collect();
+        assert_eq!(vec!["first", "second"], entries.unwrap());
+        assert_eq!(2, spill.entries_written());
+        let path = spill.path.clone();
+        drop(spill);
+        assert!(!path.exists());
+    }
+}
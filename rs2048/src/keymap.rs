@@ -0,0 +1,325 @@
+use crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// Something the player can do with a key while playing.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Undo,
+    Redo,
+    AutoPlay,
+    Restart,
+    Pause,
+    Menu,
+    /// Shows the board turned a quarter turn, or back.
+    RotateView,
+    /// Shows or hides the points each move would score, next to the edges of the board.
+    ScorePreview,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Undo,
+        Action::Redo,
+        Action::AutoPlay,
+        Action::Restart,
+        Action::Pause,
+        Action::Menu,
+        Action::RotateView,
+        Action::ScorePreview,
+    ];
+
+    /// Gets the name of the action shown to the player.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::AutoPlay => "Auto-Play",
+            Action::Restart => "Restart",
+            Action::Pause => "Pause",
+            Action::Menu => "Menu",
+            Action::RotateView => "Rotate View",
+            Action::ScorePreview => "Score Preview",
+        }
+    }
+
+    /// Gets the key an action is bound to unless the config file says otherwise.
+    fn default_key(self) -> Key {
+        Key(match self {
+            Action::MoveUp => KeyCode::Up,
+            Action::MoveDown => KeyCode::Down,
+            Action::MoveLeft => KeyCode::Left,
+            Action::MoveRight => KeyCode::Right,
+            Action::Undo => KeyCode::Char('u'),
+            Action::Redo => KeyCode::Char('y'),
+            Action::AutoPlay => KeyCode::Char('a'),
+            Action::Restart => KeyCode::Char('r'),
+            Action::Pause => KeyCode::Esc,
+            Action::Menu => KeyCode::Char('q'),
+            Action::RotateView => KeyCode::Char('t'),
+            Action::ScorePreview => KeyCode::Char('v'),
+        })
+    }
+}
+
+/// A key that can be bound to an action, written in the config file by name, e.g. `"up"`,
+/// `"space"`, or `"w"`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Key(pub KeyCode);
+
+/// The names of the keys that aren't characters.
+const NAMED_KEYS: [(&str, KeyCode); 13] = [
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("page_up", KeyCode::PageUp),
+    ("page_down", KeyCode::PageDown),
+];
+
+impl Key {
+    /// Gets the name the key is written as in the config file.
+    ///
+    /// # Returns
+    ///
+    /// The name of the key, or `None` if it can't be bound.
+    pub fn name(self) -> Option<String> {
+        match self.0 {
+            KeyCode::Char(' ') => Some("space".to_string()),
+            KeyCode::Char(c) => Some(c.to_lowercase().to_string()),
+            code => NAMED_KEYS
+                .iter()
+                .find(|&&(_, named)| named == code)
+                .map(|&(name, _)| name.to_string()),
+        }
+    }
+
+    /// Looks up a key by the name it is written as in the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the key.
+    ///
+    /// # Returns
+    ///
+    /// The matching `Key`, or `None` if there isn't one.
+    pub fn from_name(name: &str) -> Option<Key> {
+        let mut chars = name.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(Key(KeyCode::Char(c.to_ascii_lowercase()))),
+            _ if name == "space" => Some(Key(KeyCode::Char(' '))),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|&&(named, _)| named == name)
+                .map(|&(_, code)| Key(code)),
+        }
+    }
+
+    /// Whether a key press is this key. Letters match in either case.
+    pub fn matches(self, code: KeyCode) -> bool {
+        match (self.0, code) {
+            (KeyCode::Char(a), KeyCode::Char(b)) => a.eq_ignore_ascii_case(&b),
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = self.name().unwrap_or_else(|| "?".to_string());
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => write!(
+                f,
+                "{}{}",
+                first.to_uppercase(),
+                chars.as_str().replace('_', " ")
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Key::from_name(&name).ok_or_else(|| format!("unknown key '{}'", name))
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.name().unwrap_or_default()
+    }
+}
+
+/// Which key does what while playing, set with the `[keys]` table in the config file. Actions
+/// missing from the table keep their default keys.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<Action, Key>", into = "BTreeMap<Action, Key>")]
+pub struct KeyBindings {
+    keys: BTreeMap<Action, Key>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::from(BTreeMap::new())
+    }
+}
+
+impl From<BTreeMap<Action, Key>> for KeyBindings {
+    fn from(mut keys: BTreeMap<Action, Key>) -> Self {
+        for action in Action::ALL {
+            keys.entry(action).or_insert_with(|| action.default_key());
+        }
+        KeyBindings { keys }
+    }
+}
+
+impl From<KeyBindings> for BTreeMap<Action, Key> {
+    fn from(bindings: KeyBindings) -> Self {
+        bindings.keys
+    }
+}
+
+impl KeyBindings {
+    /// Gets the key an action is bound to.
+    pub fn key(&self, action: Action) -> Key {
+        self.keys[&action]
+    }
+
+    /// Finds the action a key press is bound to.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The key that was pressed.
+    ///
+    /// # Returns
+    ///
+    /// The bound `Action`, or `None` if the key isn't bound.
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|&action| self.key(action).matches(code))
+    }
+
+    /// Binds a key to an action, unless another action already uses it.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to rebind.
+    /// * `key` - The new key for the action.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the key was bound, or `Err` with the other action that uses the key.
+    pub fn bind(&mut self, action: Action, key: Key) -> Result<(), Action> {
+        match self.action(key.0) {
+            Some(other) if other != action => Err(other),
+            _ => {
+                self.keys.insert(action, key);
+                Ok(())
+            }
+        }
+    }
+
+    /// Binds a key to an action, giving the action's old key to whichever action had the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to rebind.
+    /// * `key` - The new key for the action.
+    pub fn swap(&mut self, action: Action, key: Key) {
+        let old_key = self.key(action);
+        if let Some(other) = self.action(key.0) {
+            self.keys.insert(other, old_key);
+        }
+        self.keys.insert(action, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_names_round_trip() {
+        for name in ["up", "esc", "page_down", "space", "w", "7"] {
+            assert_eq!(Some(name.to_string()), Key::from_name(name).unwrap().name());
+        }
+        assert_eq!(None, Key::from_name("hyper"));
+        assert_eq!(None, Key(KeyCode::F(5)).name());
+    }
+
+    #[test]
+    fn key_labels() {
+        assert_eq!("Page down", Key(KeyCode::PageDown).to_string());
+        assert_eq!("W", Key(KeyCode::Char('w')).to_string());
+        assert_eq!("Space", Key(KeyCode::Char(' ')).to_string());
+    }
+
+    #[test]
+    fn letters_match_either_case() {
+        assert!(Key(KeyCode::Char('u')).matches(KeyCode::Char('U')));
+        assert!(!Key(KeyCode::Char('u')).matches(KeyCode::Char('r')));
+    }
+
+    #[test]
+    fn missing_actions_keep_their_defaults() {
+        let bindings: KeyBindings = toml::from_str("move_up = \"w\"").unwrap();
+
+        assert_eq!(Some(Action::MoveUp), bindings.action(KeyCode::Char('w')));
+        assert_eq!(None, bindings.action(KeyCode::Up));
+        assert_eq!(Some(Action::MoveDown), bindings.action(KeyCode::Down));
+    }
+
+    #[test]
+    fn unknown_key_is_an_error() {
+        assert!(toml::from_str::<KeyBindings>("move_up = \"hyper\"").is_err());
+    }
+
+    #[test]
+    fn bind_detects_conflicts() {
+        let mut bindings = KeyBindings::default();
+
+        assert_eq!(
+            Err(Action::Restart),
+            bindings.bind(Action::Undo, Key(KeyCode::Char('r')))
+        );
+        assert_eq!(Ok(()), bindings.bind(Action::Undo, Key(KeyCode::Char('z'))));
+        assert_eq!(Ok(()), bindings.bind(Action::Undo, Key(KeyCode::Char('z'))));
+        assert_eq!(Some(Action::Undo), bindings.action(KeyCode::Char('z')));
+    }
+
+    #[test]
+    fn swap_exchanges_keys() {
+        let mut bindings = KeyBindings::default();
+        bindings.swap(Action::Undo, Key(KeyCode::Char('r')));
+
+        assert_eq!(Some(Action::Undo), bindings.action(KeyCode::Char('r')));
+        assert_eq!(Some(Action::Restart), bindings.action(KeyCode::Char('u')));
+    }
+}
@@ -1,9 +1,175 @@
-use std::io;
+use crate::board::Board;
+use crate::config::{Config, Overrides};
+use crate::diagnostics::Diagnostics;
+use crate::recovery::SnapshotRing;
+#[cfg(unix)]
+use signal_hook::consts::SIGHUP;
+#[cfg(unix)]
+use signal_hook::iterator::Signals;
+use std::io::{BufWriter, Write};
+use std::sync::{Arc, Mutex};
+#[cfg(unix)]
+use std::thread;
+use std::{env, io, panic, process};
 
+mod achievements;
+mod ai;
 mod board;
+mod cli;
+mod coach;
+mod config;
+mod diagnostics;
+mod eval;
+mod focus;
+mod frame_pacing;
 mod game;
+mod game_stats;
+mod history;
+mod keymap;
+mod merge;
+mod number_format;
+mod persistence;
+mod race;
+mod recovery;
+mod rpc;
+mod rules;
+mod save;
+mod solver;
+mod spawn;
+mod stats;
+mod status_hook;
+mod theme;
+mod tile;
+mod tournament;
+mod trace;
+mod transform;
+mod tune;
+#[cfg(feature = "net")]
+mod update_check;
 mod user_interface;
+#[cfg(feature = "net")]
+mod webhook;
+
+/// How many bytes of output are buffered, which is enough for a whole frame of the biggest board.
+const FRAME_BUFFER_SIZE: usize = 1 << 16;
 
 fn main() {
-    user_interface::start_app(&mut io::stdout()).unwrap();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let ascii = args.iter().any(|arg| arg == "--ascii");
+    args.retain(|arg| arg != "--ascii");
+    let json_errors = args.iter().any(|arg| arg == "--json-errors");
+    args.retain(|arg| arg != "--json-errors");
+    // -v, -vv and -vvv log more and more spans
+    let is_verbosity = |arg: &String| {
+        arg.strip_prefix('-')
+            .is_some_and(|flags| !flags.is_empty() && flags.chars().all(|flag| flag == 'v'))
+    };
+    let verbosity: usize = args
+        .iter()
+        .filter(|arg| is_verbosity(arg))
+        .map(|arg| arg.len() - 1)
+        .sum();
+    args.retain(|arg| !is_verbosity(arg));
+    if verbosity > 0 {
+        match trace::start(verbosity.min(3) as u8) {
+            Ok(path) => eprintln!("Logging spans to {}", path.display()),
+            Err(e) => eprintln!("Couldn't start logging spans: {}", e),
+        }
+    }
+    let practice = match args.iter().position(|arg| arg == "--practice") {
+        Some(index) => {
+            let Some(source) = args.get(index + 1).cloned() else {
+                let error = cli::CliError::from("Missing value for --practice".to_string());
+                process::exit(cli::report(&error, json_errors));
+            };
+            args.drain(index..=index + 1);
+            match cli::practice_game(&source) {
+                Ok(game) => Some(game),
+                Err(e) => process::exit(cli::report(&e, json_errors)),
+            }
+        }
+        None => None,
+    };
+    if !args.is_empty() {
+        process::exit(cli::main(&args, json_errors));
+    }
+
+    let mut snapshots = SnapshotRing::new();
+    snapshots.take_saved_snapshots();
+    let snapshots = Arc::new(Mutex::new(snapshots));
+    install_crash_reporter(Arc::clone(&snapshots));
+    #[cfg(unix)]
+    install_hangup_handler(Arc::clone(&snapshots));
+    let overrides = Overrides {
+        ascii_borders: ascii,
+    };
+    let mut config = Config::load();
+    overrides.apply_to(&mut config);
+    // frames are buffered and written in one go, so that the terminal doesn't show them half drawn
+    let mut terminal = BufWriter::with_capacity(FRAME_BUFFER_SIZE, io::stdout());
+    if let Err(e) =
+        user_interface::start_app(&mut terminal, &config, overrides, snapshots, practice)
+    {
+        // stderr may be gone along with the terminal, so don't panic if this can't be printed
+        let _ = writeln!(
+            io::stderr(),
+            "rs2048 stopped because {}. The game can be recovered from the main menu.",
+            e
+        );
+        process::exit(1);
+    }
+}
+
+/// Makes panics restore the terminal before printing anything, so the message can be read, and
+/// save a crash report with details of the terminal for reproducing the problem. The recent
+/// snapshots are saved too, so the game can be recovered on the next start.
+///
+/// # Arguments
+///
+/// * `snapshots` - The snapshots the screens take while playing.
+fn install_crash_reporter(snapshots: Arc<Mutex<SnapshotRing>>) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let diagnostics = Diagnostics::gather();
+        let _ = user_interface::restore_terminal();
+        default_hook(info);
+        // the panic may have happened while the snapshots were locked, so don't wait for them
+        let snapshots = snapshots.try_lock().ok();
+        let board = snapshots
+            .as_ref()
+            .and_then(|snapshots| snapshots.newest_first().next())
+            .and_then(|saved| Board::try_from(saved.board.clone()).ok());
+        match diagnostics::write_crash_report(&info.to_string(), &diagnostics, board.as_ref()) {
+            Ok(path) => eprintln!("A crash report was saved to {}", path.display()),
+            Err(e) => eprintln!("Couldn't save a crash report: {}", e),
+        }
+        if let Some(snapshots) = snapshots {
+            if let Err(e) = snapshots.save_for_recovery() {
+                eprintln!("Couldn't save the game for recovery: {}", e);
+            }
+        }
+    }));
+}
+
+/// Makes the app save the recent snapshots and exit when its terminal hangs up, e.g. when the ssh
+/// connection drops, instead of being killed and losing the game. The screens can't notice the
+/// hangup themselves, since reading from a terminal that hung up just finds nothing to read.
+///
+/// # Arguments
+///
+/// * `snapshots` - The snapshots the screens take while playing, which include the game in
+///   progress as of its last move.
+#[cfg(unix)]
+fn install_hangup_handler(snapshots: Arc<Mutex<SnapshotRing>>) {
+    let Ok(mut signals) = Signals::new([SIGHUP]) else {
+        return;
+    };
+    thread::spawn(move || {
+        if signals.forever().next().is_some() {
+            if let Ok(snapshots) = snapshots.lock() {
+                let _ = snapshots.save_for_recovery();
+            }
+            process::exit(1);
+        }
+    });
 }
@@ -0,0 +1,689 @@
+use crate::board::TileType;
+use crate::number_format::NumberFormat;
+use crate::tile;
+use serde::{Deserialize, Serialize};
+
+/// The most tiles a `MergeRule` can merge at once.
+const MAX_ARITY: usize = 4;
+
+/// Decides which tiles combine when they slide into each other, and what that's worth.
+///
+/// Tiles are stored the same way as on a `Board`, as exponents with `0` meaning an empty cell.
+pub trait MergeRule {
+    /// Checks whether two neighbouring tiles merge. For rules that merge more than two tiles at
+    /// once, this checks whether `back` joins the group that `front` starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `front` - The tile closer to the direction of motion. Never empty.
+    /// * `back` - The tile sliding into it. Never empty.
+    ///
+    /// # Returns
+    ///
+    /// The tile the two become, or the group once it's full, or `None` if they don't merge.
+    fn merge(&self, front: TileType, back: TileType) -> Option<TileType>;
+
+    /// Gets the points earned for creating a tile by merging.
+    fn points(&self, merged: TileType) -> u32;
+
+    /// Gets the value shown on a tile, which merging keeps the total of.
+    ///
+    /// # Arguments
+    ///
+    /// * `tile` - The tile, `0` being an empty cell.
+    fn value(&self, tile: TileType) -> u64 {
+        tile::value(tile)
+    }
+
+    /// Gets whether a swipe moves the tiles of a line by at most one cell, like in Threes!,
+    /// instead of as far as they go.
+    fn shifts_one_cell(&self) -> bool {
+        false
+    }
+
+    /// Gets how many tiles in a row merge into one, from 2 up to 4. Rules that shift tiles one
+    /// cell at a time merge pairs.
+    fn arity(&self) -> usize {
+        2
+    }
+}
+
+/// The standard 2048 rule: two equal tiles merge into one of double the value, scoring the value
+/// of the new tile.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClassicRule;
+
+impl MergeRule for ClassicRule {
+    fn merge(&self, front: TileType, back: TileType) -> Option<TileType> {
+        (front == back).then_some(front + 1)
+    }
+
+    fn points(&self, merged: TileType) -> u32 {
+        1 << merged
+    }
+}
+
+/// The rule of the Fibonacci variant, also called 2584: tile `n` holds the `n`th number of the
+/// sequence 1, 2, 3, 5, 8, 13..., and two tiles merge when they're next to each other in it, into
+/// their sum. Two 1s merge too, since 1 + 1 = 2. The new tile's value is scored, like in 2048.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FibonacciRule;
+
+impl MergeRule for FibonacciRule {
+    fn merge(&self, front: TileType, back: TileType) -> Option<TileType> {
+        let consecutive = front.abs_diff(back) == 1 || (front == 1 && back == 1);
+        consecutive.then(|| front.max(back) + 1)
+    }
+
+    fn points(&self, merged: TileType) -> u32 {
+        self.value(merged).min(u32::MAX as u64) as u32
+    }
+
+    fn value(&self, tile: TileType) -> u64 {
+        tile::fibonacci_value(tile)
+    }
+}
+
+/// The rule of the Threes! variant: tiles 1 and 2 hold a 1 and a 2, which only merge with each
+/// other, into a 3. From then on each tile doubles the one before, 3, 6, 12..., and only equal
+/// tiles merge. A swipe shifts a line by at most one cell. The new tile's value is scored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreesRule;
+
+impl MergeRule for ThreesRule {
+    fn merge(&self, front: TileType, back: TileType) -> Option<TileType> {
+        match (front, back) {
+            (1, 2) | (2, 1) => Some(3),
+            _ if front == back && front >= 3 => Some(front + 1),
+            _ => None,
+        }
+    }
+
+    fn points(&self, merged: TileType) -> u32 {
+        self.value(merged).min(u32::MAX as u64) as u32
+    }
+
+    fn value(&self, tile: TileType) -> u64 {
+        tile::threes_value(tile)
+    }
+
+    fn shifts_one_cell(&self) -> bool {
+        true
+    }
+}
+
+/// The rule of the powers of 3 variant: tile `n` holds 3 to the power of `n`, and three equal
+/// tiles in a row merge into the next one, e.g. three 3s into a 9. The new tile's value is
+/// scored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PowersOfThreeRule;
+
+impl MergeRule for PowersOfThreeRule {
+    fn merge(&self, front: TileType, back: TileType) -> Option<TileType> {
+        (front == back).then_some(front + 1)
+    }
+
+    fn points(&self, merged: TileType) -> u32 {
+        self.value(merged).min(u32::MAX as u64) as u32
+    }
+
+    fn value(&self, tile: TileType) -> u64 {
+        tile::power_of_three_value(tile)
+    }
+
+    fn arity(&self) -> usize {
+        3
+    }
+}
+
+/// Which numbers the tiles of a game hold and which of them merge, set with `variant` in the
+/// config file.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// Powers of two, merged by `ClassicRule`.
+    #[default]
+    Classic,
+    /// Fibonacci numbers, merged by `FibonacciRule`.
+    Fibonacci,
+    /// 1s, 2s and multiples of 3, merged by `ThreesRule`.
+    Threes,
+    /// Powers of three, merged three at a time by `PowersOfThreeRule`.
+    PowersOfThree,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 4] = [
+        Variant::Classic,
+        Variant::Fibonacci,
+        Variant::Threes,
+        Variant::PowersOfThree,
+    ];
+
+    /// Gets the name of the variant shown in the settings screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            Variant::Classic => "Classic (2048)",
+            Variant::Fibonacci => "Fibonacci (2584)",
+            Variant::Threes => "Threes (1+2=3)",
+            Variant::PowersOfThree => "Powers of 3 (2187)",
+        }
+    }
+
+    /// Gets the short name of the variant, e.g. for the high score table.
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Classic => "Classic",
+            Variant::Fibonacci => "Fibonacci",
+            Variant::Threes => "Threes",
+            Variant::PowersOfThree => "Powers of 3",
+        }
+    }
+
+    /// Gets the next variant, wrapping around.
+    pub fn next(self) -> Variant {
+        let index = Variant::ALL
+            .iter()
+            .position(|&variant| variant == self)
+            .unwrap_or(0);
+        Variant::ALL[(index + 1) % Variant::ALL.len()]
+    }
+
+    /// Writes the value of a tile for the player, like `tile::display_value` does for classic
+    /// tiles.
+    ///
+    /// # Arguments
+    ///
+    /// * `tile` - The tile.
+    /// * `number_format` - How to group the digits of big values.
+    ///
+    /// # Returns
+    ///
+    /// The value of the tile, or an empty string for an empty cell.
+    pub fn display_value(self, tile: TileType, number_format: NumberFormat) -> String {
+        match tile {
+            0 => String::new(),
+            _ => number_format.format(self.value(tile)),
+        }
+    }
+
+    /// Writes the value of a tile in at most four characters, like `tile::short_value` does for
+    /// classic tiles.
+    pub fn short_value(self, tile: TileType) -> String {
+        match tile {
+            0 => String::new(),
+            _ => tile::shorten(self.value(tile)),
+        }
+    }
+}
+
+impl MergeRule for Variant {
+    fn merge(&self, front: TileType, back: TileType) -> Option<TileType> {
+        match self {
+            Variant::Classic => ClassicRule.merge(front, back),
+            Variant::Fibonacci => FibonacciRule.merge(front, back),
+            Variant::Threes => ThreesRule.merge(front, back),
+            Variant::PowersOfThree => PowersOfThreeRule.merge(front, back),
+        }
+    }
+
+    fn points(&self, merged: TileType) -> u32 {
+        match self {
+            Variant::Classic => ClassicRule.points(merged),
+            Variant::Fibonacci => FibonacciRule.points(merged),
+            Variant::Threes => ThreesRule.points(merged),
+            Variant::PowersOfThree => PowersOfThreeRule.points(merged),
+        }
+    }
+
+    fn value(&self, tile: TileType) -> u64 {
+        match self {
+            Variant::Classic => ClassicRule.value(tile),
+            Variant::Fibonacci => FibonacciRule.value(tile),
+            Variant::Threes => ThreesRule.value(tile),
+            Variant::PowersOfThree => PowersOfThreeRule.value(tile),
+        }
+    }
+
+    fn shifts_one_cell(&self) -> bool {
+        match self {
+            Variant::Classic => ClassicRule.shifts_one_cell(),
+            Variant::Fibonacci => FibonacciRule.shifts_one_cell(),
+            Variant::Threes => ThreesRule.shifts_one_cell(),
+            Variant::PowersOfThree => PowersOfThreeRule.shifts_one_cell(),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Variant::Classic => ClassicRule.arity(),
+            Variant::Fibonacci => FibonacciRule.arity(),
+            Variant::Threes => ThreesRule.arity(),
+            Variant::PowersOfThree => PowersOfThreeRule.arity(),
+        }
+    }
+}
+
+/// What happened to a line when it was merged.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct LineOutcome {
+    /// Whether any tile moved or merged, i.e. whether the line changed.
+    pub moved: bool,
+    /// How many merges happened, each of `MergeRule::arity` tiles.
+    pub merges: u32,
+    /// The points earned by those merges, according to the `MergeRule`.
+    pub points: u32,
+}
+
+/// Slides and merges one row or column, as if motion is from the back of the line to the front.
+///
+/// Empty cells (`0`) are skipped, so every tile ends up packed against the front in its original
+/// order. Walking from the front, each tile is offered to `rule` together with the tile behind it;
+/// when they merge, the result takes the front tile's place and can't merge again during this
+/// call. So with the classic rule `[1, 1, 1, 0]` becomes `[2, 1, 0, 0]` and `[1, 1, 2, 0]` becomes
+/// `[2, 2, 0, 0]`. The returned line is padded with empty cells to the length of the input.
+///
+/// Rules with a higher `MergeRule::arity` merge groups of that many tiles the same way, so with
+/// `PowersOfThreeRule` `[1, 1, 1, 1]` becomes `[2, 1, 0, 0]` and `[1, 1, 2, 2]` doesn't merge.
+///
+/// If the rule shifts tiles one cell at a time, tiles only move into the first empty cell or
+/// merge from the front, and every tile behind it moves up by one, so `[1, 0, 2, 2]` becomes
+/// `[1, 2, 2, 0]`.
+///
+/// Only the returned `Vec` is allocated, so this is cheap enough to call from search code.
+///
+/// # Arguments
+///
+/// * `tiles` - The tiles of the line, front first.
+/// * `rule` - Decides which tiles merge.
+///
+/// # Returns
+///
+/// The merged line and a `LineOutcome` describing what changed.
+pub fn merge_line(
+    tiles: impl Iterator<Item = TileType>,
+    rule: &impl MergeRule,
+) -> (Vec<TileType>, LineOutcome) {
+    let mut result: Vec<TileType> = Vec::with_capacity(tiles.size_hint().0);
+    let (length, outcome) = merge_tiles_into(tiles, rule, |tile, to, _| {
+        if result.len() <= to {
+            result.resize(to + 1, 0);
+        }
+        result[to] = tile;
+    });
+    result.resize(length, 0);
+    (result, outcome)
+}
+
+/// Slides and merges a line stored in an array, exactly like `merge_line` but without allocating,
+/// for searching fixed-size boards.
+///
+/// # Arguments
+///
+/// * `tiles` - The tiles of the line, front first.
+/// * `rule` - Decides which tiles merge.
+///
+/// # Returns
+///
+/// The merged line and a `LineOutcome` describing what changed.
+pub fn merge_array<const N: usize>(
+    tiles: [TileType; N],
+    rule: &impl MergeRule,
+) -> ([TileType; N], LineOutcome) {
+    let mut result = [0; N];
+    let (_, outcome) = merge_tiles_into(tiles.into_iter(), rule, |tile, to, _| result[to] = tile);
+    (result, outcome)
+}
+
+/// Where a tile of a line ends up when the line is merged, from `trace_line`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LineMove {
+    /// The index of the tile before merging, counted from the front.
+    pub from: usize,
+    /// The index of the tile after merging, counted from the front.
+    pub to: usize,
+    /// Whether the tile merged with another one, which moved to the same index.
+    pub merged: bool,
+}
+
+/// Works out where every tile of a line goes when it's merged like `merge_line` merges it, e.g.
+/// to animate the move.
+///
+/// # Arguments
+///
+/// * `tiles` - The tiles of the line, front first.
+/// * `rule` - Decides which tiles merge.
+///
+/// # Returns
+///
+/// A `LineMove` for every tile of the line, including those that stay where they are, ordered by
+/// where they end up.
+pub fn trace_line(tiles: impl Iterator<Item = TileType>, rule: &impl MergeRule) -> Vec<LineMove> {
+    let mut moves = Vec::new();
+    merge_tiles_into(tiles, rule, |_, to, sources| {
+        let merged = sources.len() > 1;
+        moves.extend(sources.iter().map(|&from| LineMove { from, to, merged }));
+    });
+    moves
+}
+
+/// Does the work of `merge_line`, handing each tile of the merged line to `push`, front first,
+/// together with its index in the merged line and the indices of the tiles it was made from.
+/// Empty cells aren't pushed.
+///
+/// # Returns
+///
+/// The length of the input line and a `LineOutcome` describing what changed.
+fn merge_tiles_into(
+    tiles: impl Iterator<Item = TileType>,
+    rule: &impl MergeRule,
+    mut push: impl FnMut(TileType, usize, &[usize]),
+) -> (usize, LineOutcome) {
+    if rule.shifts_one_cell() {
+        return shift_tiles_into(tiles, rule, push);
+    }
+    let arity = rule.arity();
+    debug_assert!(
+        (2..=MAX_ARITY).contains(&arity),
+        "unsupported arity {}",
+        arity
+    );
+    let mut outcome = LineOutcome::default();
+    let mut length = 0;
+    let mut pushed = 0;
+    // the tiles seen that might still merge together, with the indices they came from
+    let mut group = [(0, 0); MAX_ARITY];
+    let mut grouped = 0;
+
+    for (index, tile) in tiles.enumerate() {
+        length += 1;
+        if tile == 0 {
+            continue;
+        }
+
+        if let Some(merged) = (grouped > 0)
+            .then(|| rule.merge(group[0].0, tile))
+            .flatten()
+        {
+            group[grouped] = (tile, index);
+            grouped += 1;
+            if grouped == arity {
+                let sources = group.map(|(_, index)| index);
+                push(merged, pushed, &sources[..arity]);
+                pushed += 1;
+                outcome.moved = true;
+                outcome.merges += 1;
+                outcome.points += rule.points(merged);
+                grouped = 0;
+            }
+            continue;
+        }
+        // the group can't grow, so its tiles don't merge
+        for &(front, front_index) in &group[..grouped] {
+            // a tile that didn't merge moved if anything before it was empty or merged
+            outcome.moved |= front_index != pushed;
+            push(front, pushed, &[front_index]);
+            pushed += 1;
+        }
+        group[0] = (tile, index);
+        grouped = 1;
+    }
+    for &(tile, index) in &group[..grouped] {
+        outcome.moved |= index != pushed;
+        push(tile, pushed, &[index]);
+        pushed += 1;
+    }
+
+    (length, outcome)
+}
+
+/// Does the work of `merge_tiles_into` for rules that shift tiles one cell at a time. The tiles
+/// in front of the first empty cell or merge stay where they are, and every tile behind it moves
+/// up by one.
+fn shift_tiles_into(
+    tiles: impl Iterator<Item = TileType>,
+    rule: &impl MergeRule,
+    mut push: impl FnMut(TileType, usize, &[usize]),
+) -> (usize, LineOutcome) {
+    let mut outcome = LineOutcome::default();
+    let mut length = 0;
+    // whether a cell has been freed in front, which every later tile moves up into
+    let mut shifting = false;
+    // the last tile seen, which stays where it is unless the tile behind it merges into it
+    let mut pending: Option<(TileType, usize)> = None;
+
+    for (index, tile) in tiles.enumerate() {
+        length += 1;
+        if shifting {
+            if tile != 0 {
+                push(tile, index - 1, &[index]);
+                outcome.moved = true;
+            }
+            continue;
+        }
+        if tile == 0 {
+            if let Some((front, front_index)) = pending.take() {
+                push(front, front_index, &[front_index]);
+            }
+            shifting = true;
+            continue;
+        }
+
+        match pending {
+            Some((front, front_index)) => match rule.merge(front, tile) {
+                Some(merged) => {
+                    push(merged, front_index, &[front_index, index]);
+                    outcome.moved = true;
+                    outcome.merges += 1;
+                    outcome.points += rule.points(merged);
+                    pending = None;
+                    shifting = true;
+                }
+                None => {
+                    push(front, front_index, &[front_index]);
+                    pending = Some((tile, index));
+                }
+            },
+            None => pending = Some((tile, index)),
+        }
+    }
+    if let Some((tile, index)) = pending {
+        push(tile, index, &[index]);
+    }
+
+    (length, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(tiles: &[TileType]) -> (Vec<TileType>, LineOutcome) {
+        merge_line(tiles.iter().copied(), &ClassicRule)
+    }
+
+    #[test]
+    fn merge_empty_line() {
+        assert_eq!((vec![], LineOutcome::default()), merge(&[]));
+        assert_eq!(
+            (vec![0, 0, 0, 0], LineOutcome::default()),
+            merge(&[0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn merge_packed_line_does_not_move() {
+        let (line, outcome) = merge(&[1, 2, 3, 0]);
+
+        assert_eq!(vec![1, 2, 3, 0], line);
+        assert!(!outcome.moved);
+    }
+
+    #[test]
+    fn merge_slides_tiles_to_the_front() {
+        let (line, outcome) = merge(&[0, 1, 0, 2]);
+
+        assert_eq!(vec![1, 2, 0, 0], line);
+        assert_eq!(
+            LineOutcome {
+                moved: true,
+                merges: 0,
+                points: 0
+            },
+            outcome
+        );
+    }
+
+    #[test]
+    fn merge_each_tile_once() {
+        assert_eq!(vec![2, 1, 0, 0], merge(&[1, 1, 1, 0]).0);
+        assert_eq!(vec![2, 2, 0, 0], merge(&[1, 1, 2, 0]).0);
+        assert_eq!(vec![2, 2, 0, 0], merge(&[1, 1, 1, 1]).0);
+    }
+
+    #[test]
+    fn merge_scores_new_tiles() {
+        let (line, outcome) = merge(&[2, 2, 0, 3, 3]);
+
+        assert_eq!(vec![3, 4, 0, 0, 0], line);
+        assert_eq!(2, outcome.merges);
+        assert_eq!(8 + 16, outcome.points);
+        assert!(outcome.moved);
+    }
+
+    #[test]
+    fn merge_with_custom_rule() {
+        // merges any two tiles into the bigger one, scoring nothing
+        struct Absorb;
+        impl MergeRule for Absorb {
+            fn merge(&self, front: TileType, back: TileType) -> Option<TileType> {
+                Some(front.max(back))
+            }
+            fn points(&self, _merged: TileType) -> u32 {
+                0
+            }
+        }
+
+        let (line, outcome) = merge_line([1, 3, 2].into_iter(), &Absorb);
+
+        assert_eq!(vec![3, 2, 0], line);
+        assert_eq!(1, outcome.merges);
+    }
+
+    #[test]
+    fn trace_follows_every_tile() {
+        let moves = trace_line([0, 1, 1, 2, 0, 2].into_iter(), &ClassicRule);
+
+        let line_move = |from, to, merged| LineMove { from, to, merged };
+        assert_eq!(
+            vec![
+                line_move(1, 0, true),
+                line_move(2, 0, true),
+                line_move(3, 1, true),
+                line_move(5, 1, true),
+            ],
+            moves
+        );
+        assert_eq!(
+            vec![line_move(0, 0, false), line_move(2, 1, false)],
+            trace_line([1, 0, 2].into_iter(), &ClassicRule)
+        );
+    }
+
+    #[test]
+    fn fibonacci_tiles_merge_with_their_neighbours_in_the_sequence() {
+        // 1, 1, 2, 3 and 5, 5
+        let (line, outcome) = merge_line([1, 1, 2, 3, 4, 4].into_iter(), &FibonacciRule);
+
+        assert_eq!(vec![2, 4, 4, 4, 0, 0], line);
+        assert_eq!(2, outcome.merges);
+        assert_eq!(2 + 5, outcome.points);
+        assert_eq!(
+            vec![5, 0, 0],
+            merge_line([3, 4, 0].into_iter(), &FibonacciRule).0
+        );
+        assert_eq!(
+            "13",
+            Variant::Fibonacci.display_value(6, NumberFormat::None)
+        );
+    }
+
+    #[test]
+    fn threes_tiles_shift_one_cell() {
+        let threes = |tiles: &[TileType]| merge_line(tiles.iter().copied(), &ThreesRule);
+
+        // only the tiles behind the gap move, and only by one cell
+        let (line, outcome) = threes(&[1, 0, 2, 2]);
+        assert_eq!(vec![1, 2, 2, 0], line);
+        assert_eq!(0, outcome.merges);
+        assert!(outcome.moved);
+
+        // 1 and 2 make 3, and the tiles behind follow without merging again
+        let (line, outcome) = threes(&[1, 2, 3, 3]);
+        assert_eq!(vec![3, 3, 3, 0], line);
+        assert_eq!(1, outcome.merges);
+        assert_eq!(3, outcome.points);
+
+        // two 1s or two 2s don't merge, but two 6s make 12
+        assert!(!threes(&[1, 1, 3, 2]).1.moved);
+        assert!(!threes(&[2, 2, 4, 3]).1.moved);
+        assert_eq!(vec![2, 5, 0, 0], threes(&[2, 4, 4, 0]).0);
+        assert_eq!("12", Variant::Threes.display_value(5, NumberFormat::None));
+
+        let line_move = |from, to, merged| LineMove { from, to, merged };
+        assert_eq!(
+            vec![
+                line_move(0, 0, false),
+                line_move(2, 1, false),
+                line_move(3, 2, false),
+            ],
+            trace_line([1, 0, 2, 2].into_iter(), &ThreesRule)
+        );
+    }
+
+    #[test]
+    fn powers_of_three_merge_three_at_a_time() {
+        let (line, outcome) = merge_line([1, 1, 1, 1, 0, 2, 2].into_iter(), &PowersOfThreeRule);
+
+        assert_eq!(vec![2, 1, 2, 2, 0, 0, 0], line);
+        assert_eq!(1, outcome.merges);
+        assert_eq!(9, outcome.points);
+        assert!(
+            !merge_line([1, 1, 2, 2].into_iter(), &PowersOfThreeRule)
+                .1
+                .moved
+        );
+        assert_eq!(
+            vec![3, 0, 0],
+            merge_line([2, 2, 2].into_iter(), &PowersOfThreeRule).0
+        );
+        assert_eq!(
+            "2,187",
+            Variant::PowersOfThree.display_value(7, NumberFormat::Comma)
+        );
+
+        let line_move = |from, to, merged| LineMove { from, to, merged };
+        assert_eq!(
+            vec![
+                line_move(0, 0, true),
+                line_move(2, 0, true),
+                line_move(3, 0, true),
+            ],
+            trace_line([1, 0, 1, 1].into_iter(), &PowersOfThreeRule)
+        );
+    }
+
+    #[test]
+    fn merge_array_matches_merge_line() {
+        for tiles in [
+            [1, 1, 1, 0],
+            [0, 1, 0, 1],
+            [1, 2, 3, 0],
+            [2, 2, 1, 1],
+            [0, 0, 0, 3],
+        ] {
+            let (array, array_outcome) = merge_array(tiles, &ClassicRule);
+            assert_eq!(merge(&tiles), (array.to_vec(), array_outcome));
+        }
+    }
+}
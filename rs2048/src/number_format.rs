@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// The character used to group the digits of large numbers into thousands.
+///
+/// `Auto` picks a separator based on the locale environment variables (`LC_ALL`, `LC_NUMERIC`,
+/// then `LANG`), falling back to a comma when no locale is set.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberFormat {
+    #[default]
+    Auto,
+    Comma,
+    Period,
+    Space,
+    None,
+}
+
+impl NumberFormat {
+    /// Formats a number with thousands separators, e.g. `12480` becomes `12,480` or `12.480`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The number to format.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the formatted number.
+    pub fn format(self, value: u64) -> String {
+        let digits = value.to_string();
+        let separator = match self.resolve() {
+            NumberFormat::Comma => ',',
+            NumberFormat::Period => '.',
+            NumberFormat::Space => ' ',
+            _ => return digits,
+        };
+
+        let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                result.push(separator);
+            }
+            result.push(digit);
+        }
+        result
+    }
+
+    /// Resolves `Auto` into a concrete format using the locale environment variables. Any other
+    /// format is returned unchanged.
+    pub fn resolve(self) -> NumberFormat {
+        if self != NumberFormat::Auto {
+            return self;
+        }
+
+        // the first variable that is set and non-empty wins, matching how POSIX resolves locales
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        NumberFormat::from_locale(&locale)
+    }
+
+    /// Picks the conventional thousands separator for a locale string such as `de_DE.UTF-8`.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - The locale string, as found in `LANG`.
+    ///
+    /// # Returns
+    ///
+    /// The separator used by the locale's language, or `Comma` if the language is unknown.
+    fn from_locale(locale: &str) -> NumberFormat {
+        let language = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match language.as_str() {
+            "de" | "es" | "it" | "pt" | "nl" | "da" | "id" | "tr" | "el" | "ro" | "sl" | "hr" => {
+                NumberFormat::Period
+            }
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "nn" | "no" | "uk" | "hu"
+            | "bg" | "et" | "lv" | "lt" => NumberFormat::Space,
+            _ => NumberFormat::Comma,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_small_number() {
+        assert_eq!("512", NumberFormat::Comma.format(512));
+    }
+
+    #[test]
+    fn format_with_comma() {
+        assert_eq!("12,480", NumberFormat::Comma.format(12480));
+    }
+
+    #[test]
+    fn format_with_period() {
+        assert_eq!("12.480", NumberFormat::Period.format(12480));
+    }
+
+    #[test]
+    fn format_with_space() {
+        assert_eq!("1 234 567", NumberFormat::Space.format(1234567));
+    }
+
+    #[test]
+    fn format_without_separator() {
+        assert_eq!("1234567", NumberFormat::None.format(1234567));
+    }
+
+    #[test]
+    fn format_exact_group_boundary() {
+        assert_eq!("100,000", NumberFormat::Comma.format(100000));
+    }
+
+    #[test]
+    fn format_zero() {
+        assert_eq!("0", NumberFormat::Period.format(0));
+    }
+
+    #[test]
+    fn locale_detection() {
+        assert_eq!(
+            NumberFormat::Period,
+            NumberFormat::from_locale("de_DE.UTF-8")
+        );
+        assert_eq!(NumberFormat::Space, NumberFormat::from_locale("fr_FR"));
+        assert_eq!(
+            NumberFormat::Comma,
+            NumberFormat::from_locale("en_US.UTF-8")
+        );
+        assert_eq!(NumberFormat::Comma, NumberFormat::from_locale("C"));
+        assert_eq!(NumberFormat::Comma, NumberFormat::from_locale(""));
+    }
+}
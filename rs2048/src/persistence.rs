@@ -0,0 +1,368 @@
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// How long to wait for another instance to finish writing before giving up.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often to retry while waiting for the lock.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The first bytes of a gzip stream, which tell compressed files from plain text when reading.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Debug)]
+pub enum PersistenceError {
+    /// Another instance held the lock for longer than the timeout.
+    Locked,
+    /// The platform has no data directory to write to.
+    NoDataDirectory,
+    /// The platform has no config directory to write to.
+    NoConfigDirectory,
+    Io(io::Error),
+}
+
+impl Display for PersistenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersistenceError::Locked => write!(
+                f,
+                "another instance of rs2048 is running and didn't finish saving in time"
+            ),
+            PersistenceError::NoDataDirectory => write!(f, "no data directory on this platform"),
+            PersistenceError::NoConfigDirectory => {
+                write!(f, "no config directory on this platform")
+            }
+            PersistenceError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for PersistenceError {
+    fn from(e: io::Error) -> Self {
+        PersistenceError::Io(e)
+    }
+}
+
+/// Gets the directory where rs2048 keeps everything it writes, e.g. `~/.local/share/rs2048` on
+/// Linux.
+///
+/// # Returns
+///
+/// The data directory, or `None` if the platform doesn't have one.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("rs2048"))
+}
+
+/// An advisory lock on a directory, held until dropped, so that two running instances don't
+/// write over each other's files.
+///
+/// The lock is taken on a `.lock` file inside the directory. The operating system releases it
+/// when the process exits, so a crash can't leave it stuck.
+pub struct DirectoryLock {
+    _file: File,
+}
+
+impl DirectoryLock {
+    /// Locks a directory, waiting up to `timeout` for another instance to release it.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to lock. It is created if it doesn't exist.
+    /// * `timeout` - How long to wait for the lock.
+    ///
+    /// # Returns
+    ///
+    /// The held `DirectoryLock`, or a `PersistenceError` if the lock couldn't be taken in time or
+    /// the lock file couldn't be opened.
+    pub fn acquire(dir: &Path, timeout: Duration) -> Result<DirectoryLock, PersistenceError> {
+        fs::create_dir_all(dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(".lock"))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(DirectoryLock { _file: file }),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    sleep(LOCK_RETRY_INTERVAL)
+                }
+                Err(TryLockError::WouldBlock) => return Err(PersistenceError::Locked),
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Writes a file in the data directory while holding the data directory's lock.
+///
+/// The contents are written to a temporary file first and then renamed over the old file, so a
+/// reader never sees a half-written file.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the file, relative to the data directory. It may be in a
+///   subdirectory, which is created if needed.
+/// * `contents` - What to write.
+///
+/// # Returns
+///
+/// The full path that was written, or a `PersistenceError` if the file couldn't be written.
+pub fn write_data_file(
+    file_name: &str,
+    contents: impl AsRef<[u8]>,
+) -> Result<PathBuf, PersistenceError> {
+    let dir = data_dir().ok_or(PersistenceError::NoDataDirectory)?;
+    write_locked(&dir, file_name, contents)
+}
+
+/// Writes a file in a directory while holding that directory's lock, like `write_data_file` does
+/// for the data directory.
+///
+/// # Arguments
+///
+/// * `dir` - The directory to lock and write in. It is created if it doesn't exist.
+/// * `file_name` - The name of the file, relative to `dir`.
+/// * `contents` - What to write.
+///
+/// # Returns
+///
+/// The full path that was written, or a `PersistenceError` if the file couldn't be written.
+pub fn write_locked(
+    dir: &Path,
+    file_name: &str,
+    contents: impl AsRef<[u8]>,
+) -> Result<PathBuf, PersistenceError> {
+    let _lock = DirectoryLock::acquire(dir, LOCK_TIMEOUT)?;
+    replace_file(dir, file_name, contents)
+}
+
+/// Rewrites a file in the data directory from what's in it now, e.g. to merge what another
+/// instance saved with this instance's changes. The data directory's lock is held from reading
+/// the file until it's written, so that no other instance writes it in between.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the file, relative to the data directory.
+/// * `update` - Makes the new contents from the current ones, which are `None` if the file
+///   doesn't exist or can't be read.
+///
+/// # Returns
+///
+/// The full path that was written, or a `PersistenceError` if the file couldn't be written or
+/// `update` failed.
+pub fn update_data_file(
+    file_name: &str,
+    update: impl FnOnce(Option<String>) -> Result<String, PersistenceError>,
+) -> Result<PathBuf, PersistenceError> {
+    let dir = data_dir().ok_or(PersistenceError::NoDataDirectory)?;
+    update_locked(&dir, file_name, update)
+}
+
+/// Rewrites a file in a directory from what's in it now while holding that directory's lock,
+/// like `update_data_file` does for the data directory.
+///
+/// # Arguments
+///
+/// * `dir` - The directory to lock and write in. It is created if it doesn't exist.
+/// * `file_name` - The name of the file, relative to `dir`.
+/// * `update` - Makes the new contents from the current ones.
+///
+/// # Returns
+///
+/// The full path that was written, or a `PersistenceError` if the file couldn't be written or
+/// `update` failed.
+pub fn update_locked(
+    dir: &Path,
+    file_name: &str,
+    update: impl FnOnce(Option<String>) -> Result<String, PersistenceError>,
+) -> Result<PathBuf, PersistenceError> {
+    let _lock = DirectoryLock::acquire(dir, LOCK_TIMEOUT)?;
+    let contents = update(fs::read_to_string(dir.join(file_name)).ok())?;
+    replace_file(dir, file_name, contents)
+}
+
+/// Writes a file through a temporary file renamed over it. The caller holds the directory's lock.
+fn replace_file(
+    dir: &Path,
+    file_name: &str,
+    contents: impl AsRef<[u8]>,
+) -> Result<PathBuf, PersistenceError> {
+    let path = dir.join(file_name);
+    let parent = path.parent().unwrap_or(dir);
+    fs::create_dir_all(parent)?;
+    let temporary_path = parent.join(format!(
+        ".{}.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    fs::write(&temporary_path, contents)?;
+    fs::rename(&temporary_path, &path)?;
+    Ok(path)
+}
+
+/// Compresses the contents of a data file that can get large, e.g. a save with every position of
+/// a long game, when built with the `compression` feature. Without it, the contents are returned
+/// as they are.
+///
+/// # Arguments
+///
+/// * `contents` - What is going to be written.
+///
+/// # Returns
+///
+/// The bytes to pass to `write_data_file`, which `read_data_file` turns back into `contents`.
+#[cfg(feature = "compression")]
+pub fn compress(contents: &str) -> Vec<u8> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    // writing into a Vec can't fail
+    let _ = encoder.write_all(contents.as_bytes());
+    encoder
+        .finish()
+        .unwrap_or_else(|_| contents.as_bytes().to_vec())
+}
+
+/// Compresses the contents of a data file that can get large when built with the `compression`
+/// feature. This build doesn't have it, so the contents are returned as they are.
+#[cfg(not(feature = "compression"))]
+pub fn compress(contents: &str) -> Vec<u8> {
+    contents.as_bytes().to_vec()
+}
+
+/// Reads a data file, decompressing it if it was written compressed by `compress`.
+///
+/// # Arguments
+///
+/// * `path` - The file to read.
+///
+/// # Returns
+///
+/// The contents of the file, or an `io::Error` if it couldn't be read, isn't text, or is
+/// compressed and this build doesn't have the `compression` feature.
+pub fn read_data_file(path: &Path) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+    }
+    decompress(&bytes)
+}
+
+#[cfg(feature = "compression")]
+fn decompress(bytes: &[u8]) -> io::Result<String> {
+    use std::io::Read;
+
+    let mut contents = String::new();
+    flate2::read::GzDecoder::new(bytes).read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_bytes: &[u8]) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the file is compressed, but rs2048 was built without the compression feature",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rs2048-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn second_lock_times_out() {
+        let dir = test_dir("lock");
+        let _held = DirectoryLock::acquire(&dir, Duration::ZERO).unwrap();
+
+        let second = DirectoryLock::acquire(&dir, Duration::from_millis(100));
+        assert!(matches!(second, Err(PersistenceError::Locked)));
+    }
+
+    #[test]
+    fn lock_is_released_on_drop() {
+        let dir = test_dir("release");
+        drop(DirectoryLock::acquire(&dir, Duration::ZERO).unwrap());
+
+        assert!(DirectoryLock::acquire(&dir, Duration::ZERO).is_ok());
+    }
+
+    #[test]
+    fn write_replaces_the_file() {
+        let dir = test_dir("write");
+        write_locked(&dir, "data.toml", "old").unwrap();
+        let path = write_locked(&dir, "data.toml", "new").unwrap();
+
+        assert_eq!("new", fs::read_to_string(path).unwrap());
+        assert!(!dir.join(".data.toml.tmp").exists());
+    }
+
+    #[test]
+    fn update_starts_from_the_current_contents() {
+        let dir = test_dir("update");
+        update_locked(&dir, "count.txt", |contents| {
+            assert_eq!(None, contents);
+            Ok("1".to_string())
+        })
+        .unwrap();
+        let path = update_locked(&dir, "count.txt", |contents| {
+            Ok(format!("{}+1", contents.unwrap()))
+        })
+        .unwrap();
+
+        assert_eq!("1+1", fs::read_to_string(path).unwrap());
+    }
+
+    #[test]
+    fn write_into_subdirectory() {
+        let dir = test_dir("subdirectory");
+        let path = write_locked(&dir, "saves/slot-1.json", "{}").unwrap();
+
+        assert_eq!(dir.join("saves").join("slot-1.json"), path);
+        assert!(!dir.join("saves").join(".slot-1.json.tmp").exists());
+    }
+
+    #[test]
+    fn write_fails_while_locked() {
+        let dir = test_dir("write-locked");
+        let _held = DirectoryLock::acquire(&dir, Duration::ZERO).unwrap();
+
+        assert!(matches!(
+            write_locked(&dir, "data.toml", "contents"),
+            Err(PersistenceError::Locked)
+        ));
+    }
+
+    #[test]
+    fn compressed_files_read_back() {
+        let dir = test_dir("compressed");
+        let contents = "{\"moves\": []}".repeat(100);
+        let path = write_locked(&dir, "saves/slot-1.json", compress(&contents)).unwrap();
+
+        assert_eq!(contents, read_data_file(&path).unwrap());
+        if cfg!(feature = "compression") {
+            assert!(fs::metadata(&path).unwrap().len() < contents.len() as u64);
+        }
+    }
+
+    #[test]
+    fn plain_files_read_back() {
+        let dir = test_dir("plain");
+        let path = write_locked(&dir, "saves/slot-1.json", "{}").unwrap();
+
+        assert_eq!("{}", read_data_file(&path).unwrap());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::board::Direction;
+use crate::game::{Game, GameError};
+use crate::rules::Rules;
+
+/// How a race ended.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RaceResult {
+    /// The player, counting from 0, made the winning tile first.
+    MadeWinningTile(usize),
+    /// Neither player made the winning tile, and the player, counting from 0, finished with the
+    /// higher score.
+    HigherScore(usize),
+    /// Neither player made the winning tile, and both finished with the same score.
+    Tie,
+}
+
+/// Two games played side by side on the same tiles, e.g. by two players sharing a keyboard. The
+/// first player to make the rules' winning tile wins; if both games end without it, the higher
+/// score wins. The players either move whenever they like or, in a hot-seat race, take turns.
+pub struct Race {
+    games: [Game; 2],
+    result: Option<RaceResult>,
+    /// Whose turn it is, counting from 0, in a hot-seat race, or `None` if the players move
+    /// whenever they like.
+    turn: Option<usize>,
+}
+
+impl Race {
+    /// Starts a race with a random seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The rules both games are played by.
+    ///
+    /// # Returns
+    ///
+    /// The new `Race`, or `GameError::InvalidBoardSize` if the rules' board size isn't supported.
+    pub fn new(rules: Rules) -> Result<Race, GameError> {
+        Race::with_seed(rand::random(), rules)
+    }
+
+    /// Starts a hot-seat race with a random seed, where the players take turns making a move.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The rules both games are played by.
+    ///
+    /// # Returns
+    ///
+    /// The new `Race`, with player one to move first, or `GameError::InvalidBoardSize` if the
+    /// rules' board size isn't supported.
+    pub fn taking_turns(rules: Rules) -> Result<Race, GameError> {
+        let mut race = Race::new(rules)?;
+        race.turn = Some(0);
+        Ok(race)
+    }
+
+    /// Starts another race by the same rules, taken in turns if this one was.
+    ///
+    /// # Returns
+    ///
+    /// The new `Race`, or `GameError::InvalidBoardSize` if the rules' board size isn't supported.
+    pub fn rematch(&self) -> Result<Race, GameError> {
+        match self.turn {
+            Some(_) => Race::taking_turns(self.rules().clone()),
+            None => Race::new(self.rules().clone()),
+        }
+    }
+
+    /// Starts a race whose games share a seed, so both players start with the same tiles and
+    /// get the same new tiles for as long as they make the same moves.
+    fn with_seed(seed: u64, rules: Rules) -> Result<Race, GameError> {
+        let first = Game::start_seeded_game(seed, rules.clone())?;
+        let second = Game::start_seeded_game(seed, rules)?;
+        Ok(Race::from_games([first, second]))
+    }
+
+    fn from_games(games: [Game; 2]) -> Race {
+        let mut race = Race {
+            games,
+            result: None,
+            turn: None,
+        };
+        race.decide();
+        race
+    }
+
+    /// Gets the games, player one's first.
+    pub fn games(&self) -> &[Game; 2] {
+        &self.games
+    }
+
+    /// Gets how the race ended, or `None` while it's still going.
+    pub fn result(&self) -> Option<RaceResult> {
+        self.result
+    }
+
+    /// Gets whose turn it is, counting from 0, in a hot-seat race that isn't over, or `None` if
+    /// the players move whenever they like.
+    pub fn turn(&self) -> Option<usize> {
+        self.turn.filter(|_| self.result.is_none())
+    }
+
+    /// Makes a move in one player's game. Moves are ignored once the race is over, in a game that
+    /// has ended while the other player goes on, and in a hot-seat race from the player whose
+    /// turn it isn't. A move that changes the board passes the turn to the other player, unless
+    /// their game has ended.
+    ///
+    /// # Arguments
+    ///
+    /// * `player` - Whose game to move in, counting from 0.
+    /// * `direction` - Which way to swipe.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or the `GameError` if the game couldn't go on, in which case it's left as it was.
+    pub fn play(&mut self, player: usize, direction: Direction) -> Result<(), GameError> {
+        if self.result.is_some() || self.turn.is_some_and(|turn| turn != player) {
+            return Ok(());
+        }
+        let update = self.games[player].clone().handle_event(direction.into())?;
+        self.games[player] = update.game;
+        self.decide();
+        if let Some(turn) = &mut self.turn {
+            let other = 1 - player;
+            if update.spawned.is_some() && !self.games[other].is_game_over() {
+                *turn = other;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the rules both games are played by.
+    pub fn rules(&self) -> &Rules {
+        self.games[0].rules()
+    }
+
+    /// Ends the games whose time limit has passed, which can end the race.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in milliseconds since the Unix epoch.
+    pub fn check_time_limits(&mut self, now: u64) {
+        if self.result.is_some() {
+            return;
+        }
+        for game in &mut self.games {
+            game.check_time_limit(now);
+        }
+        self.decide();
+        // a player whose time ran out can't take their turn
+        if let Some(turn) = &mut self.turn {
+            if self.games[*turn].is_game_over() {
+                *turn = 1 - *turn;
+            }
+        }
+    }
+
+    /// Decides the race if a player has made the winning tile or both games have ended. Moves
+    /// are made one at a time, so only one player can have just made the winning tile.
+    fn decide(&mut self) {
+        if self.result.is_some() {
+            return;
+        }
+        if let Some(player) = self.games.iter().position(Game::has_won) {
+            self.result = Some(RaceResult::MadeWinningTile(player));
+        } else if self.games.iter().all(Game::is_game_over) {
+            let [first, second] = [self.games[0].score(), self.games[1].score()];
+            self.result = Some(if first > second {
+                RaceResult::HigherScore(0)
+            } else if second > first {
+                RaceResult::HigherScore(1)
+            } else {
+                RaceResult::Tie
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    fn restore(notation: &str, rules: &Rules) -> Game {
+        let board = Board::from_notation(notation).unwrap();
+        Game::restore(
+            board,
+            0,
+            7,
+            rules.clone(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn both_players_start_with_the_same_tiles() {
+        let race = Race::with_seed(42, Rules::default()).unwrap();
+        let [first, second] = race.games();
+
+        assert_eq!(first.board(), second.board());
+        assert_eq!(None, race.result());
+    }
+
+    #[test]
+    fn the_first_player_to_make_the_winning_tile_wins() {
+        let rules = Rules::default();
+        let board = "1024,1024../2.../..../....";
+        let mut race = Race::from_games([restore(board, &rules), restore(board, &rules)]);
+
+        race.play(0, Direction::Down).unwrap();
+        assert_eq!(None, race.result());
+        race.play(1, Direction::Left).unwrap();
+        assert_eq!(Some(RaceResult::MadeWinningTile(1)), race.result());
+
+        // the race is over, so the other player can't catch up
+        race.play(0, Direction::Right).unwrap();
+        assert!(!race.games()[0].has_won());
+        assert_eq!(Some(RaceResult::MadeWinningTile(1)), race.result());
+    }
+
+    #[test]
+    fn the_higher_score_wins_when_both_games_end() {
+        let rules = Rules {
+            move_limit: Some(1),
+            ..Rules::default()
+        };
+        let board = "2,2../..../..../....";
+        let mut race = Race::from_games([restore(board, &rules), restore(board, &rules)]);
+
+        race.play(1, Direction::Left).unwrap();
+        assert_eq!(None, race.result());
+        race.play(0, Direction::Down).unwrap();
+        assert_eq!(Some(RaceResult::HigherScore(1)), race.result());
+    }
+
+    #[test]
+    fn hot_seat_players_take_turns() {
+        let rules = Rules::default();
+        let board = "2,2../..../..../....";
+        let mut race = Race::from_games([restore(board, &rules), restore(board, &rules)]);
+        race.turn = Some(0);
+
+        // player two has to wait for their turn
+        race.play(1, Direction::Left).unwrap();
+        assert_eq!(board, race.games()[1].board().to_notation());
+        assert_eq!(Some(0), race.turn());
+
+        // a move that doesn't change the board doesn't use up the turn
+        race.play(0, Direction::Up).unwrap();
+        assert_eq!(Some(0), race.turn());
+        race.play(0, Direction::Left).unwrap();
+        assert_eq!(Some(1), race.turn());
+        race.play(1, Direction::Left).unwrap();
+        assert_eq!(Some(0), race.turn());
+    }
+
+    #[test]
+    fn equal_scores_tie() {
+        let rules = Rules {
+            move_limit: Some(1),
+            ..Rules::default()
+        };
+        let board = "2,2../..../..../....";
+        let mut race = Race::from_games([restore(board, &rules), restore(board, &rules)]);
+
+        race.play(0, Direction::Right).unwrap();
+        race.play(1, Direction::Left).unwrap();
+        assert_eq!(Some(RaceResult::Tie), race.result());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::game::Game;
+use crate::persistence;
+use crate::persistence::PersistenceError;
+use crate::save::SavedGame;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+/// How many snapshots are kept.
+pub const CAPACITY: usize = 16;
+
+/// The file the snapshots are written to when the app crashes, in the data directory.
+const RECOVERY_FILE_NAME: &str = "recovery.json";
+
+/// The last few positions of the games played in this session, kept apart from the undo history
+/// so that a game can be got back after an accidental restart, a bug in undo, or a crash.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotRing {
+    /// The snapshots, oldest first.
+    snapshots: VecDeque<SavedGame>,
+    /// Whether snapshots saved before a crash were recovered.
+    recovered_from_crash: bool,
+}
+
+impl SnapshotRing {
+    pub fn new() -> SnapshotRing {
+        SnapshotRing::default()
+    }
+
+    /// Takes a snapshot of a game, dropping the oldest snapshot if the ring is full. Nothing is
+    /// taken if the game looks the same as in the newest snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game to take a snapshot of.
+    /// * `taken_at` - When the snapshot is taken, in seconds since the Unix epoch.
+    pub fn push(&mut self, game: &Game, taken_at: u64) {
+        let unchanged = self.snapshots.back().is_some_and(|newest| {
+            &newest.board == game.read_board_state() && newest.score == game.score()
+        });
+        if unchanged {
+            return;
+        }
+        if self.snapshots.len() == CAPACITY {
+            self.snapshots.pop_front();
+        }
+        self.snapshots
+            .push_back(SavedGame::from_game(game, taken_at));
+    }
+
+    /// Gets the snapshots, newest first.
+    pub fn newest_first(&self) -> impl Iterator<Item = &SavedGame> {
+        self.snapshots.iter().rev()
+    }
+
+    /// Whether snapshots saved before a crash were recovered when the app started.
+    pub fn recovered_from_crash(&self) -> bool {
+        self.recovered_from_crash
+    }
+
+    /// Writes the snapshots to the data directory so they can be recovered on the next start,
+    /// e.g. when the app crashes.
+    ///
+    /// # Returns
+    ///
+    /// The path that was written, or a `PersistenceError` if it couldn't be written.
+    pub fn save_for_recovery(&self) -> Result<PathBuf, PersistenceError> {
+        let contents =
+            serde_json::to_string(&self.snapshots).map_err(|e| PersistenceError::Io(e.into()))?;
+        persistence::write_data_file(RECOVERY_FILE_NAME, persistence::compress(&contents))
+    }
+
+    /// Adds the snapshots written by `save_for_recovery`, e.g. before a crash, as the oldest
+    /// snapshots and deletes the file, so that they are only offered once.
+    pub fn take_saved_snapshots(&mut self) {
+        let Some(path) = persistence::data_dir().map(|dir| dir.join(RECOVERY_FILE_NAME)) else {
+            return;
+        };
+        let saved: Option<Vec<SavedGame>> = persistence::read_data_file(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        let _ = fs::remove_file(&path);
+
+        let Some(saved) = saved.filter(|saved| !saved.is_empty()) else {
+            return;
+        };
+        for snapshot in saved.into_iter().rev() {
+            if self.snapshots.len() == CAPACITY {
+                break;
+            }
+            self.snapshots.push_front(snapshot);
+        }
+        self.recovered_from_crash = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameEvent;
+
+    #[test]
+    fn keeps_the_newest_snapshots() {
+        let mut ring = SnapshotRing::new();
+        let mut game = Game::start_new_game().unwrap();
+        let mut changed_at = Vec::new();
+        for index in 0..CAPACITY as u64 * 3 {
+            let event = if index % 2 == 0 {
+                GameEvent::SwipeLeft
+            } else {
+                GameEvent::SwipeRight
+            };
+            let before = game.read_board_state().clone();
+            game = game.handle_event(event).unwrap().game;
+            if game.read_board_state() != &before {
+                changed_at.push(index);
+            }
+            ring.push(&game, index);
+        }
+
+        let taken_at: Vec<u64> = ring.newest_first().map(|saved| saved.saved_at).collect();
+        let expected: Vec<u64> = changed_at.into_iter().rev().take(CAPACITY).collect();
+        assert_eq!(expected, taken_at);
+    }
+
+    #[test]
+    fn unchanged_games_are_skipped() {
+        let mut ring = SnapshotRing::new();
+        let game = Game::start_new_game().unwrap();
+        ring.push(&game, 1);
+        ring.push(&game, 2);
+
+        assert_eq!(1, ring.newest_first().count());
+    }
+}
@@ -0,0 +1,546 @@
+use crate::board::Direction;
+use crate::game::{Game, GameError, GameEvent, GameUpdate};
+use crate::rules::Rules;
+use crate::solver::{EvalWeights, Solver};
+use serde_json::{json, Value};
+#[cfg(feature = "net")]
+use std::io::BufReader;
+use std::io::{self, BufRead, Write};
+#[cfg(feature = "net")]
+use std::net::TcpListener;
+#[cfg(feature = "net")]
+use std::thread;
+
+/// The version of the protocol spoken by `serve`. Clients must send it in the `handshake` request
+/// before calling anything else, and it changes whenever a method changes incompatibly.
+pub const PROTOCOL_VERSION: u64 = 3;
+
+/// How many moves ahead `get_hint` looks.
+const HINT_DEPTH: u32 = 2;
+
+// standard JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// codes for errors specific to this server
+const HANDSHAKE_REQUIRED: i64 = -32000;
+const NO_GAME: i64 = -32001;
+const GAME_FAILED: i64 = -32002;
+
+/// An error returned to the client in a JSON-RPC error response.
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// One client's connection to the server, with its own game.
+///
+/// Requests are JSON-RPC 2.0 objects, one per line. The methods are:
+///
+/// * `handshake` - `{"protocol_version": 3}`. Must be called first.
+/// * `new_game` - Starts a new game and returns its state. An optional `{"size": 5}` plays on a
+///   board of another size.
+/// * `apply_move` - `{"direction": "up" | "down" | "left" | "right"}`. Returns the new state and
+///   whether the board changed. Moves don't change the board while the game is paused or
+///   waiting for `keep_playing`.
+/// * `keep_playing` - Goes on after winning, in endless mode. Returns the new state.
+/// * `pause` and `resume` - Pause the game, which stops its clock and ignores moves, and go on
+///   with it. Return the new state.
+/// * `get_state` - Returns the state of the current game.
+/// * `get_hint` - Returns the move the solver recommends, and its evaluation of every move.
+///
+/// States are serialized `GameView`s: the board as tile values (`0` for empty cells), the score,
+/// the number of moves, whether the winning tile is on the board, and the `GameState`, e.g.
+/// `"playing"`, `"paused"`, `{"won": {"continued": false}}` while waiting for `keep_playing`, or
+/// `{"lost": {"kind": "no_moves_left"}}`.
+pub struct Session {
+    handshake_done: bool,
+    game: Option<Game>,
+    solver: Solver,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session {
+            handshake_done: false,
+            game: None,
+            solver: Solver::new(EvalWeights::load(), HINT_DEPTH),
+        }
+    }
+
+    /// Handles one line of input from the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - A JSON-RPC request.
+    ///
+    /// # Returns
+    ///
+    /// The response to send back, or `None` if the request was a notification (it had no `id`).
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                ))
+            }
+        };
+        let id = request.get("id").cloned();
+
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some(method) => {
+                let params = request.get("params").cloned().unwrap_or(Value::Null);
+                self.call(method, &params)
+            }
+            None => Err(RpcError::new(INVALID_REQUEST, "missing method")),
+        };
+
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}).to_string(),
+            Err(e) => error_response(id, e),
+        })
+    }
+
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        if method == "handshake" {
+            return self.handshake(params);
+        }
+        if !self.handshake_done {
+            return Err(RpcError::new(
+                HANDSHAKE_REQUIRED,
+                "call handshake before any other method",
+            ));
+        }
+
+        match method {
+            "new_game" => {
+                let game = match params.get("size") {
+                    None => Game::start_new_game(),
+                    Some(size) => {
+                        let size = size.as_u64().ok_or_else(|| {
+                            RpcError::new(INVALID_PARAMS, "size must be a whole number")
+                        })?;
+                        Game::start_new_game_with_size(size as usize)
+                    }
+                }
+                .map_err(|e| match e {
+                    GameError::InvalidBoardSize(size) => RpcError::new(
+                        INVALID_PARAMS,
+                        format!(
+                            "size {} isn't supported, it must be from {} to {}",
+                            size,
+                            Rules::MIN_BOARD_SIZE,
+                            Rules::MAX_BOARD_SIZE
+                        ),
+                    ),
+                    e => RpcError::new(GAME_FAILED, format!("{:?}", e)),
+                })?;
+                let state = game_state(&game);
+                self.game = Some(game);
+                Ok(state)
+            }
+            "apply_move" => {
+                let direction = params
+                    .get("direction")
+                    .and_then(Value::as_str)
+                    .and_then(Direction::from_name)
+                    .ok_or_else(|| {
+                        RpcError::new(
+                            INVALID_PARAMS,
+                            "direction must be one of up, down, left, or right",
+                        )
+                    })?;
+                let moved = self.current_game()?.peek_move(direction).changes_board;
+                let state = game_state(
+                    self.update_game(|game| game.handle_event(GameEvent::from(direction)))?,
+                );
+                Ok(json!({"moved": moved, "state": state}))
+            }
+            "keep_playing" | "pause" | "resume" => {
+                let event = match method {
+                    "pause" => GameEvent::Pause,
+                    "resume" => GameEvent::Resume,
+                    _ => GameEvent::KeepPlaying,
+                };
+                Ok(game_state(
+                    self.update_game(|game| game.handle_event(event))?,
+                ))
+            }
+            "get_state" => Ok(game_state(self.current_game()?)),
+            "get_hint" => {
+                let game = self.current_game()?;
+                let moves: serde_json::Map<String, Value> = self
+                    .solver
+                    .evaluate_legal_moves(game)
+                    .into_iter()
+                    .map(|(direction, value)| (direction.name().to_string(), json!(value)))
+                    .collect();
+                let best = self.solver.best_legal_move(game).map(Direction::name);
+                Ok(json!({"direction": best, "evaluations": moves}))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{}'", method),
+            )),
+        }
+    }
+
+    fn handshake(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let version = params
+            .get("protocol_version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "protocol_version is required"))?;
+        if version != PROTOCOL_VERSION {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                format!(
+                    "unsupported protocol version {}, this server speaks version {}",
+                    version, PROTOCOL_VERSION
+                ),
+            ));
+        }
+
+        self.handshake_done = true;
+        Ok(json!({
+            "protocol_version": PROTOCOL_VERSION,
+            "server": "rs2048",
+            "server_version": env!("CARGO_PKG_VERSION"),
+        }))
+    }
+
+    fn current_game(&self) -> Result<&Game, RpcError> {
+        self.game
+            .as_ref()
+            .ok_or_else(|| RpcError::new(NO_GAME, "no game in progress, call new_game first"))
+    }
+
+    /// Updates the game in progress, e.g. with a move. The update works on a copy, so a failed
+    /// update leaves the game as it was instead of losing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `update` - Makes the updated game from a copy of the game in progress.
+    ///
+    /// # Returns
+    ///
+    /// The updated game, or an `RpcError` if there is no game or the update failed.
+    fn update_game(
+        &mut self,
+        update: impl FnOnce(Game) -> Result<GameUpdate, GameError>,
+    ) -> Result<&Game, RpcError> {
+        let game = update(self.current_game()?.clone())
+            .map_err(|e| RpcError::new(GAME_FAILED, format!("{:?}", e)))?
+            .game;
+        Ok(self.game.insert(game))
+    }
+}
+
+/// Serves one session, reading requests from `reader` until it is closed.
+///
+/// # Arguments
+///
+/// * `reader` - Where requests come from, one per line.
+/// * `writer` - Where responses are written, one per line.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+pub fn serve<R: BufRead, W: Write>(reader: R, mut writer: W) -> io::Result<()> {
+    let mut session = Session::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = session.handle_line(&line) {
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Accepts connections on a local TCP port, serving each one as its own session on its own
+/// thread. Never returns unless the port can't be listened on.
+///
+/// # Arguments
+///
+/// * `port` - The port to listen on. Only connections from this machine are accepted.
+///
+/// # Returns
+///
+/// An `io::Error` if the port couldn't be listened on.
+#[cfg(feature = "net")]
+pub fn serve_tcp(port: u16) -> io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    eprintln!("Listening on {}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Couldn't accept a connection: {}", e);
+                continue;
+            }
+        };
+        thread::spawn(move || {
+            let reader = match stream.try_clone() {
+                Ok(reader) => BufReader::new(reader),
+                Err(e) => return eprintln!("Couldn't read from a connection: {}", e),
+            };
+            if let Err(e) = serve(reader, stream) {
+                eprintln!("Connection closed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn error_response(id: Value, error: RpcError) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": error.code, "message": error.message},
+    })
+    .to_string()
+}
+
+fn game_state(game: &Game) -> Value {
+    serde_json::to_value(game.view()).expect("a game view is always valid JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(session: &mut Session, request: Value) -> Value {
+        serde_json::from_str(&session.handle_line(&request.to_string()).unwrap()).unwrap()
+    }
+
+    fn connected_session() -> Session {
+        let mut session = Session::new();
+        call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 0, "method": "handshake", "params": {"protocol_version": PROTOCOL_VERSION}}),
+        );
+        session
+    }
+
+    #[test]
+    fn handshake_is_required() {
+        let mut session = Session::new();
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "new_game"}),
+        );
+
+        assert_eq!(HANDSHAKE_REQUIRED, response["error"]["code"]);
+    }
+
+    #[test]
+    fn handshake_rejects_other_versions() {
+        let mut session = Session::new();
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "handshake", "params": {"protocol_version": 99}}),
+        );
+
+        assert_eq!(INVALID_PARAMS, response["error"]["code"]);
+        assert!(!session.handshake_done);
+    }
+
+    #[test]
+    fn play_a_move() {
+        let mut session = connected_session();
+        let state = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "new_game"}),
+        );
+        assert_eq!(4, state["result"]["board"].as_array().unwrap().len());
+
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "apply_move", "params": {"direction": "left"}}),
+        );
+        assert_eq!(2, response["id"]);
+        assert!(response["result"]["moved"].is_boolean());
+    }
+
+    #[test]
+    fn hint_recommends_a_move() {
+        let mut session = connected_session();
+        call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "new_game"}),
+        );
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "get_hint"}),
+        );
+
+        let direction = response["result"]["direction"].as_str().unwrap();
+        assert!(Direction::from_name(direction).is_some());
+    }
+
+    #[test]
+    fn new_game_takes_a_size() {
+        let mut session = connected_session();
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "new_game", "params": {"size": 5}}),
+        );
+        assert_eq!(5, response["result"]["board"].as_array().unwrap().len());
+
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "new_game", "params": {"size": 20}}),
+        );
+        assert_eq!(INVALID_PARAMS, response["error"]["code"]);
+    }
+
+    #[test]
+    fn state_without_game() {
+        let mut session = connected_session();
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "get_state"}),
+        );
+
+        assert_eq!(NO_GAME, response["error"]["code"]);
+    }
+
+    #[test]
+    fn keep_playing_before_winning_changes_nothing() {
+        let mut session = connected_session();
+        call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "new_game"}),
+        );
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "keep_playing"}),
+        );
+
+        assert_eq!("playing", response["result"]["state"]);
+    }
+
+    #[test]
+    fn paused_games_ignore_moves() {
+        let mut session = connected_session();
+        call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "new_game"}),
+        );
+        let paused = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "pause"}),
+        );
+        assert_eq!("paused", paused["result"]["state"]);
+
+        let moves = ["up", "down", "left", "right"].map(|direction| {
+            call(
+                &mut session,
+                json!({"jsonrpc": "2.0", "id": 3, "method": "apply_move", "params": {"direction": direction}}),
+            )
+        });
+        assert!(moves
+            .iter()
+            .all(|response| response["result"]["moved"] == false));
+
+        let resumed = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 4, "method": "resume"}),
+        );
+        assert_eq!("playing", resumed["result"]["state"]);
+    }
+
+    #[test]
+    fn invalid_direction() {
+        let mut session = connected_session();
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "apply_move", "params": {"direction": "sideways"}}),
+        );
+
+        assert_eq!(INVALID_PARAMS, response["error"]["code"]);
+    }
+
+    #[test]
+    fn failed_updates_keep_the_game() {
+        let mut session = connected_session();
+        call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "new_game"}),
+        );
+        let before = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "get_state"}),
+        );
+
+        let error = session
+            .update_game(|_| Err(GameError::AddRandomTileError))
+            .unwrap_err();
+        assert_eq!(GAME_FAILED, error.code);
+        let after = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 3, "method": "get_state"}),
+        );
+        assert_eq!(before["result"], after["result"]);
+    }
+
+    #[test]
+    fn unknown_method() {
+        let mut session = connected_session();
+        let response = call(
+            &mut session,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "undo"}),
+        );
+
+        assert_eq!(METHOD_NOT_FOUND, response["error"]["code"]);
+    }
+
+    #[test]
+    fn notifications_get_no_response() {
+        let mut session = connected_session();
+        assert_eq!(
+            None,
+            session.handle_line(r#"{"jsonrpc": "2.0", "method": "new_game"}"#)
+        );
+    }
+
+    #[test]
+    fn malformed_json() {
+        let mut session = Session::new();
+        let response: Value =
+            serde_json::from_str(&session.handle_line("{not json").unwrap()).unwrap();
+
+        assert_eq!(PARSE_ERROR, response["error"]["code"]);
+        assert_eq!(Value::Null, response["id"]);
+    }
+
+    #[test]
+    fn serve_answers_each_line() {
+        let input = format!(
+            "{}\n\n{}\n",
+            json!({"jsonrpc": "2.0", "id": 1, "method": "handshake", "params": {"protocol_version": PROTOCOL_VERSION}}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "new_game"}),
+        );
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(2, String::from_utf8(output).unwrap().lines().count());
+    }
+}
@@ -0,0 +1,343 @@
+use crate::board::{Board, TileType};
+use crate::game::WINNING_TILE;
+use crate::merge::Variant;
+use crate::solver::MoveRules;
+use crate::spawn::{FairSpawn, SpawnPolicy, SpawnRule, SpawnWeights};
+use serde::{Deserialize, Serialize};
+
+/// The rules a game is played by, chosen in the settings when the game starts and stored with it
+/// in saves and replays.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// How many cells wide and tall the board is, from `MIN_BOARD_SIZE` to `MAX_BOARD_SIZE`.
+    pub board_size: usize,
+    /// Where new tiles appear.
+    pub spawn: SpawnRule,
+    /// Which tiles spawn and how often.
+    pub spawn_weights: SpawnWeights,
+    /// Which numbers the tiles hold and which of them merge.
+    pub variant: Variant,
+    /// The tile that wins the game, e.g. `10` to win at 1024 on a small board. Like every tile,
+    /// what it's worth depends on the `variant`.
+    pub win_exponent: TileType,
+    /// How many tiles the board starts with. It's kept between one and the number of cells.
+    pub starting_tiles: usize,
+    /// The most moves the game may last, or `None` for no limit.
+    pub move_limit: Option<u32>,
+    /// How many seconds the game may last from its first move, or `None` for no limit.
+    pub time_limit: Option<u64>,
+    /// How many moves may be undone in the game, or `None` for no limit.
+    pub undo_limit: Option<u32>,
+    /// Whether tiles sliding off one edge of the board come back in from the opposite edge. Games
+    /// played this way have their own best score.
+    pub wrap_around: bool,
+    /// Whether the tiles fall to the bottom of the board after every move to the left or right,
+    /// without merging.
+    pub gravity: bool,
+    /// Whether a new tile that would leave no move is swapped for one that leaves a move, where
+    /// one can, as a forgiving casual mode. See `FairSpawn`.
+    pub fair_spawns: bool,
+    /// Whether a board that locks up has its oldest smallest tile taken off instead of ending the
+    /// game, as a relaxing mode that can't be lost. Limits on moves and time still end it.
+    pub zen: bool,
+    /// Whether the tile odds follow how well the game is going, with more of the bigger tiles
+    /// while the board is kept clear and fewer while it fills up. See `adaptive_spawn_weights`.
+    /// Adaptive games are kept off the high scores.
+    pub adaptive: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            board_size: 4,
+            spawn: SpawnRule::default(),
+            spawn_weights: SpawnWeights::default(),
+            starting_tiles: 2,
+            variant: Variant::default(),
+            win_exponent: WINNING_TILE,
+            move_limit: None,
+            time_limit: None,
+            undo_limit: None,
+            wrap_around: false,
+            gravity: false,
+            fair_spawns: false,
+            zen: false,
+            adaptive: false,
+        }
+    }
+}
+
+impl Rules {
+    /// The smallest board a game can be played on.
+    pub const MIN_BOARD_SIZE: usize = 2;
+    /// The biggest board a game can be played on, which still fits on most terminals.
+    pub const MAX_BOARD_SIZE: usize = 8;
+    /// The board sizes offered in the settings screen, in the order they are cycled through.
+    const BOARD_SIZES: [usize; 4] = [4, 5, 6, 3];
+    /// The winning tiles offered in the settings screen, as exponents, in the order they are cycled
+    /// through.
+    const WIN_EXPONENTS: [TileType; 4] = [WINNING_TILE, 12, 13, 10];
+    /// The winning tiles offered for the Fibonacci variant: 2584, 4181, 6765 and 1597.
+    const FIBONACCI_WIN_EXPONENTS: [TileType; 4] = [17, 18, 19, 16];
+    /// The winning tiles offered for the Threes variant: 768, 1536, 3072 and 6144.
+    const THREES_WIN_EXPONENTS: [TileType; 4] = [11, 12, 13, 14];
+    /// The winning tiles offered for the powers of 3 variant: 2187, 6561, 19683 and 729.
+    const POWERS_OF_THREE_WIN_EXPONENTS: [TileType; 4] = [7, 8, 9, 6];
+    /// The move limits offered in the settings screen, in the order they are cycled through.
+    const MOVE_LIMITS: [Option<u32>; 4] = [None, Some(100), Some(250), Some(500)];
+    /// The time limits offered in the settings screen, in seconds, in the order they are cycled
+    /// through.
+    const TIME_LIMITS: [Option<u64>; 4] = [None, Some(3 * 60), Some(5 * 60), Some(10 * 60)];
+    /// The undo limits offered in the settings screen, in the order they are cycled through.
+    const UNDO_LIMITS: [Option<u32>; 4] = [None, Some(0), Some(1), Some(3)];
+    /// How many of the latest positions adaptive difficulty judges the game by, so that the odds
+    /// shift gradually rather than with every move.
+    pub const ADAPTIVE_WINDOW: usize = 8;
+
+    /// Gets the policy that spawns tiles in games played by these rules.
+    pub fn spawn_policy(&self) -> Box<dyn SpawnPolicy + '_> {
+        self.spawn_policy_with(self.spawn_weights.clone())
+    }
+
+    /// Gets the policy that spawns tiles in games played by these rules, with other tile odds,
+    /// e.g. the ones from `adaptive_spawn_weights`.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - Which tiles spawn and how often.
+    pub fn spawn_policy_with(&self, weights: SpawnWeights) -> Box<dyn SpawnPolicy + '_> {
+        let policy = self.spawn.policy(weights.clone(), MoveRules::of(self));
+        if self.fair_spawns {
+            Box::new(FairSpawn {
+                inner: policy,
+                weights,
+                variant: self.variant,
+            })
+        } else {
+            policy
+        }
+    }
+
+    /// Gets the tile odds for the next tile from how well the game has been going, which is how
+    /// much of the board was empty over the latest positions. A quarter of the board empty keeps
+    /// the usual odds. Emptier boards make the bigger tiles up to twice as likely, and fuller ones
+    /// make them as little as half as likely. Without `adaptive` the odds never change.
+    ///
+    /// # Arguments
+    ///
+    /// * `recent` - The latest positions, up to `ADAPTIVE_WINDOW` of them.
+    ///
+    /// # Returns
+    ///
+    /// The weights to spawn the next tile with.
+    pub fn adaptive_spawn_weights<'a>(
+        &self,
+        recent: impl IntoIterator<Item = &'a Board>,
+    ) -> SpawnWeights {
+        if !self.adaptive {
+            return self.spawn_weights.clone();
+        }
+        let (mut empty, mut cells) = (0, 0);
+        for board in recent.into_iter().take(Rules::ADAPTIVE_WINDOW) {
+            let rows = board.get_data_for_display();
+            empty += board.empty_positions().len();
+            cells += rows.len() * rows.len();
+        }
+        let Some(empty_percent) = (empty * 100).checked_div(cells) else {
+            return self.spawn_weights.clone();
+        };
+        // in percent of the usual weight of the bigger tiles
+        let scale = (empty_percent as u64 * 4).clamp(50, 200);
+        let smallest = self.spawn_weights.smallest_tile();
+        let weights: Vec<(TileType, u64)> = self
+            .spawn_weights
+            .0
+            .iter()
+            .map(|&(tile, weight)| {
+                if tile == smallest {
+                    (tile, weight as u64 * 100)
+                } else {
+                    (tile, weight as u64 * scale)
+                }
+            })
+            .collect();
+        SpawnWeights::divided_to_fit(weights)
+    }
+
+    /// Gets whether undoing a move marks the game as assisted. Undos the rules hand out a few of
+    /// are part of the game, but unlimited ones can take back any mistake.
+    pub fn undo_assists(&self) -> bool {
+        self.undo_limit.is_none()
+    }
+
+    /// Gets how many tiles a game starts with, keeping `starting_tiles` between one and the
+    /// number of cells on the board.
+    pub fn starting_tile_count(&self) -> usize {
+        self.starting_tiles
+            .min(self.board_size * self.board_size)
+            .max(1)
+    }
+
+    /// Gets the next board size offered in the settings screen, like `next_move_limit`.
+    pub fn next_board_size(size: usize) -> usize {
+        next_of(&Rules::BOARD_SIZES, size)
+    }
+
+    /// Gets the next winning tile offered in the settings screen, like `next_move_limit`. The
+    /// first one offered for a variant is the one it's named after, e.g. 2584 for Fibonacci.
+    ///
+    /// # Arguments
+    ///
+    /// * `exponent` - The winning tile now.
+    /// * `variant` - The variant the tiles belong to.
+    pub fn next_win_exponent(exponent: TileType, variant: Variant) -> TileType {
+        match variant {
+            Variant::Classic => next_of(&Rules::WIN_EXPONENTS, exponent),
+            Variant::Fibonacci => next_of(&Rules::FIBONACCI_WIN_EXPONENTS, exponent),
+            Variant::Threes => next_of(&Rules::THREES_WIN_EXPONENTS, exponent),
+            Variant::PowersOfThree => next_of(&Rules::POWERS_OF_THREE_WIN_EXPONENTS, exponent),
+        }
+    }
+
+    /// Switches the rules to another variant, along with the winning tile and the tile odds,
+    /// since the winning tile of one variant means something else in another.
+    ///
+    /// # Arguments
+    ///
+    /// * `variant` - The variant to play.
+    ///
+    /// # Returns
+    ///
+    /// The rules for the variant, otherwise the same as these.
+    pub fn with_variant(self, variant: Variant) -> Rules {
+        Rules {
+            variant,
+            win_exponent: Rules::next_win_exponent(0, variant),
+            spawn_weights: SpawnWeights::for_variant(variant),
+            ..self
+        }
+    }
+
+    /// Gets the next move limit offered in the settings screen. Limits that aren't offered, e.g.
+    /// set in the config file, move on to no limit.
+    pub fn next_move_limit(limit: Option<u32>) -> Option<u32> {
+        next_of(&Rules::MOVE_LIMITS, limit)
+    }
+
+    /// Gets the next time limit offered in the settings screen, like `next_move_limit`.
+    pub fn next_time_limit(limit: Option<u64>) -> Option<u64> {
+        next_of(&Rules::TIME_LIMITS, limit)
+    }
+
+    /// Gets the next undo limit offered in the settings screen, like `next_move_limit`.
+    pub fn next_undo_limit(limit: Option<u32>) -> Option<u32> {
+        next_of(&Rules::UNDO_LIMITS, limit)
+    }
+}
+
+/// Gets the choice after `current`, wrapping around, or the first choice if `current` isn't one.
+fn next_of<T: Copy + PartialEq>(choices: &[T], current: T) -> T {
+    let next = choices
+        .iter()
+        .position(|&choice| choice == current)
+        .map_or(0, |index| (index + 1) % choices.len());
+    choices[next]
+}
+
+/// Describes a number of seconds in minutes and seconds, e.g. "4:05".
+pub fn format_duration(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_cycle() {
+        assert_eq!(Some(100), Rules::next_move_limit(None));
+        assert_eq!(None, Rules::next_move_limit(Some(500)));
+        assert_eq!(None, Rules::next_move_limit(Some(42)));
+        assert_eq!(Some(180), Rules::next_time_limit(None));
+        assert_eq!(Some(0), Rules::next_undo_limit(None));
+        assert_eq!(None, Rules::next_undo_limit(Some(3)));
+        assert_eq!(5, Rules::next_board_size(4));
+        assert_eq!(4, Rules::next_board_size(3));
+        assert_eq!(4, Rules::next_board_size(7));
+        assert_eq!(12, Rules::next_win_exponent(11, Variant::Classic));
+        assert_eq!(11, Rules::next_win_exponent(10, Variant::Classic));
+        // switching to Fibonacci starts at 2584
+        assert_eq!(17, Rules::next_win_exponent(11, Variant::Fibonacci));
+        assert_eq!(12, Rules::next_win_exponent(11, Variant::Threes));
+    }
+
+    #[test]
+    fn parses_partial_rules() {
+        let rules: Rules = toml::from_str("move_limit = 50").unwrap();
+
+        assert_eq!(
+            Rules {
+                move_limit: Some(50),
+                ..Rules::default()
+            },
+            rules
+        );
+    }
+
+    #[test]
+    fn adaptive_odds_follow_how_empty_the_board_is() {
+        let rules = Rules {
+            adaptive: true,
+            ..Rules::default()
+        };
+        let clear = Board::from_notation("2.../..../..../....").unwrap();
+        let par = Board::from_notation("2,4,8,16/32,64,128,256/2,4,8,16/....").unwrap();
+        let full = Board::from_notation("2,4,8,16/32,64,128,256/2,4,8,16/32,64,128,256").unwrap();
+
+        assert_eq!(
+            40,
+            rules.adaptive_spawn_weights([&clear]).bigger_tile_percent()
+        );
+        assert_eq!(
+            25,
+            rules.adaptive_spawn_weights([&par]).bigger_tile_percent()
+        );
+        assert_eq!(
+            14,
+            rules.adaptive_spawn_weights([&full]).bigger_tile_percent()
+        );
+        // the odds shift gradually, since the earlier positions count too
+        assert_eq!(
+            38,
+            rules
+                .adaptive_spawn_weights([&clear, &full])
+                .bigger_tile_percent()
+        );
+        assert_eq!(
+            SpawnWeights::default(),
+            Rules::default().adaptive_spawn_weights([&clear])
+        );
+    }
+
+    #[test]
+    fn adaptive_odds_fit_huge_weights() {
+        let rules = Rules {
+            adaptive: true,
+            spawn_weights: SpawnWeights(vec![(1, u32::MAX), (2, u32::MAX / 3)]),
+            ..Rules::default()
+        };
+        let clear = Board::from_notation("1073741824.../..../..../....").unwrap();
+
+        let weights = rules.adaptive_spawn_weights([&clear]);
+        let total: u64 = weights.0.iter().map(|&(_, weight)| weight as u64).sum();
+        assert!(total <= u32::MAX as u64);
+        assert_eq!(40, weights.bigger_tile_percent());
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!("4:05", format_duration(245));
+        assert_eq!("0:00", format_duration(0));
+    }
+}
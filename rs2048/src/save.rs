@@ -0,0 +1,643 @@
+use crate::board::{Board, TileType};
+use crate::game::{Game, GameEvent, MoveRecord, WinState};
+use crate::game_stats::GameStats;
+use crate::persistence;
+use crate::persistence::PersistenceError;
+use crate::rules::Rules;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many save slots there are.
+pub const SLOT_COUNT: usize = 3;
+
+/// The version of the save format written by this version of the game. Saves from before the
+/// version was stored are version `0`.
+pub const SAVE_FORMAT: u32 = 1;
+
+/// A game as stored in a save slot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    /// The rows of the board, with tiles stored as exponents like on a `Board`.
+    pub board: Vec<Vec<TileType>>,
+    pub score: u32,
+    /// When the game was saved, in seconds since the Unix epoch.
+    pub saved_at: u64,
+    /// Decides where new tiles appear. Saves from before it was stored get a new one.
+    #[serde(default = "rand::random")]
+    pub seed: u64,
+    /// The rules the game is played by. Saves from before they were stored used the normal rules,
+    /// and saves from before `starting_tiles` was stored started with one tile.
+    #[serde(default = "old_rules", deserialize_with = "deserialize_rules")]
+    pub rules: Rules,
+    /// How many more moves may be undone under the rules' undo limit. Saves without it get every
+    /// undo back.
+    #[serde(default)]
+    pub undos_left: Option<u32>,
+    /// Whether the game was won and went on in endless mode.
+    #[serde(default)]
+    pub keep_playing: bool,
+    /// Whether the player had help, e.g. undoing, which keeps the game off the best scores.
+    #[serde(default)]
+    pub assisted: bool,
+    /// Whether the game was already counted in the statistics, so that it isn't counted again.
+    #[serde(default)]
+    pub recorded: bool,
+    /// The score after each move, oldest first.
+    #[serde(default)]
+    pub score_history: Vec<u32>,
+    /// The board and score before each move, most recent last, so that moves made before saving
+    /// can still be undone after loading.
+    #[serde(default)]
+    pub undo_stack: Vec<SavedPosition>,
+    /// The moves that led to the board and when they were made, oldest first.
+    #[serde(default)]
+    pub moves: Vec<MoveRecord>,
+    /// How many moves were made before the oldest one in `moves` and `score_history`, which were
+    /// forgotten because the game's history was capped.
+    #[serde(default)]
+    pub forgotten_moves: usize,
+    /// When the first move was made, in milliseconds since the Unix epoch, even if it was
+    /// forgotten. Saves without it use the first of the `moves`.
+    #[serde(default)]
+    pub first_move_at: Option<u64>,
+    /// How long the game had been played, in milliseconds, not counting pauses.
+    #[serde(default)]
+    pub play_time: u64,
+    /// What had happened over the game. Saves without it start counting from when they're loaded.
+    #[serde(default)]
+    pub stats: GameStats,
+    /// The version of the save format the game was saved in.
+    #[serde(default)]
+    pub format: u32,
+    /// The names of the rules that the save didn't store, which were filled in when it was read
+    /// by `SavedGame::parse`.
+    #[serde(skip)]
+    pub migrated_rules: Vec<String>,
+}
+
+/// A rule of a saved game that's worth pointing out when it's loaded, because new games are
+/// played differently or because the save didn't store it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RuleChange {
+    /// The name of the rule, as it's written in the config file.
+    pub name: String,
+    /// The value the saved game is played by.
+    pub saved: String,
+    /// The value new games are played by.
+    pub current: String,
+    /// Whether the save didn't store the rule, so that `saved` was filled in for it.
+    pub migrated: bool,
+}
+
+/// A board and score that a saved game can be undone back to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPosition {
+    pub board: Vec<Vec<TileType>>,
+    pub score: u32,
+}
+
+impl SavedGame {
+    /// Captures a game so it can be saved.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game to capture.
+    /// * `saved_at` - When the game is being saved, in seconds since the Unix epoch.
+    pub fn from_game(game: &Game, saved_at: u64) -> SavedGame {
+        SavedGame {
+            board: game.read_board_state().clone(),
+            score: game.score(),
+            saved_at,
+            seed: game.seed(),
+            rules: game.rules().clone(),
+            undos_left: game.undos_left(),
+            keep_playing: game.win_state() == WinState::KeepPlaying,
+            assisted: game.is_assisted(),
+            recorded: game.is_recorded(),
+            score_history: game.score_history().to_vec(),
+            undo_stack: game
+                .undo_stack()
+                .iter()
+                .map(|(board, score)| SavedPosition {
+                    board: board.get_data_for_display().clone(),
+                    score: *score,
+                })
+                .collect(),
+            moves: game.moves().to_vec(),
+            forgotten_moves: game.forgotten_moves(),
+            first_move_at: game.first_move_at(),
+            play_time: game.elapsed(saved_at * 1000).as_millis() as u64,
+            stats: game.stats().clone(),
+            format: SAVE_FORMAT,
+            migrated_rules: Vec::new(),
+        }
+    }
+
+    /// Reads a saved game from the contents of its file, noting which rules had to be filled in.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The JSON the game was saved as.
+    ///
+    /// # Returns
+    ///
+    /// The `SavedGame`, or a `serde_json::Error` if the contents aren't a saved game.
+    pub fn parse(contents: &str) -> Result<SavedGame, serde_json::Error> {
+        let value: serde_json::Value = serde_json::from_str(contents)?;
+        let stored_rules: Vec<String> = value
+            .get("rules")
+            .and_then(serde_json::Value::as_object)
+            .map_or(Vec::new(), |rules| rules.keys().cloned().collect());
+        let mut saved: SavedGame = serde_json::from_value(value)?;
+        saved.migrated_rules = rule_values(&saved.rules)
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| !stored_rules.contains(name))
+            .collect();
+        Ok(saved)
+    }
+
+    /// Checks whether the game was saved by an older version of the game, whose rules may have
+    /// been filled in or may work differently now.
+    pub fn is_outdated(&self) -> bool {
+        self.format < SAVE_FORMAT
+    }
+
+    /// Lists the rules of the saved game that differ from the rules new games are played by, or
+    /// that had to be filled in because the save didn't store them.
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The rules new games are played by.
+    ///
+    /// # Returns
+    ///
+    /// A `RuleChange` for each such rule, sorted by name.
+    pub fn rule_changes(&self, current: &Rules) -> Vec<RuleChange> {
+        rule_values(&self.rules)
+            .into_iter()
+            .zip(rule_values(current))
+            .map(|((name, saved), (_, current))| RuleChange {
+                migrated: self.migrated_rules.contains(&name),
+                name,
+                saved,
+                current,
+            })
+            .filter(|change| change.migrated || change.saved != change.current)
+            .collect()
+    }
+
+    /// Turns the saved game back into a playable one.
+    ///
+    /// # Returns
+    ///
+    /// The restored `Game`, or `None` if the saved board or one of the boards in the undo stack
+    /// isn't a valid grid.
+    pub fn to_game(&self) -> Option<Game> {
+        let board = Board::try_from(self.board.clone()).ok()?;
+        let undo_stack = self
+            .undo_stack
+            .iter()
+            .map(|position| {
+                Some((
+                    Board::try_from(position.board.clone()).ok()?,
+                    position.score,
+                ))
+            })
+            .collect::<Option<Vec<(Board, u32)>>>()?;
+        let game = Game::restore(
+            board,
+            self.score,
+            self.seed,
+            self.rules.clone(),
+            self.score_history.clone(),
+            undo_stack,
+            self.moves.clone(),
+        )
+        .with_forgotten_moves(self.forgotten_moves, self.first_move_at)
+        .with_play_time(self.play_time)
+        .with_assisted(self.assisted)
+        .with_recorded(self.recorded)
+        .with_stats(self.stats.clone());
+        let game = match self.undos_left {
+            Some(undos_left) => game.with_undos_left(undos_left),
+            None => game,
+        };
+        if self.keep_playing {
+            game.handle_event(GameEvent::KeepPlaying)
+                .ok()
+                .map(|update| update.game)
+        } else {
+            Some(game)
+        }
+    }
+
+    /// Gets the exponent of the biggest tile on the saved board.
+    pub fn highest_tile(&self) -> TileType {
+        self.board.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// Describes how long ago the game was saved, e.g. "5 minutes ago".
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// A `String` describing the age of the save in the largest whole unit.
+    pub fn age_description(&self, now: u64) -> String {
+        describe_age(self.saved_at, now)
+    }
+}
+
+/// Describes how long ago something happened, e.g. "5 minutes ago".
+///
+/// # Arguments
+///
+/// * `then` - When it happened, in seconds since the Unix epoch.
+/// * `now` - The current time, in seconds since the Unix epoch.
+///
+/// # Returns
+///
+/// A `String` describing the time since `then` in the largest whole unit.
+pub fn describe_age(then: u64, now: u64) -> String {
+    const UNITS: [(u64, &str); 3] = [(86_400, "day"), (3_600, "hour"), (60, "minute")];
+
+    let age = now.saturating_sub(then);
+    UNITS
+        .iter()
+        .find(|&&(seconds, _)| age >= seconds)
+        .map(|&(seconds, unit)| {
+            let count = age / seconds;
+            format!(
+                "{} {}{} ago",
+                count,
+                unit,
+                if count == 1 { "" } else { "s" }
+            )
+        })
+        .unwrap_or_else(|| "just now".to_string())
+}
+
+/// Describes the size of a file, e.g. "3.2 KB".
+///
+/// # Arguments
+///
+/// * `bytes` - The size of the file.
+///
+/// # Returns
+///
+/// A `String` with the size in the largest unit it's at least one of, with one decimal for
+/// kilobytes and megabytes.
+pub fn describe_size(bytes: u64) -> String {
+    const UNITS: [(u64, &str); 2] = [(1024 * 1024, "MB"), (1024, "KB")];
+
+    UNITS
+        .iter()
+        .find(|&&(size, _)| bytes >= size)
+        .map(|&(size, unit)| format!("{:.1} {}", bytes as f64 / size as f64, unit))
+        .unwrap_or_else(|| format!("{} B", bytes))
+}
+
+/// Gets the current time in seconds since the Unix epoch, as stored in `SavedGame::saved_at`.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Saves a game into a slot, replacing whatever was there.
+///
+/// # Arguments
+///
+/// * `slot` - The slot to save into, from `0` to `SLOT_COUNT - 1`.
+/// * `game` - The game to save.
+///
+/// # Returns
+///
+/// The path of the save file, or a `PersistenceError` if it couldn't be written.
+pub fn save_to_slot(slot: usize, game: &Game) -> Result<PathBuf, PersistenceError> {
+    let contents = serde_json::to_string_pretty(&SavedGame::from_game(game, now()))
+        .map_err(|e| PersistenceError::Io(e.into()))?;
+    persistence::write_data_file(&slot_file_name(slot), persistence::compress(&contents))
+}
+
+/// Reads every save slot.
+///
+/// # Returns
+///
+/// A `Vec` with an entry for each slot, which is `None` if the slot is empty or its save file
+/// can't be read.
+pub fn read_slots() -> Vec<Option<SavedGame>> {
+    (0..SLOT_COUNT)
+        .map(|slot| {
+            persistence::data_dir()
+                .and_then(|dir| persistence::read_data_file(&dir.join(slot_file_name(slot))).ok())
+                .and_then(|contents| SavedGame::parse(&contents).ok())
+        })
+        .collect()
+}
+
+/// Gets the size of each save slot's file on disk, e.g. to show how much a compressed save saves.
+///
+/// # Returns
+///
+/// A `Vec` with an entry for each slot, which is `None` if the slot has no save file.
+pub fn slot_file_sizes() -> Vec<Option<u64>> {
+    (0..SLOT_COUNT)
+        .map(|slot| {
+            persistence::data_dir()
+                .and_then(|dir| fs::metadata(dir.join(slot_file_name(slot))).ok())
+                .map(|metadata| metadata.len())
+        })
+        .collect()
+}
+
+/// Checks whether any save slot has a save file, e.g. to decide whether loading is possible.
+/// This only looks at which files exist, so it's cheap enough to call whenever a menu is drawn.
+pub fn has_saved_games() -> bool {
+    slot_file_sizes().iter().any(Option::is_some)
+}
+
+/// Describes every rule by its name and value, as they're written in the config file.
+///
+/// # Arguments
+///
+/// * `rules` - The rules to describe.
+///
+/// # Returns
+///
+/// The name and value of each rule, sorted by name.
+fn rule_values(rules: &Rules) -> Vec<(String, String)> {
+    let Ok(serde_json::Value::Object(values)) = serde_json::to_value(rules) else {
+        return Vec::new();
+    };
+    values
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::Null => "none".to_string(),
+                serde_json::Value::String(value) => value,
+                value => value.to_string(),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// Gets the rules games were played by before they were stored in saves.
+fn old_rules() -> Rules {
+    Rules {
+        starting_tiles: 1,
+        ..Rules::default()
+    }
+}
+
+/// Reads the rules of a saved game, keeping the one starting tile of games saved before
+/// `starting_tiles` was stored, so that they can still be replayed.
+fn deserialize_rules<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rules, D::Error> {
+    let mut rules = serde_json::Value::deserialize(deserializer)?;
+    if let Some(rules) = rules.as_object_mut() {
+        rules
+            .entry("starting_tiles")
+            .or_insert(old_rules().starting_tiles.into());
+    }
+    serde_json::from_value(rules).map_err(D::Error::custom)
+}
+
+fn slot_file_name(slot: usize) -> String {
+    format!("saves/slot-{}.json", slot + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Direction;
+    use std::time::Duration;
+
+    fn saved_game(board: Vec<Vec<TileType>>) -> SavedGame {
+        SavedGame {
+            board,
+            score: 120,
+            saved_at: 1_000_000,
+            seed: 5,
+            rules: Rules::default(),
+            undos_left: None,
+            keep_playing: false,
+            assisted: false,
+            recorded: false,
+            score_history: vec![40, 120],
+            undo_stack: vec![
+                SavedPosition {
+                    board: vec![vec![0, 0], vec![0, 0]],
+                    score: 0,
+                },
+                SavedPosition {
+                    board: vec![vec![1, 0], vec![0, 1]],
+                    score: 40,
+                },
+            ],
+            moves: vec![
+                MoveRecord {
+                    direction: Direction::Left,
+                    played_at: 999_000_000,
+                },
+                MoveRecord {
+                    direction: Direction::Up,
+                    played_at: 999_004_500,
+                },
+            ],
+            forgotten_moves: 0,
+            first_move_at: Some(999_000_000),
+            play_time: 75_000,
+            stats: GameStats {
+                best_move_points: 80,
+                directions: [1, 0, 1, 0],
+                ..GameStats::default()
+            },
+            format: SAVE_FORMAT,
+            migrated_rules: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn round_trip_through_game() {
+        let saved = saved_game(vec![vec![1, 0], vec![0, 3]]);
+        let game = saved.to_game().unwrap();
+
+        assert_eq!(&saved.board, game.read_board_state());
+        assert_eq!(120, game.score());
+        assert_eq!(saved, SavedGame::from_game(&game, 1_000_000));
+    }
+
+    #[test]
+    fn undo_works_after_loading() {
+        let game = saved_game(vec![vec![1, 0], vec![0, 3]]).to_game().unwrap();
+        let game = game.handle_event(GameEvent::Undo).unwrap().game;
+
+        assert_eq!(&vec![vec![1, 0], vec![0, 1]], game.read_board_state());
+        assert_eq!(40, game.score());
+        assert_eq!(&[40], game.score_history());
+        assert_eq!(1, game.moves().len());
+        assert!(game.is_assisted(), "undo isn't limited by the rules");
+        assert!(SavedGame::from_game(&game, 0).assisted);
+    }
+
+    #[test]
+    fn undos_left_are_kept() {
+        let mut saved = saved_game(vec![vec![1, 0], vec![0, 3]]);
+        saved.rules.undo_limit = Some(3);
+        saved.undos_left = Some(1);
+        let game = saved.to_game().unwrap();
+        assert_eq!(Some(1), game.undos_left());
+        assert_eq!(Some(1), SavedGame::from_game(&game, 0).undos_left);
+
+        let game = game.handle_event(GameEvent::Undo).unwrap().game;
+        let game = game.handle_event(GameEvent::Undo).unwrap().game;
+        assert_eq!(1, game.undo_stack().len(), "only one undo was left");
+        assert_eq!(Some(0), game.undos_left());
+    }
+
+    #[test]
+    fn endless_mode_is_kept() {
+        let mut saved = saved_game(vec![vec![11, 0], vec![0, 3]]);
+        assert_eq!(WinState::Won, saved.to_game().unwrap().win_state());
+
+        saved.keep_playing = true;
+        let game = saved.to_game().unwrap();
+        assert_eq!(WinState::KeepPlaying, game.win_state());
+        assert!(SavedGame::from_game(&game, 0).keep_playing);
+    }
+
+    #[test]
+    fn forgotten_moves_are_counted() {
+        let mut saved = saved_game(vec![vec![1, 0], vec![0, 3]]);
+        saved.forgotten_moves = 10;
+        saved.first_move_at = Some(900_000_000);
+        let game = saved.to_game().unwrap();
+
+        assert_eq!(12, game.view().moves);
+        assert_eq!(Some(900_000_000), game.first_move_at());
+        assert_eq!(Duration::from_millis(75_000), game.elapsed(0));
+        assert!(game.replay().is_none());
+        assert_eq!(saved, SavedGame::from_game(&game, 1_000_000));
+    }
+
+    #[test]
+    fn old_saves_still_load() {
+        let saved: SavedGame =
+            serde_json::from_str(r#"{"board": [[1, 0], [0, 3]], "score": 8, "saved_at": 0}"#)
+                .unwrap();
+        let game = saved.to_game().unwrap();
+
+        assert_eq!(8, game.score());
+        assert!(game.undo_stack().is_empty());
+        assert_eq!(1, game.rules().starting_tiles);
+
+        let saved: SavedGame = serde_json::from_str(
+            r#"{"board": [[1, 0], [0, 3]], "score": 8, "saved_at": 0, "rules": {"gravity": true}}"#,
+        )
+        .unwrap();
+        assert_eq!(1, saved.rules.starting_tiles);
+        assert!(saved.rules.gravity);
+        let saved = SavedGame::from_game(&Game::start_new_game().unwrap(), 0);
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(
+            2,
+            serde_json::from_str::<SavedGame>(&json)
+                .unwrap()
+                .rules
+                .starting_tiles
+        );
+    }
+
+    #[test]
+    fn old_saves_list_their_rule_changes() {
+        let saved = SavedGame::parse(
+            r#"{"board": [[1, 0], [0, 3]], "score": 8, "saved_at": 0, "rules": {"gravity": true}}"#,
+        )
+        .unwrap();
+        assert!(saved.is_outdated());
+        let changes = saved.rule_changes(&Rules::default());
+        let gravity = changes.iter().find(|change| change.name == "gravity");
+        assert_eq!(
+            Some(&RuleChange {
+                name: "gravity".to_string(),
+                saved: "true".to_string(),
+                current: "false".to_string(),
+                migrated: false,
+            }),
+            gravity
+        );
+        let starting_tiles = changes
+            .iter()
+            .find(|change| change.name == "starting_tiles");
+        assert_eq!(
+            Some(&RuleChange {
+                name: "starting_tiles".to_string(),
+                saved: "1".to_string(),
+                current: "2".to_string(),
+                migrated: true,
+            }),
+            starting_tiles
+        );
+        assert!(changes
+            .iter()
+            .any(|change| change.name == "move_limit" && change.saved == "none"));
+
+        let game = Game::start_new_game().unwrap();
+        let json = serde_json::to_string(&SavedGame::from_game(&game, 0)).unwrap();
+        let saved = SavedGame::parse(&json).unwrap();
+        assert!(!saved.is_outdated());
+        assert!(saved.rule_changes(&Rules::default()).is_empty());
+    }
+
+    #[test]
+    fn ragged_board_is_rejected() {
+        assert!(saved_game(vec![vec![1, 0], vec![0]]).to_game().is_none());
+    }
+
+    #[test]
+    fn ragged_undo_board_is_rejected() {
+        let mut saved = saved_game(vec![vec![1, 0], vec![0, 3]]);
+        saved.undo_stack[0].board = vec![vec![1, 0], vec![0]];
+        assert!(saved.to_game().is_none());
+    }
+
+    #[test]
+    fn restored_full_board_is_over() {
+        let game = saved_game(vec![vec![1, 2], vec![2, 1]]).to_game().unwrap();
+        assert!(game.is_game_over());
+    }
+
+    #[test]
+    fn highest_tile() {
+        assert_eq!(5, saved_game(vec![vec![1, 5], vec![0, 3]]).highest_tile());
+    }
+
+    #[test]
+    fn age_descriptions() {
+        let saved = saved_game(vec![vec![0]]);
+
+        assert_eq!("just now", saved.age_description(1_000_030));
+        assert_eq!("1 minute ago", saved.age_description(1_000_060));
+        assert_eq!(
+            "3 hours ago",
+            saved.age_description(1_000_000 + 3 * 3_600 + 5)
+        );
+        assert_eq!("2 days ago", saved.age_description(1_000_000 + 2 * 86_400));
+        assert_eq!("just now", saved.age_description(0));
+    }
+
+    #[test]
+    fn size_descriptions() {
+        assert_eq!("512 B", describe_size(512));
+        assert_eq!("1.0 KB", describe_size(1024));
+        assert_eq!("3.2 KB", describe_size(3 * 1024 + 200));
+        assert_eq!("1.5 MB", describe_size(3 * 512 * 1024));
+    }
+}
@@ -0,0 +1,665 @@
+use crate::board::{Board, Direction, TileType};
+use crate::game::Game;
+use crate::merge::{merge_array, Variant};
+use crate::persistence;
+use crate::persistence::PersistenceError;
+use crate::rules::Rules;
+use data_grid::FixedGrid;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::{fs, io};
+
+const WEIGHTS_FILE_NAME: &str = "weights.toml";
+
+/// How much each property of a board contributes to its evaluation.
+///
+/// The defaults are reasonable hand-picked values. `rs2048 tune` searches for better ones and
+/// saves them to `weights.toml` in the data directory, where `EvalWeights::load` picks them up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalWeights {
+    /// Rewards rows and columns whose tiles only increase or only decrease.
+    pub monotonicity: f64,
+    /// Rewards neighbouring tiles having similar values, which makes merges likely.
+    pub smoothness: f64,
+    /// Rewards every empty cell.
+    pub empty_cells: f64,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        EvalWeights {
+            monotonicity: 1.0,
+            smoothness: 0.1,
+            empty_cells: 2.7,
+        }
+    }
+}
+
+impl EvalWeights {
+    /// Loads the tuned weights, falling back to the defaults if none have been saved.
+    ///
+    /// # Returns
+    ///
+    /// The loaded `EvalWeights`.
+    pub fn load() -> EvalWeights {
+        EvalWeights::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves the weights so that future calls to `load` return them.
+    ///
+    /// # Returns
+    ///
+    /// The path the weights were written to, or a `PersistenceError` if they couldn't be written.
+    pub fn save(&self) -> Result<PathBuf, PersistenceError> {
+        let contents =
+            toml::to_string(self).map_err(|e| PersistenceError::Io(io::Error::other(e)))?;
+        persistence::write_data_file(WEIGHTS_FILE_NAME, &contents)
+    }
+
+    /// Gets the location of the tuned weights file.
+    ///
+    /// # Returns
+    ///
+    /// The path to `weights.toml`, or `None` if the platform has no data directory.
+    pub fn path() -> Option<PathBuf> {
+        persistence::data_dir().map(|dir| dir.join(WEIGHTS_FILE_NAME))
+    }
+}
+
+/// Scores how promising a board is. Higher is better.
+///
+/// # Arguments
+///
+/// * `board` - The board to evaluate.
+/// * `weights` - How much each property of the board counts.
+///
+/// # Returns
+///
+/// The weighted sum of the board's monotonicity, smoothness, and number of empty cells.
+pub fn evaluate(board: &Board, weights: &EvalWeights) -> f64 {
+    let rows = board.get_data_for_display();
+    let columns: Vec<Vec<TileType>> = (0..rows[0].len())
+        .map(|x| rows.iter().map(|row| row[x]).collect())
+        .collect();
+    evaluate_lines(rows, &columns, weights)
+}
+
+/// Does the work of `evaluate` given both the rows and the columns of a board, so that boards
+/// stored in arrays can be evaluated without allocating.
+fn evaluate_lines<R: AsRef<[TileType]>, C: AsRef<[TileType]>>(
+    rows: &[R],
+    columns: &[C],
+    weights: &EvalWeights,
+) -> f64 {
+    let lines = || {
+        rows.iter()
+            .map(AsRef::as_ref)
+            .chain(columns.iter().map(AsRef::as_ref))
+    };
+
+    let empty_cells = rows
+        .iter()
+        .flat_map(AsRef::as_ref)
+        .filter(|&&tile| tile == 0)
+        .count() as f64;
+    let monotonicity: f64 = lines().map(line_monotonicity).sum();
+    let smoothness: f64 = lines().map(line_smoothness).sum();
+
+    weights.monotonicity * monotonicity
+        + weights.smoothness * smoothness
+        + weights.empty_cells * empty_cells
+}
+
+/// Measures how far a line is from being sorted in either direction, as a negative number where
+/// zero means perfectly sorted.
+fn line_monotonicity(line: &[TileType]) -> f64 {
+    let (mut increases, mut decreases) = (0.0, 0.0);
+    for pair in line.windows(2) {
+        let difference = pair[1] as f64 - pair[0] as f64;
+        if difference > 0.0 {
+            increases += difference;
+        } else {
+            decreases -= difference;
+        }
+    }
+    -f64::min(increases, decreases)
+}
+
+/// Measures how well ordered the board is, for showing the player: how much of the change from
+/// tile to tile along the rows and columns keeps to the way each line mostly runs. It's the
+/// monotonicity of `evaluate` as a share, so it's as cheap to work out after every move.
+///
+/// # Arguments
+///
+/// * `board` - The board to measure.
+///
+/// # Returns
+///
+/// The percentage from 0 to 100, where 100 means every row and column is sorted.
+pub fn organization_percent(board: &Board) -> u32 {
+    let rows = board.get_data_for_display();
+    let columns: Vec<Vec<TileType>> = (0..rows[0].len())
+        .map(|x| rows.iter().map(|row| row[x]).collect())
+        .collect();
+    let (mut disorder, mut change) = (0.0, 0.0);
+    for line in rows.iter().chain(&columns) {
+        disorder -= line_monotonicity(line);
+        change += line
+            .windows(2)
+            .map(|pair| (pair[1] as f64 - pair[0] as f64).abs())
+            .sum::<f64>();
+    }
+    if change == 0.0 {
+        return 100;
+    }
+    (100.0 * (1.0 - disorder / change)).round() as u32
+}
+
+/// Measures how different neighbouring tiles in a line are, ignoring empty cells, as a negative
+/// number where zero means every tile matches its neighbours.
+fn line_smoothness(line: &[TileType]) -> f64 {
+    let mut tiles = line.iter().copied().filter(|&tile| tile != 0);
+    let Some(mut previous) = tiles.next() else {
+        return 0.0;
+    };
+    -tiles
+        .map(|tile| {
+            let difference = (tile as f64 - previous as f64).abs();
+            previous = tile;
+            difference
+        })
+        .sum::<f64>()
+}
+
+/// The rules that decide how the tiles move, which the search has to follow to see the boards a
+/// game would really get to.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MoveRules {
+    /// Which tiles merge.
+    pub variant: Variant,
+    /// Whether tiles sliding off one edge come back in from the opposite edge.
+    pub wrap_around: bool,
+    /// Whether the tiles fall after sideways moves.
+    pub gravity: bool,
+}
+
+impl MoveRules {
+    /// Gets how the tiles move under a game's rules.
+    pub fn of(rules: &Rules) -> MoveRules {
+        MoveRules {
+            variant: rules.variant,
+            wrap_around: rules.wrap_around,
+            gravity: rules.gravity,
+        }
+    }
+
+    /// Gets whether every line is merged on its own, which boards stored in arrays can do,
+    /// rather than wrapping around or falling afterwards.
+    fn merges_lines_alone(self) -> bool {
+        !self.wrap_around && !self.gravity
+    }
+}
+
+/// Applies a move to a copy of the board, without spawning a tile afterwards.
+///
+/// # Arguments
+///
+/// * `board` - The board to move on.
+/// * `direction` - The direction to swipe in.
+/// * `rules` - How the tiles move.
+///
+/// # Returns
+///
+/// The board after the move, or `None` if the move doesn't change anything.
+pub fn apply_move(board: &Board, direction: Direction, rules: MoveRules) -> Option<Board> {
+    let mut after = board.clone();
+    let mut moved = after
+        .merge(direction, rules.wrap_around, &rules.variant)
+        .moved;
+    if rules.gravity && matches!(direction, Direction::Left | Direction::Right) {
+        moved |= after.compact(Direction::Down);
+    }
+    moved.then_some(after)
+}
+
+/// Picks the move with the highest evaluation.
+fn best_of(evaluations: Vec<(Direction, f64)>) -> Option<Direction> {
+    evaluations
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(direction, _)| direction)
+}
+
+/// The standard 4x4 board stored in arrays, which the solver searches instead of a `Board` so that
+/// copying and merging boards in the search doesn't allocate.
+type FixedBoard = FixedGrid<TileType, 4, 4>;
+
+/// A way of storing a board that the solver can search.
+trait SearchBoard: Sized {
+    /// Gets the board after a move, without spawning a tile, or `None` if the move doesn't change
+    /// anything.
+    fn after_move(&self, direction: Direction, rules: MoveRules) -> Option<Self>;
+
+    /// Gets the `(x, y)` positions of the empty cells, ordered row by row.
+    fn empty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_;
+
+    /// Gets a copy of the board with a tile placed at `(x, y)`.
+    fn with_tile(&self, x: usize, y: usize, tile: TileType) -> Self;
+
+    /// Scores the board like `evaluate`.
+    fn evaluate(&self, weights: &EvalWeights) -> f64;
+}
+
+impl SearchBoard for Board {
+    fn after_move(&self, direction: Direction, rules: MoveRules) -> Option<Self> {
+        apply_move(self, direction, rules)
+    }
+
+    fn empty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.empty_positions().into_iter()
+    }
+
+    fn with_tile(&self, x: usize, y: usize, tile: TileType) -> Self {
+        let mut board = self.clone();
+        board.place_item_in_board(y, x, tile).unwrap();
+        board
+    }
+
+    fn evaluate(&self, weights: &EvalWeights) -> f64 {
+        evaluate(self, weights)
+    }
+}
+
+impl SearchBoard for FixedBoard {
+    /// Only merges each line on its own, so the search uses a `Board` for rules that don't.
+    fn after_move(&self, direction: Direction, rules: MoveRules) -> Option<Self> {
+        debug_assert!(rules.merges_lines_alone());
+        let merge = |line| merge_array(line, &rules.variant).0;
+        let merge_reversed = |mut line: [TileType; 4]| {
+            line.reverse();
+            let mut merged = merge(line);
+            merged.reverse();
+            merged
+        };
+
+        let mut after = *self;
+        for index in 0..4 {
+            match direction {
+                Direction::Up => after.update_column(index, merge(after.get_column(index)?)),
+                Direction::Down => {
+                    after.update_column(index, merge_reversed(after.get_column(index)?))
+                }
+                Direction::Left => after.update_row(index, merge(after.get_row(index)?)),
+                Direction::Right => after.update_row(index, merge_reversed(after.get_row(index)?)),
+            }
+            .ok()?;
+        }
+        (after != *self).then_some(after)
+    }
+
+    fn empty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.iter_rows().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|&(_, &tile)| tile == 0)
+                .map(move |(x, _)| (x, y))
+        })
+    }
+
+    fn with_tile(&self, x: usize, y: usize, tile: TileType) -> Self {
+        let mut board = *self;
+        board.update_single_position(y, x, tile).unwrap();
+        board
+    }
+
+    fn evaluate(&self, weights: &EvalWeights) -> f64 {
+        evaluate_lines(self.get_values(), self.transpose().get_values(), weights)
+    }
+}
+
+/// Picks moves with a depth-limited expectimax search: moves are chosen to maximize the expected
+/// evaluation over every tile that could spawn afterwards.
+pub struct Solver {
+    weights: EvalWeights,
+    depth: u32,
+}
+
+impl Solver {
+    /// The evaluation given to boards where no move is possible, so that losing is always worse
+    /// than any board where the game goes on.
+    const GAME_OVER_PENALTY: f64 = -1.0e6;
+
+    /// Creates a new solver.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - How boards are evaluated.
+    /// * `depth` - How many moves to look ahead, including the one being chosen. Each extra move
+    ///   makes the search roughly 30 times slower on a 4x4 board.
+    pub fn new(weights: EvalWeights, depth: u32) -> Solver {
+        Solver {
+            weights,
+            depth: depth.max(1),
+        }
+    }
+
+    /// Evaluates every move that changes the board, playing by the classic rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The board to choose a move for.
+    ///
+    /// # Returns
+    ///
+    /// The expected evaluation of each possible move, in `Direction::ALL` order. Empty when the
+    /// game is over.
+    #[tracing::instrument(level = "debug", name = "search", skip_all, fields(depth = self.depth))]
+    pub fn evaluate_moves(&self, board: &Board) -> Vec<(Direction, f64)> {
+        let rules = MoveRules::default();
+        match FixedBoard::try_from(board.get_data_for_display().clone()) {
+            Ok(fixed) => self.evaluate_moves_on(&fixed, rules),
+            Err(_) => self.evaluate_moves_on(board, rules),
+        }
+    }
+
+    /// Evaluates every legal move in a game, so that its rules decide which moves are possible
+    /// and how the tiles move, e.g. how they merge in the game's variant, whether they wrap
+    /// around, and whether they fall after sideways moves, all the way down the search.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game to choose a move for.
+    ///
+    /// # Returns
+    ///
+    /// The expected evaluation of each move in `Game::legal_moves`, in `Direction::ALL` order.
+    /// Empty when no move is legal.
+    #[tracing::instrument(level = "debug", name = "search", skip_all, fields(depth = self.depth))]
+    pub fn evaluate_legal_moves(&self, game: &Game) -> Vec<(Direction, f64)> {
+        let rules = MoveRules::of(game.rules());
+        game.legal_moves()
+            .map(|(direction, outcome)| {
+                let fixed = FixedBoard::try_from(outcome.board.get_data_for_display().clone())
+                    .ok()
+                    .filter(|_| rules.merges_lines_alone());
+                let value = match fixed {
+                    Some(fixed) => self.expected_value(&fixed, self.depth - 1, rules),
+                    None => self.expected_value(&outcome.board, self.depth - 1, rules),
+                };
+                (direction, value)
+            })
+            .collect()
+    }
+
+    /// Chooses the legal move in a game with the best expected evaluation.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game to choose a move for.
+    ///
+    /// # Returns
+    ///
+    /// The best move, or `None` if no move is legal.
+    pub fn best_legal_move(&self, game: &Game) -> Option<Direction> {
+        best_of(self.evaluate_legal_moves(game))
+    }
+
+    /// Chooses the move with the best expected evaluation.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The board to choose a move for.
+    ///
+    /// # Returns
+    ///
+    /// The best move, or `None` if no move changes the board.
+    pub fn best_move(&self, board: &Board) -> Option<Direction> {
+        best_of(self.evaluate_moves(board))
+    }
+
+    /// Does the work of `evaluate_moves` on either way of storing a board.
+    fn evaluate_moves_on<B: SearchBoard>(
+        &self,
+        board: &B,
+        rules: MoveRules,
+    ) -> Vec<(Direction, f64)> {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| {
+                board.after_move(direction, rules).map(|after| {
+                    (
+                        direction,
+                        self.expected_value(&after, self.depth - 1, rules),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Averages the value of a board over every tile that could spawn on it, weighted the same
+    /// way `UniformSpawn` picks them by default.
+    fn expected_value<B: SearchBoard>(&self, board: &B, depth: u32, rules: MoveRules) -> f64 {
+        if depth == 0 {
+            return board.evaluate(&self.weights);
+        }
+
+        let (mut total, mut empty_cells) = (0.0, 0);
+        for (x, y) in board.empty_cells() {
+            empty_cells += 1;
+            total += [(1 as TileType, 0.75), (2, 0.25)]
+                .iter()
+                .map(|&(tile, probability)| {
+                    probability * self.best_value(&board.with_tile(x, y, tile), depth, rules)
+                })
+                .sum::<f64>();
+        }
+        if empty_cells == 0 {
+            return board.evaluate(&self.weights);
+        }
+        total / empty_cells as f64
+    }
+
+    /// Finds the value of the best move on a board.
+    fn best_value<B: SearchBoard>(&self, board: &B, depth: u32, rules: MoveRules) -> f64 {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| board.after_move(direction, rules))
+            .map(|after| self.expected_value(&after, depth - 1, rules))
+            .max_by(|a, b| a.total_cmp(b))
+            .unwrap_or(Solver::GAME_OVER_PENALTY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board_from(values: Vec<Vec<TileType>>) -> Board {
+        let mut board = Board::new(values.len());
+        for (y, row) in values.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                board.place_item_in_board(y, x, tile).unwrap();
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn monotonic_line() {
+        assert_eq!(0.0, line_monotonicity(&[4, 3, 2, 1]));
+        assert_eq!(0.0, line_monotonicity(&[1, 2, 3, 4]));
+        assert_eq!(-1.0, line_monotonicity(&[1, 3, 2, 4]));
+    }
+
+    #[test]
+    fn organization_of_boards() {
+        let sorted = board_from(vec![
+            vec![4, 3, 2, 1],
+            vec![3, 2, 1, 0],
+            vec![2, 1, 0, 0],
+            vec![1, 0, 0, 0],
+        ]);
+        let zigzag = board_from(vec![
+            vec![1, 2, 1, 2],
+            vec![2, 1, 2, 1],
+            vec![1, 2, 1, 2],
+            vec![2, 1, 2, 1],
+        ]);
+
+        assert_eq!(100, organization_percent(&sorted));
+        assert_eq!(100, organization_percent(&Board::new(4)));
+        // every line goes up as much as it goes down, apart from its last step
+        assert_eq!(67, organization_percent(&zigzag));
+    }
+
+    #[test]
+    fn smooth_line_ignores_empty_cells() {
+        assert_eq!(0.0, line_smoothness(&[2, 0, 2, 0]));
+        assert_eq!(-3.0, line_smoothness(&[1, 0, 0, 4]));
+    }
+
+    #[test]
+    fn empty_board_evaluation() {
+        let weights = EvalWeights::default();
+        assert_eq!(
+            16.0 * weights.empty_cells,
+            evaluate(&Board::new(4), &weights)
+        );
+    }
+
+    #[test]
+    fn apply_move_that_does_nothing() {
+        let board = board_from(vec![
+            vec![1, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+        ]);
+        let rules = MoveRules::default();
+        assert_eq!(None, apply_move(&board, Direction::Up, rules));
+        assert_eq!(None, apply_move(&board, Direction::Left, rules));
+        assert!(apply_move(&board, Direction::Down, rules).is_some());
+    }
+
+    #[test]
+    fn search_follows_the_games_rules() {
+        // 1 and 2 are next to each other in the Fibonacci sequence, so only there do they merge
+        let board = board_from(vec![
+            vec![1, 2, 1, 2],
+            vec![2, 1, 2, 1],
+            vec![1, 2, 1, 2],
+            vec![2, 1, 2, 1],
+        ]);
+        let game = |rules: Rules| {
+            Game::restore(
+                board.clone(),
+                0,
+                1,
+                rules,
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+            )
+        };
+        let solver = Solver::new(EvalWeights::default(), 2);
+        let fibonacci = MoveRules {
+            variant: Variant::Fibonacci,
+            ..MoveRules::default()
+        };
+
+        assert_eq!(
+            None,
+            apply_move(&board, Direction::Left, MoveRules::default())
+        );
+        assert!(apply_move(&board, Direction::Left, fibonacci).is_some());
+        assert!(solver
+            .evaluate_legal_moves(&game(Rules::default()))
+            .is_empty());
+        let evaluations = solver.evaluate_legal_moves(&game(Rules {
+            variant: Variant::Fibonacci,
+            ..Rules::default()
+        }));
+        assert_eq!(4, evaluations.len());
+        assert!(evaluations
+            .iter()
+            .all(|&(_, value)| value > Solver::GAME_OVER_PENALTY));
+
+        // a wrapped line of 2, _, _, 2 merges over the edge
+        let wrapped = board_from(vec![
+            vec![1, 0, 0, 1],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+        ]);
+        let wrap_around = MoveRules {
+            wrap_around: true,
+            ..MoveRules::default()
+        };
+        let merged = apply_move(&wrapped, Direction::Left, wrap_around).unwrap();
+        assert_eq!(15, merged.empty_positions().len());
+    }
+
+    #[test]
+    fn best_move_merges_tiles() {
+        let board = board_from(vec![
+            vec![3, 1, 2, 1],
+            vec![2, 3, 1, 2],
+            vec![1, 2, 3, 1],
+            vec![5, 5, 2, 3],
+        ]);
+        let solver = Solver::new(EvalWeights::default(), 2);
+
+        // the only moves that do anything merge the two 32s
+        assert!(matches!(
+            solver.best_move(&board),
+            Some(Direction::Left | Direction::Right)
+        ));
+    }
+
+    #[test]
+    fn no_move_when_stuck() {
+        let board = board_from(vec![
+            vec![1, 2, 1, 2],
+            vec![2, 1, 2, 1],
+            vec![1, 2, 1, 2],
+            vec![2, 1, 2, 1],
+        ]);
+        let solver = Solver::new(EvalWeights::default(), 1);
+
+        assert!(solver.evaluate_moves(&board).is_empty());
+        assert_eq!(None, solver.best_move(&board));
+    }
+
+    #[test]
+    fn weights_round_trip_through_toml() {
+        let weights = EvalWeights {
+            monotonicity: 2.0,
+            smoothness: 0.5,
+            empty_cells: 1.5,
+        };
+        let parsed: EvalWeights = toml::from_str(&toml::to_string(&weights).unwrap()).unwrap();
+        assert_eq!(weights, parsed);
+    }
+
+    #[test]
+    fn fixed_board_search_matches_board_search() {
+        let board = board_from(vec![
+            vec![3, 1, 0, 1],
+            vec![2, 0, 1, 2],
+            vec![1, 2, 0, 0],
+            vec![0, 1, 2, 4],
+        ]);
+        let fixed = FixedBoard::try_from(board.get_data_for_display().clone()).unwrap();
+        let solver = Solver::new(EvalWeights::default(), 2);
+
+        let rules = MoveRules::default();
+        assert_eq!(
+            solver.evaluate_moves_on(&board, rules),
+            solver.evaluate_moves_on(&fixed, rules)
+        );
+    }
+}
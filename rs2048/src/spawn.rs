@@ -0,0 +1,716 @@
+use crate::board::{Board, Direction, TileType};
+use crate::merge::{MergeRule, Variant};
+use crate::solver;
+use crate::solver::{EvalWeights, MoveRules};
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Decides where a new tile appears after each move and what it is.
+///
+/// Policies only see the board and how many moves have been made, and draw their randomness from
+/// the given RNG, so that a game can be replayed exactly from its seed and policy.
+pub trait SpawnPolicy {
+    /// Chooses the next tile.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The board the tile is added to.
+    /// * `turn` - How many tiles were spawned before this one, i.e. `0` for the first tile the
+    ///   game starts with. The tiles a game starts with come before the one after its first move.
+    /// * `rng` - Where the randomness comes from.
+    ///
+    /// # Returns
+    ///
+    /// The `(x, y)` position of an empty cell and the exponent of the new tile, or `None` if the
+    /// board is full.
+    fn choose_spawn(
+        &self,
+        board: &Board,
+        turn: usize,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), TileType)>;
+}
+
+/// Which tiles can spawn and how often, as `(exponent, weight)` pairs with weights relative to
+/// each other, e.g. `[[1, 9], [2, 1]]` for a 4 in every ten tiles, or `[[1, 6], [2, 3], [3, 1]]`
+/// for the occasional 8. Pairs with a weight or an exponent of zero are ignored, and if no pair
+/// is left, only 2s spawn. Tiles are stored as exponents, so 1s can't spawn.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SpawnWeights(pub Vec<(TileType, u32)>);
+
+impl Default for SpawnWeights {
+    /// The 3:1 ratio of 2s to 4s the game has always had.
+    fn default() -> Self {
+        SpawnWeights(vec![(1, 3), (2, 1)])
+    }
+}
+
+impl SpawnWeights {
+    /// The weights offered in the settings screen, in the order they are cycled through: the
+    /// usual 3:1, the 9:1 of many other versions of the game, as many 4s as 2s, and a hard mode
+    /// with the odd 8.
+    const CHOICES: [&'static [(TileType, u32)]; 4] = [
+        &[(1, 3), (2, 1)],
+        &[(1, 9), (2, 1)],
+        &[(1, 1), (2, 1)],
+        &[(1, 6), (2, 3), (3, 1)],
+    ];
+
+    /// Picks a tile by the weights.
+    ///
+    /// # Returns
+    ///
+    /// The exponent of the tile, e.g. `1` for a 2 and `2` for a 4.
+    pub fn choose(&self, rng: &mut dyn RngCore) -> TileType {
+        let weight = |&(tile, weight): &(TileType, u32)| if tile == 0 { 0 } else { weight };
+        let total: u64 = self.0.iter().map(|pair| weight(pair) as u64).sum();
+        if total > u32::MAX as u64 {
+            // the weights can't be added up as they are, see `divided_to_fit`
+            let fitting = SpawnWeights::divided_to_fit(
+                self.0
+                    .iter()
+                    .map(|pair| (pair.0, weight(pair) as u64))
+                    .collect(),
+            );
+            return fitting.choose(rng);
+        }
+        self.0
+            .choose_weighted(rng, weight)
+            .map_or(1, |&(tile, _)| tile)
+    }
+
+    /// Divides weights that are too big to add up down together, keeping their odds, so that
+    /// huge weights from the config file still fit when a tile is picked. Weights that fit are
+    /// kept as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - `(exponent, weight)` pairs like the ones in `SpawnWeights`.
+    ///
+    /// # Returns
+    ///
+    /// The `SpawnWeights`, whose weights add up to at most `u32::MAX`.
+    pub fn divided_to_fit(weights: Vec<(TileType, u64)>) -> SpawnWeights {
+        let total: u64 = weights.iter().map(|&(_, weight)| weight).sum();
+        let divisor = total / u32::MAX as u64 + 1;
+        SpawnWeights(
+            weights
+                .into_iter()
+                .map(|(tile, weight)| (tile, (weight / divisor) as u32))
+                .collect(),
+        )
+    }
+
+    /// Gets the exponents of the tiles that can spawn.
+    fn tiles(&self) -> Vec<TileType> {
+        let tiles: Vec<TileType> = self
+            .0
+            .iter()
+            .filter(|&&(tile, weight)| tile != 0 && weight != 0)
+            .map(|&(tile, _)| tile)
+            .collect();
+        if tiles.is_empty() {
+            return vec![1];
+        }
+        tiles
+    }
+
+    /// Describes the weights in the settings screen, e.g. "2/4 at 3:1".
+    ///
+    /// # Arguments
+    ///
+    /// * `variant` - The variant the tiles belong to, which decides what they're worth.
+    pub fn label(&self, variant: Variant) -> String {
+        let tiles: Vec<String> = self
+            .0
+            .iter()
+            .map(|&(tile, _)| variant.value(tile).to_string())
+            .collect();
+        let weights: Vec<String> = self
+            .0
+            .iter()
+            .map(|(_, weight)| weight.to_string())
+            .collect();
+        format!("{} at {}", tiles.join("/"), weights.join(":"))
+    }
+
+    /// Gets the exponent of the smallest tile that can spawn, e.g. `1` for the 2s.
+    pub fn smallest_tile(&self) -> TileType {
+        self.tiles().into_iter().min().unwrap_or(1)
+    }
+
+    /// Gets how often a tile bigger than the smallest one spawns, e.g. `25` for 3:1 2s and 4s.
+    ///
+    /// # Returns
+    ///
+    /// The share of the tiles, in percent.
+    pub fn bigger_tile_percent(&self) -> u32 {
+        let smallest = self.smallest_tile();
+        let (mut bigger, mut total) = (0, 0);
+        for &(tile, weight) in self.0.iter().filter(|&&(tile, _)| tile != 0) {
+            total += weight as u64;
+            if tile != smallest {
+                bigger += weight as u64;
+            }
+        }
+        (bigger * 100).checked_div(total).unwrap_or(0) as u32
+    }
+
+    /// Gets the weights a variant is played with unless others are chosen. Threes needs as many
+    /// 2s as 1s, since 1s only merge with 2s; the other variants use the usual weights.
+    pub fn for_variant(variant: Variant) -> SpawnWeights {
+        match variant {
+            Variant::Threes => SpawnWeights(vec![(1, 1), (2, 1)]),
+            Variant::Classic | Variant::Fibonacci | Variant::PowersOfThree => {
+                SpawnWeights::default()
+            }
+        }
+    }
+
+    /// Gets the next weights offered in the settings screen. Weights that aren't offered, e.g.
+    /// set in the config file, move on to the usual ones.
+    pub fn next(&self) -> SpawnWeights {
+        let next = SpawnWeights::CHOICES
+            .iter()
+            .position(|&choice| choice == self.0.as_slice())
+            .map_or(0, |index| (index + 1) % SpawnWeights::CHOICES.len());
+        SpawnWeights(SpawnWeights::CHOICES[next].to_vec())
+    }
+}
+
+/// Spawns tiles in any empty cell with the same chance. This is the normal game.
+pub struct UniformSpawn {
+    pub weights: SpawnWeights,
+}
+
+impl SpawnPolicy for UniformSpawn {
+    fn choose_spawn(
+        &self,
+        board: &Board,
+        _turn: usize,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), TileType)> {
+        let position = *board.empty_positions().choose(rng)?;
+        Some((position, self.weights.choose(rng)))
+    }
+}
+
+/// A corner of the board.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    const ALL: [Corner; 4] = [
+        Corner::TopLeft,
+        Corner::TopRight,
+        Corner::BottomLeft,
+        Corner::BottomRight,
+    ];
+
+    /// Gets the name of the corner as shown to the player.
+    pub fn label(self) -> &'static str {
+        match self {
+            Corner::TopLeft => "top-left",
+            Corner::TopRight => "top-right",
+            Corner::BottomLeft => "bottom-left",
+            Corner::BottomRight => "bottom-right",
+        }
+    }
+
+    /// Gets how many steps a cell is from the corner, moving only along rows and columns.
+    fn distance(self, (x, y): (usize, usize), width: usize, height: usize) -> usize {
+        let (corner_x, corner_y) = match self {
+            Corner::TopLeft => (0, 0),
+            Corner::TopRight => (width - 1, 0),
+            Corner::BottomLeft => (0, height - 1),
+            Corner::BottomRight => (width - 1, height - 1),
+        };
+        x.abs_diff(corner_x) + y.abs_diff(corner_y)
+    }
+}
+
+/// Spawns tiles more often near one corner, or more often far from it, to practise a strategy.
+/// Spawning away from the corner the big tiles are kept in is gentler than the normal game, and
+/// spawning toward it is harsher.
+pub struct CornerBiasSpawn {
+    pub corner: Corner,
+    /// Whether tiles spawn more often near the corner rather than far from it.
+    pub toward: bool,
+    pub weights: SpawnWeights,
+}
+
+impl SpawnPolicy for CornerBiasSpawn {
+    fn choose_spawn(
+        &self,
+        board: &Board,
+        _turn: usize,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), TileType)> {
+        let rows = board.get_data_for_display();
+        let (width, height) = (rows[0].len(), rows.len());
+        let farthest = width + height - 2;
+        let position = *board
+            .empty_positions()
+            .choose_weighted(rng, |&cell| {
+                let distance = self.corner.distance(cell, width, height);
+                // squared so that the bias is strong enough to notice while playing
+                let closeness = if self.toward {
+                    farthest - distance
+                } else {
+                    distance
+                };
+                (closeness + 1).pow(2)
+            })
+            .ok()?;
+        Some((position, self.weights.choose(rng)))
+    }
+}
+
+/// Spawns the tile that leaves the player the worst position, judged by the solver's evaluation
+/// of the best move after it. This never uses the RNG, so it's the same in every game. Only tiles
+/// that have a weight are considered.
+pub struct AdversarialSpawn {
+    pub weights: SpawnWeights,
+    /// How the tiles move in the game, so that the player's replies are the real ones.
+    pub rules: MoveRules,
+}
+
+impl AdversarialSpawn {
+    /// Judges a position by the player's best move from it, or as hopeless if there is none.
+    fn best_reply(&self, board: &Board, weights: &EvalWeights) -> f64 {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| solver::apply_move(board, direction, self.rules))
+            .map(|after| solver::evaluate(&after, weights))
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+}
+
+impl SpawnPolicy for AdversarialSpawn {
+    fn choose_spawn(
+        &self,
+        board: &Board,
+        _turn: usize,
+        _rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), TileType)> {
+        // the default weights rather than the player's tuned ones, so that replays don't change
+        let weights = EvalWeights::default();
+        let tiles = self.weights.tiles();
+        board
+            .empty_positions()
+            .into_iter()
+            .flat_map(|position| tiles.iter().map(move |&tile| (position, tile)))
+            .map(|((x, y), tile)| {
+                let mut after = board.clone();
+                after.place_item_in_board(y, x, tile).unwrap();
+                (((x, y), tile), self.best_reply(&after, &weights))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(spawn, _)| spawn)
+    }
+}
+
+/// A tile placed by `ScriptedSpawn`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedTile {
+    pub x: usize,
+    pub y: usize,
+    /// The exponent of the tile, e.g. `1` for a 2, like on a `Board`.
+    pub tile: TileType,
+}
+
+/// Spawns a fixed sequence of tiles, starting with the ones the game starts with, e.g. for
+/// tutorials and tests. Once the script runs out, or if its cell for a turn is taken, tiles spawn
+/// like in the normal game.
+pub struct ScriptedSpawn<'a> {
+    pub tiles: &'a [ScriptedTile],
+    /// How the tiles after the script are picked.
+    pub weights: SpawnWeights,
+}
+
+impl SpawnPolicy for ScriptedSpawn<'_> {
+    fn choose_spawn(
+        &self,
+        board: &Board,
+        turn: usize,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), TileType)> {
+        match self.tiles.get(turn) {
+            Some(&ScriptedTile { x, y, tile }) if board.empty_positions().contains(&(x, y)) => {
+                Some(((x, y), tile))
+            }
+            _ => UniformSpawn {
+                weights: self.weights.clone(),
+            }
+            .choose_spawn(board, turn, rng),
+        }
+    }
+}
+
+/// Spawns tiles like another policy, except where its tile would leave the player without a move
+/// while another tile wouldn't, as a forgiving casual mode. Each choice is checked by trying the
+/// tile on a copy of the board. A tile can only end the game by filling the last empty cell, so the
+/// alternatives are the other tiles that can spawn there, picked by their weights.
+pub struct FairSpawn<'a> {
+    /// The policy whose choices are checked.
+    pub inner: Box<dyn SpawnPolicy + 'a>,
+    /// Which tiles can spawn instead of the one `inner` chose, and how often.
+    pub weights: SpawnWeights,
+    /// Decides which tiles merge, and so whether a move is left.
+    pub variant: Variant,
+}
+
+impl SpawnPolicy for FairSpawn<'_> {
+    fn choose_spawn(
+        &self,
+        board: &Board,
+        turn: usize,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), TileType)> {
+        let ((x, y), chosen) = self.inner.choose_spawn(board, turn, rng)?;
+        let leaves_a_move = |tile| {
+            let mut after = board.clone();
+            after.place_item_in_board(y, x, tile).unwrap();
+            after.has_available_move(&self.variant)
+        };
+        if leaves_a_move(chosen) {
+            return Some(((x, y), chosen));
+        }
+
+        let fair = SpawnWeights(
+            self.weights
+                .0
+                .iter()
+                .copied()
+                .filter(|&(tile, weight)| tile != 0 && weight != 0 && leaves_a_move(tile))
+                .collect(),
+        );
+        // when every tile ends the game, there's nothing to be fair about
+        let tile = if fair.0.is_empty() {
+            chosen
+        } else {
+            fair.choose(rng)
+        };
+        Some(((x, y), tile))
+    }
+}
+
+/// The spawn policy of a game, as chosen in the settings and stored in saves and replays so that
+/// the game can be played back exactly. It is serialized as the policy's name and its parameters,
+/// e.g. `{ policy = "corner_bias", corner = "top_left", toward = true }`.
+#[derive(Debug, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum SpawnRule {
+    /// The normal game, see `UniformSpawn`.
+    #[default]
+    Uniform,
+    /// Practice rules, see `CornerBiasSpawn`.
+    CornerBias { corner: Corner, toward: bool },
+    /// The worst case, see `AdversarialSpawn`.
+    Adversarial,
+    /// A fixed sequence of tiles, see `ScriptedSpawn`.
+    Scripted { tiles: Vec<ScriptedTile> },
+}
+
+impl SpawnRule {
+    /// Gets the policy that spawns tiles under this rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - How often the policy spawns a 2 rather than a 4.
+    /// * `rules` - How the tiles move in the game, for policies that look ahead.
+    pub fn policy(&self, weights: SpawnWeights, rules: MoveRules) -> Box<dyn SpawnPolicy + '_> {
+        match self {
+            SpawnRule::Uniform => Box::new(UniformSpawn { weights }),
+            &SpawnRule::CornerBias { corner, toward } => Box::new(CornerBiasSpawn {
+                corner,
+                toward,
+                weights,
+            }),
+            SpawnRule::Adversarial => Box::new(AdversarialSpawn { weights, rules }),
+            SpawnRule::Scripted { tiles } => Box::new(ScriptedSpawn { tiles, weights }),
+        }
+    }
+
+    /// Describes the rule in the settings screen and replays.
+    pub fn label(&self) -> String {
+        match self {
+            SpawnRule::Uniform => "Anywhere".to_string(),
+            SpawnRule::CornerBias {
+                corner,
+                toward: true,
+            } => format!("Toward {}", corner.label()),
+            SpawnRule::CornerBias {
+                corner,
+                toward: false,
+            } => format!("Away from {}", corner.label()),
+            SpawnRule::Adversarial => "Worst possible".to_string(),
+            SpawnRule::Scripted { .. } => "Scripted".to_string(),
+        }
+    }
+
+    /// Gets the next rule in the settings screen: the normal game, then away from and toward each
+    /// corner in turn, then the worst case, then back to the normal game. Scripts can only be set
+    /// in the config file, and move on to the normal game.
+    pub fn next(&self) -> SpawnRule {
+        let rules: Vec<SpawnRule> = std::iter::once(SpawnRule::Uniform)
+            .chain(Corner::ALL.into_iter().flat_map(|corner| {
+                [false, true].map(|toward| SpawnRule::CornerBias { corner, toward })
+            }))
+            .chain(std::iter::once(SpawnRule::Adversarial))
+            .collect();
+        let next = rules
+            .iter()
+            .position(|rule| rule == self)
+            .map_or(0, |index| (index + 1) % rules.len());
+        rules[next].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Counts how many of many tiles spawned on an empty board land in each row.
+    fn spawns_per_row(rule: SpawnRule) -> [usize; 4] {
+        let mut rng = StdRng::seed_from_u64(7);
+        let board = Board::new(4);
+        let policy = rule.policy(SpawnWeights::default(), MoveRules::default());
+        let mut counts = [0; 4];
+        for _ in 0..2000 {
+            let ((_, y), tile) = policy.choose_spawn(&board, 0, &mut rng).unwrap();
+            assert!(tile == 1 || tile == 2);
+            counts[y] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn corner_bias_leans_toward_or_away_from_the_corner() {
+        let away = spawns_per_row(SpawnRule::CornerBias {
+            corner: Corner::TopLeft,
+            toward: false,
+        });
+        let toward = spawns_per_row(SpawnRule::CornerBias {
+            corner: Corner::TopLeft,
+            toward: true,
+        });
+
+        assert!(away[3] > 2 * away[0], "{away:?}");
+        assert!(toward[0] > 2 * toward[3], "{toward:?}");
+    }
+
+    #[test]
+    fn spawns_only_in_empty_cells() {
+        let mut board = Board::try_from(vec![vec![1, 1], vec![1, 0]]).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let policy = CornerBiasSpawn {
+            corner: Corner::BottomRight,
+            toward: false,
+            weights: SpawnWeights::default(),
+        };
+
+        assert_eq!(
+            Some((1, 1)),
+            policy
+                .choose_spawn(&board, 0, &mut rng)
+                .map(|(cell, _)| cell)
+        );
+        board.place_item_in_board(1, 1, 1).unwrap();
+        assert_eq!(None, policy.choose_spawn(&board, 0, &mut rng));
+    }
+
+    #[test]
+    fn adversary_spawns_where_it_hurts_most() {
+        // a 2 in the empty cell locks the board up, while a 4 could merge with the 4 next to it
+        let board = Board::try_from(vec![vec![1, 2], vec![2, 0]]).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let adversary = AdversarialSpawn {
+            weights: SpawnWeights::default(),
+            rules: MoveRules::default(),
+        };
+
+        assert_eq!(
+            Some(((1, 1), 1)),
+            adversary.choose_spawn(&board, 0, &mut rng)
+        );
+        // without 2s, the adversary has to give the player the 4
+        let adversary = AdversarialSpawn {
+            weights: SpawnWeights(vec![(1, 0), (2, 1)]),
+            rules: MoveRules::default(),
+        };
+        assert_eq!(
+            Some(((1, 1), 2)),
+            adversary.choose_spawn(&board, 0, &mut rng)
+        );
+    }
+
+    #[test]
+    fn weights_decide_which_tiles_spawn() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let counts = |weights: SpawnWeights, rng: &mut StdRng| {
+            let mut counts = [0; 4];
+            for _ in 0..1000 {
+                counts[weights.choose(rng) as usize] += 1;
+            }
+            counts
+        };
+
+        assert_eq!(
+            [0, 1000, 0, 0],
+            counts(SpawnWeights(vec![(1, 1), (2, 0)]), &mut rng)
+        );
+        assert_eq!(
+            [0, 0, 1000, 0],
+            counts(SpawnWeights(vec![(1, 0), (2, 5)]), &mut rng)
+        );
+        // empty cells can't spawn, and with nothing left 2s do
+        assert_eq!(
+            [0, 1000, 0, 0],
+            counts(SpawnWeights(vec![(0, 5)]), &mut rng)
+        );
+        let hard = counts(SpawnWeights(vec![(1, 1), (2, 1), (3, 2)]), &mut rng);
+        assert!((400..600).contains(&hard[3]), "{hard:?}");
+        // weights that add up past what fits in a u32 keep their odds
+        let huge = counts(
+            SpawnWeights(vec![(1, u32::MAX), (2, u32::MAX), (3, u32::MAX - 1)]),
+            &mut rng,
+        );
+        assert!(
+            huge.iter()
+                .skip(1)
+                .all(|&count| (250..420).contains(&count)),
+            "{huge:?}"
+        );
+        assert_eq!(
+            vec![1, 3],
+            SpawnWeights(vec![(1, 1), (2, 0), (3, 2)]).tiles()
+        );
+    }
+
+    #[test]
+    fn weights_cycle() {
+        let weights = SpawnWeights::default().next();
+        assert_eq!("2/4 at 9:1", weights.label(Variant::Classic));
+        assert_eq!(
+            "2/4/8 at 6:3:1",
+            weights.next().next().label(Variant::Classic)
+        );
+        assert_eq!("1/2 at 9:1", weights.label(Variant::Fibonacci));
+        assert_eq!(
+            "1/2 at 1:1",
+            SpawnWeights::for_variant(Variant::Threes).label(Variant::Threes)
+        );
+        assert_eq!(SpawnWeights::default(), weights.next().next().next());
+        assert_eq!(
+            SpawnWeights::default(),
+            SpawnWeights(vec![(1, 7), (2, 2)]).next()
+        );
+    }
+
+    #[test]
+    fn script_is_followed_while_its_cells_are_free() {
+        let board = Board::try_from(vec![vec![1, 0], vec![0, 0]]).unwrap();
+        let tiles = [
+            ScriptedTile {
+                x: 1,
+                y: 1,
+                tile: 2,
+            },
+            ScriptedTile {
+                x: 0,
+                y: 0,
+                tile: 2,
+            },
+        ];
+        let policy = ScriptedSpawn {
+            tiles: &tiles,
+            weights: SpawnWeights::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(Some(((1, 1), 2)), policy.choose_spawn(&board, 0, &mut rng));
+        // the scripted cell is taken, so the tile goes somewhere else
+        let ((x, y), _) = policy.choose_spawn(&board, 1, &mut rng).unwrap();
+        assert_ne!((0, 0), (x, y));
+    }
+
+    #[test]
+    fn fair_spawns_avoid_dead_ends() {
+        let board = Board::from_notation("2,4/4,.").unwrap();
+        let dead_end = [ScriptedTile {
+            x: 1,
+            y: 1,
+            tile: 1,
+        }];
+        let fair = |weights| FairSpawn {
+            inner: Box::new(ScriptedSpawn {
+                tiles: &dead_end,
+                weights: SpawnWeights::default(),
+            }),
+            weights,
+            variant: Variant::Classic,
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // a 2 in the last cell ends the game, but a 4 can merge
+        let weights = SpawnWeights(vec![(1, 1), (2, 1), (3, 1)]);
+        assert_eq!(
+            Some(((1, 1), 2)),
+            fair(weights).choose_spawn(&board, 0, &mut rng)
+        );
+        // without 4s, every tile ends the game
+        assert_eq!(
+            Some(((1, 1), 1)),
+            fair(SpawnWeights(vec![(1, 1), (3, 1)])).choose_spawn(&board, 0, &mut rng)
+        );
+        // tiles that don't end the game are left alone
+        let board = Board::from_notation("2,4/.,.").unwrap();
+        assert_eq!(
+            Some(((1, 1), 1)),
+            fair(SpawnWeights::default()).choose_spawn(&board, 0, &mut rng)
+        );
+    }
+
+    #[test]
+    fn rules_serialize_by_name_and_parameters() {
+        let rule = SpawnRule::Scripted {
+            tiles: vec![ScriptedTile {
+                x: 2,
+                y: 3,
+                tile: 1,
+            }],
+        };
+        let json = serde_json::to_value(&rule).unwrap();
+
+        assert_eq!(
+            serde_json::json!({"policy": "scripted", "tiles": [{"x": 2, "y": 3, "tile": 1}]}),
+            json
+        );
+        assert_eq!(rule, serde_json::from_value(json).unwrap());
+        assert_eq!(
+            SpawnRule::Adversarial,
+            serde_json::from_str(r#"{"policy": "adversarial"}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn rules_cycle_through_every_corner() {
+        let mut rule = SpawnRule::Uniform;
+        for _ in 0..9 {
+            rule = rule.next();
+            assert_ne!(SpawnRule::Uniform, rule);
+        }
+        assert_eq!(SpawnRule::Uniform, rule.next());
+    }
+}
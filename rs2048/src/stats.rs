@@ -0,0 +1,1352 @@
+use crate::board::TileType;
+use crate::game::{Game, MoveRecord};
+use crate::merge::Variant;
+use crate::number_format::NumberFormat;
+use crate::persistence;
+use crate::persistence::PersistenceError;
+use crate::rules::Rules;
+use crate::tile;
+use chrono::{Local, Offset, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::ops::{Add, Sub};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Gaps between moves longer than this are breaks, e.g. the game was saved and loaded the next
+/// day, so they aren't counted as thinking time.
+const BREAK_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// How many games the high score table keeps of each kind of rules, assisted and not, so that
+/// hiding the assisted ones still leaves a full table.
+const HIGH_SCORES_KEPT: usize = 10;
+
+/// How long the player spent thinking about their moves.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ThinkingTimes {
+    /// How many moves were timed.
+    pub moves: usize,
+    pub median: Duration,
+    /// Nine in ten moves were made at least this quickly.
+    pub ninetieth_percentile: Duration,
+    pub longest: Duration,
+}
+
+impl ThinkingTimes {
+    /// Works out how long the player thought about each move from when the moves were made.
+    ///
+    /// The first move isn't timed, since there's nothing to measure it from, and neither are moves
+    /// made after a break.
+    ///
+    /// # Arguments
+    ///
+    /// * `moves` - The moves of a game, oldest first, e.g. streamed with `Game::stream_moves`.
+    ///
+    /// # Returns
+    ///
+    /// The `ThinkingTimes`, or `None` if no move could be timed.
+    pub fn from_moves(moves: impl IntoIterator<Item = MoveRecord>) -> Option<ThinkingTimes> {
+        let mut previous: Option<MoveRecord> = None;
+        let mut times: Vec<Duration> = moves
+            .into_iter()
+            .filter_map(|record| {
+                let played_before = previous.replace(record)?.played_at;
+                Some(Duration::from_millis(
+                    record.played_at.saturating_sub(played_before),
+                ))
+            })
+            .filter(|&time| time <= BREAK_THRESHOLD)
+            .collect();
+        times.sort();
+        if times.is_empty() {
+            return None;
+        }
+
+        let percentile = |percent: usize| times[(times.len() - 1) * percent / 100];
+        Some(ThinkingTimes {
+            moves: times.len(),
+            median: percentile(50),
+            ninetieth_percentile: percentile(90),
+            longest: times[times.len() - 1],
+        })
+    }
+}
+
+/// The kind of rules a game was played by, as far as its score goes: each kind keeps its own best
+/// scores, since e.g. a bigger board or Fibonacci tiles score very differently.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct ScoreKind {
+    pub variant: Variant,
+    pub board_size: usize,
+    pub wrap_around: bool,
+}
+
+impl ScoreKind {
+    /// Gets the kind of rules the game is played by.
+    pub fn of(rules: &Rules) -> ScoreKind {
+        ScoreKind {
+            variant: rules.variant,
+            board_size: rules.board_size,
+            wrap_around: rules.wrap_around,
+        }
+    }
+
+    /// Gets whether this is the usual game: powers of two on a 4x4 board, with or without
+    /// wrap-around, whose bests are kept in `Statistics::best_score` and
+    /// `Statistics::best_wrap_around_score`.
+    fn is_usual(self) -> bool {
+        self.variant == Variant::Classic && self.board_size == 4
+    }
+
+    /// Names the best score of the kind of rules, e.g. "Best Wrap-Around Score" or
+    /// "Best Fibonacci 5x5 Score".
+    pub fn best_score_label(self) -> String {
+        match (self.is_usual(), self.wrap_around) {
+            (true, false) => "Best Score".to_string(),
+            (true, true) => "Best Wrap-Around Score".to_string(),
+            (false, _) => format!("Best {} Score", self.name()),
+        }
+    }
+
+    /// Describes the kind of rules, e.g. "Fibonacci 5x5, Wrap-Around".
+    pub fn name(self) -> String {
+        let name = format!(
+            "{} {}x{}",
+            self.variant.name(),
+            self.board_size,
+            self.board_size
+        );
+        if self.wrap_around {
+            format!("{}, Wrap-Around", name)
+        } else {
+            name
+        }
+    }
+}
+
+impl Default for ScoreKind {
+    /// The usual game.
+    fn default() -> Self {
+        ScoreKind::of(&Rules::default())
+    }
+}
+
+/// A game that has ended, as it's kept on the high score table.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FinishedGame {
+    pub score: u32,
+    /// The exponent of the biggest tile made in the game.
+    pub highest_tile: TileType,
+    /// Whether the game was played with `Rules::wrap_around`.
+    pub wrap_around: bool,
+    /// Which sequence the tiles counted through, for the value of `highest_tile`.
+    #[serde(default)]
+    pub variant: Variant,
+    /// The width and height of the board. Games saved before it was kept were played on 4x4.
+    #[serde(default = "usual_board_size")]
+    pub board_size: usize,
+    /// Whether the game was assisted by undoing, hints or auto-play, which keeps it off the best
+    /// scores.
+    pub assisted: bool,
+    /// Whether the game was played with `Rules::adaptive`, whose easier odds keep it off the best
+    /// scores like help does.
+    #[serde(default)]
+    pub adaptive: bool,
+    /// Whether the game reached the rules' winning tile before it ended.
+    #[serde(default)]
+    pub won: bool,
+    /// When the game ended, in seconds since the Unix epoch.
+    pub finished_at: u64,
+}
+
+impl FinishedGame {
+    /// Captures a game as it ended.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game that ended.
+    /// * `finished_at` - When it ended, in seconds since the Unix epoch.
+    pub fn of(game: &Game, finished_at: u64) -> FinishedGame {
+        FinishedGame {
+            score: game.score(),
+            highest_tile: game.highest_tile(),
+            wrap_around: game.rules().wrap_around,
+            variant: game.rules().variant,
+            board_size: game.rules().board_size,
+            assisted: game.is_assisted(),
+            adaptive: game.rules().adaptive,
+            won: game.has_won(),
+            finished_at,
+        }
+    }
+
+    /// Gets whether the game counts towards the best scores, which it doesn't if it was assisted
+    /// or adaptive.
+    pub fn is_unaided(&self) -> bool {
+        !self.assisted && !self.adaptive
+    }
+
+    /// Gets the kind of rules the game was played by.
+    pub fn kind(&self) -> ScoreKind {
+        ScoreKind {
+            variant: self.variant,
+            board_size: self.board_size,
+            wrap_around: self.wrap_around,
+        }
+    }
+}
+
+/// What happened in one run of the app.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    /// When the app was started, in seconds since the Unix epoch.
+    pub started_at: u64,
+    /// When the last game of the session ended, in seconds since the Unix epoch.
+    pub last_played_at: u64,
+    pub games_played: u32,
+    pub best_score: u32,
+    /// How many of the games were assisted by undoing, which keeps them out of `best_score`.
+    #[serde(default)]
+    pub assisted_games: u32,
+}
+
+/// The player's record across every game, kept in `statistics.json` in the data directory.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    /// How many games have been played to the end.
+    pub games_played: u32,
+    /// The best score of a game played on a normal 4x4 board of powers of two. Assisted games
+    /// don't count towards the best scores.
+    pub best_score: u32,
+    /// The best score of a game played with `Rules::wrap_around`, kept apart since those games
+    /// play differently.
+    pub best_wrap_around_score: u32,
+    /// The best scores of the other kinds of rules, e.g. other variants or board sizes, which
+    /// have theirs kept apart too.
+    pub other_best_scores: Vec<(ScoreKind, u32)>,
+    /// The days on which the player made a move, as counted by `day_number`.
+    pub days_played: BTreeSet<u64>,
+    /// The most recent session in which a game was finished.
+    pub last_session: Option<SessionSummary>,
+    /// What the player has done on the last day they played, for the daily goal.
+    pub today: Option<DailyProgress>,
+    /// The days on which the daily goal was met.
+    pub goals_met: BTreeSet<u64>,
+    /// How closely the player follows the coach's hints.
+    pub hints: HintAdherence,
+    /// The exponent of the biggest tile ever made.
+    pub highest_tile: TileType,
+    /// The best games, highest score first, keeping `HIGH_SCORES_KEPT` of the assisted games
+    /// and as many of the others for each kind of rules.
+    pub high_scores: Vec<FinishedGame>,
+    /// The best score of each day's challenge played to the end, by the day as counted by
+    /// `day_number`.
+    pub daily_challenges: BTreeMap<u64, u32>,
+    /// What every game played to the end adds up to, for the statistics screen.
+    pub lifetime: LifetimeTotals,
+    /// The statistics as they were in the file when this instance last read or wrote it, which
+    /// tells its own changes apart from another instance's when saving.
+    #[serde(skip)]
+    on_disk: Option<Box<Statistics>>,
+}
+
+/// What the games played to the end add up to, assisted or not. They're counted apart from
+/// `Statistics::games_played`, since statistics saved before these were kept don't have the
+/// scores of the games they counted.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeTotals {
+    pub games: u32,
+    /// How many of the games reached the winning tile.
+    pub wins: u32,
+    /// The scores of the games added up.
+    pub total_score: u64,
+    /// How many games ended with each exponent as their biggest tile, e.g. `{11: 3}` for three
+    /// games that got as far as 2048. Only games that count in powers of two are in here, since
+    /// a Fibonacci tile of the same exponent isn't the same tile.
+    pub best_tiles: BTreeMap<TileType, u32>,
+}
+
+impl LifetimeTotals {
+    /// Adds a game that has ended.
+    fn record(&mut self, game: &FinishedGame) {
+        self.games += 1;
+        self.wins += game.won as u32;
+        self.total_score += game.score as u64;
+        if game.variant == Variant::Classic {
+            *self.best_tiles.entry(game.highest_tile).or_default() += 1;
+        }
+    }
+
+    /// Gets the share of the games that were won, in percent, or `None` before any game.
+    pub fn win_rate(&self) -> Option<u32> {
+        (self.games > 0).then(|| (self.wins as u64 * 100 / self.games as u64) as u32)
+    }
+
+    /// Gets the average score of the games, or `None` before any game.
+    pub fn average_score(&self) -> Option<u64> {
+        (self.games > 0).then(|| self.total_score / self.games as u64)
+    }
+}
+
+/// How closely the player follows the coach's hints, over every move made with coaching on.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HintAdherence {
+    /// How many moves were made with coaching on.
+    pub moves: u32,
+    /// How many of those moves were the move the coach would have made.
+    pub followed: u32,
+    /// How much worse than the coach's move the other moves were, relative to it, added up in
+    /// hundredths of a percent.
+    pub loss_basis_points: u64,
+}
+
+impl HintAdherence {
+    /// Counts a move made with coaching on.
+    ///
+    /// # Arguments
+    ///
+    /// * `followed` - Whether the move was the one the coach would have made.
+    /// * `loss` - How much worse the move was than the coach's, relative to it, e.g. `0.1` for
+    ///   10% worse.
+    pub fn record(&mut self, followed: bool, loss: f64) {
+        self.moves += 1;
+        if followed {
+            self.followed += 1;
+        } else {
+            self.loss_basis_points += (loss * 10_000.0).round() as u64;
+        }
+    }
+
+    /// Describes how closely the hints were followed, e.g. for the end of a game.
+    ///
+    /// # Returns
+    ///
+    /// The description, or `None` if no move was made with coaching on.
+    pub fn describe(&self) -> Option<String> {
+        if self.moves == 0 {
+            return None;
+        }
+        let percent = self.followed as u64 * 100 / self.moves as u64;
+        let missed = self.moves - self.followed;
+        Some(if missed == 0 {
+            format!("Hints followed: {}% of {} moves", percent, self.moves)
+        } else {
+            let average_loss = self.loss_basis_points as f64 / missed as f64 / 100.0;
+            format!(
+                "Hints followed: {}% of {} moves, others {:.1}% worse",
+                percent, self.moves, average_loss
+            )
+        })
+    }
+}
+
+/// What the player has done on one day, as far as the daily goals are concerned.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DailyProgress {
+    /// The day, as counted by `day_number`.
+    pub day: u64,
+    /// The exponent of the biggest tile made that day.
+    pub highest_tile: TileType,
+    pub games_finished: u32,
+}
+
+/// Something to aim for each day. Every player gets the same goal on the same day.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DailyGoal {
+    /// Make a tile at least this big, given as an exponent like on a `Board`.
+    ReachTile(TileType),
+    /// Play this many games to the end.
+    FinishGames(u32),
+}
+
+impl DailyGoal {
+    const CHOICES: [DailyGoal; 6] = [
+        DailyGoal::ReachTile(8),
+        DailyGoal::FinishGames(2),
+        DailyGoal::ReachTile(9),
+        DailyGoal::ReachTile(10),
+        DailyGoal::FinishGames(3),
+        DailyGoal::ReachTile(11),
+    ];
+
+    /// Gets the goal for a day.
+    ///
+    /// # Arguments
+    ///
+    /// * `day` - The day, as counted by `day_number`.
+    pub fn for_day(day: u64) -> DailyGoal {
+        DailyGoal::CHOICES[(day % DailyGoal::CHOICES.len() as u64) as usize]
+    }
+
+    /// Describes the goal, e.g. "Reach 512 today".
+    pub fn describe(self, number_format: NumberFormat) -> String {
+        match self {
+            DailyGoal::ReachTile(tile) => {
+                format!("Reach {} today", tile::display_value(tile, number_format))
+            }
+            DailyGoal::FinishGames(games) => format!("Finish {} games today", games),
+        }
+    }
+
+    /// Whether a day's progress meets the goal.
+    fn is_met_by(self, progress: &DailyProgress) -> bool {
+        match self {
+            DailyGoal::ReachTile(tile) => progress.highest_tile >= tile,
+            DailyGoal::FinishGames(games) => progress.games_finished >= games,
+        }
+    }
+}
+
+impl Statistics {
+    const FILE_NAME: &'static str = "statistics.json";
+
+    /// Loads the statistics, starting from nothing if they don't exist or can't be read.
+    ///
+    /// # Returns
+    ///
+    /// The loaded `Statistics`.
+    pub fn load() -> Statistics {
+        let statistics: Statistics = persistence::data_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(Statistics::FILE_NAME)).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        statistics.into_saved()
+    }
+
+    /// Writes the statistics to the data directory. Another instance may have saved games since
+    /// these were read, so the file is read again first and this instance's changes are merged
+    /// into it, all while holding the data directory's lock. These statistics then become the
+    /// merged ones.
+    ///
+    /// # Returns
+    ///
+    /// The path of the statistics file, or a `PersistenceError` if it couldn't be written.
+    pub fn save(&mut self) -> Result<PathBuf, PersistenceError> {
+        let mut merged = None;
+        let path = persistence::update_data_file(Statistics::FILE_NAME, |contents| {
+            let on_disk = contents
+                .and_then(|contents| serde_json::from_str(&contents).ok())
+                .unwrap_or_default();
+            let statistics = self.merged_into(on_disk);
+            let contents = serde_json::to_string_pretty(&statistics)
+                .map_err(|e| PersistenceError::Io(e.into()))?;
+            merged = Some(statistics);
+            Ok(contents)
+        })?;
+        if let Some(merged) = merged {
+            *self = merged.into_saved();
+        }
+        Ok(path)
+    }
+
+    /// Remembers these statistics as what's in the file, for merging when they're next saved.
+    fn into_saved(mut self) -> Statistics {
+        self.on_disk = None;
+        self.on_disk = Some(Box::new(self.clone()));
+        self
+    }
+
+    /// Merges the changes made since these statistics were read into what's in the file now,
+    /// which another instance may have changed in the meantime. Counts add up both instances'
+    /// games, bests keep the better of the two, and days and high scores from either are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_disk` - The statistics in the file now.
+    ///
+    /// # Returns
+    ///
+    /// The merged statistics.
+    fn merged_into(&self, on_disk: Statistics) -> Statistics {
+        let default = Statistics::default();
+        let base = self.on_disk.as_deref().unwrap_or(&default);
+        let mut merged = Statistics {
+            games_played: added(on_disk.games_played, base.games_played, self.games_played),
+            best_score: on_disk.best_score.max(self.best_score),
+            best_wrap_around_score: on_disk
+                .best_wrap_around_score
+                .max(self.best_wrap_around_score),
+            other_best_scores: on_disk.other_best_scores,
+            days_played: &on_disk.days_played | &self.days_played,
+            last_session: match (on_disk.last_session, self.last_session) {
+                (Some(theirs), Some(ours))
+                    if theirs.started_at != ours.started_at
+                        && theirs.last_played_at > ours.last_played_at =>
+                {
+                    Some(theirs)
+                }
+                (theirs, ours) => ours.or(theirs),
+            },
+            today: match (on_disk.today, self.today) {
+                (Some(theirs), Some(ours)) if theirs.day == ours.day => {
+                    let base_games = base
+                        .today
+                        .filter(|progress| progress.day == ours.day)
+                        .map_or(0, |progress| progress.games_finished);
+                    Some(DailyProgress {
+                        day: ours.day,
+                        highest_tile: theirs.highest_tile.max(ours.highest_tile),
+                        games_finished: added(
+                            theirs.games_finished,
+                            base_games,
+                            ours.games_finished,
+                        ),
+                    })
+                }
+                (Some(theirs), Some(ours)) if theirs.day > ours.day => Some(theirs),
+                (theirs, ours) => ours.or(theirs),
+            },
+            goals_met: &on_disk.goals_met | &self.goals_met,
+            hints: HintAdherence {
+                moves: added(on_disk.hints.moves, base.hints.moves, self.hints.moves),
+                followed: added(
+                    on_disk.hints.followed,
+                    base.hints.followed,
+                    self.hints.followed,
+                ),
+                loss_basis_points: added(
+                    on_disk.hints.loss_basis_points,
+                    base.hints.loss_basis_points,
+                    self.hints.loss_basis_points,
+                ),
+            },
+            highest_tile: on_disk.highest_tile.max(self.highest_tile),
+            high_scores: on_disk.high_scores,
+            daily_challenges: on_disk.daily_challenges,
+            lifetime: LifetimeTotals {
+                games: added(
+                    on_disk.lifetime.games,
+                    base.lifetime.games,
+                    self.lifetime.games,
+                ),
+                wins: added(
+                    on_disk.lifetime.wins,
+                    base.lifetime.wins,
+                    self.lifetime.wins,
+                ),
+                total_score: added(
+                    on_disk.lifetime.total_score,
+                    base.lifetime.total_score,
+                    self.lifetime.total_score,
+                ),
+                best_tiles: on_disk.lifetime.best_tiles,
+            },
+            on_disk: None,
+        };
+        for &(kind, score) in &self.other_best_scores {
+            let best = merged.best_score_mut(kind);
+            *best = (*best).max(score);
+        }
+        for game in &self.high_scores {
+            if !base.high_scores.contains(game) && !merged.high_scores.contains(game) {
+                merged.record_high_score(*game);
+            }
+        }
+        for (&day, &score) in &self.daily_challenges {
+            let best = merged.daily_challenges.entry(day).or_default();
+            *best = (*best).max(score);
+        }
+        for (&tile, &count) in &self.lifetime.best_tiles {
+            let base_count = base.lifetime.best_tiles.get(&tile).copied().unwrap_or(0);
+            let merged_count = merged.lifetime.best_tiles.entry(tile).or_default();
+            *merged_count = added(*merged_count, base_count, count);
+        }
+        merged
+    }
+
+    /// Counts a game that has ended. Assisted games count as played and go on the high score
+    /// table, but are kept off the best scores.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game that ended.
+    /// * `session_started_at` - When the app was started, which tells this session apart from
+    ///   the last one.
+    pub fn record_game(&mut self, game: FinishedGame, session_started_at: u64) {
+        let now = game.finished_at;
+        self.games_played += 1;
+        self.lifetime.record(&game);
+        let counted_score = if game.is_unaided() { game.score } else { 0 };
+        let best_score = self.best_score_mut(game.kind());
+        *best_score = (*best_score).max(counted_score);
+        self.record_high_score(game);
+        let today = day_number(now);
+        self.mark_day_played(today);
+        self.update_progress(today, |progress| progress.games_finished += 1);
+
+        let session = match self.last_session {
+            Some(session) if session.started_at == session_started_at => session,
+            _ => SessionSummary {
+                started_at: session_started_at,
+                last_played_at: now,
+                games_played: 0,
+                best_score: 0,
+                assisted_games: 0,
+            },
+        };
+        self.last_session = Some(SessionSummary {
+            last_played_at: now,
+            games_played: session.games_played + 1,
+            best_score: session.best_score.max(counted_score),
+            assisted_games: session.assisted_games + game.assisted as u32,
+            ..session
+        });
+    }
+
+    /// Counts a game that is over, or that the player left after winning, unless it was counted
+    /// already: a lost game that is undone and lost again, or a won game that goes on and is lost
+    /// later, is only counted the first time. A game of the normal rules on a daily challenge's
+    /// seed also counts for that day's challenge.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game, which is marked as counted.
+    /// * `session_started_at` - When the app was started, which tells this session apart from
+    ///   the last one.
+    /// * `now` - The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// Whether the game made a new best score, or `None` if it was counted already.
+    pub fn record_finished_game(
+        &mut self,
+        game: &mut Game,
+        session_started_at: u64,
+        now: u64,
+    ) -> Option<bool> {
+        if game.is_recorded() {
+            return None;
+        }
+        game.mark_recorded();
+        let kind = ScoreKind::of(game.rules());
+        let previous_best = self.best_score_for(kind);
+        self.record_game(FinishedGame::of(game, now), session_started_at);
+        if *game.rules() == Rules::default() {
+            if let Some(day) = daily_challenge_day(game.seed(), day_number(now)) {
+                self.record_daily_challenge(day, game.score());
+            }
+        }
+        Some(self.best_score_for(kind) > previous_best)
+    }
+
+    /// Puts a game on the high score table if it's among the best of its kind.
+    fn record_high_score(&mut self, game: FinishedGame) {
+        self.high_scores.push(game);
+        // the sort is stable, so an older game keeps its place ahead of a newer one with the same
+        // score
+        self.high_scores.sort_by_key(|game| Reverse(game.score));
+        let mut kept: BTreeMap<(ScoreKind, bool), usize> = BTreeMap::new();
+        self.high_scores.retain(|game| {
+            let kept = kept.entry((game.kind(), game.is_unaided())).or_default();
+            *kept += 1;
+            *kept <= HIGH_SCORES_KEPT
+        });
+    }
+
+    /// Gets the high score table of a kind of rules, highest score first.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of rules the games were played by.
+    /// * `include_assisted` - Whether to list the assisted and adaptive games, or only the games
+    ///   played unaided so that their scores can be compared.
+    pub fn high_scores(&self, kind: ScoreKind, include_assisted: bool) -> Vec<FinishedGame> {
+        self.high_scores
+            .iter()
+            .filter(|game| game.kind() == kind)
+            .filter(|game| include_assisted || game.is_unaided())
+            .take(HIGH_SCORES_KEPT)
+            .copied()
+            .collect()
+    }
+
+    /// Gets the kinds of rules that have games on the high score table, the usual game first.
+    pub fn high_score_kinds(&self) -> Vec<ScoreKind> {
+        let kinds: BTreeSet<ScoreKind> = self.high_scores.iter().map(FinishedGame::kind).collect();
+        let (mut usual, others): (Vec<ScoreKind>, Vec<ScoreKind>) =
+            kinds.into_iter().partition(|kind| kind.is_usual());
+        usual.extend(others);
+        usual
+    }
+
+    /// Gets the best score of the games played by a kind of rules, since each kind keeps its
+    /// own.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of rules the games were played by.
+    pub fn best_score_for(&self, kind: ScoreKind) -> u32 {
+        if !kind.is_usual() {
+            return self
+                .other_best_scores
+                .iter()
+                .find(|&&(other, _)| other == kind)
+                .map_or(0, |&(_, score)| score);
+        }
+        if kind.wrap_around {
+            self.best_wrap_around_score
+        } else {
+            self.best_score
+        }
+    }
+
+    /// Gets where the best score of a kind of rules is kept, adding a place for it if it's the
+    /// first game of its kind.
+    fn best_score_mut(&mut self, kind: ScoreKind) -> &mut u32 {
+        if kind.is_usual() {
+            return if kind.wrap_around {
+                &mut self.best_wrap_around_score
+            } else {
+                &mut self.best_score
+            };
+        }
+        let index = match self
+            .other_best_scores
+            .iter()
+            .position(|&(other, _)| other == kind)
+        {
+            Some(index) => index,
+            None => {
+                self.other_best_scores.push((kind, 0));
+                self.other_best_scores.len() - 1
+            }
+        };
+        &mut self.other_best_scores[index].1
+    }
+
+    /// Gets the best score of the games finished in a session, of any rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_started_at` - When the app was started, which tells the session apart.
+    ///
+    /// # Returns
+    ///
+    /// The best score, or `0` if no game has been finished in the session.
+    pub fn session_best(&self, session_started_at: u64) -> u32 {
+        self.last_session
+            .filter(|session| session.started_at == session_started_at)
+            .map_or(0, |session| session.best_score)
+    }
+
+    /// Remembers that the player played on a day, and the biggest tile they have made in the
+    /// game.
+    ///
+    /// # Arguments
+    ///
+    /// * `today` - The current day, as counted by `day_number`.
+    /// * `highest_tile` - The exponent of the biggest tile made, as given by `Game::highest_tile`.
+    ///
+    /// # Returns
+    ///
+    /// Whether the statistics changed and should be saved.
+    pub fn record_play(&mut self, today: u64, highest_tile: TileType) -> bool {
+        let new_day = self.mark_day_played(today);
+        let new_tile = self.progress(today).highest_tile < highest_tile;
+        if new_tile {
+            self.update_progress(today, |progress| progress.highest_tile = highest_tile);
+        }
+        let new_record = self.highest_tile < highest_tile;
+        self.highest_tile = self.highest_tile.max(highest_tile);
+        new_day || new_tile || new_record
+    }
+
+    /// Remembers that the player played on a day.
+    ///
+    /// # Returns
+    ///
+    /// Whether this is the first time the day was marked.
+    fn mark_day_played(&mut self, today: u64) -> bool {
+        self.days_played.insert(today)
+    }
+
+    /// Gets what the player has done on a day, which is nothing if they haven't played yet.
+    fn progress(&self, today: u64) -> DailyProgress {
+        self.today
+            .filter(|progress| progress.day == today)
+            .unwrap_or(DailyProgress {
+                day: today,
+                ..DailyProgress::default()
+            })
+    }
+
+    /// Changes a day's progress and checks whether it meets the day's goal.
+    fn update_progress(&mut self, today: u64, update: impl FnOnce(&mut DailyProgress)) {
+        let mut progress = self.progress(today);
+        update(&mut progress);
+        if DailyGoal::for_day(today).is_met_by(&progress) {
+            self.goals_met.insert(today);
+        }
+        self.today = Some(progress);
+    }
+
+    /// Gets the goal for a day and whether it has been met.
+    ///
+    /// # Arguments
+    ///
+    /// * `today` - The day, as counted by `day_number`.
+    pub fn daily_goal(&self, today: u64) -> (DailyGoal, bool) {
+        (DailyGoal::for_day(today), self.goals_met.contains(&today))
+    }
+
+    /// Remembers a daily challenge played to the end, keeping the best score of the day.
+    ///
+    /// # Arguments
+    ///
+    /// * `day` - The day of the challenge, as counted by `day_number`.
+    /// * `score` - The score of the game.
+    pub fn record_daily_challenge(&mut self, day: u64, score: u32) {
+        let best = self.daily_challenges.entry(day).or_default();
+        *best = (*best).max(score);
+    }
+
+    /// Gets the best score of a day's challenge, or `None` if it wasn't played to the end.
+    ///
+    /// # Arguments
+    ///
+    /// * `day` - The day, as counted by `day_number`.
+    pub fn daily_challenge_score(&self, day: u64) -> Option<u32> {
+        self.daily_challenges.get(&day).copied()
+    }
+
+    /// Counts the days in a row the player has played, up to today. A streak isn't broken until
+    /// a whole day passes without playing, so playing yesterday but not yet today still counts.
+    ///
+    /// # Arguments
+    ///
+    /// * `today` - The current day, as counted by `day_number`.
+    ///
+    /// # Returns
+    ///
+    /// The length of the streak in days, or `0` if the player didn't play today or yesterday.
+    pub fn streak(&self, today: u64) -> u32 {
+        let mut day = if self.days_played.contains(&today) {
+            today
+        } else {
+            today.saturating_sub(1)
+        };
+        let mut streak = 0;
+        while self.days_played.contains(&day) {
+            streak += 1;
+            match day.checked_sub(1) {
+                Some(previous) => day = previous,
+                None => break,
+            }
+        }
+        streak
+    }
+}
+
+/// Gets the seed of a day's challenge, which every player gets on the same date, so they all see
+/// the same tiles for the same moves.
+///
+/// # Arguments
+///
+/// * `day` - The day, as counted by `day_number`.
+pub fn daily_seed(day: u64) -> u64 {
+    // SplitMix64, so that neighbouring days get unrelated seeds on every platform and version
+    let mut z = day.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Finds which day's challenge a game is, if any. A challenge started just before midnight
+/// still counts for the day it was started.
+///
+/// # Arguments
+///
+/// * `seed` - The seed of the game.
+/// * `today` - The current day, as counted by `day_number`.
+///
+/// # Returns
+///
+/// The day of the challenge, or `None` if the seed isn't the challenge of today or yesterday.
+pub fn daily_challenge_day(seed: u64, today: u64) -> Option<u64> {
+    [today, today.saturating_sub(1)]
+        .into_iter()
+        .find(|&day| daily_seed(day) == seed)
+}
+
+/// Gets the board size of the games kept before the board size was, which were all 4x4.
+fn usual_board_size() -> usize {
+    4
+}
+
+/// Adds the change one instance made to a count on top of the count in the file, which may
+/// include another instance's changes.
+///
+/// # Arguments
+///
+/// * `on_disk` - The count in the file now.
+/// * `base` - The count when this instance read it.
+/// * `ours` - The count in this instance.
+fn added<T>(on_disk: T, base: T, ours: T) -> T
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+{
+    if ours > base {
+        on_disk + (ours - base)
+    } else {
+        on_disk
+    }
+}
+
+/// Gets the day a moment falls on in the player's time zone, counted in whole days since the
+/// Unix epoch, so that a new day starts at local midnight.
+///
+/// # Arguments
+///
+/// * `timestamp` - The moment, in seconds since the Unix epoch.
+pub fn day_number(timestamp: u64) -> u64 {
+    // the offset at that moment rather than now, so days before a daylight saving change stay put
+    let offset = Local
+        .timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map_or(0, |time| time.offset().fix().local_minus_utc());
+    day_number_with_offset(timestamp, offset)
+}
+
+/// Gets the day a moment falls on in a time zone that is `offset` seconds ahead of UTC.
+fn day_number_with_offset(timestamp: u64, offset: i32) -> u64 {
+    (timestamp as i64 + offset as i64).max(0) as u64 / 86_400
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::board::Direction;
+    use crate::game::GameEvent;
+
+    fn moves_at(times: &[u64]) -> Vec<MoveRecord> {
+        times
+            .iter()
+            .map(|&played_at| MoveRecord {
+                direction: Direction::Left,
+                played_at,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn thinking_times() {
+        let moves = moves_at(&[0, 1_000, 1_500, 4_500, 5_000, 7_000]);
+        let times = ThinkingTimes::from_moves(moves).unwrap();
+
+        assert_eq!(5, times.moves);
+        assert_eq!(Duration::from_millis(1_000), times.median);
+        assert_eq!(Duration::from_millis(2_000), times.ninetieth_percentile);
+        assert_eq!(Duration::from_millis(3_000), times.longest);
+    }
+
+    #[test]
+    fn hint_adherence() {
+        let mut hints = HintAdherence::default();
+        assert_eq!(None, hints.describe());
+
+        hints.record(true, 0.0);
+        assert_eq!(
+            Some("Hints followed: 100% of 1 moves".to_string()),
+            hints.describe()
+        );
+        hints.record(false, 0.1);
+        hints.record(false, 0.02);
+        hints.record(true, 0.0);
+        assert_eq!(
+            Some("Hints followed: 50% of 4 moves, others 6.0% worse".to_string()),
+            hints.describe()
+        );
+    }
+
+    #[test]
+    fn breaks_are_not_thinking() {
+        let moves = moves_at(&[0, 2_000, 86_400_000, 86_401_000]);
+        let times = ThinkingTimes::from_moves(moves).unwrap();
+
+        assert_eq!(2, times.moves);
+        assert_eq!(Duration::from_millis(2_000), times.longest);
+    }
+
+    #[test]
+    fn too_few_moves() {
+        assert_eq!(None, ThinkingTimes::from_moves([]));
+        assert_eq!(None, ThinkingTimes::from_moves(moves_at(&[5])));
+    }
+
+    const DAY: u64 = 86_400;
+
+    #[test]
+    fn streak_counts_days_in_a_row() {
+        let mut statistics = Statistics::default();
+        for day in [1, 3, 4, 5] {
+            statistics.record_play(day, 1);
+        }
+
+        assert_eq!(3, statistics.streak(5));
+        // not played yet today, but yesterday keeps the streak going
+        assert_eq!(3, statistics.streak(6));
+        assert_eq!(0, statistics.streak(7));
+        assert_eq!(1, statistics.streak(1));
+    }
+
+    #[test]
+    fn days_start_at_local_midnight() {
+        let late_evening_utc = 10 * DAY + 23 * 3_600;
+
+        assert_eq!(10, day_number_with_offset(late_evening_utc, 0));
+        assert_eq!(11, day_number_with_offset(late_evening_utc, 2 * 3_600));
+        assert_eq!(9, day_number_with_offset(10 * DAY + 3_600, -5 * 3_600));
+    }
+
+    fn finished(score: u32, wrap_around: bool, assisted: bool, finished_at: u64) -> FinishedGame {
+        FinishedGame {
+            score,
+            highest_tile: 5,
+            wrap_around,
+            variant: Variant::Classic,
+            board_size: 4,
+            assisted,
+            adaptive: false,
+            won: false,
+            finished_at,
+        }
+    }
+
+    #[test]
+    fn games_are_counted_per_session() {
+        let mut statistics = Statistics::default();
+        statistics.record_game(finished(500, false, false, 2_000), 1_000);
+        statistics.record_game(finished(300, false, false, 3_000), 1_000);
+        assert_eq!(
+            Some(SessionSummary {
+                started_at: 1_000,
+                last_played_at: 3_000,
+                games_played: 2,
+                best_score: 500,
+                assisted_games: 0,
+            }),
+            statistics.last_session
+        );
+
+        statistics.record_game(finished(100, false, false, 9_500), 9_000);
+        assert_eq!(3, statistics.games_played);
+        assert_eq!(500, statistics.best_score);
+        assert_eq!(1, statistics.last_session.unwrap().games_played);
+        assert_eq!(100, statistics.last_session.unwrap().best_score);
+    }
+
+    fn won_game() -> Game {
+        Game::from_board(Board::from_notation("1024,1024,./.,.,./.,.,.").unwrap(), 0)
+            .unwrap()
+            .with_assisted(false)
+            .handle_event(GameEvent::SwipeLeft)
+            .unwrap()
+            .game
+    }
+
+    #[test]
+    fn won_games_are_counted_once() {
+        let mut statistics = Statistics::default();
+        let mut game = won_game();
+        assert!(game.has_won());
+
+        assert_eq!(
+            Some(true),
+            statistics.record_finished_game(&mut game, 1_000, 2_000)
+        );
+        // playing on after leaving it and losing later doesn't count it again
+        let mut game = game.handle_event(GameEvent::KeepPlaying).unwrap().game;
+        assert_eq!(
+            None,
+            statistics.record_finished_game(&mut game, 1_000, 3_000)
+        );
+        assert_eq!(
+            (1, 1),
+            (statistics.lifetime.games, statistics.lifetime.wins)
+        );
+        assert_eq!(1, statistics.games_played);
+        assert_eq!(1, statistics.high_scores.len());
+    }
+
+    #[test]
+    fn undoing_a_lost_game_doesnt_count_it_again() {
+        let mut statistics = Statistics::default();
+        // whichever tile comes in the corner, nothing can merge
+        let game = Game::from_board(Board::from_notation("8,16/.,32").unwrap(), 0).unwrap();
+        let mut game = game.handle_event(GameEvent::SwipeLeft).unwrap().game;
+        assert!(game.is_game_over());
+        assert_eq!(
+            Some(false),
+            statistics.record_finished_game(&mut game, 1_000, 2_000)
+        );
+
+        let game = game.handle_event(GameEvent::Undo).unwrap().game;
+        let mut game = game.handle_event(GameEvent::SwipeLeft).unwrap().game;
+        assert!(game.is_game_over());
+        assert_eq!(
+            None,
+            statistics.record_finished_game(&mut game, 1_000, 3_000)
+        );
+        assert_eq!(1, statistics.lifetime.games);
+        assert_eq!(1, statistics.high_scores.len());
+    }
+
+    #[test]
+    fn wrap_around_games_have_their_own_best() {
+        let mut statistics = Statistics::default();
+        statistics.record_game(finished(500, true, false, 2_000), 1_000);
+        statistics.record_game(finished(300, false, false, 3_000), 1_000);
+
+        assert_eq!(300, statistics.best_score);
+        assert_eq!(500, statistics.best_wrap_around_score);
+        assert_eq!(500, statistics.last_session.unwrap().best_score);
+        assert_eq!(300, statistics.best_score_for(ScoreKind::default()));
+        let wrap_around = ScoreKind {
+            wrap_around: true,
+            ..ScoreKind::default()
+        };
+        assert_eq!(500, statistics.best_score_for(wrap_around));
+    }
+
+    #[test]
+    fn other_variants_and_board_sizes_have_their_own_bests() {
+        let fibonacci = FinishedGame {
+            variant: Variant::Fibonacci,
+            ..finished(900, false, false, 3_000)
+        };
+        let big_board = FinishedGame {
+            board_size: 5,
+            ..finished(800, false, false, 4_000)
+        };
+        let mut statistics = Statistics::default();
+        statistics.record_game(fibonacci, 1_000);
+        statistics.record_game(big_board, 1_000);
+        statistics.record_game(finished(300, false, false, 5_000), 1_000);
+
+        assert_eq!(300, statistics.best_score);
+        assert_eq!(900, statistics.best_score_for(fibonacci.kind()));
+        assert_eq!(800, statistics.best_score_for(big_board.kind()));
+        assert_eq!(
+            vec![finished(300, false, false, 5_000)],
+            statistics.high_scores(ScoreKind::default(), true)
+        );
+        assert_eq!(
+            vec![fibonacci],
+            statistics.high_scores(fibonacci.kind(), true)
+        );
+        assert_eq!(
+            vec![ScoreKind::default(), big_board.kind(), fibonacci.kind()],
+            statistics.high_score_kinds()
+        );
+        assert_eq!(
+            "Best Fibonacci 4x4 Score",
+            fibonacci.kind().best_score_label()
+        );
+
+        let mut other = Statistics::default();
+        other.record_game(
+            FinishedGame {
+                variant: Variant::Fibonacci,
+                ..finished(1_000, false, false, 6_000)
+            },
+            2_000,
+        );
+        let merged = statistics.merged_into(other.into_saved());
+        assert_eq!(1_000, merged.best_score_for(fibonacci.kind()));
+        assert_eq!(800, merged.best_score_for(big_board.kind()));
+    }
+
+    #[test]
+    fn assisted_games_are_kept_off_the_best_scores() {
+        let mut statistics = Statistics::default();
+        statistics.record_game(finished(300, false, false, 2_000), 1_000);
+        statistics.record_game(finished(900, false, true, 3_000), 1_000);
+
+        assert_eq!(2, statistics.games_played);
+        assert_eq!(300, statistics.best_score);
+        let session = statistics.last_session.unwrap();
+        assert_eq!(300, session.best_score);
+        assert_eq!(1, session.assisted_games);
+    }
+
+    #[test]
+    fn adaptive_games_are_kept_off_the_best_scores() {
+        let mut statistics = Statistics::default();
+        statistics.record_game(finished(300, false, false, 2_000), 1_000);
+        let adaptive = FinishedGame {
+            adaptive: true,
+            ..finished(900, false, false, 3_000)
+        };
+        statistics.record_game(adaptive, 1_000);
+
+        assert_eq!(300, statistics.best_score);
+        assert_eq!(
+            vec![finished(300, false, false, 2_000)],
+            statistics.high_scores(ScoreKind::default(), false)
+        );
+        assert_eq!(
+            adaptive,
+            statistics.high_scores(ScoreKind::default(), true)[0]
+        );
+    }
+
+    #[test]
+    fn lifetime_totals_add_up_every_game() {
+        let mut statistics = Statistics::default();
+        assert_eq!(None, statistics.lifetime.win_rate());
+        assert_eq!(None, statistics.lifetime.average_score());
+
+        statistics.record_game(
+            FinishedGame {
+                won: true,
+                highest_tile: 11,
+                ..finished(20_000, false, false, 2_000)
+            },
+            1_000,
+        );
+        statistics.record_game(finished(1_000, false, true, 3_000), 1_000);
+        statistics.record_game(finished(600, true, false, 4_000), 1_000);
+        statistics.record_game(
+            FinishedGame {
+                variant: Variant::Fibonacci,
+                ..finished(400, false, false, 5_000)
+            },
+            1_000,
+        );
+
+        let lifetime = &statistics.lifetime;
+        assert_eq!(4, lifetime.games);
+        assert_eq!(Some(25), lifetime.win_rate());
+        assert_eq!(Some(5_500), lifetime.average_score());
+        assert_eq!(BTreeMap::from([(5, 2), (11, 1)]), lifetime.best_tiles);
+    }
+
+    #[test]
+    fn saving_merges_what_another_instance_saved() {
+        let mut before = Statistics::default();
+        before.record_game(finished(100, false, false, 1_000), 500);
+        let before = before.into_saved();
+
+        // two instances start from the same file, and each finishes a game
+        let mut first = before.clone();
+        first.record_game(finished(700, false, false, 2_000), 1_500);
+        let on_disk = first.merged_into(before.clone());
+        let mut second = before.clone();
+        second.record_game(finished(300, true, false, 3_000), 2_500);
+        second.hints.record(true, 0.0);
+        let merged = second.merged_into(on_disk);
+
+        assert_eq!(3, merged.games_played);
+        assert_eq!(3, merged.lifetime.games);
+        assert_eq!(1_100, merged.lifetime.total_score);
+        assert_eq!(BTreeMap::from([(5, 3)]), merged.lifetime.best_tiles);
+        assert_eq!(700, merged.best_score);
+        assert_eq!(300, merged.best_wrap_around_score);
+        assert_eq!(1, merged.hints.moves);
+        assert_eq!(
+            vec![700, 300, 100],
+            merged
+                .high_scores
+                .iter()
+                .map(|game| game.score)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2_500, merged.last_session.unwrap().started_at);
+        assert_eq!(3, merged.today.unwrap().games_finished);
+
+        // merging again without new changes changes nothing
+        let merged = merged.into_saved();
+        assert_eq!(merged, merged.merged_into(merged.clone()).into_saved());
+    }
+
+    #[test]
+    fn high_scores_keep_the_best_of_each_kind() {
+        let mut statistics = Statistics::default();
+        for score in 1..=12 {
+            statistics.record_game(finished(score * 100, false, true, 1_000), 1_000);
+        }
+        statistics.record_game(finished(50, false, false, 2_000), 1_000);
+
+        let all = statistics.high_scores(ScoreKind::default(), true);
+        assert_eq!(10, all.len());
+        assert_eq!(1_200, all[0].score);
+        assert!(all.iter().all(|game| game.assisted));
+        assert_eq!(
+            vec![finished(50, false, false, 2_000)],
+            statistics.high_scores(ScoreKind::default(), false)
+        );
+        assert_eq!(
+            11,
+            statistics.high_scores.len(),
+            "the unaided game was kept too"
+        );
+    }
+
+    #[test]
+    fn session_best_starts_over_each_session() {
+        let mut statistics = Statistics::default();
+        assert_eq!(0, statistics.session_best(1_000));
+
+        statistics.record_game(finished(500, false, false, 2_000), 1_000);
+        statistics.record_game(finished(300, false, false, 3_000), 1_000);
+        assert_eq!(500, statistics.session_best(1_000));
+        assert_eq!(0, statistics.session_best(9_000));
+        statistics.record_game(finished(100, false, false, 9_500), 9_000);
+        assert_eq!(100, statistics.session_best(9_000));
+        assert_eq!(500, statistics.best_score);
+    }
+
+    #[test]
+    fn daily_goal_is_met_once_reached() {
+        // the goal on day 2 is to reach 512
+        let day = 2;
+        assert_eq!(DailyGoal::ReachTile(9), DailyGoal::for_day(day));
+        let mut statistics = Statistics::default();
+
+        assert!(statistics.record_play(day, 8));
+        assert!(!statistics.record_play(day, 8));
+        assert_eq!((DailyGoal::ReachTile(9), false), statistics.daily_goal(day));
+        assert!(statistics.record_play(day, 9));
+        assert_eq!((DailyGoal::ReachTile(9), true), statistics.daily_goal(day));
+
+        // progress starts over the next day, but the record doesn't
+        statistics.record_play(day + 1, 7);
+        assert_eq!(7, statistics.today.unwrap().highest_tile);
+        assert_eq!(9, statistics.highest_tile);
+        assert!(!statistics.daily_goal(day + 3).1);
+    }
+
+    #[test]
+    fn daily_challenge_keeps_the_best_score() {
+        let day = 20_000;
+        let mut statistics = Statistics::default();
+        assert_eq!(None, statistics.daily_challenge_score(day));
+
+        statistics.record_daily_challenge(day, 1_200);
+        statistics.record_daily_challenge(day, 800);
+        assert_eq!(Some(1_200), statistics.daily_challenge_score(day));
+        assert_eq!(None, statistics.daily_challenge_score(day + 1));
+
+        assert_ne!(daily_seed(day), daily_seed(day + 1));
+        assert_eq!(Some(day), daily_challenge_day(daily_seed(day), day + 1));
+        assert_eq!(None, daily_challenge_day(daily_seed(day), day + 2));
+    }
+
+    #[test]
+    fn describes_goals() {
+        assert_eq!(
+            "Reach 1,024 today",
+            DailyGoal::ReachTile(10).describe(NumberFormat::Comma)
+        );
+        assert_eq!(
+            "Finish 3 games today",
+            DailyGoal::FinishGames(3).describe(NumberFormat::Comma)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::game::Game;
+use crate::solver;
+
+/// Something that happened to the game in progress, as told to every `StatusHook`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GameNotification {
+    /// A new game was started, or a saved one was picked up.
+    Started,
+    /// A move changed the board, scoring `points`.
+    Moved { points: u32 },
+    /// The last move was undone.
+    Undone,
+    /// The game was won or lost.
+    Ended,
+}
+
+/// A hook that follows the game in progress and adds its own segment to the status bar, e.g. a
+/// chess clock or a streak counter. Hooks are picked by name with `status_hooks` in the config
+/// file when the app starts; a fork adds its own to `by_name` without touching the interface.
+pub trait StatusHook {
+    /// Tells the hook what just happened to the game.
+    ///
+    /// # Arguments
+    ///
+    /// * `notification` - What happened.
+    fn notify(&mut self, notification: GameNotification);
+
+    /// Gets the text the hook shows on the status bar.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game in progress.
+    ///
+    /// # Returns
+    ///
+    /// The text, or `None` to show nothing for now.
+    fn segment(&self, game: &Game) -> Option<String>;
+}
+
+/// Creates a hook by the name it's given in the config file.
+///
+/// # Arguments
+///
+/// * `name` - The name of the hook, e.g. `scoring_streak`.
+///
+/// # Returns
+///
+/// The hook, or `None` if there's no hook by that name.
+pub fn by_name(name: &str) -> Option<Box<dyn StatusHook>> {
+    match name {
+        "scoring_streak" => Some(Box::new(ScoringStreak::default())),
+        "organization" => Some(Box::new(Organization)),
+        _ => None,
+    }
+}
+
+/// Counts the moves in a row that scored, starting over with a move that didn't, an undo or a
+/// new game.
+#[derive(Debug, Default)]
+pub struct ScoringStreak {
+    streak: u32,
+}
+
+impl StatusHook for ScoringStreak {
+    fn notify(&mut self, notification: GameNotification) {
+        self.streak = match notification {
+            GameNotification::Moved { points } if points > 0 => self.streak + 1,
+            GameNotification::Ended => self.streak,
+            _ => 0,
+        };
+    }
+
+    fn segment(&self, _game: &Game) -> Option<String> {
+        (self.streak > 1).then(|| format!("Streak: {}", self.streak))
+    }
+}
+
+/// Shows how well ordered the board is, from `solver::organization_percent`, so the player gets
+/// feedback on keeping the tiles sorted.
+#[derive(Debug)]
+pub struct Organization;
+
+impl StatusHook for Organization {
+    fn notify(&mut self, _notification: GameNotification) {}
+
+    fn segment(&self, game: &Game) -> Option<String> {
+        Some(format!(
+            "Order: {}%",
+            solver::organization_percent(game.board())
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+
+    #[test]
+    fn scoring_streak_counts_moves_that_scored() {
+        let game = Game::start_seeded_game(1, Rules::default()).unwrap();
+        let mut hook = by_name("scoring_streak").unwrap();
+        hook.notify(GameNotification::Started);
+        hook.notify(GameNotification::Moved { points: 4 });
+        assert_eq!(None, hook.segment(&game), "a single move isn't a streak");
+
+        hook.notify(GameNotification::Moved { points: 8 });
+        hook.notify(GameNotification::Ended);
+        assert_eq!(Some("Streak: 2".to_string()), hook.segment(&game));
+
+        hook.notify(GameNotification::Moved { points: 0 });
+        assert_eq!(None, hook.segment(&game));
+        assert!(by_name("chess_clock").is_none());
+    }
+}
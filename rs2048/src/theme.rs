@@ -0,0 +1,458 @@
+use crate::board::TileType;
+use crossterm::style::Color;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// The colours one tile is drawn in. In theme files colours are written by name, e.g.
+/// `"dark_red"`, or as `"#rrggbb"`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+pub struct TileStyle {
+    pub foreground: Color,
+    pub background: Color,
+}
+
+impl TileStyle {
+    const fn new(foreground: Color, background: Color) -> TileStyle {
+        TileStyle {
+            foreground,
+            background,
+        }
+    }
+}
+
+/// The colours of everything around the board, so that a theme can skin the whole interface,
+/// e.g. dark text for a light terminal.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+pub struct UiColors {
+    /// Menus, banners, and messages.
+    pub text: TileStyle,
+    /// The bar of controls and the score at the bottom of the game screen.
+    pub status_bar: TileStyle,
+    /// The selected option in menus and lists, and titles.
+    pub highlight: Color,
+    /// The borders of menu boxes and banners.
+    pub border: Color,
+    /// Errors and warnings.
+    pub error: Color,
+}
+
+impl UiColors {
+    /// The colours rs2048 has always used, for dark terminals.
+    const DARK: UiColors = UiColors {
+        text: TileStyle::new(Color::White, Color::Black),
+        status_bar: TileStyle::new(Color::Black, Color::White),
+        highlight: Color::Yellow,
+        border: Color::White,
+        error: Color::Red,
+    };
+}
+
+impl Default for UiColors {
+    fn default() -> Self {
+        UiColors::DARK
+    }
+}
+
+/// A row of the colour legend: the tiles from `first` to `last` that share a style.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LegendEntry {
+    pub first: TileType,
+    /// The last tile with this style, or `None` if every bigger tile has it too.
+    pub last: Option<TileType>,
+    pub style: TileStyle,
+}
+
+/// A set of colours for the tiles and the rest of the interface, selected by name with `theme`
+/// in the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    /// The style of each tile, indexed by exponent, with empty cells at index `0`. The last style
+    /// is used for every tile bigger than the list.
+    tiles: Vec<TileStyle>,
+    pub ui: UiColors,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
+
+impl Theme {
+    /// The colours rs2048 has always used.
+    pub fn classic() -> Theme {
+        let mut tiles = vec![TileStyle::new(Color::White, Color::Black)];
+        for (count, style) in [
+            (2, TileStyle::new(Color::Black, Color::White)),
+            (3, TileStyle::new(Color::Black, Color::Yellow)),
+            (3, TileStyle::new(Color::White, Color::Red)),
+            (3, TileStyle::new(Color::Black, Color::Magenta)),
+            (3, TileStyle::new(Color::Black, Color::Cyan)),
+            (1, TileStyle::new(Color::Black, Color::Green)),
+        ] {
+            tiles.extend([style].repeat(count));
+        }
+        Theme {
+            name: "classic".to_string(),
+            tiles,
+            ui: UiColors::DARK,
+        }
+    }
+
+    /// Shades of grey, for terminals with few colours or players who find the classic colours
+    /// hard to tell apart.
+    pub fn mono() -> Theme {
+        let mut tiles = vec![TileStyle::new(Color::White, Color::Black)];
+        for (count, style) in [
+            (3, TileStyle::new(Color::White, Color::DarkGrey)),
+            (3, TileStyle::new(Color::Black, Color::Grey)),
+            (1, TileStyle::new(Color::Black, Color::White)),
+        ] {
+            tiles.extend([style].repeat(count));
+        }
+        Theme {
+            name: "mono".to_string(),
+            tiles,
+            ui: UiColors::DARK,
+        }
+    }
+
+    /// Dark text and softer tiles, for terminals with a light background.
+    pub fn light() -> Theme {
+        let mut tiles = vec![TileStyle::new(Color::Black, Color::White)];
+        for (count, style) in [
+            (2, TileStyle::new(Color::Black, Color::Grey)),
+            (3, TileStyle::new(Color::Black, Color::Yellow)),
+            (3, TileStyle::new(Color::White, Color::DarkRed)),
+            (3, TileStyle::new(Color::White, Color::DarkMagenta)),
+            (3, TileStyle::new(Color::White, Color::DarkBlue)),
+            (1, TileStyle::new(Color::White, Color::DarkGreen)),
+        ] {
+            tiles.extend([style].repeat(count));
+        }
+        Theme {
+            name: "light".to_string(),
+            tiles,
+            ui: UiColors {
+                text: TileStyle::new(Color::Black, Color::White),
+                status_bar: TileStyle::new(Color::White, Color::DarkBlue),
+                highlight: Color::DarkBlue,
+                border: Color::DarkGrey,
+                error: Color::DarkRed,
+            },
+        }
+    }
+
+    /// Gets every built-in theme.
+    fn built_in() -> Vec<Theme> {
+        vec![Theme::classic(), Theme::mono(), Theme::light()]
+    }
+
+    /// Gets every theme the player can choose: the built-in ones, then the theme files in
+    /// `themes_dir` in order of their names.
+    pub fn all() -> Vec<Theme> {
+        let mut themes = Theme::built_in();
+        themes.extend(Theme::from_files());
+        themes
+    }
+
+    /// Gets where the player's own themes are kept, one TOML file per theme named after it, e.g.
+    /// `~/.config/rs2048/themes/solarized.toml` on Linux.
+    ///
+    /// # Returns
+    ///
+    /// The path of the themes directory, or `None` if the platform has no config directory.
+    pub fn themes_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rs2048").join("themes"))
+    }
+
+    /// Reads the themes in `themes_dir`. Files that can't be read or parsed, and files named after
+    /// a built-in theme, are skipped.
+    fn from_files() -> Vec<Theme> {
+        let Some(entries) = Theme::themes_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "toml")
+            })
+            .collect();
+        paths.sort();
+        let built_in = Theme::built_in();
+        paths
+            .iter()
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_str()?;
+                if built_in.iter().any(|theme| theme.name == name) {
+                    return None;
+                }
+                Theme::from_toml(name, &fs::read_to_string(path).ok()?).ok()
+            })
+            .collect()
+    }
+
+    /// Reads a theme file, e.g.
+    ///
+    /// ```toml
+    /// tiles = [
+    ///     { foreground = "white", background = "black" },
+    ///     { foreground = "black", background = "#eee4da" },
+    /// ]
+    /// ui = { text = { foreground = "white", background = "black" }, status_bar = ..., highlight = "yellow", border = "white", error = "red" }
+    /// ```
+    ///
+    /// `tiles` lists the style of empty cells followed by the style of each tile from 2 upwards,
+    /// and the last style is used for every bigger tile. `ui` can be left out to keep the colours
+    /// of the classic theme.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the theme.
+    /// * `contents` - The contents of the file.
+    ///
+    /// # Returns
+    ///
+    /// The `Theme`, or an error message if the file isn't a valid theme.
+    pub fn from_toml(name: &str, contents: &str) -> Result<Theme, String> {
+        #[derive(Deserialize)]
+        struct ThemeFile {
+            tiles: Vec<TileStyle>,
+            #[serde(default)]
+            ui: UiColors,
+        }
+
+        let file: ThemeFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+        if file.tiles.len() < 2 {
+            return Err("tiles needs a style for empty cells and at least one tile".to_string());
+        }
+        Ok(Theme {
+            name: name.to_string(),
+            tiles: file.tiles,
+            ui: file.ui,
+        })
+    }
+
+    /// Looks up a theme by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the theme.
+    ///
+    /// # Returns
+    ///
+    /// The matching `Theme`, or `None` if there isn't one.
+    pub fn by_name(name: &str) -> Option<Theme> {
+        Theme::all().into_iter().find(|theme| theme.name == name)
+    }
+
+    /// Gets the colours a tile is drawn in.
+    ///
+    /// # Arguments
+    ///
+    /// * `tile` - The tile to get the colours of, or `0` for an empty cell.
+    ///
+    /// # Returns
+    ///
+    /// The `TileStyle` of the tile.
+    pub fn tile_style(&self, tile: TileType) -> TileStyle {
+        let index = (tile as usize).min(self.tiles.len() - 1);
+        self.tiles[index]
+    }
+
+    /// Makes a darker copy of the theme, e.g. for a board shown behind a menu.
+    ///
+    /// # Returns
+    ///
+    /// A `Theme` with the same name whose tile colours are all darker, or unchanged if they're
+    /// already as dark as they go.
+    pub fn dimmed(&self) -> Theme {
+        Theme {
+            name: self.name.clone(),
+            ui: self.ui,
+            tiles: self
+                .tiles
+                .iter()
+                .map(|style| TileStyle::new(dim(style.foreground), dim(style.background)))
+                .collect(),
+        }
+    }
+
+    /// Groups the tiles that share a style, for showing the player which colour means what.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of `LegendEntry`s in increasing tile order, not including empty cells. The last
+    /// entry is open-ended.
+    pub fn legend(&self) -> Vec<LegendEntry> {
+        let mut entries: Vec<LegendEntry> = Vec::new();
+        for (tile, &style) in self.tiles.iter().enumerate().skip(1) {
+            let tile = tile as TileType;
+            match entries.last_mut() {
+                Some(entry) if entry.style == style => entry.last = Some(tile),
+                _ => entries.push(LegendEntry {
+                    first: tile,
+                    last: Some(tile),
+                    style,
+                }),
+            }
+        }
+        if let Some(entry) = entries.last_mut() {
+            entry.last = None;
+        }
+        entries
+    }
+}
+
+/// Gets a darker version of a colour.
+fn dim(color: Color) -> Color {
+    match color {
+        Color::White => Color::Grey,
+        Color::Grey => Color::DarkGrey,
+        Color::Red => Color::DarkRed,
+        Color::Green => Color::DarkGreen,
+        Color::Yellow => Color::DarkYellow,
+        Color::Blue => Color::DarkBlue,
+        Color::Magenta => Color::DarkMagenta,
+        Color::Cyan => Color::DarkCyan,
+        Color::Rgb { r, g, b } => Color::Rgb {
+            r: r / 2,
+            g: g / 2,
+            b: b / 2,
+        },
+        color => color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_names_round_trip() {
+        for theme in Theme::built_in() {
+            assert_eq!(Some(theme.clone()), Theme::by_name(&theme.name));
+        }
+        assert_eq!(None, Theme::by_name("neon"));
+    }
+
+    #[test]
+    fn big_tiles_use_the_last_style() {
+        let theme = Theme::classic();
+        assert_eq!(theme.tile_style(15), theme.tile_style(TileType::MAX));
+    }
+
+    #[test]
+    fn classic_legend() {
+        let legend = Theme::classic().legend();
+        let ranges: Vec<(TileType, Option<TileType>)> = legend
+            .iter()
+            .map(|entry| (entry.first, entry.last))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (1, Some(2)),
+                (3, Some(5)),
+                (6, Some(8)),
+                (9, Some(11)),
+                (12, Some(14)),
+                (15, None)
+            ],
+            ranges
+        );
+        assert_eq!(Color::Yellow, legend[1].style.background);
+    }
+
+    #[test]
+    fn every_theme_has_a_legend() {
+        for theme in Theme::built_in() {
+            let legend = theme.legend();
+            assert_eq!(1, legend[0].first);
+            assert_eq!(None, legend.last().unwrap().last);
+        }
+    }
+
+    #[test]
+    fn interface_text_is_readable() {
+        for theme in Theme::built_in() {
+            let ui = theme.ui;
+            assert_ne!(ui.text.foreground, ui.text.background, "{}", theme.name);
+            assert_ne!(
+                ui.status_bar.foreground, ui.status_bar.background,
+                "{}",
+                theme.name
+            );
+            assert_ne!(ui.highlight, ui.text.background, "{}", theme.name);
+            assert_ne!(ui.error, ui.text.background, "{}", theme.name);
+        }
+    }
+
+    #[test]
+    fn reads_theme_files() {
+        let theme = Theme::from_toml(
+            "paper",
+            r##"
+            tiles = [
+                { foreground = "black", background = "white" },
+                { foreground = "black", background = "#eee4da" },
+                { foreground = "white", background = "dark_red" },
+            ]
+
+            [ui]
+            text = { foreground = "black", background = "white" }
+            status_bar = { foreground = "white", background = "dark_blue" }
+            highlight = "dark_blue"
+            border = "grey"
+            error = "dark_red"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!("paper", theme.name);
+        assert_eq!(
+            Color::Rgb {
+                r: 0xee,
+                g: 0xe4,
+                b: 0xda
+            },
+            theme.tile_style(1).background
+        );
+        assert_eq!(Color::DarkRed, theme.tile_style(9).background);
+        assert_eq!(Color::DarkBlue, theme.ui.highlight);
+    }
+
+    #[test]
+    fn theme_files_need_tiles() {
+        let ui_only = Theme::from_toml("empty", "tiles = []");
+        assert!(ui_only.is_err());
+
+        let theme = Theme::from_toml(
+            "plain",
+            r#"tiles = [{ foreground = "white", background = "black" }, { foreground = "black", background = "white" }]"#,
+        )
+        .unwrap();
+        assert_eq!(Theme::classic().ui, theme.ui);
+        assert!(Theme::from_toml(
+            "typo",
+            r#"tiles = [{ foreground = "whte", background = "black" }]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn dimmed_theme_is_darker() {
+        let theme = Theme::classic();
+        let dimmed = theme.dimmed();
+
+        assert_eq!(Color::DarkYellow, dimmed.tile_style(3).background);
+        assert_eq!(Color::Grey, dimmed.tile_style(0).foreground);
+        assert_eq!(Color::Black, dimmed.tile_style(0).background);
+        assert_eq!(theme.legend().len(), dimmed.legend().len());
+        assert_eq!(theme.ui, dimmed.ui, "only the board is dimmed");
+    }
+}
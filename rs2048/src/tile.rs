@@ -0,0 +1,183 @@
+use crate::board::TileType;
+use crate::number_format::NumberFormat;
+
+/// The suffixes `short_value` shortens big values with, each a thousand times the one before.
+const SHORT_SUFFIXES: [&str; 6] = ["k", "M", "G", "T", "P", "E"];
+
+/// Gets the value of a tile, which boards store as an exponent.
+///
+/// # Arguments
+///
+/// * `tile` - The exponent of the tile, e.g. `11` for 2048, or `0` for an empty cell.
+///
+/// # Returns
+///
+/// The value of the tile, `0` for an empty cell, or `u64::MAX` if it's too big for a `u64`.
+pub fn value(tile: TileType) -> u64 {
+    match tile {
+        0 => 0,
+        _ => 1u64.checked_shl(tile as u32).unwrap_or(u64::MAX),
+    }
+}
+
+/// Gets the value of a tile in the Fibonacci variant, where tile `n` holds the `n`th number of the
+/// sequence 1, 2, 3, 5, 8...
+///
+/// # Arguments
+///
+/// * `tile` - The tile, e.g. `17` for 2584, or `0` for an empty cell.
+///
+/// # Returns
+///
+/// The value of the tile, `0` for an empty cell, or `u64::MAX` if it's too big for a `u64`.
+pub fn fibonacci_value(tile: TileType) -> u64 {
+    if tile == 0 {
+        return 0;
+    }
+    let (mut value, mut next) = (1u64, 2u64);
+    for _ in 1..tile {
+        (value, next) = (next, value.saturating_add(next));
+    }
+    value
+}
+
+/// Gets the value of a tile in the Threes variant, where tiles 1 and 2 hold a 1 and a 2, and
+/// every tile after them doubles the one before from 3: 3, 6, 12, 24...
+///
+/// # Arguments
+///
+/// * `tile` - The tile, e.g. `14` for 6144, or `0` for an empty cell.
+///
+/// # Returns
+///
+/// The value of the tile, `0` for an empty cell, or `u64::MAX` if it's too big for a `u64`.
+pub fn threes_value(tile: TileType) -> u64 {
+    match tile {
+        0..=2 => tile as u64,
+        _ => 1u64
+            .checked_shl(tile as u32 - 3)
+            .and_then(|power| power.checked_mul(3))
+            .unwrap_or(u64::MAX),
+    }
+}
+
+/// Gets the value of a tile in the powers of 3 variant, where tile `n` holds 3 to the power of `n`.
+///
+/// # Arguments
+///
+/// * `tile` - The tile, e.g. `7` for 2187, or `0` for an empty cell.
+///
+/// # Returns
+///
+/// The value of the tile, `0` for an empty cell, or `u64::MAX` if it's too big for a `u64`.
+pub fn power_of_three_value(tile: TileType) -> u64 {
+    match tile {
+        0 => 0,
+        _ => 3u64.checked_pow(tile as u32).unwrap_or(u64::MAX),
+    }
+}
+
+/// Writes the value of a tile for the player, e.g. "2,048".
+///
+/// # Arguments
+///
+/// * `tile` - The exponent of the tile.
+/// * `number_format` - How to group the digits of big values.
+///
+/// # Returns
+///
+/// The value of the tile, or an empty string for an empty cell.
+pub fn display_value(tile: TileType, number_format: NumberFormat) -> String {
+    match tile {
+        0 => String::new(),
+        _ => number_format.format(value(tile)),
+    }
+}
+
+/// Writes the value of a tile in at most four characters, for when the full value doesn't fit,
+/// e.g. "512", "8192", "16k" or "1M". The value is rounded down.
+///
+/// # Arguments
+///
+/// * `tile` - The exponent of the tile.
+///
+/// # Returns
+///
+/// The shortened value of the tile, or an empty string for an empty cell.
+pub fn short_value(tile: TileType) -> String {
+    match tile {
+        0 => String::new(),
+        _ => shorten(value(tile)),
+    }
+}
+
+/// Writes a value in at most four characters, like `short_value`.
+pub fn shorten(mut value: u64) -> String {
+    if value < 10_000 {
+        return value.to_string();
+    }
+    let mut suffixes = SHORT_SUFFIXES.iter();
+    let mut suffix = "";
+    while value >= 1000 {
+        value /= 1000;
+        suffix = suffixes.next().copied().unwrap_or(suffix);
+    }
+    format!("{}{}", value, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_of_tiles() {
+        assert_eq!(0, value(0));
+        assert_eq!(2, value(1));
+        assert_eq!(2048, value(11));
+        assert_eq!(1 << 63, value(63));
+        assert_eq!(u64::MAX, value(64));
+        assert_eq!(u64::MAX, value(TileType::MAX));
+    }
+
+    #[test]
+    fn fibonacci_values_of_tiles() {
+        let values: Vec<u64> = (0..=7).map(fibonacci_value).collect();
+        assert_eq!(vec![0, 1, 2, 3, 5, 8, 13, 21], values);
+        assert_eq!(2584, fibonacci_value(17));
+        assert_eq!(u64::MAX, fibonacci_value(TileType::MAX));
+    }
+
+    #[test]
+    fn threes_values_of_tiles() {
+        let values: Vec<u64> = (0..=6).map(threes_value).collect();
+        assert_eq!(vec![0, 1, 2, 3, 6, 12, 24], values);
+        assert_eq!(6144, threes_value(14));
+        assert_eq!(u64::MAX, threes_value(TileType::MAX));
+    }
+
+    #[test]
+    fn powers_of_three_values_of_tiles() {
+        let values: Vec<u64> = (0..=4).map(power_of_three_value).collect();
+        assert_eq!(vec![0, 3, 9, 27, 81], values);
+        assert_eq!(2187, power_of_three_value(7));
+        assert_eq!(u64::MAX, power_of_three_value(TileType::MAX));
+    }
+
+    #[test]
+    fn displays_values() {
+        assert_eq!("", display_value(0, NumberFormat::Comma));
+        assert_eq!("2,048", display_value(11, NumberFormat::Comma));
+        assert_eq!("131072", display_value(17, NumberFormat::None));
+    }
+
+    #[test]
+    fn shortens_big_values() {
+        assert_eq!("", short_value(0));
+        assert_eq!("8192", short_value(13));
+        assert_eq!("16k", short_value(14));
+        assert_eq!("131k", short_value(17));
+        assert_eq!("1M", short_value(20));
+        assert_eq!("9E", short_value(63));
+        assert_eq!("18E", short_value(100));
+    }
+}
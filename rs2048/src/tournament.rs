@@ -0,0 +1,344 @@
+use crate::ai::{play_game, GameResult, Strategy, StrategyKind};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// How tournament results are written out.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OutputFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl OutputFormat {
+    /// Looks up an output format by its command line name (`csv`, `json`, or `markdown`).
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "csv" => Some(OutputFormat::Csv),
+            "json" => Some(OutputFormat::Json),
+            "markdown" | "md" => Some(OutputFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// Summary statistics for one measurement (e.g. score) over all games played by a strategy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    pub mean: f64,
+    pub std_dev: f64,
+    /// The lower bound of the 95% confidence interval of the mean.
+    pub ci95_low: f64,
+    /// The upper bound of the 95% confidence interval of the mean.
+    pub ci95_high: f64,
+    pub min: f64,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    /// Computes summary statistics for a set of samples.
+    ///
+    /// The confidence interval uses the normal approximation, which is accurate for the hundreds
+    /// of games a tournament usually plays. Percentiles use the nearest-rank method.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The values to summarize. Must not be empty.
+    ///
+    /// # Returns
+    ///
+    /// The `Summary` of the samples.
+    pub fn from_samples(samples: &[f64]) -> Summary {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let count = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / count;
+        let std_dev = if sorted.len() > 1 {
+            (sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        let margin = 1.96 * std_dev / count.sqrt();
+
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * count).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+
+        Summary {
+            mean,
+            std_dev,
+            ci95_low: mean - margin,
+            ci95_high: mean + margin,
+            min: sorted[0],
+            p10: percentile(10.0),
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// The aggregated results of every game one strategy played in a tournament.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategyResults {
+    pub strategy: String,
+    pub games: usize,
+    pub score: Summary,
+    /// Summarizes the value (not the exponent) of the biggest tile reached.
+    pub highest_tile: Summary,
+    pub moves: Summary,
+}
+
+impl StrategyResults {
+    /// Aggregates the games played by a strategy.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - The strategy that played the games.
+    /// * `results` - The result of every game. Must not be empty.
+    pub fn from_results(strategy: StrategyKind, results: &[GameResult]) -> StrategyResults {
+        let collect = |f: fn(&GameResult) -> f64| results.iter().map(f).collect::<Vec<f64>>();
+
+        StrategyResults {
+            strategy: strategy.name().to_string(),
+            games: results.len(),
+            score: Summary::from_samples(&collect(|r| r.score as f64)),
+            highest_tile: Summary::from_samples(&collect(|r| 2f64.powi(r.highest_tile as i32))),
+            moves: Summary::from_samples(&collect(|r| r.moves as f64)),
+        }
+    }
+
+    fn metrics(&self) -> [(&'static str, &Summary); 3] {
+        [
+            ("score", &self.score),
+            ("highest_tile", &self.highest_tile),
+            ("moves", &self.moves),
+        ]
+    }
+}
+
+/// Plays every strategy against the same number of games, spreading the games over several
+/// threads.
+///
+/// # Arguments
+///
+/// * `strategies` - The strategies to compare.
+/// * `games_per_strategy` - How many games each strategy plays. Must be at least 1.
+/// * `threads` - How many games are played at once.
+///
+/// # Returns
+///
+/// The `StrategyResults` of each strategy, in the same order as `strategies`, or an error message
+/// if a game couldn't be played.
+pub fn run(
+    strategies: &[StrategyKind],
+    games_per_strategy: usize,
+    threads: usize,
+) -> Result<Vec<StrategyResults>, String> {
+    let results = play_games(strategies.len() * games_per_strategy, threads, |index| {
+        strategies[index / games_per_strategy].create()
+    })?;
+
+    Ok(strategies
+        .iter()
+        .zip(results.chunks(games_per_strategy))
+        .map(|(&strategy, games)| StrategyResults::from_results(strategy, games))
+        .collect())
+}
+
+/// Plays a number of games to the end, spreading them over several threads.
+///
+/// # Arguments
+///
+/// * `games` - How many games to play.
+/// * `threads` - How many games are played at once.
+/// * `create_strategy` - Creates the strategy that plays the game with the given index.
+///
+/// # Returns
+///
+/// The `GameResult` of each game, in index order, or an error message if a game couldn't be
+/// played.
+pub fn play_games<F>(
+    games: usize,
+    threads: usize,
+    create_strategy: F,
+) -> Result<Vec<GameResult>, String>
+where
+    F: Fn(usize) -> Box<dyn Strategy> + Sync,
+{
+    // every game is a job, handed out to whichever thread is free next
+    let next_job = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<GameResult>>> = Mutex::new(vec![None; games]);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| -> Result<(), String> {
+                    loop {
+                        let index = next_job.fetch_add(1, Ordering::Relaxed);
+                        if index >= games {
+                            return Ok(());
+                        }
+                        let result = play_game(create_strategy(index).as_mut())
+                            .map_err(|e| format!("Game failed: {:?}", e))?;
+                        results.lock().unwrap()[index] = Some(result);
+                    }
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .try_for_each(|worker| worker.join().expect("tournament thread panicked"))
+    })?;
+
+    Ok(results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every job was played"))
+        .collect())
+}
+
+/// Writes tournament results in the requested format, with one row per strategy and metric for
+/// the tabular formats.
+///
+/// # Arguments
+///
+/// * `results` - The results to write.
+/// * `format` - The format to write them in.
+///
+/// # Returns
+///
+/// A `String` containing the formatted results.
+pub fn format_results(results: &[StrategyResults], format: OutputFormat) -> String {
+    const COLUMNS: [&str; 12] = [
+        "strategy",
+        "games",
+        "metric",
+        "mean",
+        "std_dev",
+        "ci95_low",
+        "ci95_high",
+        "min",
+        "p10",
+        "p50",
+        "p90",
+        "max",
+    ];
+
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .flat_map(|result| {
+            result.metrics().map(|(metric, summary)| {
+                let mut row = vec![
+                    result.strategy.clone(),
+                    result.games.to_string(),
+                    metric.to_string(),
+                ];
+                row.extend(
+                    [
+                        summary.mean,
+                        summary.std_dev,
+                        summary.ci95_low,
+                        summary.ci95_high,
+                        summary.min,
+                        summary.p10,
+                        summary.p50,
+                        summary.p90,
+                        summary.max,
+                    ]
+                    .map(|value| format!("{:.1}", value)),
+                );
+                row
+            })
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Csv => {
+            let mut output = COLUMNS.join(",") + "\n";
+            for row in rows {
+                output += &(row.join(",") + "\n");
+            }
+            output
+        }
+        OutputFormat::Json => serde_json::to_string_pretty(results).unwrap() + "\n",
+        OutputFormat::Markdown => {
+            let mut output = format!("| {} |\n", COLUMNS.join(" | "));
+            output += &format!("|{}\n", "---|".repeat(COLUMNS.len()));
+            for row in rows {
+                output += &format!("| {} |\n", row.join(" | "));
+            }
+            output
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_of_samples() {
+        let samples: Vec<f64> = (1..=10).map(|x| x as f64).collect();
+        let summary = Summary::from_samples(&samples);
+
+        assert_eq!(5.5, summary.mean);
+        assert_eq!(1.0, summary.min);
+        assert_eq!(1.0, summary.p10);
+        assert_eq!(5.0, summary.p50);
+        assert_eq!(9.0, summary.p90);
+        assert_eq!(10.0, summary.max);
+        assert!(summary.ci95_low < summary.mean && summary.mean < summary.ci95_high);
+    }
+
+    #[test]
+    fn summary_of_single_sample() {
+        let summary = Summary::from_samples(&[42.0]);
+
+        assert_eq!(42.0, summary.mean);
+        assert_eq!(0.0, summary.std_dev);
+        assert_eq!(42.0, summary.ci95_low);
+        assert_eq!(42.0, summary.p90);
+    }
+
+    #[test]
+    fn run_small_tournament() {
+        let results = run(&[StrategyKind::Random, StrategyKind::Corner], 3, 2).unwrap();
+
+        assert_eq!(2, results.len());
+        assert_eq!("random", results[0].strategy);
+        assert_eq!("corner", results[1].strategy);
+        assert!(results.iter().all(|result| result.games == 3));
+    }
+
+    #[test]
+    fn csv_has_a_row_per_strategy_and_metric() {
+        let results = run(&[StrategyKind::Random], 1, 1).unwrap();
+        let csv = format_results(&results, OutputFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(4, lines.len());
+        assert!(lines[0].starts_with("strategy,games,metric,mean"));
+        assert!(lines[1].starts_with("random,1,score,"));
+    }
+
+    #[test]
+    fn markdown_table_is_well_formed() {
+        let results = run(&[StrategyKind::Greedy], 1, 1).unwrap();
+        let markdown = format_results(&results, OutputFormat::Markdown);
+
+        assert!(markdown
+            .lines()
+            .all(|line| line.starts_with('|') && line.ends_with('|')));
+        assert_eq!(5, markdown.lines().count());
+    }
+}
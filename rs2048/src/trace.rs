@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+/// Starts logging spans, each with how long it took when it closes, so that a slow frame can be
+/// pinned on input handling, moves, rendering or the solver's search. They're logged to
+/// `trace.log` in the data directory, since the game draws over the whole terminal.
+///
+/// # Arguments
+///
+/// * `verbosity` - How much to log, from the `-v` flags: `1` for the frames of the event loop,
+///   `2` for moves and the solver's searches too, and `3` or more for every merge.
+///
+/// # Returns
+///
+/// The file the spans are logged to, or why they can't be.
+#[cfg(feature = "trace")]
+pub fn start(verbosity: u8) -> Result<PathBuf, String> {
+    use std::fs::{self, File};
+    use std::sync::Mutex;
+    use tracing::Level;
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    let dir = crate::persistence::data_dir().ok_or("there's no data directory")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join("trace.log");
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let level = match verbosity {
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .try_init()
+        .map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Starts logging spans when built with the `trace` feature. This build doesn't have it.
+#[cfg(not(feature = "trace"))]
+pub fn start(_verbosity: u8) -> Result<PathBuf, String> {
+    Err("-v needs rs2048 to be built with the `trace` feature".to_string())
+}
@@ -0,0 +1,181 @@
+use crate::board::Direction;
+use serde::{Deserialize, Serialize};
+
+/// How the board is turned and flipped on the screen, e.g. for a tall, narrow terminal pane or a
+/// mirrored display, set with `board_view` in the config file, e.g.
+/// `board_view = { rotation = 90, mirrored = true }`.
+///
+/// It sits between the game and the screen: the renderer shows the board through `apply`, and
+/// directions pressed on the screen go through `board_direction` before reaching the game, so
+/// that Up always moves the tiles up on the screen. The game itself never sees the transform.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayTransform {
+    /// How far the board is turned clockwise, in degrees: 0, 90, 180 or 270. Other values are
+    /// rounded down to one of these.
+    pub rotation: u16,
+    /// Whether the board is flipped left to right before it's turned.
+    pub mirrored: bool,
+}
+
+impl DisplayTransform {
+    /// How many quarter turns clockwise the board is turned, from 0 to 3.
+    fn quarter_turns(self) -> u16 {
+        self.rotation / 90 % 4
+    }
+
+    /// Gets the transform turned another quarter turn clockwise, wrapping around from 270° to 0°.
+    pub fn turned_clockwise(self) -> DisplayTransform {
+        DisplayTransform {
+            rotation: (self.quarter_turns() + 1) % 4 * 90,
+            ..self
+        }
+    }
+
+    /// Describes the rotation for the settings screen, e.g. "90°".
+    pub fn rotation_label(self) -> String {
+        format!("{}°", self.quarter_turns() * 90)
+    }
+
+    /// Turns and flips the rows of a board for showing them. The board itself isn't changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The rows of the board, all the same length.
+    ///
+    /// # Returns
+    ///
+    /// The rows as they are shown.
+    pub fn apply<T: Copy>(self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        let mut shown = rows.to_vec();
+        if self.mirrored {
+            shown.iter_mut().for_each(|row| row.reverse());
+        }
+        for _ in 0..self.quarter_turns() {
+            shown = rotate_clockwise(&shown);
+        }
+        shown
+    }
+
+    /// Gets the direction to swipe the board in when a direction is pressed while the board is
+    /// shown through `apply`, so that the tiles move the way the pressed direction points on the
+    /// screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `pressed` - The direction as seen on the screen.
+    ///
+    /// # Returns
+    ///
+    /// The direction on the board.
+    pub fn board_direction(self, pressed: Direction) -> Direction {
+        let mut direction = pressed;
+        for _ in 0..self.quarter_turns() {
+            direction = match direction {
+                Direction::Up => Direction::Left,
+                Direction::Left => Direction::Down,
+                Direction::Down => Direction::Right,
+                Direction::Right => Direction::Up,
+            };
+        }
+        match direction {
+            Direction::Left if self.mirrored => Direction::Right,
+            Direction::Right if self.mirrored => Direction::Left,
+            direction => direction,
+        }
+    }
+}
+
+/// Turns the rows of a board a quarter turn clockwise, so that the left column becomes the top
+/// row.
+fn rotate_clockwise<T: Copy>(rows: &[Vec<T>]) -> Vec<Vec<T>> {
+    let width = rows.first().map_or(0, Vec::len);
+    (0..width)
+        .map(|column| rows.iter().rev().map(|row| row[column]).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::merge::ClassicRule;
+
+    fn all_transforms() -> impl Iterator<Item = DisplayTransform> {
+        [false, true].into_iter().flat_map(|mirrored| {
+            [0, 90, 180, 270].map(|rotation| DisplayTransform { rotation, mirrored })
+        })
+    }
+
+    fn swipe(board: &mut Board, direction: Direction) {
+        board.merge(direction, false, &ClassicRule);
+    }
+
+    #[test]
+    fn rotates_and_mirrors() {
+        let rows = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let rotated = |rotation| DisplayTransform {
+            rotation,
+            mirrored: false,
+        };
+
+        assert_eq!(
+            vec![vec![4, 1], vec![5, 2], vec![6, 3]],
+            rotated(90).apply(&rows)
+        );
+        assert_eq!(
+            vec![vec![6, 5, 4], vec![3, 2, 1]],
+            rotated(180).apply(&rows)
+        );
+        assert_eq!(rows, rotated(360).apply(&rows));
+        assert_eq!(
+            vec![vec![3, 2, 1], vec![6, 5, 4]],
+            DisplayTransform {
+                rotation: 0,
+                mirrored: true
+            }
+            .apply(&rows)
+        );
+    }
+
+    #[test]
+    fn pressed_directions_move_tiles_that_way_on_the_screen() {
+        for transform in all_transforms() {
+            for pressed in Direction::ALL {
+                let mut board = Board::from_notation("2.../..4./.4../...8").unwrap();
+                let mut shown =
+                    Board::try_from(transform.apply(board.get_data_for_display())).unwrap();
+                swipe(&mut board, transform.board_direction(pressed));
+                swipe(&mut shown, pressed);
+
+                assert_eq!(
+                    shown.get_data_for_display(),
+                    &transform.apply(board.get_data_for_display()),
+                    "{transform:?} {pressed:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn turns_wrap_around() {
+        let transform = DisplayTransform {
+            rotation: 270,
+            mirrored: true,
+        };
+
+        assert_eq!(
+            DisplayTransform {
+                rotation: 0,
+                mirrored: true
+            },
+            transform.turned_clockwise()
+        );
+        assert_eq!(
+            "90°",
+            DisplayTransform::default()
+                .turned_clockwise()
+                .rotation_label()
+        );
+    }
+}
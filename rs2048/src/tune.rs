@@ -0,0 +1,183 @@
+use crate::ai::SolverStrategy;
+use crate::solver::{EvalWeights, Solver};
+use crate::tournament;
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+/// The range each weight is searched over, in the order monotonicity, smoothness, empty cells.
+const WEIGHT_RANGES: [RangeInclusive<f64>; 3] = [0.0..=4.0, 0.0..=1.0, 0.0..=8.0];
+
+/// How the weights to try are picked.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SearchMethod {
+    /// Tries evenly spaced values of every weight in every combination.
+    Grid,
+    /// Tries weights picked uniformly at random.
+    Random,
+}
+
+impl SearchMethod {
+    /// Looks up a search method by its command line name (`grid` or `random`).
+    pub fn from_name(name: &str) -> Option<SearchMethod> {
+        match name {
+            "grid" => Some(SearchMethod::Grid),
+            "random" => Some(SearchMethod::Random),
+            _ => None,
+        }
+    }
+}
+
+/// How well one set of weights played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub weights: EvalWeights,
+    pub mean_score: f64,
+    pub mean_moves: f64,
+    /// The mean value (not the exponent) of the biggest tile reached.
+    pub mean_highest_tile: f64,
+}
+
+/// Picks the weights to try.
+///
+/// # Arguments
+///
+/// * `method` - How to pick them.
+/// * `samples` - Roughly how many sets of weights to pick. A grid search rounds this down to the
+///   nearest cube, with at least two values per weight.
+///
+/// # Returns
+///
+/// A `Vec` of the `EvalWeights` to try.
+pub fn candidates(method: SearchMethod, samples: usize) -> Vec<EvalWeights> {
+    match method {
+        SearchMethod::Grid => {
+            let steps = ((samples as f64).cbrt().floor() as usize).max(2);
+            let values = |range: &RangeInclusive<f64>| -> Vec<f64> {
+                (0..steps)
+                    .map(|i| {
+                        range.start()
+                            + (range.end() - range.start()) * i as f64 / (steps - 1) as f64
+                    })
+                    .collect()
+            };
+            let [monotonicity, smoothness, empty_cells] = WEIGHT_RANGES.each_ref().map(values);
+
+            let mut weights = Vec::new();
+            for &monotonicity in &monotonicity {
+                for &smoothness in &smoothness {
+                    for &empty_cells in &empty_cells {
+                        weights.push(EvalWeights {
+                            monotonicity,
+                            smoothness,
+                            empty_cells,
+                        });
+                    }
+                }
+            }
+            weights
+        }
+        SearchMethod::Random => {
+            let mut rng = rand::thread_rng();
+            (0..samples)
+                .map(|_| {
+                    let [monotonicity, smoothness, empty_cells] =
+                        WEIGHT_RANGES.clone().map(|range| rng.gen_range(range));
+                    EvalWeights {
+                        monotonicity,
+                        smoothness,
+                        empty_cells,
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Plays headless games with every set of weights and ranks them.
+///
+/// # Arguments
+///
+/// * `weights` - The sets of weights to try.
+/// * `games` - How many games each set of weights plays. Must be at least 1.
+/// * `depth` - How many moves ahead the solver looks.
+/// * `threads` - How many games are played at once.
+///
+/// # Returns
+///
+/// A `Candidate` for each set of weights, best first, or an error message if a game couldn't be
+/// played. Candidates are ranked by mean score, then by mean number of moves survived.
+pub fn run(
+    weights: &[EvalWeights],
+    games: usize,
+    depth: u32,
+    threads: usize,
+) -> Result<Vec<Candidate>, String> {
+    let results = tournament::play_games(weights.len() * games, threads, |index| {
+        Box::new(SolverStrategy::new(Solver::new(
+            weights[index / games],
+            depth,
+        )))
+    })?;
+
+    let mut candidates: Vec<Candidate> = weights
+        .iter()
+        .zip(results.chunks(games))
+        .map(|(&weights, results)| {
+            let mean = |f: fn(&_) -> f64| results.iter().map(f).sum::<f64>() / games as f64;
+            Candidate {
+                weights,
+                mean_score: mean(|r| r.score as f64),
+                mean_moves: mean(|r| r.moves as f64),
+                mean_highest_tile: mean(|r| 2f64.powi(r.highest_tile as i32)),
+            }
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        b.mean_score
+            .total_cmp(&a.mean_score)
+            .then(b.mean_moves.total_cmp(&a.mean_moves))
+    });
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_rounds_down_to_a_cube() {
+        assert_eq!(27, candidates(SearchMethod::Grid, 30).len());
+        assert_eq!(8, candidates(SearchMethod::Grid, 1).len());
+    }
+
+    #[test]
+    fn grid_covers_the_ranges() {
+        let weights = candidates(SearchMethod::Grid, 8);
+
+        assert_eq!(0.0, weights[0].monotonicity);
+        assert_eq!(8.0, weights[7].empty_cells);
+        assert_eq!(4.0, weights[7].monotonicity);
+    }
+
+    #[test]
+    fn random_weights_are_in_range() {
+        let weights = candidates(SearchMethod::Random, 20);
+
+        assert_eq!(20, weights.len());
+        assert!(weights
+            .iter()
+            .all(|w| WEIGHT_RANGES[0].contains(&w.monotonicity)
+                && WEIGHT_RANGES[1].contains(&w.smoothness)
+                && WEIGHT_RANGES[2].contains(&w.empty_cells)));
+    }
+
+    #[test]
+    fn run_ranks_every_candidate() {
+        let weights = [EvalWeights::default(), EvalWeights::default()];
+        let ranked = run(&weights, 1, 1, 2).unwrap();
+
+        assert_eq!(2, ranked.len());
+        assert!(ranked[0].mean_score >= ranked[1].mean_score);
+    }
+}
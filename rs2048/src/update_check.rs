@@ -0,0 +1,125 @@
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+const CRATES_IO_URL: &str = "https://crates.io/api/v1/crates/rs2048";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug)]
+pub enum UpdateCheckError {
+    RequestFailed(String),
+    InvalidResponse(String),
+}
+
+impl Display for UpdateCheckError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateCheckError::RequestFailed(reason) => write!(f, "request failed ({})", reason),
+            UpdateCheckError::InvalidResponse(reason) => {
+                write!(f, "invalid response ({})", reason)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CratesIoResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+}
+
+#[derive(Deserialize)]
+struct CrateInfo {
+    max_stable_version: String,
+}
+
+/// Asks crates.io for the newest published release of the game.
+///
+/// This blocks until the request finishes or times out, so it should only be called in response
+/// to the user explicitly asking for it.
+///
+/// # Returns
+///
+/// - `Ok(Some(version))` if a newer release than the running one exists.
+/// - `Ok(None)` if the running version is up to date.
+/// - An `UpdateCheckError` if the request failed or the response couldn't be understood.
+pub fn check_for_updates() -> Result<Option<String>, UpdateCheckError> {
+    let response: CratesIoResponse = ureq::get(CRATES_IO_URL)
+        .timeout(Duration::from_secs(5))
+        // crates.io rejects requests without a user agent
+        .set("User-Agent", concat!("rs2048/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(|e| UpdateCheckError::RequestFailed(e.to_string()))?
+        .into_json()
+        .map_err(|e| UpdateCheckError::InvalidResponse(e.to_string()))?;
+
+    let latest = response.krate.max_stable_version;
+    if is_newer(&latest, CURRENT_VERSION)? {
+        Ok(Some(latest))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Compares two `major.minor.patch` version strings.
+///
+/// # Arguments
+///
+/// * `candidate` - The version that might be newer.
+/// * `current` - The version to compare against.
+///
+/// # Returns
+///
+/// `Ok(true)` if `candidate` is strictly newer than `current`, or an error if either string isn't
+/// a valid version.
+fn is_newer(candidate: &str, current: &str) -> Result<bool, UpdateCheckError> {
+    Ok(parse_version(candidate)? > parse_version(current)?)
+}
+
+/// Parses a `major.minor.patch` version string into its numeric parts. Pre-release and build
+/// suffixes are ignored.
+fn parse_version(version: &str) -> Result<(u64, u64, u64), UpdateCheckError> {
+    let invalid = || UpdateCheckError::InvalidResponse(format!("Invalid version: {}", version));
+
+    let core = version.split(['-', '+']).next().ok_or_else(invalid)?;
+    let parts = core
+        .split('.')
+        .map(|part| part.parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<u64>, UpdateCheckError>>()?;
+
+    match parts[..] {
+        [major, minor, patch] => Ok((major, minor, patch)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_patch_version() {
+        assert!(is_newer("0.1.1", "0.1.0").unwrap());
+    }
+
+    #[test]
+    fn same_version_is_not_newer() {
+        assert!(!is_newer("0.1.0", "0.1.0").unwrap());
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(is_newer("0.10.0", "0.9.0").unwrap());
+        assert!(!is_newer("0.9.0", "0.10.0").unwrap());
+    }
+
+    #[test]
+    fn suffixes_are_ignored() {
+        assert!(!is_newer("0.1.0-beta.1", "0.1.0").unwrap());
+    }
+
+    #[test]
+    fn invalid_version() {
+        assert!(is_newer("latest", "0.1.0").is_err());
+    }
+}
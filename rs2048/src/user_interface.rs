@@ -1,34 +1,79 @@
-use crate::board::TileType;
-use crate::game::{Game, GameError, GameEvent};
-use crate::user_interface::MainMenuOption::{LoadGame, NewGame, Quit};
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
-use crossterm::style::{Color, StyledContent, Stylize};
+use crate::achievements::Achievements;
+use crate::config::{Config, ConfigWatcher, Overrides};
+use crate::diagnostics;
+use crate::frame_pacing::FramePacer;
+use crate::game::Game;
+use crate::recovery::SnapshotRing;
+use crate::save;
+use crate::stats::Statistics;
+use crate::status_hook;
+use crate::theme::Theme;
+use crate::user_interface::borders::Borders;
+use crate::user_interface::game_screen::GameScreen;
+use crate::user_interface::main_menu::MainMenuScreen;
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crate::user_interface::splash::SplashScreen;
+use crossterm::event::Event;
 use crossterm::terminal::{Clear, ClearType};
 use crossterm::{cursor, event, queue, style, terminal, ExecutableCommand, QueueableCommand};
-use std::process::exit;
+use std::io;
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
-use std::time::Duration;
-use std::{cmp, io};
-
-#[derive(Debug, Eq, PartialEq)]
-enum MainMenuOption {
-    NewGame,
-    LoadGame,
-    Quit,
-}
+use std::time::{Duration, Instant};
+
+mod achievements;
+mod borders;
+mod color_legend;
+mod confetti;
+mod end_screen;
+mod game_screen;
+mod game_summary;
+mod high_scores;
+mod key_bindings;
+mod layout;
+mod main_menu;
+mod menu;
+mod mini_board;
+mod new_game_menu;
+mod pause_menu;
+mod race_screen;
+mod recovery;
+mod replay_screen;
+mod save_changes;
+mod save_slots;
+mod screen;
+mod settings;
+mod splash;
+mod statistics;
+mod undo_confirm;
 
 /// This is the entrypoint to the game.
 ///
-/// This function initializes the TUI and starts the main menu event loop.
+/// This function initializes the TUI and runs the screen stack, starting at the main menu, or at
+/// the summary of the last session if there is one to show. A practice game is started over the
+/// main menu instead, so leaving it goes back to the menu.
 ///
 /// # Arguments
 ///
 /// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `config` - The user's settings, with the command line's overrides applied.
+/// * `overrides` - The settings given on the command line.
+/// * `snapshots` - Where to keep snapshots of the games played, for recovering from mistakes.
+/// * `practice` - A practice game to start with, from `--practice`, if any.
 ///
 /// # Returns
 ///
-/// Returns an `io::Result` that indicates success or failure.
-pub fn start_app<W: io::Write>(writer: &mut W) -> io::Result<()> {
+/// Returns an `io::Result` that indicates success or failure. If the terminal goes away, e.g.
+/// because the ssh connection dropped, the game in progress is saved for recovery and the error
+/// is returned.
+pub fn start_app<W: io::Write>(
+    writer: &mut W,
+    config: &Config,
+    overrides: Overrides,
+    snapshots: Arc<Mutex<SnapshotRing>>,
+    practice: Option<Game>,
+) -> io::Result<()> {
+    use_borders(config);
     writer.execute(terminal::EnterAlternateScreen)?;
     terminal::enable_raw_mode()?;
     queue!(
@@ -40,599 +85,247 @@ pub fn start_app<W: io::Write>(writer: &mut W) -> io::Result<()> {
     )?;
     writer.flush()?;
 
-    main_menu_loop(writer)?;
-    writer.execute(terminal::LeaveAlternateScreen)?; // todo if program throws error, this line doesn't execute, and terminal stays in curses mode when the shell regains control
+    let mut context = AppContext {
+        config: config.clone(),
+        overrides,
+        theme: Theme::by_name(&config.theme).unwrap_or_default(),
+        game: None,
+        snapshots,
+        statistics: Statistics::load(),
+        achievements: Achievements::load(),
+        session_started_at: save::now(),
+        status_hooks: config
+            .status_hooks
+            .iter()
+            .filter_map(|name| status_hook::by_name(name))
+            .collect(),
+        pacing: FramePacer::new(),
+    };
+    let stack: Vec<Box<dyn Screen<W>>> = match (practice, SplashScreen::new(&context)) {
+        (Some(game), _) => vec![
+            Box::new(MainMenuScreen::new()),
+            Box::new(GameScreen::new(Ok(game), &mut context)),
+        ],
+        (None, Some(splash)) => vec![Box::new(splash)],
+        (None, None) => vec![Box::new(MainMenuScreen::new())],
+    };
+    if let Err(e) = run_screen_stack(writer, stack, &mut context) {
+        save_for_recovery(&context);
+        let _ = restore_terminal();
+        return Err(e);
+    }
+    writer.execute(terminal::LeaveAlternateScreen)?;
     Ok(())
 }
 
-/// Main loop for the game's main menu.
-///
-/// This function handles user input and navigation within the main menu.
+/// Chooses the characters the borders are drawn with, which are ASCII if the config asks for them
+/// or the terminal can't show box-drawing characters.
 ///
 /// # Arguments
 ///
-/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `config` - The user's settings.
+fn use_borders(config: &Config) {
+    Borders::use_ascii(config.ascii_borders || !diagnostics::supports_box_drawing_from_env());
+}
+
+/// Saves the game in progress and the snapshots taken so far, so that the game can be recovered
+/// on the next start after the app had to stop, e.g. because the terminal went away.
 ///
-/// # Returns
+/// # Arguments
 ///
-/// Returns an `io::Result` that indicates success or failure.
-fn main_menu_loop<W: io::Write>(writer: &mut W) -> io::Result<()> {
-    let mut selected_option = NewGame;
-    loop {
-        render_main_menu(writer, &selected_option)?;
-
-        match event::read()? {
-            Event::Key(KeyEvent {
-                code: c,
-                kind: KeyEventKind::Press,
-                modifiers: _,
-                state: _,
-            }) => {
-                match c {
-                    KeyCode::Up => match selected_option {
-                        NewGame => selected_option = Quit,
-                        LoadGame => selected_option = NewGame,
-                        Quit => selected_option = LoadGame,
-                    },
-                    KeyCode::Down => match selected_option {
-                        NewGame => selected_option = LoadGame,
-                        LoadGame => selected_option = Quit,
-                        Quit => selected_option = NewGame,
-                    },
-                    KeyCode::Enter => {
-                        match selected_option {
-                            NewGame => {
-                                writer.execute(Clear(ClearType::All))?;
-                                game_loop(writer, Game::start_new_game())?;
-                            }
-                            LoadGame => {
-                                unimplemented!()
-                            }
-                            Quit => {
-                                return Ok(()); // breaks loop and allows cleanup code to run
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            Event::Resize(_, _) => {
-                writer.execute(Clear(ClearType::All))?;
-                continue;
-            }
-            _ => {}
+/// * `context` - State shared between all screens, with the game and the snapshots.
+fn save_for_recovery(context: &AppContext) {
+    // a screen may have stopped while holding the snapshots, so don't wait for them
+    if let Ok(mut snapshots) = context.snapshots.try_lock() {
+        if let Some(game) = &context.game {
+            snapshots.push(game, save::now());
         }
-        sleep(Duration::from_millis(100));
+        let _ = snapshots.save_for_recovery();
     }
 }
 
-/// Renders the main menu on the terminal.
-///
-/// This function draws the main menu options and highlights the selected option. All parameters
-/// such as positions, sizes, etc are hardcoded and immutable.
+/// Draws a line of details for finding problems in the top left corner, over the screens.
 ///
 /// # Arguments
 ///
 /// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
-/// * `selected_option` - The currently selected main menu option. This option will be drawn in
-///   yellow.
+/// * `context` - State shared between all screens, with how quickly the terminal takes frames.
 ///
 /// # Returns
 ///
 /// Returns an `io::Result` that indicates success or failure.
-fn render_main_menu<W: io::Write>(
-    writer: &mut W,
-    selected_option: &MainMenuOption,
-) -> io::Result<()> {
-    const MENU_BOX_WIDTH: u16 = 16;
-    const MENU_BOX_HEIGHT: u16 = 5;
-
-    let size = terminal::size()?;
-    let menu_box_left_x = (size.0 - MENU_BOX_WIDTH) / 2;
-    let menu_box_right_x = (size.0 + MENU_BOX_WIDTH) / 2 - 1;
-    let menu_box_top_y = (size.1 - MENU_BOX_HEIGHT) / 2;
-    let menu_box_bottom_y = (size.1 + MENU_BOX_HEIGHT) / 2 - 1;
-
-    // draw box
-    for y in menu_box_top_y..=menu_box_bottom_y {
-        for x in menu_box_left_x..=menu_box_right_x {
-            if (y == menu_box_top_y || y == menu_box_bottom_y)
-                || (x == menu_box_left_x || x == menu_box_right_x)
-            {
-                let printed_char: char = match (x, y) {
-                    (x, y) if (x == menu_box_left_x && y == menu_box_top_y) => '┌',
-                    (x, y) if (x == menu_box_right_x && y == menu_box_top_y) => '┐',
-                    (x, y) if (x == menu_box_left_x && y == menu_box_bottom_y) => '└',
-                    (x, y) if (x == menu_box_right_x && y == menu_box_bottom_y) => '┘',
-                    (x, _) if (x == menu_box_left_x || x == menu_box_right_x) => '│',
-                    (_, y) if (y == menu_box_top_y || y == menu_box_bottom_y) => '─',
-                    _ => unreachable!(),
-                };
-                queue!(
-                    writer,
-                    cursor::MoveTo(x, y),
-                    style::PrintStyledContent(printed_char.white())
-                )?;
-            }
-        }
-    }
-
-    // draw text
+fn render_debug_overlay<W: io::Write>(writer: &mut W, context: &AppContext) -> io::Result<()> {
     queue!(
         writer,
-        cursor::MoveTo(menu_box_left_x + 1, menu_box_top_y + 1),
-        style::SetForegroundColor(if *selected_option == NewGame {
-            style::Color::Yellow
-        } else {
-            style::Color::White
-        }),
-        style::Print(get_padded_string("New Game", (MENU_BOX_WIDTH - 2) as usize)),
-        cursor::MoveTo(menu_box_left_x + 1, menu_box_top_y + 2),
-        style::SetForegroundColor(if *selected_option == LoadGame {
-            style::Color::Yellow
-        } else {
-            style::Color::White
-        }),
-        style::Print(get_padded_string("Load", (MENU_BOX_WIDTH - 2) as usize)),
-        cursor::MoveTo(menu_box_left_x + 1, menu_box_top_y + 3),
-        style::SetForegroundColor(if *selected_option == Quit {
-            style::Color::Yellow
-        } else {
-            style::Color::White
-        }),
-        style::Print(get_padded_string("Quit", (MENU_BOX_WIDTH - 2) as usize)),
-    )?;
-
-    writer.flush()?;
-
-    Ok(())
+        cursor::MoveTo(0, 0),
+        style::SetBackgroundColor(context.theme.ui.status_bar.background),
+        style::SetForegroundColor(context.theme.ui.status_bar.foreground),
+        style::Print(format!(" {} ", context.pacing.describe())),
+        style::ResetColor,
+    )
 }
 
-/// Returns a padded string with specified width.
-///
-/// This function takes some text and pads it with spaces on both sides to
-/// achieve the desired width. It ensures that the text is centered within the width.
-/// If the text is longer than the desired width, just returns the text.
-///
-/// # Arguments
-///
-/// * `text` - The text to pad.
-/// * `width` - The desired width of the padded string.
+/// Puts the terminal back the way it was before the app started, e.g. when the app panics.
 ///
 /// # Returns
 ///
-/// A `String` containing the padded text.
-fn get_padded_string(text: &str, width: usize) -> String {
-    if text.len() >= width {
-        return text.to_string();
-    }
-    let num_spaces_on_left = (width - text.len()) / 2;
-    let num_spaces_on_right = width - (num_spaces_on_left + text.len());
-    format!(
-        "{}{}{}",
-        " ".repeat(num_spaces_on_left),
-        text,
-        " ".repeat(num_spaces_on_right)
-    )
+/// Returns an `io::Result` that indicates success or failure.
+pub fn restore_terminal() -> io::Result<()> {
+    terminal::disable_raw_mode()?;
+    io::stdout().execute(terminal::LeaveAlternateScreen)?;
+    io::stdout().execute(cursor::Show)?;
+    Ok(())
 }
 
-/// Runs the main game loop, handling user input and game state updates.
+/// Runs the screen stack until the last screen is popped.
 ///
-/// This function takes an `io::Write` implementor for rendering to the terminal and the initial game state.
-/// It continuously updates the game state based on user input and renders the game board and controls.
+/// Only the top screen receives input. It is drawn on top of the screens below it if it is an
+/// overlay, so e.g. the pause menu is shown over the board. The terminal is cleared whenever the
+/// stack changes or the terminal is resized. If the top screen is animated, it is ticked and the
+/// stack redrawn once per frame until an event arrives, from a cleared screen if it's an overlay,
+/// which may have shrunk. Ticks can change the stack like events. Each frame is drawn as one
+/// synchronized update, so terminals that support it never show a frame half drawn. How long
+/// each frame takes to flush is measured, so that the screens draw less while the terminal can't
+/// keep up.
+/// The config and the theme are reloaded when their files change, and every screen stops when the
+/// terminal can't be read or written.
 ///
 /// # Arguments
 ///
 /// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
-/// * `initial_game_state` - The initial game state, represented as a `Result<Game, GameError>`.
+/// * `stack` - The screens to start with, bottom first. The app exits when the bottom one is
+///   popped, but not when it is replaced, e.g. by the splash screen making way for the main menu.
+/// * `context` - State shared between all screens.
 ///
 /// # Returns
 ///
 /// Returns an `io::Result` that indicates success or failure.
-fn game_loop<W: io::Write>(
+fn run_screen_stack<W: io::Write>(
     writer: &mut W,
-    initial_game_state: Result<Game, GameError>,
+    mut stack: Vec<Box<dyn Screen<W>>>,
+    context: &mut AppContext,
 ) -> io::Result<()> {
-    render_everything_except_board(writer)?;
-    let mut game_state = initial_game_state;
+    let mut watcher = ConfigWatcher::for_config();
+    let mut needs_render = true;
+    // whether the next frame starts from a cleared screen
+    let mut clear = true;
+
+    while !stack.is_empty() {
+        if watcher.has_changed() {
+            context.config = Config::load();
+            // the file doesn't know about the command line, so its flags still have to win
+            context.overrides.apply_to(&mut context.config);
+            use_borders(&context.config);
+            context.theme = Theme::by_name(&context.config.theme).unwrap_or_default();
+            clear = true;
+            needs_render = true;
+        }
 
-    loop {
-        match &game_state {
-            Err(err) => {
-                render_game_state_error(writer, err);
+        if needs_render {
+            // draw from the topmost screen that isn't an overlay upwards
+            let first_visible = stack
+                .iter()
+                .rposition(|screen| !screen.is_overlay())
+                .unwrap_or(0);
+            writer.queue(terminal::BeginSynchronizedUpdate)?;
+            if clear {
+                writer.queue(Clear(ClearType::All))?;
+                clear = false;
+            }
+            let _render =
+                tracing::info_span!("render", screens = stack.len() - first_visible).entered();
+            for screen in stack[first_visible..].iter_mut() {
+                screen.render(writer, context)?;
+            }
+            if context.config.debug_overlay {
+                render_debug_overlay(writer, context)?;
             }
-            Ok(game) => {
-                render_board(writer, game)?;
+            writer.queue(terminal::EndSynchronizedUpdate)?;
+            // frames are buffered, so flushing is when the terminal has to take them in
+            let flush_started = Instant::now();
+            writer.flush()?;
+            if context.pacing.record(flush_started.elapsed()) {
+                tracing::info!(pacing = context.pacing.describe(), "frame pacing changed");
+                // the screens draw differently now, so start from a clean slate
+                clear = true;
             }
         }
-        match event::read()? {
-            Event::Key(KeyEvent {
-                code: c,
-                kind: KeyEventKind::Press,
-                ..
-            }) => match c {
-                KeyCode::Up => {
-                    game_state = game_state.unwrap().handle_event(GameEvent::SwipeUp);
-                }
-                KeyCode::Left => {
-                    game_state = game_state.unwrap().handle_event(GameEvent::SwipeLeft);
-                }
-                KeyCode::Right => {
-                    game_state = game_state.unwrap().handle_event(GameEvent::SwipeRight);
-                }
-                KeyCode::Down => {
-                    game_state = game_state.unwrap().handle_event(GameEvent::SwipeDown);
-                }
-                KeyCode::Char('q') => {
-                    writer.execute(Clear(ClearType::All))?;
-                    break;
-                }
-                KeyCode::Char('r') => {
-                    game_state = game_state.unwrap().handle_event(GameEvent::NewGame);
+        needs_render = true;
+
+        let top = stack.last_mut().expect("stack is not empty");
+        let transition = match top.frame_interval(context) {
+            // animated screens are ticked whenever a frame passes without any input
+            Some(interval) if !event::poll(interval)? => {
+                clear |= top.is_overlay();
+                let _tick = tracing::info_span!("tick").entered();
+                top.tick(context)
+            }
+            // still screens wake up now and then to look for changed config files
+            None if !event::poll(ConfigWatcher::INTERVAL)? => {
+                needs_render = false;
+                continue;
+            }
+            _ => {
+                let event = event::read()?;
+                if let Event::Resize(_, _) = event {
+                    clear = true;
+                    continue;
                 }
-                _ => {}
-            },
-            Event::Resize(_, _) => {
-                let game = game_state.unwrap();
-                render_everything_except_board(writer)?;
-                render_board(writer, &game)?;
-                game_state = Ok(game);
+                let transition = {
+                    let _input = tracing::info_span!("input").entered();
+                    top.handle_event(&event, context)
+                };
+                sleep(Duration::from_millis(100));
+                transition
             }
-            _ => {}
+        };
+
+        if !matches!(transition, Transition::None) {
+            clear = true;
+        }
+        match transition {
+            Transition::None => {}
+            Transition::Push(screen) => stack.push(screen),
+            Transition::Pop => {
+                stack.pop();
+            }
+            Transition::Replace(screen) => {
+                stack.pop();
+                stack.push(screen);
+            }
+            Transition::PopToRoot => stack.truncate(1),
+            Transition::Quit => stack.clear(),
         }
-        sleep(Duration::from_millis(100));
     }
 
     Ok(())
 }
 
-/// Renders all elements on the screen except the game board.
-///
-/// This function clears the terminal and renders game controls and score information.
-///
-/// # Arguments
-///
-/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
-///
-/// # Returns
-///
-/// Returns an `io::Result` that indicates success or failure.
-fn render_everything_except_board<W: io::Write>(writer: &mut W) -> io::Result<()> {
-    writer.queue(Clear(ClearType::All))?;
-
-    let size = terminal::size()?;
-    let controls = " Arrow Keys: Merge  R: Restart  Q: Quit";
-    queue!(
-        writer,
-        cursor::MoveTo(0, size.1),
-        style::SetBackgroundColor(Color::White),
-        style::SetForegroundColor(Color::Black),
-        style::Print(format!(
-            "{}{}",
-            controls,
-            " ".repeat(size.0 as usize - controls.chars().count())
-        )),
-        style::ResetColor
-    )?;
-
-    //todo draw score
-
-    writer.flush()?;
-    Ok(())
-}
-
-/// Renders the game board on the terminal.
+/// Returns a padded string with specified width.
 ///
-/// This function renders the game board, including tiles and borders, on the terminal.
+/// This function takes some text and pads it with spaces on both sides to
+/// achieve the desired width. It ensures that the text is centered within the width.
+/// If the text is longer than the desired width, just returns the text.
 ///
 /// # Arguments
 ///
-/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
-/// * `game` - A reference to the `Game` struct representing the game state.
+/// * `text` - The text to pad.
+/// * `width` - The desired width of the padded string.
 ///
 /// # Returns
 ///
-/// Returns an `io::Result` that indicates success or failure.
-fn render_board<W: io::Write>(writer: &mut W, game: &Game) -> io::Result<()> {
-    let game_state = game.read_board_state();
-    let max_item_length = game_state.iter().fold(0usize, |max_row_len, vec| {
-        cmp::max(
-            max_row_len,
-            vec.iter().fold(0usize, |max_item_len, item| {
-                cmp::max(max_item_len, (2u32.pow(*item as u32)).to_string().len())
-            }),
-        )
-    });
-
-    let size = terminal::size()?;
-
-    let cell_width = max_item_length + 2; // add two for a space on each side
-    let grid_width = game_state[0].len();
-
-    let board_height = game_state.len() * 4; // in rows
-    let board_width = (cell_width + 1) * grid_width + 1; // in columns
-
-    let board_left_side_x_pos = (size.0 - board_width as u16) / 2;
-    let board_top_side_y_pos = (size.1 - board_height as u16) / 2;
-
-    for (index, row) in game_state.iter().enumerate() {
-        queue!(
-            writer,
-            cursor::MoveTo(
-                board_left_side_x_pos,
-                board_top_side_y_pos + (4 * index as u16) + 1
-            ),
-            style::Print(create_data_row_without_text(cell_width, '│', '│', '│', row)),
-            cursor::MoveTo(
-                board_left_side_x_pos,
-                board_top_side_y_pos + (4 * index as u16) + 2
-            ),
-            style::Print(create_data_row(cell_width, '│', '│', '│', row)),
-            cursor::MoveTo(
-                board_left_side_x_pos,
-                board_top_side_y_pos + (4 * index as u16) + 3
-            ),
-            style::Print(create_data_row_without_text(cell_width, '│', '│', '│', row)),
-            cursor::MoveTo(
-                board_left_side_x_pos,
-                board_top_side_y_pos + (4 * index as u16) + 4
-            ),
-            style::Print(create_constant_row(
-                grid_width, cell_width, '├', '┼', '┤', '─'
-            )),
-        )?;
+/// A `String` containing the padded text.
+fn get_padded_string(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length >= width {
+        return text.to_string();
     }
-
-    // draw top and bottom borders
-    queue!(
-        writer,
-        cursor::MoveTo(board_left_side_x_pos, board_top_side_y_pos),
-        style::Print(create_constant_row(grid_width, cell_width, '┌', '┬', '┐', '─').as_str()),
-        cursor::MoveTo(
-            board_left_side_x_pos,
-            board_top_side_y_pos + board_height as u16
-        ),
-        style::Print(create_constant_row(grid_width, cell_width, '└', '┴', '┘', '─').as_str())
-    )?;
-
-    Ok(())
-}
-
-/// Creates a constant row of text for the grid with specified formatting.
-///
-/// This function generates a row of text with a specified number of cells, each cell having a
-/// specified width and containing the same filler character. The row is formatted with opening,
-/// joining, and closing characters.
-///
-/// # Arguments
-///
-/// - `number_of_cells`: The number of cells in the row.
-/// - `cell_width`: The width of each cell, including spaces.
-/// - `opening_char`: The character used at the beginning of the row.
-/// - `joining_char`: The character used to join cells within the row.
-/// - `closing_char`: The character used at the end of the row.
-/// - `filler_char`: The character used to fill each cell.
-///
-/// # Returns
-///
-/// A `String` containing the generated row of text.
-///
-fn create_constant_row(
-    number_of_cells: usize,
-    cell_width: usize,
-    opening_char: char,
-    joining_char: char,
-    closing_char: char,
-    filler_char: char,
-) -> String {
-    format!(
-        "{}{}{}\n",
-        opening_char,
-        (0..number_of_cells)
-            .map(|_| filler_char.to_string().repeat(cell_width))
-            .collect::<Vec<String>>()
-            .join(joining_char.to_string().as_str()),
-        closing_char
-    )
-}
-
-/// Creates a row of text with data for the game board.
-///
-/// This function formats the provided data to match how it is shown on the screen, including
-/// setting the correct padding, colours, and tile separators.
-///
-/// # Arguments
-///
-/// * `cell_width` - The width of each cell, including spaces.
-/// * `opening_char` - The character used at the beginning of the row.
-/// * `joining_char` - The character used to join cells within the row.
-/// * `closing_char` - The character used at the end of the row.
-/// * `data` - A slice containing the tile data to be displayed in the row.
-///
-/// # Returns
-///
-/// A `String` containing the generated row of text.
-fn create_data_row(
-    cell_width: usize,
-    opening_char: char,
-    joining_char: char,
-    closing_char: char,
-    data: &[TileType],
-) -> String {
+    let num_spaces_on_left = (width - length) / 2;
+    let num_spaces_on_right = width - (num_spaces_on_left + length);
     format!(
-        "{}{}{}\n",
-        opening_char.white().on_black(),
-        data.iter()
-            .map(|&tile| format_tile_for_display_with_number(tile, cell_width).to_string())
-            .collect::<Vec<String>>()
-            .join(joining_char.white().on_black().to_string().as_str()),
-        closing_char.white().on_black()
-    )
-}
-
-/// Creates a row of text with data for the game board without displaying tile numbers.
-///
-/// This function is identical to create_data_row, except that it replaces the numbers in each tile with spaces.
-///
-/// # Arguments
-///
-/// * `cell_width` - The width of each cell, including spaces.
-/// * `opening_char` - The character used at the beginning of the row.
-/// * `joining_char` - The character used to join cells within the row.
-/// * `closing_char` - The character used at the end of the row.
-/// * `data` - A slice containing the tile data to be displayed in the row.
-///
-/// # Returns
-///
-/// A `String` containing the generated row of text.
-fn create_data_row_without_text(
-    cell_width: usize,
-    opening_char: char,
-    joining_char: char,
-    closing_char: char,
-    data: &[TileType],
-) -> String {
-    format!(
-        "{}{}{}\n",
-        opening_char.white().on_black(),
-        data.iter()
-            .map(|&tile| format_tile_for_display_without_number(tile, cell_width).to_string())
-            .collect::<Vec<String>>()
-            .join(joining_char.white().on_black().to_string().as_str()),
-        closing_char.white().on_black()
-    )
-}
-
-/// Formats a tile for display on the game board. This function does not print the number for the tile.
-/// It sets the background colour depending on the value of the tile.
-///
-/// # Arguments
-///
-/// * `tile` - The tile value (TileType) to be formatted.
-/// * `cell_width` - The width of the cell, including spaces.
-///
-/// # Returns
-///
-/// A `StyledContent` containing the tile formatted for display without the tile number.
-fn format_tile_for_display_without_number(
-    tile: TileType,
-    cell_width: usize,
-) -> StyledContent<String> {
-    let padded_string = " ".repeat(cell_width);
-    match tile {
-        0 => padded_string.on_black(),
-        1 => padded_string.on_white(),
-        2 => padded_string.on_white(),
-        3 => padded_string.on_yellow(),
-        4 => padded_string.on_yellow(),
-        5 => padded_string.on_yellow(),
-        6 => padded_string.on_red(),
-        7 => padded_string.on_red(),
-        8 => padded_string.on_red(),
-        9 => padded_string.on_magenta(),
-        10 => padded_string.on_magenta(),
-        11 => padded_string.on_magenta(),
-        12 => padded_string.on_cyan(),
-        13 => padded_string.on_cyan(),
-        14 => padded_string.on_cyan(),
-        15 => padded_string.on_green(),
-        16 => padded_string.on_green(),
-        _ => padded_string.on_green(),
-    }
-}
-
-/// Formats a tile for display on the game board including the tile number.
-///
-/// This function formats a tile to be displayed on the game board, including the tile number.
-///
-/// # Arguments
-///
-/// * `tile` - The tile value (TileType) to be formatted.
-/// * `cell_width` - The width of the cell, including spaces.
-///
-/// # Returns
-///
-/// A `StyledContent` containing the tile formatted for display with the tile number.
-fn format_tile_for_display_with_number(tile: TileType, cell_width: usize) -> StyledContent<String> {
-    let number_as_string = if tile == 0 {
-        " ".to_string()
-    } else {
-        2u32.pow(tile as u32).to_string()
-    };
-
-    let spaces_before = (cell_width - number_as_string.len()) / 2;
-    let spaces_after = (cell_width - number_as_string.len()) - spaces_before; // subtract here because spaces_before and spaces_after aren't equal if cell_width - item length is odd, and want all cells to be consistent width
-    let padded_string = format!(
         "{}{}{}",
-        " ".repeat(spaces_before),
-        number_as_string,
-        " ".repeat(spaces_after)
-    );
-    match tile {
-        0 => padded_string.white().on_black(),
-        1 => padded_string.black().on_white(),
-        2 => padded_string.black().on_white(),
-        3 => padded_string.black().on_yellow(),
-        4 => padded_string.black().on_yellow(),
-        5 => padded_string.black().on_yellow(),
-        6 => padded_string.white().on_red(),
-        7 => padded_string.white().on_red(),
-        8 => padded_string.white().on_red(),
-        9 => padded_string.black().on_magenta(),
-        10 => padded_string.black().on_magenta(),
-        11 => padded_string.black().on_magenta(),
-        12 => padded_string.black().on_cyan(),
-        13 => padded_string.black().on_cyan(),
-        14 => padded_string.black().on_cyan(),
-        15 => padded_string.black().on_green(),
-        16 => padded_string.black().on_green(),
-        _ => padded_string.black().on_green(),
-    }
-}
-
-/// Renders the error state and exits the program.
-///
-/// This function renders the error message and terminates the program.
-///
-/// # Arguments
-///
-/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
-/// * `e` - A reference to the `GameError` containing the error information.
-fn render_game_state_error<W: io::Write>(writer: &mut W, e: &GameError) -> ! {
-    // this function always exits the program anyway, so if printing the error fails
-    // we just panic
-    queue!(
-        writer,
-        Clear(ClearType::All),
-        cursor::MoveTo(0, 0),
-        style::Print("Cannot continue the game. Error: "),
-    )
-    .unwrap();
-    let substrings: Vec<String> = format!("{:#?}", e)
-        .split('\n')
-        .map(|s| s.to_string())
-        .collect();
-    for str in substrings {
-        queue!(writer, cursor::MoveDown(1), style::Print(str)).unwrap();
-    }
-    queue!(
-        writer,
-        cursor::MoveDown(1),
-        style::Print("Press any key to exit the game.")
+        " ".repeat(num_spaces_on_left),
+        text,
+        " ".repeat(num_spaces_on_right)
     )
-    .unwrap();
-    writer.flush().unwrap();
-
-    loop {
-        if let Ok(Event::Key(KeyEvent {
-            kind: KeyEventKind::Press,
-            ..
-        })) = event::read()
-        {
-            writer
-                .execute(terminal::LeaveAlternateScreen)
-                .expect("Couldn't leave alternate screen buffer");
-            exit(1);
-        }
-    }
 }
@@ -0,0 +1,97 @@
+use crate::achievements::Achievement;
+use crate::save;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// Lists every achievement with what it takes, marking the unlocked ones with when they were
+/// unlocked.
+pub struct AchievementsScreen;
+
+impl AchievementsScreen {
+    pub fn new() -> AchievementsScreen {
+        AchievementsScreen
+    }
+
+    /// Gets the lines of the list, one for each achievement.
+    fn lines(context: &AppContext) -> Vec<String> {
+        let now = save::now();
+        let name_width = Achievement::ALL
+            .iter()
+            .map(|achievement| achievement.name().chars().count())
+            .max()
+            .unwrap_or(0);
+        Achievement::ALL
+            .into_iter()
+            .map(|achievement| {
+                let unlocked_at = context.achievements.unlocked_at(achievement);
+                let line = format!(
+                    "[{}] {:<width$}  {}",
+                    if unlocked_at.is_some() { "x" } else { " " },
+                    achievement.name(),
+                    achievement.description(),
+                    width = name_width
+                );
+                match unlocked_at {
+                    Some(then) => format!("{} ({})", line, save::describe_age(then, now)),
+                    None => line,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<W: io::Write> Screen<W> for AchievementsScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let mut lines = AchievementsScreen::lines(context);
+        lines.push(String::new());
+        lines.push("Esc: Back".to_string());
+
+        let screen = Rect::terminal()?;
+        let width = lines.iter().map(|line| line.chars().count()).max();
+        let Some(area) =
+            layout::center_rect(screen, (width.unwrap_or(0) as u16, lines.len() as u16 + 2))
+        else {
+            return layout::render_too_small(writer, screen);
+        };
+
+        let title = format!(
+            "Achievements ({}/{})",
+            context.achievements.unlocked.len(),
+            Achievement::ALL.len()
+        );
+        queue!(
+            writer,
+            Clear(ClearType::All),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            cursor::MoveTo(0, area.y),
+            style::Print(get_padded_string(&title, screen.width as usize)),
+        )?;
+        // the list is left-aligned so that the descriptions line up
+        for (index, line) in lines.iter().enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(area.x, area.y + 2 + index as u16),
+                style::Print(line),
+            )?;
+        }
+        queue!(writer, style::ResetColor)?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, _context: &mut AppContext) -> Transition<W> {
+        match event {
+            Event::Key(KeyEvent {
+                code: KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'),
+                kind: KeyEventKind::Press,
+                ..
+            }) => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether borders are drawn with ASCII characters, chosen once when the app starts.
+static ASCII_BORDERS: AtomicBool = AtomicBool::new(false);
+
+/// The characters the borders of the board, menus and dialogs are drawn with. Terminals that
+/// can't show box-drawing characters get plain ASCII ones instead.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Borders {
+    pub horizontal: char,
+    pub vertical: char,
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    /// Where a vertical line meets the top border.
+    pub top_join: char,
+    /// Where a vertical line meets the bottom border.
+    pub bottom_join: char,
+    /// Where a horizontal line meets the left border.
+    pub left_join: char,
+    /// Where a horizontal line meets the right border.
+    pub right_join: char,
+    /// Where a horizontal and a vertical line cross.
+    pub cross: char,
+}
+
+impl Borders {
+    pub const UNICODE: Borders = Borders {
+        horizontal: '─',
+        vertical: '│',
+        top_left: '┌',
+        top_right: '┐',
+        bottom_left: '└',
+        bottom_right: '┘',
+        top_join: '┬',
+        bottom_join: '┴',
+        left_join: '├',
+        right_join: '┤',
+        cross: '┼',
+    };
+
+    pub const ASCII: Borders = Borders {
+        horizontal: '-',
+        vertical: '|',
+        top_left: '+',
+        top_right: '+',
+        bottom_left: '+',
+        bottom_right: '+',
+        top_join: '+',
+        bottom_join: '+',
+        left_join: '+',
+        right_join: '+',
+        cross: '+',
+    };
+
+    /// Chooses the borders every screen draws from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `ascii` - Whether to draw them with ASCII characters.
+    pub fn use_ascii(ascii: bool) {
+        ASCII_BORDERS.store(ascii, Ordering::Relaxed);
+    }
+
+    /// Gets the borders chosen with `use_ascii`, which are box-drawing characters unless ASCII
+    /// was asked for.
+    pub fn current() -> Borders {
+        if ASCII_BORDERS.load(Ordering::Relaxed) {
+            Borders::ASCII
+        } else {
+            Borders::UNICODE
+        }
+    }
+
+    /// Gets the top line of a box.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner_width` - How many columns there are between the left and right borders.
+    pub fn top(&self, inner_width: usize) -> String {
+        self.line(self.top_left, self.top_right, inner_width)
+    }
+
+    /// Gets the bottom line of a box, like `top`.
+    pub fn bottom(&self, inner_width: usize) -> String {
+        self.line(self.bottom_left, self.bottom_right, inner_width)
+    }
+
+    fn line(&self, left: char, right: char, inner_width: usize) -> String {
+        format!(
+            "{}{}{}",
+            left,
+            self.horizontal.to_string().repeat(inner_width),
+            right
+        )
+    }
+}
@@ -0,0 +1,105 @@
+use crate::theme::LegendEntry;
+use crate::tile;
+use crate::user_interface::game_screen::format_tile_for_display_with_number;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// How wide each sample tile is drawn.
+const SAMPLE_WIDTH: usize = 8;
+
+/// The most sample tiles drawn on one row of the legend.
+const MAX_SAMPLES: usize = 3;
+
+/// Shows which colour goes with which tile value in the active theme. The rows come from the
+/// theme's data, so every theme gets a correct legend.
+pub struct ColorLegendScreen;
+
+impl ColorLegendScreen {
+    pub fn new() -> ColorLegendScreen {
+        ColorLegendScreen
+    }
+
+    /// Describes the tile values covered by a row of the legend, e.g. "8 to 32".
+    fn describe(entry: &LegendEntry, context: &AppContext) -> String {
+        let value = |tile| tile::display_value(tile, context.config.number_format);
+        match entry.last {
+            Some(last) if last == entry.first => value(entry.first),
+            Some(last) => format!("{} to {}", value(entry.first), value(last)),
+            None => format!("{} and up", value(entry.first)),
+        }
+    }
+}
+
+impl<W: io::Write> Screen<W> for ColorLegendScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let legend = context.theme.legend();
+        let screen = Rect::terminal()?;
+        let samples_width = (SAMPLE_WIDTH * MAX_SAMPLES) as u16;
+        // the samples start left of the middle, leaving room for the labels on the right
+        let Some(area) = layout::center_rect(screen, (samples_width * 2, legend.len() as u16 + 4))
+        else {
+            return layout::render_too_small(writer, screen);
+        };
+        let (left_x, top_y) = (area.x, area.y);
+
+        queue!(
+            writer,
+            cursor::MoveTo(0, top_y),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            style::Print(get_padded_string(
+                &format!("Tile Colors ({} theme)", context.theme.name),
+                screen.width as usize
+            )),
+            style::ResetColor,
+        )?;
+
+        for (index, entry) in legend.iter().enumerate() {
+            let y = top_y + 2 + index as u16;
+            let last = entry.last.unwrap_or(entry.first);
+            let tiles = entry.first..=last.min(entry.first + MAX_SAMPLES as u8 - 1);
+
+            queue!(writer, cursor::MoveTo(left_x, y))?;
+            for tile in tiles {
+                let mut value = tile::display_value(tile, context.config.number_format);
+                if value.chars().count() > SAMPLE_WIDTH - 2 {
+                    value = tile::short_value(tile);
+                }
+                let sample =
+                    format_tile_for_display_with_number(tile, &value, SAMPLE_WIDTH, &context.theme);
+                queue!(writer, style::PrintStyledContent(sample))?;
+            }
+            queue!(
+                writer,
+                cursor::MoveTo(left_x + samples_width + 2, y),
+                style::SetForegroundColor(context.theme.ui.text.foreground),
+                style::Print(ColorLegendScreen::describe(entry, context)),
+                style::ResetColor,
+            )?;
+        }
+
+        queue!(
+            writer,
+            cursor::MoveTo(0, top_y + legend.len() as u16 + 3),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            style::Print(get_padded_string("Esc: Back", screen.width as usize)),
+            style::ResetColor,
+        )?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, _context: &mut AppContext) -> Transition<W> {
+        match event {
+            Event::Key(KeyEvent {
+                code: KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'),
+                kind: KeyEventKind::Press,
+                ..
+            }) => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::diagnostics::ColorSupport;
+use crate::user_interface::layout::Rect;
+use crate::user_interface::screen::AppContext;
+use crossterm::style::{Color, Stylize};
+use crossterm::{cursor, queue, style};
+use rand::rngs::ThreadRng;
+use rand::Rng;
+use std::io;
+
+/// The characters a piece of confetti cycles through as it falls, so that it seems to tumble.
+const GLYPHS: [char; 4] = ['*', '+', 'x', '+'];
+
+/// The colors of the pieces.
+const COLORS: [Color; 6] = [
+    Color::Red,
+    Color::Yellow,
+    Color::Green,
+    Color::Cyan,
+    Color::Blue,
+    Color::Magenta,
+];
+
+/// How many frames new pieces keep being thrown for. The last ones fall out of the region
+/// afterwards, so the effect ends on its own.
+const THROWN_FOR: usize = 40;
+
+/// How many columns of the region there are for each piece thrown in a frame.
+const COLUMNS_PER_PIECE: u16 = 10;
+
+/// A piece of confetti, at a position relative to the top left of the region.
+struct Piece {
+    x: u16,
+    y: u16,
+    glyph: usize,
+    color: Color,
+    /// Whether the piece falls only every other frame, so that not every piece falls together.
+    slow: bool,
+}
+
+/// Confetti falling through a region of the terminal, e.g. over the win banner. It's advanced by
+/// the screen showing it once per frame of its animation, and clipped to the region it's
+/// drawn in, so it can't spill onto the rest of the terminal when that shrinks.
+pub struct Confetti {
+    pieces: Vec<Piece>,
+    frame: usize,
+    rng: ThreadRng,
+}
+
+impl Confetti {
+    pub fn new() -> Confetti {
+        Confetti {
+            pieces: Vec::new(),
+            frame: 0,
+            rng: rand::thread_rng(),
+        }
+    }
+
+    /// Gets whether confetti should be shown, which it isn't with animations off, e.g. on a slow
+    /// connection, or on a terminal that can't show colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - State shared between all screens, with the player's settings.
+    pub fn is_enabled(context: &AppContext) -> bool {
+        context.animating() && ColorSupport::from_env() != ColorSupport::None
+    }
+
+    /// Moves every piece down, letting it drift sideways, and throws new pieces in at the top
+    /// until the effect is winding down.
+    ///
+    /// # Arguments
+    ///
+    /// * `area` - The region the confetti falls through.
+    pub fn advance(&mut self, area: Rect) {
+        self.frame += 1;
+        for piece in &mut self.pieces {
+            piece.glyph = (piece.glyph + 1) % GLYPHS.len();
+            if piece.slow && self.frame.is_multiple_of(2) {
+                continue;
+            }
+            piece.y += 1;
+            piece.x = match self.rng.gen_range(0..3) {
+                0 => piece.x.saturating_sub(1),
+                1 => piece.x,
+                _ => (piece.x + 1).min(area.width.saturating_sub(1)),
+            };
+        }
+        self.pieces.retain(|piece| piece.y < area.height);
+
+        if self.frame <= THROWN_FOR && area.width > 0 {
+            for _ in 0..area.width.div_ceil(COLUMNS_PER_PIECE) {
+                self.pieces.push(Piece {
+                    x: self.rng.gen_range(0..area.width),
+                    y: 0,
+                    glyph: self.rng.gen_range(0..GLYPHS.len()),
+                    color: COLORS[self.rng.gen_range(0..COLORS.len())],
+                    slow: self.rng.gen(),
+                });
+            }
+        }
+    }
+
+    /// Draws the pieces that are inside the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+    /// * `area` - The region the confetti falls through.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `io::Result` that indicates success or failure.
+    pub fn render<W: io::Write>(&self, writer: &mut W, area: Rect) -> io::Result<()> {
+        for piece in &self.pieces {
+            if piece.x >= area.width || piece.y >= area.height {
+                continue;
+            }
+            queue!(
+                writer,
+                cursor::MoveTo(area.x + piece.x, area.y + piece.y),
+                style::PrintStyledContent(GLYPHS[piece.glyph].with(piece.color)),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,420 @@
+use crate::game::{Game, GameEvent, GameState, WINNING_TILE};
+use crate::merge::Variant;
+use crate::persistence;
+use crate::rules;
+use crate::save;
+use crate::stats::ScoreKind;
+use crate::user_interface::borders::Borders;
+use crate::user_interface::confetti::Confetti;
+use crate::user_interface::game_screen::{record_finished_game, write_board_art};
+use crate::user_interface::game_summary::GameSummaryScreen;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::replay_screen::ReplayScreen;
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crate::user_interface::undo_confirm::UndoConfirmScreen;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::{Color, Stylize};
+use crossterm::{cursor, queue, style};
+use std::io;
+use std::time::Duration;
+
+/// The colors the border cycles through when animations are on.
+const BORDER_COLORS: [Color; 6] = [
+    Color::Red,
+    Color::Yellow,
+    Color::Green,
+    Color::Cyan,
+    Color::Blue,
+    Color::Magenta,
+];
+
+/// The bars the score graph is drawn with, from lowest to highest.
+const GRAPH_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The most bars in the score graph. Longer games are squeezed to fit.
+const GRAPH_WIDTH: usize = 30;
+
+/// How long each frame of the border animation is shown for.
+const FRAME_INTERVAL: Duration = Duration::from_millis(120);
+
+/// Why the end screen is being shown.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Ending {
+    /// The winning tile was just made. The player can keep going.
+    Won,
+    /// No move can change the board.
+    GameOver,
+}
+
+/// A banner shown over the board when the game is won or lost, with a border that cycles through
+/// colors unless animations are turned off in the config. Confetti falls behind the banner after
+/// a win or a new best score.
+///
+/// After a win the player can keep playing. Leaving a won game instead counts it in the
+/// statistics, like a lost game is counted when it ends. Leaving a lost game for the main menu
+/// discards it, since there is nothing left to continue. The final board of a lost game can be exported as
+/// ANSI art, to share it or look at it later with `cat`.
+pub struct EndScreen {
+    ending: Ending,
+    frame: usize,
+    new_best_score: bool,
+    confetti: Option<Confetti>,
+    /// Where the final board was exported to, or why it couldn't be, or why the statistics
+    /// couldn't be saved.
+    message: Option<String>,
+}
+
+impl EndScreen {
+    pub fn new(ending: Ending) -> EndScreen {
+        EndScreen {
+            ending,
+            frame: 0,
+            new_best_score: false,
+            confetti: (ending == Ending::Won).then(Confetti::new),
+            message: None,
+        }
+    }
+
+    /// Announces that the game that just ended set a new best score, with confetti.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_best_score` - Whether the game's score beat the best score of its kind of rules.
+    ///
+    /// # Returns
+    ///
+    /// The `EndScreen`, celebrating if there's a new best score.
+    pub fn with_new_best_score(mut self, new_best_score: bool) -> EndScreen {
+        self.new_best_score = new_best_score;
+        if new_best_score && self.confetti.is_none() {
+            self.confetti = Some(Confetti::new());
+        }
+        self
+    }
+
+    /// Exports the final board as ANSI art to a file in the `boards` folder of the data
+    /// directory, named after the game's seed and when it was exported.
+    ///
+    /// # Returns
+    ///
+    /// A message saying where the board was exported to, or why it couldn't be.
+    fn export_board(game: &Game, context: &AppContext) -> String {
+        let mut art = Vec::new();
+        if let Err(e) = write_board_art(
+            &mut art,
+            game.read_board_state(),
+            game.rules().variant,
+            context.config.number_format,
+            &context.theme,
+        ) {
+            return format!("Couldn't export the board: {}", e);
+        }
+        let file_name = format!("boards/{:016x}-{}.ans", game.seed(), save::now());
+        match persistence::write_data_file(&file_name, art) {
+            Ok(path) => format!("Exported to {}", path.display()),
+            Err(e) => format!("Couldn't export the board: {}", e),
+        }
+    }
+
+    /// Counts a won game the player is leaving instead of playing on in the statistics, since
+    /// it won't end by itself. A lost game was counted when it ended.
+    ///
+    /// # Returns
+    ///
+    /// Whether the game can be left, which it can't the first time the statistics couldn't be
+    /// saved, so that the player sees why. They're saved with the next change.
+    fn leave(&mut self, context: &mut AppContext) -> bool {
+        let Some(mut game) = context.game.take() else {
+            return true;
+        };
+        let recorded = record_finished_game(&mut game, context).is_some();
+        context.game = Some(game);
+        if !recorded {
+            return true;
+        }
+        match context.statistics.save() {
+            Ok(_) => true,
+            Err(e) => {
+                self.message = Some(format!("Couldn't save the statistics: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Gets the lines of text shown inside the banner.
+    fn lines(&self, game: Option<&Game>, context: &AppContext) -> Vec<String> {
+        let view = game.map(Game::view);
+        let score = view.as_ref().map_or(0, |view| view.score) as u64;
+        let score = context.config.number_format.format(score);
+        let variant = game.map_or(Variant::Classic, |game| game.rules().variant);
+        match self.ending {
+            Ending::Won => vec![
+                format!(
+                    "You made {}!",
+                    variant.display_value(
+                        game.map_or(WINNING_TILE, |game| game.rules().win_exponent),
+                        context.config.number_format
+                    )
+                ),
+                format!("Score: {}", score),
+                self.message.clone().unwrap_or_default(),
+                "Enter: Keep Playing  R: New Game  Q: Menu".to_string(),
+            ],
+            Ending::GameOver => vec![
+                if self.new_best_score {
+                    "Game Over - New Best Score!".to_string()
+                } else {
+                    "Game Over".to_string()
+                },
+                match view.map(|view| view.state) {
+                    Some(GameState::Lost(reason)) => reason.to_string(),
+                    _ => String::new(),
+                },
+                format!("Final Score: {}", score),
+                format!(
+                    "Highest Tile: {}",
+                    variant.display_value(
+                        game.map_or(0, Game::highest_tile),
+                        context.config.number_format
+                    )
+                ),
+                format!(
+                    "Time: {}",
+                    rules::format_duration(
+                        game.map_or(0, |game| game.elapsed(save::now() * 1000).as_secs())
+                    )
+                ),
+                best_score_line(game, context),
+                if game.is_some_and(Game::is_assisted) {
+                    "Assisted, so it isn't a best score".to_string()
+                } else if game.is_some_and(|game| game.rules().adaptive) {
+                    "Adaptive, so it isn't a best score".to_string()
+                } else {
+                    String::new()
+                },
+                score_graph(game.map_or(&[], |game| game.score_history())),
+                context.statistics.hints.describe().unwrap_or_default(),
+                self.message.clone().unwrap_or_default(),
+                String::new(),
+                // once the rules' undos are used up or the time is up, undoing would only go back
+                // to the lost board
+                if game.is_some_and(|game| !game.can_undo()) {
+                    "P: Replay  S: Summary  E: Export  R: New Game  Q: Menu".to_string()
+                } else {
+                    "U: Undo  P: Replay  S: Summary  E: Export  R: New Game  Q: Menu".to_string()
+                },
+            ],
+        }
+    }
+}
+
+/// Describes the best score of games played by the same kind of rules as `game`, since each kind
+/// keeps its own, and the best score of this session.
+fn best_score_line(game: Option<&Game>, context: &AppContext) -> String {
+    let kind = game.map_or_else(ScoreKind::default, |game| ScoreKind::of(game.rules()));
+    let number_format = context.config.number_format;
+    format!(
+        "{}: {} (this session: {})",
+        kind.best_score_label(),
+        number_format.format(context.statistics.best_score_for(kind) as u64),
+        number_format.format(context.statistics.session_best(context.session_started_at) as u64)
+    )
+}
+
+/// Draws how the score grew over a game as a row of bars, each showing the score at the end of an
+/// equal share of the moves.
+fn score_graph(score_history: &[u32]) -> String {
+    let highest = score_history.iter().copied().max().unwrap_or(0).max(1) as u64;
+    let width = score_history.len().min(GRAPH_WIDTH);
+    (1..=width)
+        .map(|bar| {
+            let score = score_history[bar * score_history.len() / width - 1] as u64;
+            GRAPH_BARS[(score * (GRAPH_BARS.len() as u64 - 1) / highest) as usize]
+        })
+        .collect()
+}
+
+impl<W: io::Write> Screen<W> for EndScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let lines = self.lines(context.game.as_ref(), context);
+        let longest_line = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        let box_width = longest_line + 4; // add room for the borders and a space on each side
+        let box_height = lines.len() as u16 + 4; // add the borders and a blank line inside each
+
+        let screen = Rect::terminal()?;
+        let Some(end_box) = layout::center_rect(screen, (box_width, box_height)) else {
+            return layout::render_too_small(writer, screen);
+        };
+        // the banner is drawn over the confetti
+        if let Some(confetti) = &self.confetti {
+            if Confetti::is_enabled(context) {
+                confetti.render(writer, screen)?;
+            }
+        }
+        let (left_x, top_y) = (end_box.x, end_box.y);
+        let (right_x, bottom_y) = (end_box.right(), end_box.bottom());
+
+        // walk the border clockwise so that the colors appear to chase each other around it
+        let borders = Borders::current();
+        let mut border: Vec<(u16, u16, char)> = vec![(left_x, top_y, borders.top_left)];
+        border.extend((left_x + 1..right_x).map(|x| (x, top_y, borders.horizontal)));
+        border.push((right_x, top_y, borders.top_right));
+        border.extend((top_y + 1..bottom_y).map(|y| (right_x, y, borders.vertical)));
+        border.push((right_x, bottom_y, borders.bottom_right));
+        border.extend(
+            (left_x + 1..right_x)
+                .rev()
+                .map(|x| (x, bottom_y, borders.horizontal)),
+        );
+        border.push((left_x, bottom_y, borders.bottom_left));
+        border.extend(
+            (top_y + 1..bottom_y)
+                .rev()
+                .map(|y| (left_x, y, borders.vertical)),
+        );
+
+        for (index, &(x, y, printed_char)) in border.iter().enumerate() {
+            let color = if context.animating() {
+                BORDER_COLORS[(index / 2 + BORDER_COLORS.len() - self.frame % BORDER_COLORS.len())
+                    % BORDER_COLORS.len()]
+            } else {
+                context.theme.ui.border
+            };
+            queue!(
+                writer,
+                cursor::MoveTo(x, y),
+                style::PrintStyledContent(
+                    printed_char
+                        .with(color)
+                        .on(context.theme.ui.text.background)
+                )
+            )?;
+        }
+
+        let inner_width = (box_width - 2) as usize;
+        let blank_line = String::new();
+        let padded_lines = std::iter::once(&blank_line)
+            .chain(lines.iter())
+            .chain(std::iter::once(&blank_line));
+        for (index, line) in padded_lines.enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(left_x + 1, top_y + 1 + index as u16),
+                style::SetBackgroundColor(context.theme.ui.text.background),
+                style::SetForegroundColor(if index == 1 {
+                    context.theme.ui.highlight
+                } else {
+                    context.theme.ui.text.foreground
+                }),
+                style::Print(get_padded_string(line, inner_width)),
+                style::ResetColor,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        match (code, self.ending) {
+            (KeyCode::Enter, Ending::Won) => {
+                if let Some(game) = context.game.take() {
+                    context.game = game
+                        .handle_event(GameEvent::KeepPlaying)
+                        .ok()
+                        .map(|update| update.game);
+                }
+                Transition::Pop
+            }
+            (KeyCode::Char('u'), Ending::GameOver) => {
+                if context.game.as_ref().is_some_and(|game| !game.can_undo()) {
+                    return Transition::None;
+                }
+                if context.game.as_ref().is_some_and(Game::undo_would_assist) {
+                    return Transition::Replace(Box::new(UndoConfirmScreen::new(Some(
+                        Ending::GameOver,
+                    ))));
+                }
+                if let Some(game) = context.game.take() {
+                    context.game = game
+                        .handle_event(GameEvent::Undo)
+                        .ok()
+                        .map(|update| update.game);
+                }
+                Transition::Pop
+            }
+            (KeyCode::Char('p'), Ending::GameOver) => {
+                match context
+                    .game
+                    .as_ref()
+                    .and_then(|game| ReplayScreen::new(game.replay()?, game))
+                {
+                    Some(screen) => Transition::Push(Box::new(screen)),
+                    None => Transition::None,
+                }
+            }
+            (KeyCode::Char('s'), Ending::GameOver) => {
+                Transition::Push(Box::new(GameSummaryScreen::new()))
+            }
+            (KeyCode::Char('e'), Ending::GameOver) => {
+                if let Some(game) = &context.game {
+                    self.message = Some(EndScreen::export_board(game, context));
+                }
+                Transition::None
+            }
+            (KeyCode::Char('r'), _) => {
+                if !self.leave(context) {
+                    return Transition::None;
+                }
+                if let Ok(game) = Game::start_new_game_with(context.config.rules.clone()) {
+                    let limits = context.config.history;
+                    context.game =
+                        Some(game.with_history_limits(&limits, limits.spill_dir().as_deref()));
+                }
+                Transition::Pop
+            }
+            (KeyCode::Char('q') | KeyCode::Esc, Ending::Won) => {
+                if !self.leave(context) {
+                    return Transition::None;
+                }
+                Transition::PopToRoot
+            }
+            (KeyCode::Char('q') | KeyCode::Esc, Ending::GameOver) => {
+                context.game = None;
+                Transition::PopToRoot
+            }
+            _ => Transition::None,
+        }
+    }
+
+    fn frame_interval(&self, context: &AppContext) -> Option<Duration> {
+        context.animating().then_some(FRAME_INTERVAL)
+    }
+
+    fn tick(&mut self, context: &mut AppContext) -> Transition<W> {
+        self.frame = self.frame.wrapping_add(1);
+        if let (Some(confetti), Ok(screen)) = (&mut self.confetti, Rect::terminal()) {
+            if Confetti::is_enabled(context) {
+                confetti.advance(screen);
+            }
+        }
+        Transition::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,1512 @@
+use crate::achievements::Achievement;
+use crate::board::{Direction, TileType};
+use crate::coach::Coach;
+use crate::game::{now_millis, Game, GameError, GameEvent, GameState};
+use crate::keymap::{Action, KeyBindings};
+use crate::merge::Variant;
+use crate::number_format::NumberFormat;
+use crate::rules;
+use crate::save;
+use crate::solver::{EvalWeights, Solver};
+use crate::stats::{self, ScoreKind};
+use crate::status_hook::GameNotification;
+use crate::theme::{Theme, UiColors};
+use crate::transform::DisplayTransform;
+use crate::user_interface::borders::Borders;
+use crate::user_interface::end_screen::{EndScreen, Ending};
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::pause_menu::PauseScreen;
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crate::user_interface::undo_confirm::UndoConfirmScreen;
+#[cfg(feature = "net")]
+use crate::webhook::{self, ScoreSubmission};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::{Color, StyledContent, Stylize};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, event, queue, style, terminal, ExecutableCommand};
+use std::process::exit;
+use std::time::Duration;
+use std::{cmp, io};
+
+/// The screen where the game is played. The game itself is stored in the `AppContext`.
+pub struct GameScreen {
+    error: Option<GameError>,
+    /// The solver's state while it plays the game, or `None` while the player is in control.
+    auto_play: Option<AutoPlay>,
+    coach: Coach,
+    /// What the coach said about the last move, shown just above the status bar.
+    coach_message: Option<String>,
+    /// The achievements the last move unlocked, announced at the top of the screen.
+    unlocked: Vec<Achievement>,
+    /// Why the last save of the statistics or achievements failed, shown in place of the coach's
+    /// comment until a save works again.
+    save_error: Option<String>,
+    /// Whether the board is shown a quarter turn further than the `board_view` setting, toggled
+    /// while playing, e.g. for a tall terminal pane.
+    rotated: bool,
+    /// The rows of the boards still to be shown while the tiles of the last move slide, before
+    /// the board of the game itself. Empty when animations are off.
+    slide_frames: Vec<Vec<Vec<TileType>>>,
+    /// Whether the next frame only has to redraw the clock, since nothing else changed. It's only
+    /// set while the terminal can't keep up, and the board is redrawn as usual otherwise.
+    clock_only: bool,
+    /// Whether the points each move would score are shown next to the edges of the board,
+    /// toggled while playing.
+    score_preview: bool,
+    /// Whether the next frame has to start from a clean slate, e.g. after the score preview was
+    /// hidden, since nothing else draws over where it was.
+    clear: bool,
+}
+
+impl GameScreen {
+    /// How long each step of a slide is shown for.
+    const SLIDE_FRAME_INTERVAL: Duration = Duration::from_millis(40);
+}
+
+/// The solver playing the game for the player, along with what it thought of its last move.
+struct AutoPlay {
+    solver: Solver,
+    /// The solver's evaluation of every move that was possible before its last move.
+    evaluations: Vec<(Direction, f64)>,
+    chosen: Option<Direction>,
+}
+
+impl AutoPlay {
+    /// How many moves ahead the solver looks while auto-playing.
+    const DEPTH: u32 = 2;
+    /// How long to wait between moves, so the player can follow along.
+    const MOVE_INTERVAL: Duration = Duration::from_millis(150);
+
+    fn new() -> AutoPlay {
+        AutoPlay {
+            solver: Solver::new(EvalWeights::load(), AutoPlay::DEPTH),
+            evaluations: Vec::new(),
+            chosen: None,
+        }
+    }
+}
+
+impl GameScreen {
+    /// Makes the given game the game in progress and creates a screen to play it.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_game_state` - The game to play, represented as a `Result<Game, GameError>`.
+    /// * `context` - State shared between all screens, where the game is stored.
+    ///
+    /// # Returns
+    ///
+    /// A new `GameScreen`. If `initial_game_state` is an error, the screen shows it when rendered.
+    pub fn new(
+        initial_game_state: Result<Game, GameError>,
+        context: &mut AppContext,
+    ) -> GameScreen {
+        match initial_game_state {
+            Ok(game) => {
+                let limits = context.config.history;
+                context.game =
+                    Some(game.with_history_limits(&limits, limits.spill_dir().as_deref()));
+                GameScreen::notify(context, GameNotification::Started);
+                GameScreen {
+                    error: None,
+                    auto_play: None,
+                    coach: Coach::new(),
+                    coach_message: None,
+                    unlocked: Vec::new(),
+                    save_error: None,
+                    rotated: false,
+                    slide_frames: Vec::new(),
+                    clock_only: false,
+                    score_preview: false,
+                    clear: false,
+                }
+            }
+            Err(e) => {
+                context.game = None;
+                GameScreen {
+                    error: Some(e),
+                    auto_play: None,
+                    coach: Coach::new(),
+                    coach_message: None,
+                    unlocked: Vec::new(),
+                    save_error: None,
+                    rotated: false,
+                    slide_frames: Vec::new(),
+                    clock_only: false,
+                    score_preview: false,
+                    clear: false,
+                }
+            }
+        }
+    }
+
+    /// Creates a screen to continue the game already stored in the `AppContext`. If that game is
+    /// waiting for the player to keep playing after a win, the win banner comes back with the
+    /// next move.
+    pub fn resume() -> GameScreen {
+        GameScreen {
+            error: None,
+            auto_play: None,
+            coach: Coach::new(),
+            coach_message: None,
+            unlocked: Vec::new(),
+            save_error: None,
+            rotated: false,
+            slide_frames: Vec::new(),
+            clock_only: false,
+            score_preview: false,
+            clear: false,
+        }
+    }
+}
+
+impl<W: io::Write> Screen<W> for GameScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        if std::mem::take(&mut self.clock_only) {
+            let game = context.game.as_ref();
+            let limits_left = game.map_or((None, None), |game| game.limits_left(now_millis()));
+            let elapsed = game.map(|game| game.elapsed(now_millis()));
+            return render_limits(writer, elapsed, limits_left, &context.theme.ui);
+        }
+        if std::mem::take(&mut self.clear) {
+            queue!(writer, Clear(ClearType::All))?;
+        }
+        if let Some(err) = &self.error {
+            render_game_state_error(writer, err, &context.theme.ui);
+        }
+
+        let undos_left = context.game.as_ref().and_then(Game::undos_left);
+        let transform = self.display_transform(context);
+        let available_moves: Vec<Direction> = match &context.game {
+            Some(game) => {
+                let moves = game.available_moves();
+                Direction::ALL
+                    .into_iter()
+                    .filter(|&pressed| moves.contains(&transform.board_direction(pressed)))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        let colors = &context.theme.ui;
+        render_everything_except_board(
+            writer,
+            &context.config.keys,
+            &available_moves,
+            undos_left,
+            colors,
+        )?;
+        render_auto_play_status(writer, self.auto_play.as_ref(), colors)?;
+        render_coach_message(
+            writer,
+            self.coach_message.as_deref(),
+            self.save_error.as_deref(),
+            colors,
+        )?;
+        render_unlocked_achievements(writer, &self.unlocked, colors)?;
+        if let Some(game) = context.game.as_ref() {
+            let view = game.view();
+            let board = transform.apply(self.slide_frames.first().unwrap_or(&view.board));
+            render_board(
+                writer,
+                &board,
+                game.rules().variant,
+                context.config.number_format,
+                &context.theme,
+            )?;
+            // the points are for the board after the slide, so they wait for it to end
+            if self.score_preview && self.slide_frames.is_empty() {
+                render_score_preview(writer, game, &board, transform, context)?;
+            }
+            let best_score = context
+                .statistics
+                .best_score_for(ScoreKind::of(game.rules()));
+            let mut segments: Vec<String> = context
+                .status_hooks
+                .iter()
+                .filter_map(|hook| hook.segment(game))
+                .collect();
+            if game.rules().adaptive {
+                segments.insert(
+                    0,
+                    format!(
+                        "Adaptive: {}% big tiles",
+                        game.spawn_weights().bigger_tile_percent()
+                    ),
+                );
+            }
+            render_score(
+                writer,
+                &segments,
+                view.score,
+                best_score.max(view.score),
+                context.config.number_format,
+                colors,
+            )?;
+        }
+        let moves_left = context.game.as_ref().map(Game::moves_until_stuck);
+        render_stuck_warning(writer, moves_left, colors)?;
+        let limits_left = context
+            .game
+            .as_ref()
+            .map_or((None, None), |game| game.limits_left(now_millis()));
+        let elapsed = context.game.as_ref().map(|game| game.elapsed(now_millis()));
+        render_limits(writer, elapsed, limits_left, colors)?;
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code: c,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+        // the player is back at the board, e.g. after continuing from the main menu
+        if let Some(game) = context.game.as_mut() {
+            game.resume(now_millis());
+        }
+
+        // P pauses too, unless it's bound to something else
+        let action = context
+            .config
+            .keys
+            .action(*c)
+            .or((*c == KeyCode::Char('p')).then_some(Action::Pause));
+        let game_event = match action {
+            Some(Action::MoveUp) => return self.play(Direction::Up, context),
+            Some(Action::MoveLeft) => return self.play(Direction::Left, context),
+            Some(Action::MoveRight) => return self.play(Direction::Right, context),
+            Some(Action::MoveDown) => return self.play(Direction::Down, context),
+            Some(Action::Restart) => GameEvent::NewGame,
+            Some(Action::Undo) => {
+                if context.game.as_ref().is_some_and(Game::undo_would_assist) {
+                    return Transition::Push(Box::new(UndoConfirmScreen::new(None)));
+                }
+                GameEvent::Undo
+            }
+            Some(Action::Redo) => GameEvent::Redo,
+            Some(Action::AutoPlay) => {
+                self.auto_play = match self.auto_play {
+                    Some(_) => None,
+                    None => Some(AutoPlay::new()),
+                };
+                return Transition::None;
+            }
+            Some(Action::Menu) => {
+                GameScreen::pause(context);
+                return Transition::Pop;
+            }
+            Some(Action::Pause) => {
+                GameScreen::pause(context);
+                return Transition::Push(Box::new(PauseScreen::new()));
+            }
+            Some(Action::RotateView) => {
+                self.rotated = !self.rotated;
+                return Transition::None;
+            }
+            Some(Action::ScorePreview) => {
+                self.score_preview = !self.score_preview;
+                self.clear = true;
+                return Transition::None;
+            }
+            None => return Transition::None,
+        };
+
+        self.coach_message = None;
+        self.apply(game_event, context)
+    }
+
+    fn frame_interval(&self, context: &AppContext) -> Option<Duration> {
+        if !self.slide_frames.is_empty() {
+            return Some(GameScreen::SLIDE_FRAME_INTERVAL);
+        }
+        let playing = context
+            .game
+            .as_ref()
+            .is_some_and(|game| matches!(game.state(), GameState::Playing | GameState::Won { .. }));
+        match &self.auto_play {
+            Some(_) => Some(AutoPlay::MOVE_INTERVAL),
+            // redraw every second so that the clocks count
+            None if playing => Some(Duration::from_secs(1)),
+            None => None,
+        }
+    }
+
+    fn tick(&mut self, context: &mut AppContext) -> Transition<W> {
+        if !self.slide_frames.is_empty() {
+            self.slide_frames.remove(0);
+            return Transition::None;
+        }
+        let timed_out = context
+            .game
+            .as_mut()
+            .is_some_and(|game| game.check_time_limit(now_millis()));
+        if timed_out {
+            let mut new_best_score = false;
+            if let Some(mut game) = context.game.take() {
+                new_best_score = self.record_statistics(&mut game, Some(Ending::GameOver), context);
+                context.game = Some(game);
+            }
+            self.auto_play = None;
+            return Transition::Push(Box::new(
+                EndScreen::new(Ending::GameOver).with_new_best_score(new_best_score),
+            ));
+        }
+        let (Some(auto_play), Some(game)) = (&mut self.auto_play, &context.game) else {
+            // only the clocks moved on
+            self.clock_only = context.pacing.is_throttled();
+            return Transition::None;
+        };
+
+        auto_play.evaluations = auto_play.solver.evaluate_legal_moves(game);
+        auto_play.chosen = auto_play
+            .evaluations
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|&(direction, _)| direction);
+        match auto_play.chosen {
+            Some(direction) => {
+                if let Some(game) = context.game.as_mut() {
+                    game.mark_assisted();
+                }
+                self.apply(direction.into(), context)
+            }
+            None => {
+                self.auto_play = None;
+                Transition::None
+            }
+        }
+    }
+}
+
+impl GameScreen {
+    /// Gets how the board is turned and flipped on the screen: the `board_view` setting, turned
+    /// once more if the player toggled the rotation.
+    fn display_transform(&self, context: &AppContext) -> DisplayTransform {
+        if self.rotated {
+            context.config.board_view.turned_clockwise()
+        } else {
+            context.config.board_view
+        }
+    }
+
+    /// Makes a move chosen by the player, letting the coach comment on it first and counting
+    /// whether it followed the coach's hint.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction pressed, as seen on the screen.
+    /// * `context` - State shared between all screens, where the game is stored.
+    fn play<W: io::Write>(
+        &mut self,
+        direction: Direction,
+        context: &mut AppContext,
+    ) -> Transition<W> {
+        let direction = self.display_transform(context).board_direction(direction);
+        let moves = context.game.as_ref().map(|game| game.view().moves);
+        let review = context
+            .game
+            .as_ref()
+            .and_then(|game| self.coach.review(context.config.coaching, game, direction));
+        self.coach_message = review.as_ref().and_then(|review| review.comment.clone());
+        // playing with the coach's hints counts as help, even if this move goes without comment
+        if let (Some(_), Some(game)) = (&review, context.game.as_mut()) {
+            game.mark_assisted();
+        }
+        if self.score_preview && context.config.score_preview_is_help {
+            if let Some(game) = context.game.as_mut() {
+                game.mark_assisted();
+            }
+        }
+
+        let transition = self.apply(direction.into(), context);
+        let moved = context.game.as_ref().map(|game| game.view().moves) > moves;
+        if let (Some(review), true) = (review, moved) {
+            context
+                .statistics
+                .hints
+                .record(review.followed_hint, review.loss);
+            self.save_statistics(context);
+        }
+        transition
+    }
+
+    /// Pauses the game while the player is away from the board, e.g. in the pause menu. It goes
+    /// on when the pause menu is closed or a key is pressed on the board.
+    fn pause(context: &mut AppContext) {
+        if let Some(game) = context.game.as_mut() {
+            game.pause(now_millis());
+        }
+    }
+
+    /// Tells the status bar hooks what happened to the game.
+    fn notify(context: &mut AppContext, notification: GameNotification) {
+        for hook in &mut context.status_hooks {
+            hook.notify(notification);
+        }
+    }
+
+    /// Updates the statistics after a move: marks the day as played, keeps track of the daily
+    /// goal, and counts the game if it just ended, posting it to the score webhook if there is
+    /// one. They are only saved when they change.
+    ///
+    /// # Returns
+    ///
+    /// Whether the game just ended with a new best score, which the end screen celebrates.
+    fn record_statistics(
+        &mut self,
+        game: &mut Game,
+        ending: Option<Ending>,
+        context: &mut AppContext,
+    ) -> bool {
+        let now = save::now();
+        // the highest tile ever and the daily goals count powers of two, which a Fibonacci tile
+        // of the same exponent isn't
+        let highest_tile = if game.rules().variant == Variant::Classic {
+            game.highest_tile()
+        } else {
+            0
+        };
+        let mut changed = context
+            .statistics
+            .record_play(stats::day_number(now), highest_tile);
+        let mut new_best_score = false;
+        if ending == Some(Ending::GameOver) {
+            if let Some(new_best) = record_finished_game(game, context) {
+                new_best_score = new_best;
+                changed = true;
+            }
+        }
+        if changed {
+            self.save_statistics(context);
+        }
+        new_best_score
+    }
+
+    /// Saves the statistics, showing why if it fails. Losing them isn't worth interrupting the
+    /// game over; the next change tries again.
+    fn save_statistics(&mut self, context: &mut AppContext) {
+        self.save_error = context
+            .statistics
+            .save()
+            .err()
+            .map(|e| format!("Couldn't save the statistics: {}", e));
+    }
+
+    /// Gets how a game has ended, if it has: lost, or won without going on in endless mode.
+    fn ending(game: &Game) -> Option<Ending> {
+        match game.state() {
+            GameState::Lost(_) => Some(Ending::GameOver),
+            GameState::Won { continued: false } => Some(Ending::Won),
+            _ => None,
+        }
+    }
+
+    /// Applies an event to the game in progress, showing the end screen if the game was won or
+    /// lost by it.
+    fn apply<W: io::Write>(
+        &mut self,
+        game_event: GameEvent,
+        context: &mut AppContext,
+    ) -> Transition<W> {
+        if let Some(game) = context.game.take() {
+            let moves_made = |game: &Game| game.forgotten_moves() + game.moves().len();
+            let (moves_before, score_before) = (moves_made(&game), game.score());
+            let ending_before = GameScreen::ending(&game);
+            match game.handle_event(game_event) {
+                Ok(update) => {
+                    self.unlocked = context.achievements.check(&update, save::now());
+                    if !self.unlocked.is_empty() {
+                        // like the statistics, losing these isn't worth interrupting the game
+                        self.save_error = context
+                            .achievements
+                            .save()
+                            .err()
+                            .map(|e| format!("Couldn't save the achievements: {}", e));
+                    }
+                    self.slide_frames = if context.animating() {
+                        update.slide_frames()
+                    } else {
+                        Vec::new()
+                    };
+                    let mut game = update.game;
+                    // a key pressed on a board that was already lost or won doesn't end the game
+                    // again, so it's only counted once
+                    let ending =
+                        GameScreen::ending(&game).filter(|&ending| ending_before != Some(ending));
+                    if let Ok(mut snapshots) = context.snapshots.lock() {
+                        snapshots.push(&game, save::now());
+                    }
+                    let new_best_score = self.record_statistics(&mut game, ending, context);
+                    match moves_made(&game).cmp(&moves_before) {
+                        cmp::Ordering::Greater => GameScreen::notify(
+                            context,
+                            GameNotification::Moved {
+                                points: game.score() - score_before,
+                            },
+                        ),
+                        cmp::Ordering::Less => {
+                            GameScreen::notify(context, GameNotification::Undone)
+                        }
+                        cmp::Ordering::Equal => {}
+                    }
+                    if ending.is_some() {
+                        GameScreen::notify(context, GameNotification::Ended);
+                    }
+                    context.game = Some(game);
+                    if let Some(ending) = ending {
+                        self.auto_play = None;
+                        self.slide_frames.clear();
+                        return Transition::Push(Box::new(
+                            EndScreen::new(ending).with_new_best_score(new_best_score),
+                        ));
+                    }
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+        Transition::None
+    }
+}
+
+/// Counts a game that is over, or that the player is leaving after winning, in the statistics,
+/// and posts it to the score webhook if there is one. A game that was counted already isn't
+/// counted again. The statistics aren't saved.
+///
+/// # Arguments
+///
+/// * `game` - The game, which is marked as counted.
+/// * `context` - State shared between all screens, with the statistics.
+///
+/// # Returns
+///
+/// Whether the game made a new best score, or `None` if it was counted already.
+pub fn record_finished_game(game: &mut Game, context: &mut AppContext) -> Option<bool> {
+    let new_best_score =
+        context
+            .statistics
+            .record_finished_game(game, context.session_started_at, save::now())?;
+    #[cfg(feature = "net")]
+    if let Some(url) = &context.config.score_webhook {
+        webhook::submit_in_background(url.clone(), ScoreSubmission::new(game));
+    }
+    Some(new_best_score)
+}
+
+/// Renders all elements on the screen except the game board.
+///
+/// This function renders the game controls in the status bar. The keys of moves that wouldn't do
+/// anything are greyed out. When undo is limited by the rules, the undos left are shown next to
+/// it, and it's greyed out once they run out.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `keys` - The key bindings to list.
+/// * `available_moves` - The directions, as seen on the screen, that would change the board.
+/// * `undos_left` - How many more moves may be undone, or `None` if there's no undo limit.
+/// * `colors` - The theme's interface colours, for the status bar.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_everything_except_board<W: io::Write>(
+    writer: &mut W,
+    keys: &KeyBindings,
+    available_moves: &[Direction],
+    undos_left: Option<u32>,
+    colors: &UiColors,
+) -> io::Result<()> {
+    let size = terminal::size()?;
+    let move_keys = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+    ]
+    .map(|action| keys.key(action));
+    let arrow_keys =
+        move_keys.map(|key| key.0) == [KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right];
+    let mut controls = vec![(" ".to_string(), colors.status_bar.foreground)];
+    for (index, (direction, key)) in Direction::ALL.into_iter().zip(move_keys).enumerate() {
+        let (label, separator) = match direction {
+            _ if !arrow_keys => (key.to_string(), "/"),
+            Direction::Up => ("↑".to_string(), ""),
+            Direction::Down => ("↓".to_string(), ""),
+            Direction::Left => ("←".to_string(), ""),
+            Direction::Right => ("→".to_string(), ""),
+        };
+        if index > 0 {
+            controls.push((separator.to_string(), colors.status_bar.foreground));
+        }
+        let color = if available_moves.contains(&direction) {
+            colors.status_bar.foreground
+        } else {
+            Color::DarkGrey
+        };
+        controls.push((label, color));
+    }
+    controls.push((": Merge".to_string(), colors.status_bar.foreground));
+    for action in [
+        Action::Undo,
+        Action::AutoPlay,
+        Action::Restart,
+        Action::Pause,
+        Action::Menu,
+    ] {
+        let mut control = format!("  {}: {}", keys.key(action), action.label());
+        let mut color = colors.status_bar.foreground;
+        if action == Action::Undo {
+            if let Some(undos_left) = undos_left {
+                control.push_str(&format!(" ({} left)", undos_left));
+                if undos_left == 0 {
+                    color = Color::DarkGrey;
+                }
+            }
+        }
+        controls.push((control, color));
+    }
+    let width: usize = controls.iter().map(|(text, _)| text.chars().count()).sum();
+    queue!(
+        writer,
+        cursor::MoveTo(0, size.1),
+        style::SetBackgroundColor(colors.status_bar.background),
+    )?;
+    for (text, color) in controls {
+        queue!(writer, style::SetForegroundColor(color), style::Print(text))?;
+    }
+    queue!(
+        writer,
+        style::Print(" ".repeat((size.0 as usize).saturating_sub(width))),
+        style::ResetColor
+    )?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Renders the current score and the best score on the right side of the status bar, after the
+/// segments of the status bar hooks.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `segments` - The text of each status bar hook that has something to show.
+/// * `score` - The score to show.
+/// * `best_score` - The best score of any game played by the same kind of rules, including this
+///   one.
+/// * `number_format` - How to separate thousands in the score.
+/// * `colors` - The theme's interface colours, for the status bar.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_score<W: io::Write>(
+    writer: &mut W,
+    segments: &[String],
+    score: u32,
+    best_score: u32,
+    number_format: NumberFormat,
+    colors: &UiColors,
+) -> io::Result<()> {
+    let size = terminal::size()?;
+    let score = segments
+        .iter()
+        .map(|segment| format!("{}  ", segment))
+        .chain(std::iter::once(format!(
+            "Best: {}  Score: {} ",
+            number_format.format(best_score as u64),
+            number_format.format(score as u64)
+        )))
+        .collect::<String>();
+    queue!(
+        writer,
+        cursor::MoveTo(size.0.saturating_sub(score.chars().count() as u16), size.1),
+        style::SetBackgroundColor(colors.status_bar.background),
+        style::SetForegroundColor(colors.status_bar.foreground),
+        style::Print(score),
+        style::ResetColor
+    )?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Warns in the top right corner when the board is close to locking up, or clears the warning.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `moves_left` - The estimate from `Game::moves_until_stuck`, or `None` if
+///   there's no game.
+/// * `colors` - The theme's interface colours, for the warning.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_stuck_warning<W: io::Write>(
+    writer: &mut W,
+    moves_left: Option<u32>,
+    colors: &UiColors,
+) -> io::Result<()> {
+    /// The warning is shown once the estimate drops to this many moves.
+    const WARN_AT: u32 = 3;
+    const WARNING_WIDTH: usize = 32;
+
+    let warning = match moves_left {
+        Some(moves_left @ 1..=WARN_AT) => format!(
+            "Board nearly full: ~{} move{} left",
+            moves_left,
+            if moves_left == 1 { "" } else { "s" }
+        ),
+        _ => String::new(),
+    };
+    let size = terminal::size()?;
+    queue!(
+        writer,
+        cursor::MoveTo(size.0.saturating_sub(WARNING_WIDTH as u16 + 1), 0),
+        style::SetForegroundColor(colors.error),
+        style::Print(format!("{:>width$}", warning, width = WARNING_WIDTH)),
+        style::ResetColor
+    )?;
+    Ok(())
+}
+
+/// Renders how long the game has been played and how much of its move and time limits is left
+/// in the top right corner, below the stuck warning, or clears it if there's no game.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `elapsed` - How long the game has been played, as given by `Game::elapsed`.
+/// * `limits_left` - The moves and seconds left, as given by `Game::limits_left`.
+/// * `colors` - The theme's interface colours.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_limits<W: io::Write>(
+    writer: &mut W,
+    elapsed: Option<Duration>,
+    limits_left: (Option<u32>, Option<u64>),
+    colors: &UiColors,
+) -> io::Result<()> {
+    const LIMITS_WIDTH: usize = 44;
+
+    let (moves_left, seconds_left) = limits_left;
+    let parts: Vec<String> = [
+        elapsed.map(|elapsed| format!("Time: {}", rules::format_duration(elapsed.as_secs()))),
+        moves_left.map(|moves| format!("Moves left: {}", moves)),
+        seconds_left.map(|seconds| format!("Time left: {}", rules::format_duration(seconds))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let size = terminal::size()?;
+    queue!(
+        writer,
+        cursor::MoveTo(size.0.saturating_sub(LIMITS_WIDTH as u16 + 1), 1),
+        style::SetForegroundColor(colors.text.foreground),
+        style::Print(format!(
+            "{:>width$}",
+            parts.join("  "),
+            width = LIMITS_WIDTH
+        )),
+        style::ResetColor
+    )?;
+    Ok(())
+}
+
+/// Renders what the coach said about the last move on the line above the status bar, or clears
+/// that line if it said nothing. A failed save is shown there instead, since it matters more.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `message` - The coach's comment, if any.
+/// * `save_error` - Why the last save failed, if it did.
+/// * `colors` - The theme's interface colours.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_coach_message<W: io::Write>(
+    writer: &mut W,
+    message: Option<&str>,
+    save_error: Option<&str>,
+    colors: &UiColors,
+) -> io::Result<()> {
+    let size = terminal::size()?;
+    let (line, color) = match (save_error, message) {
+        (Some(error), _) => (format!(" {}", error), colors.error),
+        (None, message) => (
+            message.map_or(String::new(), |message| format!(" Coach: {}", message)),
+            colors.highlight,
+        ),
+    };
+    queue!(
+        writer,
+        cursor::MoveTo(0, size.1.saturating_sub(1)),
+        style::SetForegroundColor(color),
+        style::Print(format!("{:<width$}", line, width = size.0 as usize)),
+        style::ResetColor
+    )?;
+    Ok(())
+}
+
+/// Renders the achievements the last move unlocked at the top of the screen, or clears them. It's
+/// drawn before the stuck warning so that the warning wins on a narrow terminal.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `unlocked` - The achievements to announce, or none to clear the announcement.
+/// * `colors` - The theme's interface colours.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_unlocked_achievements<W: io::Write>(
+    writer: &mut W,
+    unlocked: &[Achievement],
+    colors: &UiColors,
+) -> io::Result<()> {
+    const LINE_WIDTH: usize = 60;
+
+    let names: Vec<&str> = unlocked
+        .iter()
+        .map(|achievement| achievement.name())
+        .collect();
+    let line = match names.len() {
+        0 => String::new(),
+        1 => format!("Achievement unlocked: {}", names[0]),
+        _ => format!("Achievements unlocked: {}", names.join(", ")),
+    };
+    let size = terminal::size()?;
+    let x = (size.0 as usize).saturating_sub(LINE_WIDTH) / 2;
+    queue!(
+        writer,
+        cursor::MoveTo(x as u16, 0),
+        style::SetForegroundColor(colors.highlight),
+        style::Print(get_padded_string(&line, LINE_WIDTH)),
+        style::ResetColor
+    )?;
+    Ok(())
+}
+
+/// Renders what the solver thought of each move while it auto-plays, in the top left corner.
+///
+/// Each possible move gets a bar scaled between the worst and best evaluation, and the move that
+/// was played is highlighted. Moves that wouldn't change the board are shown without a bar. This
+/// is drawn before the board so that the board wins if they overlap on a small terminal.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `auto_play` - The solver's state, or `None` to erase the status after auto-play stops.
+/// * `colors` - The theme's interface colours.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_auto_play_status<W: io::Write>(
+    writer: &mut W,
+    auto_play: Option<&AutoPlay>,
+    colors: &UiColors,
+) -> io::Result<()> {
+    const BAR_WIDTH: usize = 10;
+    const STATUS_WIDTH: usize = BAR_WIDTH + 17;
+
+    let Some(auto_play) = auto_play else {
+        for y in 1..=3 + Direction::ALL.len() as u16 {
+            queue!(
+                writer,
+                cursor::MoveTo(1, y),
+                style::Print(" ".repeat(STATUS_WIDTH))
+            )?;
+        }
+        return Ok(());
+    };
+
+    let values = auto_play.evaluations.iter().map(|&(_, value)| value);
+    let worst = values.clone().fold(f64::INFINITY, f64::min);
+    let best = values.fold(f64::NEG_INFINITY, f64::max);
+
+    queue!(
+        writer,
+        cursor::MoveTo(1, 1),
+        style::SetForegroundColor(colors.text.foreground),
+        style::Print("Auto-playing (A to stop)"),
+    )?;
+    for (index, direction) in Direction::ALL.into_iter().enumerate() {
+        let (arrow, name) = match direction {
+            Direction::Up => ('↑', "Up"),
+            Direction::Down => ('↓', "Down"),
+            Direction::Left => ('←', "Left"),
+            Direction::Right => ('→', "Right"),
+        };
+        let evaluation = auto_play
+            .evaluations
+            .iter()
+            .find(|&&(candidate, _)| candidate == direction)
+            .map(|&(_, value)| value);
+        let bar = match evaluation {
+            Some(value) => {
+                let fraction = if best > worst {
+                    (value - worst) / (best - worst)
+                } else {
+                    1.0
+                };
+                let filled = 1 + (fraction * (BAR_WIDTH - 1) as f64).round() as usize;
+                format!(
+                    "{}{} {:>8.1}",
+                    "█".repeat(filled),
+                    " ".repeat(BAR_WIDTH - filled),
+                    value
+                )
+            }
+            None => format!("{:<width$}", "-", width = BAR_WIDTH + 9),
+        };
+        queue!(
+            writer,
+            cursor::MoveTo(1, 3 + index as u16),
+            style::SetForegroundColor(if auto_play.chosen == Some(direction) {
+                colors.highlight
+            } else {
+                colors.text.foreground
+            }),
+            style::Print(format!("{} {:<6}{}", arrow, name, bar)),
+        )?;
+    }
+    queue!(writer, style::ResetColor)?;
+
+    Ok(())
+}
+
+/// Renders the game board on the terminal.
+///
+/// This function renders the game board, including tiles and borders, on the terminal.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `game_state` - The rows of the board to draw.
+/// * `variant` - Which sequence the tiles count through, for the values written on them.
+/// * `number_format` - How to separate thousands in large tile values. If the board is too wide
+///   for the terminal with every digit, the values are shortened instead, e.g. to "131k".
+/// * `theme` - The colours of the tiles.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+pub(super) fn render_board<W: io::Write>(
+    writer: &mut W,
+    game_state: &[Vec<TileType>],
+    variant: Variant,
+    number_format: NumberFormat,
+    theme: &Theme,
+) -> io::Result<()> {
+    render_board_in(
+        writer,
+        Rect::terminal()?,
+        game_state,
+        variant,
+        number_format,
+        theme,
+    )
+}
+
+/// Renders a game board centered in part of the terminal, e.g. one half of a split screen.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `area` - Where on the terminal the board goes. Its values are shortened if the board is
+///   too wide for it with every digit.
+/// * `game_state` - The rows of the board to draw.
+/// * `variant` - Which sequence the tiles count through, for the values written on them.
+/// * `number_format` - How to separate thousands in large tile values.
+/// * `theme` - The colours of the tiles.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+pub(super) fn render_board_in<W: io::Write>(
+    writer: &mut W,
+    area: Rect,
+    game_state: &[Vec<TileType>],
+    variant: Variant,
+    number_format: NumberFormat,
+    theme: &Theme,
+) -> io::Result<()> {
+    let layout = BoardLayout::new(area, game_state, variant, number_format);
+    let Some(board) = layout.area else {
+        return layout::render_too_small(writer, area);
+    };
+    for (index, row) in board_rows(game_state, layout.cell_width, &layout.value, theme)
+        .iter()
+        .enumerate()
+    {
+        queue!(
+            writer,
+            cursor::MoveTo(board.x, board.y + index as u16),
+            style::Print(row),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// How a board is drawn in part of the terminal.
+struct BoardLayout {
+    /// Writes the value of a tile, shortened if the board is too wide with every digit.
+    value: Box<dyn Fn(TileType) -> String>,
+    /// The width of each cell, including spaces.
+    cell_width: usize,
+    /// Where the board goes, or `None` if it doesn't fit.
+    area: Option<Rect>,
+}
+
+impl BoardLayout {
+    /// Works out how a board is drawn in part of the terminal.
+    ///
+    /// # Arguments
+    ///
+    /// * `area` - Where on the terminal the board goes.
+    /// * `game_state` - The rows of the board to draw.
+    /// * `variant` - Which sequence the tiles count through, for the values written on them.
+    /// * `number_format` - How to separate thousands in large tile values.
+    fn new(
+        area: Rect,
+        game_state: &[Vec<TileType>],
+        variant: Variant,
+        number_format: NumberFormat,
+    ) -> BoardLayout {
+        let max_item_length = |value: &dyn Fn(TileType) -> String| {
+            game_state
+                .iter()
+                .flatten()
+                .map(|&tile| value(tile).chars().count())
+                .fold(1, cmp::max)
+        };
+
+        let grid_width = game_state[0].len();
+        let mut value: Box<dyn Fn(TileType) -> String> =
+            Box::new(move |tile| variant.display_value(tile, number_format));
+        if (max_item_length(&value) + 3) * grid_width + 1 > area.width as usize {
+            value = Box::new(move |tile| variant.short_value(tile));
+        }
+        let cell_width = max_item_length(&value) + 2; // add two for a space on each side
+
+        let board_height = game_state.len() * 4 + 1; // in rows
+        let board_width = (cell_width + 1) * grid_width + 1; // in columns
+        BoardLayout {
+            value,
+            cell_width,
+            area: layout::center_rect(area, (board_width as u16, board_height as u16)),
+        }
+    }
+}
+
+/// Renders the points each move would score next to the edge of the board the tiles would move
+/// towards, e.g. "+16" above the board for Up, so that beginners can see how scoring works. The
+/// moves that score the most are highlighted, and moves that wouldn't change the board get a
+/// dash.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `game` - The game whose moves are previewed.
+/// * `board` - The rows of the board as drawn on the screen, to find its edges.
+/// * `transform` - How the board is turned and flipped on the screen, so that each number is
+///   next to the edge the arrow key for it points at.
+/// * `context` - State shared between all screens, for the number format and colours.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_score_preview<W: io::Write>(
+    writer: &mut W,
+    game: &Game,
+    board: &[Vec<TileType>],
+    transform: DisplayTransform,
+    context: &AppContext,
+) -> io::Result<()> {
+    let number_format = context.config.number_format;
+    let screen = Rect::terminal()?;
+    let Some(area) = BoardLayout::new(screen, board, game.rules().variant, number_format).area
+    else {
+        return Ok(());
+    };
+    let previews = Direction::ALL.map(|pressed| game.peek_move(transform.board_direction(pressed)));
+    let most_points = previews
+        .iter()
+        .filter(|preview| preview.changes_board)
+        .map(|preview| preview.points)
+        .max()
+        .unwrap_or(0);
+
+    for (pressed, preview) in Direction::ALL.into_iter().zip(previews) {
+        let label = if preview.changes_board {
+            format!("+{}", number_format.format(preview.points as u64))
+        } else {
+            "-".to_string()
+        };
+        let width = label.chars().count() as u16;
+        let position = match pressed {
+            Direction::Up => area
+                .y
+                .checked_sub(1)
+                .map(|y| (area.x + area.width.saturating_sub(width) / 2, y)),
+            Direction::Down => Some((
+                area.x + area.width.saturating_sub(width) / 2,
+                area.bottom() + 1,
+            )),
+            Direction::Left => area
+                .x
+                .checked_sub(width + 1)
+                .map(|x| (x, area.y + area.height / 2)),
+            Direction::Right => Some((area.right() + 2, area.y + area.height / 2)),
+        };
+        // labels that would go off the screen are left out rather than drawn over the board
+        let Some((x, y)) =
+            position.filter(|&(x, y)| x + width <= screen.width && y < screen.height)
+        else {
+            continue;
+        };
+        let color = if preview.changes_board && preview.points == most_points && most_points > 0 {
+            context.theme.ui.highlight
+        } else {
+            context.theme.ui.text.foreground
+        };
+        queue!(
+            writer,
+            cursor::MoveTo(x, y),
+            style::SetForegroundColor(color),
+            style::Print(label),
+        )?;
+    }
+    queue!(writer, style::ResetColor)?;
+
+    Ok(())
+}
+
+/// Draws a board as ANSI art, e.g. into a file, that shows the board when printed to a terminal
+/// with `cat`, since every row is on its own line rather than placed with cursor movements.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor to write the art to.
+/// * `game_state` - The rows of the board to draw.
+/// * `variant` - Which sequence the tiles count through, for the values written on them.
+/// * `number_format` - How to separate thousands in tile values.
+/// * `theme` - The colours of the tiles.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+pub(super) fn write_board_art<W: io::Write>(
+    writer: &mut W,
+    game_state: &[Vec<TileType>],
+    variant: Variant,
+    number_format: NumberFormat,
+    theme: &Theme,
+) -> io::Result<()> {
+    let value = |tile| variant.display_value(tile, number_format);
+    let cell_width = game_state
+        .iter()
+        .flatten()
+        .map(|&tile| value(tile).chars().count())
+        .fold(1, cmp::max)
+        + 2; // add two for a space on each side
+    for row in board_rows(game_state, cell_width, &value, theme) {
+        queue!(writer, style::Print(row))?;
+    }
+    queue!(writer, style::ResetColor)?;
+    writer.flush()
+}
+
+/// Creates the rows of text that draw a board, from its top border to its bottom border, each
+/// ending in a newline.
+///
+/// # Arguments
+///
+/// * `game_state` - The rows of the board to draw.
+/// * `cell_width` - The width of each cell, including spaces.
+/// * `value` - Writes the value of a tile.
+/// * `theme` - The colours of the tiles.
+fn board_rows(
+    game_state: &[Vec<TileType>],
+    cell_width: usize,
+    value: &dyn Fn(TileType) -> String,
+    theme: &Theme,
+) -> Vec<String> {
+    let grid_width = game_state[0].len();
+    let borders = Borders::current();
+    let vertical = borders.vertical;
+
+    let mut rows = vec![create_constant_row(
+        grid_width,
+        cell_width,
+        borders.top_left,
+        borders.top_join,
+        borders.top_right,
+        borders.horizontal,
+    )];
+    for (index, row) in game_state.iter().enumerate() {
+        rows.push(create_data_row_without_text(
+            cell_width, vertical, vertical, vertical, row, theme,
+        ));
+        rows.push(create_data_row(
+            cell_width, vertical, vertical, vertical, row, value, theme,
+        ));
+        rows.push(create_data_row_without_text(
+            cell_width, vertical, vertical, vertical, row, theme,
+        ));
+        rows.push(if index + 1 == game_state.len() {
+            create_constant_row(
+                grid_width,
+                cell_width,
+                borders.bottom_left,
+                borders.bottom_join,
+                borders.bottom_right,
+                borders.horizontal,
+            )
+        } else {
+            create_constant_row(
+                grid_width,
+                cell_width,
+                borders.left_join,
+                borders.cross,
+                borders.right_join,
+                borders.horizontal,
+            )
+        });
+    }
+    rows
+}
+
+/// Creates a constant row of text for the grid with specified formatting.
+///
+/// This function generates a row of text with a specified number of cells, each cell having a
+/// specified width and containing the same filler character. The row is formatted with opening,
+/// joining, and closing characters.
+///
+/// # Arguments
+///
+/// - `number_of_cells`: The number of cells in the row.
+/// - `cell_width`: The width of each cell, including spaces.
+/// - `opening_char`: The character used at the beginning of the row.
+/// - `joining_char`: The character used to join cells within the row.
+/// - `closing_char`: The character used at the end of the row.
+/// - `filler_char`: The character used to fill each cell.
+///
+/// # Returns
+///
+/// A `String` containing the generated row of text.
+///
+fn create_constant_row(
+    number_of_cells: usize,
+    cell_width: usize,
+    opening_char: char,
+    joining_char: char,
+    closing_char: char,
+    filler_char: char,
+) -> String {
+    format!(
+        "{}{}{}\n",
+        opening_char,
+        (0..number_of_cells)
+            .map(|_| filler_char.to_string().repeat(cell_width))
+            .collect::<Vec<String>>()
+            .join(joining_char.to_string().as_str()),
+        closing_char
+    )
+}
+
+/// Creates a row of text with data for the game board.
+///
+/// This function formats the provided data to match how it is shown on the screen, including
+/// setting the correct padding, colours, and tile separators.
+///
+/// # Arguments
+///
+/// * `cell_width` - The width of each cell, including spaces.
+/// * `opening_char` - The character used at the beginning of the row.
+/// * `joining_char` - The character used to join cells within the row.
+/// * `closing_char` - The character used at the end of the row.
+/// * `data` - A slice containing the tile data to be displayed in the row.
+/// * `value` - Writes the value of a tile, e.g. with `tile::display_value`.
+/// * `theme` - The colours of the tiles.
+///
+/// # Returns
+///
+/// A `String` containing the generated row of text.
+fn create_data_row(
+    cell_width: usize,
+    opening_char: char,
+    joining_char: char,
+    closing_char: char,
+    data: &[TileType],
+    value: &dyn Fn(TileType) -> String,
+    theme: &Theme,
+) -> String {
+    format!(
+        "{}{}{}\n",
+        opening_char.white().on_black(),
+        data.iter()
+            .map(|&tile| {
+                format_tile_for_display_with_number(tile, &value(tile), cell_width, theme)
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join(joining_char.white().on_black().to_string().as_str()),
+        closing_char.white().on_black()
+    )
+}
+
+/// Creates a row of text with data for the game board without displaying tile numbers.
+///
+/// This function is identical to create_data_row, except that it replaces the numbers in each tile with spaces.
+///
+/// # Arguments
+///
+/// * `cell_width` - The width of each cell, including spaces.
+/// * `opening_char` - The character used at the beginning of the row.
+/// * `joining_char` - The character used to join cells within the row.
+/// * `closing_char` - The character used at the end of the row.
+/// * `data` - A slice containing the tile data to be displayed in the row.
+/// * `theme` - The colours of the tiles.
+///
+/// # Returns
+///
+/// A `String` containing the generated row of text.
+fn create_data_row_without_text(
+    cell_width: usize,
+    opening_char: char,
+    joining_char: char,
+    closing_char: char,
+    data: &[TileType],
+    theme: &Theme,
+) -> String {
+    format!(
+        "{}{}{}\n",
+        opening_char.white().on_black(),
+        data.iter()
+            .map(
+                |&tile| format_tile_for_display_without_number(tile, cell_width, theme).to_string()
+            )
+            .collect::<Vec<String>>()
+            .join(joining_char.white().on_black().to_string().as_str()),
+        closing_char.white().on_black()
+    )
+}
+
+/// Formats a tile for display on the game board. This function does not print the number for the tile.
+/// It sets the background colour depending on the value of the tile.
+///
+/// # Arguments
+///
+/// * `tile` - The tile value (TileType) to be formatted.
+/// * `cell_width` - The width of the cell, including spaces.
+/// * `theme` - The colours of the tiles.
+///
+/// # Returns
+///
+/// A `StyledContent` containing the tile formatted for display without the tile number.
+fn format_tile_for_display_without_number(
+    tile: TileType,
+    cell_width: usize,
+    theme: &Theme,
+) -> StyledContent<String> {
+    " ".repeat(cell_width).on(theme.tile_style(tile).background)
+}
+
+/// Formats a tile for display on the game board including the tile number.
+///
+/// This function formats a tile to be displayed on the game board, including the tile number.
+///
+/// # Arguments
+///
+/// * `tile` - The tile value (TileType) to be formatted.
+/// * `number_as_string` - The value written on the tile, e.g. from `tile::display_value`.
+/// * `cell_width` - The width of the cell, including spaces.
+/// * `theme` - The colours of the tiles.
+///
+/// # Returns
+///
+/// A `StyledContent` containing the tile formatted for display with the tile number.
+pub(super) fn format_tile_for_display_with_number(
+    tile: TileType,
+    number_as_string: &str,
+    cell_width: usize,
+    theme: &Theme,
+) -> StyledContent<String> {
+    let number_length = number_as_string.chars().count();
+    let spaces_before = cell_width.saturating_sub(number_length) / 2;
+    let spaces_after = cell_width.saturating_sub(number_length) - spaces_before; // subtract here because spaces_before and spaces_after aren't equal if cell_width - item length is odd, and want all cells to be consistent width
+    let padded_string = format!(
+        "{}{}{}",
+        " ".repeat(spaces_before),
+        number_as_string,
+        " ".repeat(spaces_after)
+    );
+    let style = theme.tile_style(tile);
+    padded_string.with(style.foreground).on(style.background)
+}
+
+/// Renders the error state and exits the program.
+///
+/// This function renders the error message and terminates the program.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `e` - A reference to the `GameError` containing the error information.
+/// * `colors` - The theme's interface colours, for the error.
+fn render_game_state_error<W: io::Write>(writer: &mut W, e: &GameError, colors: &UiColors) -> ! {
+    // this function always exits the program anyway, so if printing the error fails
+    // we just panic
+    queue!(
+        writer,
+        Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        style::SetForegroundColor(colors.error),
+        style::Print("Cannot continue the game. Error: "),
+    )
+    .unwrap();
+    let substrings: Vec<String> = format!("{:#?}", e)
+        .split('\n')
+        .map(|s| s.to_string())
+        .collect();
+    for str in substrings {
+        queue!(writer, cursor::MoveDown(1), style::Print(str)).unwrap();
+    }
+    queue!(
+        writer,
+        cursor::MoveDown(1),
+        style::Print("Press any key to exit the game.")
+    )
+    .unwrap();
+    writer.flush().unwrap();
+
+    loop {
+        match event::read() {
+            Ok(Event::Key(KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            })) => {
+                writer
+                    .execute(terminal::LeaveAlternateScreen)
+                    .expect("Couldn't leave alternate screen buffer");
+                exit(1);
+            }
+            Ok(_) => {}
+            // the terminal went away, so no key will ever come
+            Err(_) => exit(1),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::game::Game;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// Sums up the game that just ended: the tiles its merges made, the best move, the undos, and
+/// which way the moves went.
+pub struct GameSummaryScreen;
+
+impl GameSummaryScreen {
+    pub fn new() -> GameSummaryScreen {
+        GameSummaryScreen
+    }
+
+    /// Gets the lines of the summary.
+    fn lines(game: &Game, context: &AppContext) -> Vec<String> {
+        let number_format = context.config.number_format;
+        let stats = game.stats();
+        let moves: u32 = stats.directions.iter().sum();
+        let directions: Vec<String> = stats
+            .directions_by_use()
+            .into_iter()
+            .map(|(direction, count)| format!("{} {}", direction.name(), count))
+            .collect();
+        let mut lines = vec![
+            format!("Moves: {} ({})", moves, directions.join(", ")),
+            format!(
+                "Best Move: {} points",
+                number_format.format(stats.best_move_points as u64)
+            ),
+            format!("Undos: {}", stats.undos),
+            String::new(),
+            format!("Merges: {}", stats.total_merges()),
+        ];
+        // the biggest tiles are the most interesting, so they come first
+        lines.extend(stats.merges.iter().rev().map(|(&tile, count)| {
+            format!(
+                "  {} made {} time{}",
+                game.rules().variant.display_value(tile, number_format),
+                count,
+                if *count == 1 { "" } else { "s" }
+            )
+        }));
+        lines
+    }
+}
+
+impl<W: io::Write> Screen<W> for GameSummaryScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let mut lines = match &context.game {
+            Some(game) => GameSummaryScreen::lines(game, context),
+            None => vec!["There's no game to sum up.".to_string()],
+        };
+        lines.push(String::new());
+        lines.push("Esc: Back".to_string());
+
+        let screen = Rect::terminal()?;
+        let width = lines.iter().map(|line| line.chars().count()).max();
+        let Some(area) =
+            layout::center_rect(screen, (width.unwrap_or(0) as u16, lines.len() as u16 + 2))
+        else {
+            return layout::render_too_small(writer, screen);
+        };
+
+        queue!(
+            writer,
+            Clear(ClearType::All),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            cursor::MoveTo(0, area.y),
+            style::Print(get_padded_string("Game Summary", screen.width as usize)),
+        )?;
+        for (index, line) in lines.iter().enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(area.x, area.y + 2 + index as u16),
+                style::Print(line),
+            )?;
+        }
+        queue!(writer, style::ResetColor)?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, _context: &mut AppContext) -> Transition<W> {
+        match event {
+            Event::Key(KeyEvent {
+                code: KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'),
+                kind: KeyEventKind::Press,
+                ..
+            }) => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::save;
+use crate::stats::{FinishedGame, ScoreKind};
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// Lists the best games played, one kind of rules at a time, since e.g. a bigger board or
+/// Fibonacci tiles score very differently. Games the player had help with are marked with an
+/// asterisk, and can be hidden so that the scores of games played unaided can be compared.
+pub struct HighScoresScreen {
+    include_assisted: bool,
+    /// Which of the kinds of rules with games on the table is shown.
+    kind_index: usize,
+}
+
+impl HighScoresScreen {
+    pub fn new() -> HighScoresScreen {
+        HighScoresScreen {
+            include_assisted: true,
+            kind_index: 0,
+        }
+    }
+
+    /// Gets the kind of rules whose games are shown, which is the usual game until there are
+    /// games of another kind.
+    fn kind(&self, context: &AppContext) -> ScoreKind {
+        let kinds = context.statistics.high_score_kinds();
+        kinds
+            .get(self.kind_index % kinds.len().max(1))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Gets the lines of the table, starting with its heading.
+    fn table(games: &[FinishedGame], context: &AppContext) -> Vec<String> {
+        let number_format = context.config.number_format;
+        let rows: Vec<[String; 5]> = games
+            .iter()
+            .enumerate()
+            .map(|(index, game)| {
+                let marker = if game.is_unaided() { " " } else { "*" };
+                let mode = if game.adaptive { "Adaptive" } else { "" }.to_string();
+                [
+                    format!("{}.", index + 1),
+                    format!("{}{}", number_format.format(game.score as u64), marker),
+                    game.variant.display_value(game.highest_tile, number_format),
+                    mode,
+                    save::describe_age(game.finished_at, save::now()),
+                ]
+            })
+            .collect();
+        let heading = [
+            String::new(),
+            "Score ".to_string(),
+            "Tile".to_string(),
+            "Mode".to_string(),
+            "Played".to_string(),
+        ];
+        let widths: Vec<usize> = (0..heading.len())
+            .map(|column| {
+                std::iter::once(&heading)
+                    .chain(&rows)
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        std::iter::once(&heading)
+            .chain(&rows)
+            .map(|row| {
+                format!(
+                    "{:>w0$}  {:>w1$}  {:>w2$}  {:<w3$}  {}",
+                    row[0],
+                    row[1],
+                    row[2],
+                    row[3],
+                    row[4],
+                    w0 = widths[0],
+                    w1 = widths[1],
+                    w2 = widths[2],
+                    w3 = widths[3],
+                )
+            })
+            .collect()
+    }
+}
+
+impl<W: io::Write> Screen<W> for HighScoresScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let kind = self.kind(context);
+        let games = context.statistics.high_scores(kind, self.include_assisted);
+        let mut lines = if games.is_empty() {
+            vec!["No games played unaided yet.".to_string()]
+        } else {
+            HighScoresScreen::table(&games, context)
+        };
+        if games.iter().any(|game| !game.is_unaided()) {
+            lines.push(String::new());
+            lines.push("* assisted by undo, hints or auto-play, or adaptive".to_string());
+        }
+        lines.push(String::new());
+        let mut controls = if self.include_assisted {
+            "A: Hide Assisted".to_string()
+        } else {
+            "A: Show Assisted".to_string()
+        };
+        if context.statistics.high_score_kinds().len() > 1 {
+            controls.push_str("  M: Other Rules");
+        }
+        controls.push_str("  Esc: Back");
+        lines.push(controls);
+
+        let screen = Rect::terminal()?;
+        let width = lines.iter().map(|line| line.chars().count()).max();
+        let Some(area) =
+            layout::center_rect(screen, (width.unwrap_or(0) as u16, lines.len() as u16 + 2))
+        else {
+            return layout::render_too_small(writer, screen);
+        };
+
+        queue!(
+            writer,
+            Clear(ClearType::All),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            cursor::MoveTo(0, area.y),
+            style::Print(get_padded_string(
+                &format!("High Scores: {}", kind.name()),
+                screen.width as usize
+            )),
+        )?;
+        // the table is left-aligned so that its columns line up
+        for (index, line) in lines.iter().enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(area.x, area.y + 2 + index as u16),
+                style::Print(line),
+            )?;
+        }
+        queue!(writer, style::ResetColor)?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        match code {
+            KeyCode::Char('a') => {
+                self.include_assisted = !self.include_assisted;
+                Transition::None
+            }
+            KeyCode::Char('m') => {
+                let kinds = context.statistics.high_score_kinds().len().max(1);
+                self.kind_index = (self.kind_index + 1) % kinds;
+                Transition::None
+            }
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+}
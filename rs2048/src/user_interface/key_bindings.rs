@@ -0,0 +1,183 @@
+use crate::keymap::{Action, Key, KeyBindings};
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// How many columns the action names are padded to, so the keys line up.
+const LABEL_WIDTH: usize = 14;
+
+/// How many columns the key names are padded to, so that shorter names fully replace longer ones
+/// when the list is redrawn.
+const KEY_WIDTH: usize = 12;
+
+/// What the key bindings screen is waiting for.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Mode {
+    /// Choosing an action to rebind.
+    Browsing,
+    /// Waiting for the new key for the selected action.
+    Listening,
+    /// The pressed key belongs to another action. Pressing it again swaps the two.
+    Conflict(Key, Action),
+}
+
+/// Lists the actions and their keys, and rebinds the selected action to the next key pressed.
+/// Keys already used by another action are only taken after confirming the swap, and every change
+/// is saved to the config file.
+pub struct KeyBindingsScreen {
+    selected_index: usize,
+    mode: Mode,
+    message: Option<String>,
+}
+
+impl KeyBindingsScreen {
+    pub fn new() -> KeyBindingsScreen {
+        KeyBindingsScreen {
+            selected_index: 0,
+            mode: Mode::Browsing,
+            message: None,
+        }
+    }
+
+    /// Gets the hint shown below the list for the current mode.
+    fn hint(&self) -> String {
+        let action = Action::ALL[self.selected_index];
+        match self.mode {
+            Mode::Browsing => "Enter: Rebind  D: Reset to Defaults  Esc: Back".to_string(),
+            Mode::Listening => format!("Press a key for {} (Esc: Cancel)", action.label()),
+            Mode::Conflict(key, other) => format!(
+                "{} is used for {}. Press it again to swap, or press another key.",
+                key,
+                other.label()
+            ),
+        }
+    }
+
+    /// Handles a key pressed while waiting for a new key.
+    fn listen(&mut self, code: KeyCode, context: &mut AppContext) {
+        let action = Action::ALL[self.selected_index];
+        // going through the name turns e.g. a shifted letter into the key itself
+        let Some(key) = Key(code).name().and_then(|name| Key::from_name(&name)) else {
+            self.message = Some("That key can't be bound.".to_string());
+            return;
+        };
+
+        let keys = &mut context.config.keys;
+        match (self.mode, keys.bind(action, key)) {
+            (_, Ok(())) => self.save(context),
+            (Mode::Conflict(conflicting_key, _), Err(_)) if conflicting_key.matches(code) => {
+                keys.swap(action, key);
+                self.save(context);
+            }
+            (_, Err(other)) => self.mode = Mode::Conflict(key, other),
+        }
+    }
+
+    /// Saves the key bindings to the config file and goes back to browsing.
+    fn save(&mut self, context: &AppContext) {
+        self.mode = Mode::Browsing;
+        self.message = context
+            .config
+            .save()
+            .err()
+            .map(|e| format!("Couldn't save the key bindings: {}", e));
+    }
+}
+
+impl<W: io::Write> Screen<W> for KeyBindingsScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let screen = Rect::terminal()?;
+        let list_width = (LABEL_WIDTH + KEY_WIDTH + 2) as u16;
+        let list_height = Action::ALL.len() as u16 + 4;
+        let Some(list) = layout::center_rect(screen, (list_width, list_height)) else {
+            return layout::render_too_small(writer, screen);
+        };
+        let (left_x, top_y) = (list.x, list.y);
+
+        queue!(
+            writer,
+            cursor::MoveTo(0, top_y),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            style::Print(get_padded_string("Key Bindings", screen.width as usize)),
+        )?;
+
+        let focus = context.config.focus_indicator;
+        for (index, action) in Action::ALL.into_iter().enumerate() {
+            let selected = index == self.selected_index;
+            let key = if selected && self.mode != Mode::Browsing {
+                "...".to_string()
+            } else {
+                context.config.keys.key(action).to_string()
+            };
+            queue!(
+                writer,
+                cursor::MoveTo(left_x, top_y + 2 + index as u16),
+                style::PrintStyledContent(focus.style(
+                    focus.prefix(
+                        &format!(
+                            "{:<label_width$}{:<key_width$}",
+                            action.label(),
+                            key,
+                            label_width = LABEL_WIDTH,
+                            key_width = KEY_WIDTH
+                        ),
+                        selected
+                    ),
+                    selected,
+                    &context.theme.ui,
+                )),
+            )?;
+        }
+
+        let hint = self.message.clone().unwrap_or_else(|| self.hint());
+        queue!(
+            writer,
+            cursor::MoveTo(0, top_y + Action::ALL.len() as u16 + 3),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            style::Print(get_padded_string(&hint, screen.width as usize)),
+            style::ResetColor,
+        )?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        self.message = None;
+        if self.mode != Mode::Browsing {
+            if *code == KeyCode::Esc {
+                self.mode = Mode::Browsing;
+            } else {
+                self.listen(*code, context);
+            }
+            return Transition::None;
+        }
+
+        match code {
+            KeyCode::Up => {
+                self.selected_index =
+                    (self.selected_index + Action::ALL.len() - 1) % Action::ALL.len();
+            }
+            KeyCode::Down => self.selected_index = (self.selected_index + 1) % Action::ALL.len(),
+            KeyCode::Enter => self.mode = Mode::Listening,
+            KeyCode::Char('d') => {
+                context.config.keys = KeyBindings::default();
+                self.save(context);
+            }
+            KeyCode::Esc => return Transition::Pop,
+            _ => {}
+        }
+        Transition::None
+    }
+}
@@ -0,0 +1,87 @@
+use crate::user_interface::get_padded_string;
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue, style, terminal};
+use std::io;
+
+/// A rectangle of cells on the terminal.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Rect {
+    /// The column of the left edge.
+    pub x: u16,
+    /// The row of the top edge.
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    /// Gets the whole terminal, whose size can change between frames.
+    ///
+    /// # Returns
+    ///
+    /// The `Rect` covering the terminal, or an `io::Error` if its size can't be read.
+    pub fn terminal() -> io::Result<Rect> {
+        let (width, height) = terminal::size()?;
+        Ok(Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        })
+    }
+
+    /// Gets the column of the right edge, which is inside the rectangle.
+    pub fn right(&self) -> u16 {
+        self.x + self.width.saturating_sub(1)
+    }
+
+    /// Gets the row of the bottom edge, which is inside the rectangle.
+    pub fn bottom(&self) -> u16 {
+        self.y + self.height.saturating_sub(1)
+    }
+}
+
+/// Centers content in a container, e.g. a menu box on the terminal. Screens use it instead of
+/// subtracting sizes themselves, since the terminal can shrink below any size at any time.
+///
+/// # Arguments
+///
+/// * `container` - Where the content goes.
+/// * `content` - The width and height of the content.
+///
+/// # Returns
+///
+/// Where to draw the content, rounding towards the top left, or `None` if it doesn't fit.
+pub fn center_rect(container: Rect, content: (u16, u16)) -> Option<Rect> {
+    let (width, height) = content;
+    Some(Rect {
+        x: container.x + container.width.checked_sub(width)? / 2,
+        y: container.y + container.height.checked_sub(height)? / 2,
+        width,
+        height,
+    })
+}
+
+/// Renders a message asking for a bigger terminal, for screens whose content doesn't fit.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `container` - Where the content should have gone.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+pub fn render_too_small<W: io::Write>(writer: &mut W, container: Rect) -> io::Result<()> {
+    let message: String = "Make the terminal bigger to see this"
+        .chars()
+        .take(container.width as usize)
+        .collect();
+    queue!(
+        writer,
+        Clear(ClearType::All),
+        cursor::MoveTo(container.x, container.y + container.height / 2),
+        style::Print(get_padded_string(&message, container.width as usize)),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,315 @@
+use crate::board::TileType;
+use crate::game::Game;
+use crate::merge::Variant;
+use crate::race::Race;
+use crate::rules::Rules;
+use crate::save;
+use crate::stats;
+use crate::user_interface::achievements::AchievementsScreen;
+use crate::user_interface::color_legend::ColorLegendScreen;
+use crate::user_interface::game_screen::{render_board, GameScreen};
+use crate::user_interface::high_scores::HighScoresScreen;
+use crate::user_interface::menu::{Menu, MenuOption};
+use crate::user_interface::new_game_menu::NewGameScreen;
+use crate::user_interface::race_screen::RaceScreen;
+use crate::user_interface::recovery::RecoveryScreen;
+use crate::user_interface::save_slots::{SaveSlotsScreen, SlotAction};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crate::user_interface::settings::SettingsScreen;
+use crate::user_interface::splash::daily_goal_line;
+use crate::user_interface::statistics::StatisticsScreen;
+use crossterm::event::{Event, KeyEvent, KeyEventKind};
+use crossterm::style::Attribute;
+use crossterm::{queue, style};
+use std::io;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum MainMenuOption {
+    Continue,
+    NewGame,
+    /// Today's challenge, and whether it has been played to the end.
+    Daily(bool),
+    Race,
+    HotSeat,
+    LoadGame,
+    HighScores,
+    Statistics,
+    Achievements,
+    TileColors,
+    Settings,
+    Recover,
+    #[cfg(feature = "net")]
+    CheckForUpdates,
+    Quit,
+}
+
+impl MenuOption for MainMenuOption {
+    fn label(&self) -> String {
+        match self {
+            MainMenuOption::Continue => "Continue",
+            MainMenuOption::Daily(false) => "Daily Challenge",
+            MainMenuOption::Daily(true) => "Daily Challenge (done)",
+            MainMenuOption::NewGame => "New Game",
+            MainMenuOption::Race => "Two-Player Race",
+            MainMenuOption::HotSeat => "Hot-Seat Race",
+            MainMenuOption::LoadGame => "Load",
+            MainMenuOption::HighScores => "High Scores",
+            MainMenuOption::Statistics => "Statistics",
+            MainMenuOption::Achievements => "Achievements",
+            MainMenuOption::TileColors => "Tile Colors",
+            MainMenuOption::Settings => "Settings",
+            MainMenuOption::Recover => "Recover",
+            #[cfg(feature = "net")]
+            MainMenuOption::CheckForUpdates => "Updates",
+            MainMenuOption::Quit => "Quit",
+        }
+        .to_string()
+    }
+}
+
+/// The first screen shown when the app starts. It sits at the bottom of the screen stack, so
+/// popping it exits the app.
+pub struct MainMenuScreen {
+    menu: Menu<MainMenuOption>,
+    message: Option<String>,
+    /// The board of the most recently saved game and the variant it's played in, shown behind
+    /// the menu when there's no game in progress.
+    last_saved_board: Option<(Vec<Vec<TileType>>, Variant)>,
+    #[cfg(feature = "net")]
+    update_check_requested: bool,
+}
+
+impl MainMenuScreen {
+    pub fn new() -> MainMenuScreen {
+        let mut menu = Menu::new(Vec::new());
+        menu.start_opening();
+        MainMenuScreen {
+            menu,
+            message: None,
+            last_saved_board: save::read_slots()
+                .into_iter()
+                .flatten()
+                .max_by_key(|saved| saved.saved_at)
+                .map(|saved| (saved.board, saved.rules.variant)),
+            #[cfg(feature = "net")]
+            update_check_requested: false,
+        }
+    }
+
+    /// Builds the list of options shown in the main menu, in display order.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - State shared between all screens, which decides whether optional entries
+    ///   are shown.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the available main menu options.
+    fn options(context: &AppContext) -> Vec<MainMenuOption> {
+        let mut options = Vec::new();
+        if context.game.is_some() {
+            options.push(MainMenuOption::Continue);
+        }
+        let today = stats::day_number(save::now());
+        let daily_done = context.statistics.daily_challenge_score(today).is_some();
+        options.extend([
+            MainMenuOption::NewGame,
+            MainMenuOption::Daily(daily_done),
+            MainMenuOption::Race,
+            MainMenuOption::HotSeat,
+            MainMenuOption::LoadGame,
+            MainMenuOption::HighScores,
+            MainMenuOption::Statistics,
+            MainMenuOption::Achievements,
+            MainMenuOption::TileColors,
+            MainMenuOption::Settings,
+        ]);
+        // recovery is for debugging, or for getting back the game that was lost in a crash
+        let recovered_from_crash = context
+            .snapshots
+            .lock()
+            .is_ok_and(|snapshots| snapshots.recovered_from_crash());
+        if cfg!(debug_assertions) || recovered_from_crash {
+            options.push(MainMenuOption::Recover);
+        }
+        #[cfg(feature = "net")]
+        if context.config.check_for_updates {
+            options.push(MainMenuOption::CheckForUpdates);
+        }
+        options.push(MainMenuOption::Quit);
+        options
+    }
+
+    /// Gets the line shown below the menu when there is no message: today's goal and the streak
+    /// of days played.
+    fn goal_line(context: &AppContext) -> String {
+        let goal = daily_goal_line(context);
+        match context.statistics.streak(stats::day_number(save::now())) {
+            0 => goal,
+            1 => format!("{}   Streak: 1 day", goal),
+            days => format!("{}   Streak: {} days", goal, days),
+        }
+    }
+}
+
+/// Renders a board dimmed behind the menu, so that the menu box drawn over it stands out while
+/// the game it belongs to stays recognizable.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `board` - The rows of the board to draw.
+/// * `variant` - Which sequence the tiles count through.
+/// * `context` - State shared between all screens, for the theme, number format, and board view.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+fn render_background_board<W: io::Write>(
+    writer: &mut W,
+    board: &[Vec<TileType>],
+    variant: Variant,
+    context: &AppContext,
+) -> io::Result<()> {
+    // the theme darkens the tiles, and the dim attribute darkens the numbers and grid lines
+    queue!(writer, style::SetAttribute(Attribute::Dim))?;
+    render_board(
+        writer,
+        &context.config.board_view.apply(board),
+        variant,
+        context.config.number_format,
+        &context.theme.dimmed(),
+    )?;
+    queue!(writer, style::SetAttribute(Attribute::Reset))
+}
+
+impl<W: io::Write> Screen<W> for MainMenuScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        if !context.animating() {
+            self.menu.skip_reveal();
+        }
+        let background = context
+            .game
+            .as_ref()
+            .map(|game| (game.read_board_state(), game.rules().variant))
+            .or(self
+                .last_saved_board
+                .as_ref()
+                .map(|(board, variant)| (board, *variant)));
+        if let Some((board, variant)) = background {
+            render_background_board(writer, board, variant, context)?;
+        }
+
+        self.menu.set_options(MainMenuScreen::options(context));
+        let has_saved_games = save::has_saved_games();
+        let has_high_scores = !context.statistics.high_scores.is_empty();
+        let has_lifetime_totals = context.statistics.lifetime.games > 0;
+        self.menu.set_disabled(|option| match option {
+            MainMenuOption::LoadGame => !has_saved_games,
+            MainMenuOption::HighScores => !has_high_scores,
+            MainMenuOption::Statistics => !has_lifetime_totals,
+            _ => false,
+        });
+        let goal = MainMenuScreen::goal_line(context);
+        self.menu.render(
+            writer,
+            Some(self.message.as_deref().unwrap_or(&goal)),
+            context.config.focus_indicator,
+            &context.theme.ui,
+        )?;
+
+        // the check blocks, so it runs after "Checking for updates..." has been drawn
+        #[cfg(feature = "net")]
+        if self.update_check_requested {
+            self.update_check_requested = false;
+            writer.flush()?;
+            self.message = Some(match crate::update_check::check_for_updates() {
+                Ok(Some(version)) => format!("Version {} is available!", version),
+                Ok(None) => "You're on the latest version.".to_string(),
+                Err(e) => format!("Update check failed: {}", e),
+            });
+            self.menu.render(
+                writer,
+                self.message.as_deref(),
+                context.config.focus_indicator,
+                &context.theme.ui,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        match self.menu.handle_key(*code) {
+            Some(MainMenuOption::Continue) => Transition::Push(Box::new(GameScreen::resume())),
+            Some(MainMenuOption::NewGame) => {
+                Transition::Push(Box::new(NewGameScreen::new(context)))
+            }
+            Some(MainMenuOption::Daily(_)) => {
+                // everyone plays the same rules, whatever their settings
+                let seed = stats::daily_seed(stats::day_number(save::now()));
+                let game = Game::start_seeded_game(seed, Rules::default());
+                Transition::Push(Box::new(GameScreen::new(game, context)))
+            }
+            Some(MainMenuOption::Race) => match Race::new(context.config.rules.clone()) {
+                Ok(race) => Transition::Push(Box::new(RaceScreen::new(race))),
+                Err(e) => {
+                    self.message = Some(format!("Couldn't start a race: {:?}", e));
+                    Transition::None
+                }
+            },
+            Some(MainMenuOption::HotSeat) => {
+                match Race::taking_turns(context.config.rules.clone()) {
+                    Ok(race) => Transition::Push(Box::new(RaceScreen::new(race))),
+                    Err(e) => {
+                        self.message = Some(format!("Couldn't start a race: {:?}", e));
+                        Transition::None
+                    }
+                }
+            }
+            Some(MainMenuOption::LoadGame) => {
+                Transition::Push(Box::new(SaveSlotsScreen::new(SlotAction::Load)))
+            }
+            Some(MainMenuOption::HighScores) => Transition::Push(Box::new(HighScoresScreen::new())),
+            Some(MainMenuOption::Statistics) => Transition::Push(Box::new(StatisticsScreen::new())),
+            Some(MainMenuOption::Achievements) => {
+                Transition::Push(Box::new(AchievementsScreen::new()))
+            }
+            Some(MainMenuOption::TileColors) => {
+                Transition::Push(Box::new(ColorLegendScreen::new()))
+            }
+            Some(MainMenuOption::Settings) => Transition::Push(Box::new(SettingsScreen::new())),
+            Some(MainMenuOption::Recover) => {
+                Transition::Push(Box::new(RecoveryScreen::new(context)))
+            }
+            #[cfg(feature = "net")]
+            Some(MainMenuOption::CheckForUpdates) => {
+                self.message = Some("Checking for updates...".to_string());
+                self.update_check_requested = true;
+                Transition::None
+            }
+            Some(MainMenuOption::Quit) => Transition::Quit,
+            None => Transition::None,
+        }
+    }
+
+    fn frame_interval(&self, _context: &AppContext) -> Option<Duration> {
+        self.menu.reveal_frame_interval()
+    }
+
+    fn tick(&mut self, _context: &mut AppContext) -> Transition<W> {
+        self.menu.advance_reveal();
+        Transition::None
+    }
+}
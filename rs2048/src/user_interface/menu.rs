@@ -0,0 +1,334 @@
+use crate::focus::FocusIndicator;
+use crate::theme::UiColors;
+use crate::user_interface::borders::Borders;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crossterm::event::KeyCode;
+use crossterm::style::Stylize;
+use crossterm::{cursor, queue, style};
+use std::time::Duration;
+use std::{cmp, io};
+
+/// How many frames a menu takes to open or close.
+const REVEAL_FRAMES: u16 = 4;
+/// How long each frame of a menu opening or closing lasts.
+const REVEAL_FRAME_INTERVAL: Duration = Duration::from_millis(30);
+
+/// How far a menu has opened. Menus that are opened or closed with an animation grow their box
+/// out of its middle, or shrink it back, over `REVEAL_FRAMES` frames. Their options are only drawn
+/// once the box is fully open.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Reveal {
+    /// Opening, with the box drawn at this many frames out of `REVEAL_FRAMES`.
+    Opening(u16),
+    Open,
+    /// Closing, with the box drawn at this many frames out of `REVEAL_FRAMES`.
+    Closing(u16),
+    Closed,
+}
+
+/// An option that can be shown in a `Menu`.
+pub trait MenuOption: Clone {
+    /// Gets the text shown for this option.
+    fn label(&self) -> String;
+}
+
+/// A vertical list of options drawn in a box in the middle of the terminal, with the selected
+/// option highlighted. Options can be disabled, which draws them greyed out and skips
+/// them when moving the selection.
+pub struct Menu<T: MenuOption> {
+    options: Vec<T>,
+    /// Whether each option is disabled, in the same order as `options`.
+    disabled: Vec<bool>,
+    selected_index: usize,
+    reveal: Reveal,
+}
+
+impl<T: MenuOption> Menu<T> {
+    /// Creates a new menu with the first option selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options to show, in display order.
+    pub fn new(options: Vec<T>) -> Menu<T> {
+        Menu {
+            disabled: vec![false; options.len()],
+            options,
+            selected_index: 0,
+            reveal: Reveal::Open,
+        }
+    }
+
+    /// Starts opening the menu from a small box in its middle. The screen showing the menu then
+    /// ticks it with `advance_reveal` every `reveal_frame_interval`.
+    pub fn start_opening(&mut self) {
+        self.reveal = Reveal::Opening(1);
+    }
+
+    /// Starts closing the menu by shrinking its box, like `start_opening`. The screen should close
+    /// once `is_closed`.
+    pub fn start_closing(&mut self) {
+        self.reveal = match self.reveal {
+            Reveal::Opening(frame) => Reveal::Closing(frame),
+            Reveal::Open => Reveal::Closing(REVEAL_FRAMES - 1),
+            reveal => reveal,
+        };
+    }
+
+    /// Moves the menu one frame further in opening or closing.
+    pub fn advance_reveal(&mut self) {
+        self.reveal = match self.reveal {
+            Reveal::Opening(frame) if frame + 1 < REVEAL_FRAMES => Reveal::Opening(frame + 1),
+            Reveal::Opening(_) => Reveal::Open,
+            Reveal::Closing(frame) if frame > 1 => Reveal::Closing(frame - 1),
+            Reveal::Closing(_) => Reveal::Closed,
+            reveal => reveal,
+        };
+    }
+
+    /// Finishes opening or closing the menu straight away, e.g. when animations are turned off.
+    pub fn skip_reveal(&mut self) {
+        self.reveal = match self.reveal {
+            Reveal::Opening(_) => Reveal::Open,
+            Reveal::Closing(_) => Reveal::Closed,
+            reveal => reveal,
+        };
+    }
+
+    /// Whether the menu has finished closing.
+    pub fn is_closed(&self) -> bool {
+        self.reveal == Reveal::Closed
+    }
+
+    /// Gets how often the screen showing the menu should tick it.
+    ///
+    /// # Returns
+    ///
+    /// `REVEAL_FRAME_INTERVAL` while the menu is opening or closing, or `None` otherwise.
+    pub fn reveal_frame_interval(&self) -> Option<Duration> {
+        matches!(self.reveal, Reveal::Opening(_) | Reveal::Closing(_))
+            .then_some(REVEAL_FRAME_INTERVAL)
+    }
+
+    /// Replaces the options shown in the menu, keeping the selection in range. All the new
+    /// options are enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options to show, in display order.
+    pub fn set_options(&mut self, options: Vec<T>) {
+        self.selected_index = cmp::min(self.selected_index, options.len().saturating_sub(1));
+        self.disabled = vec![false; options.len()];
+        self.options = options;
+    }
+
+    /// Selects an option, e.g. the one chosen last time. The selection stays put if the menu
+    /// doesn't show it.
+    ///
+    /// # Arguments
+    ///
+    /// * `option` - The option to select.
+    pub fn select(&mut self, option: &T)
+    where
+        T: PartialEq,
+    {
+        if let Some(index) = self.options.iter().position(|shown| shown == option) {
+            self.selected_index = index;
+        }
+    }
+
+    /// Decides which options can't be chosen right now, e.g. loading when nothing has been saved.
+    /// If the selected option becomes disabled, the selection moves down to the next enabled one.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_disabled` - Whether an option is disabled.
+    pub fn set_disabled(&mut self, is_disabled: impl Fn(&T) -> bool) {
+        self.disabled = self.options.iter().map(is_disabled).collect();
+        if self.disabled.get(self.selected_index) == Some(&true) {
+            self.move_selection(1);
+        }
+    }
+
+    /// Moves the selection by `step` places, wrapping around at either end and skipping disabled
+    /// options. The selection stays put if every other option is disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - How far to move, `1` for down or `len - 1` for up.
+    fn move_selection(&mut self, step: usize) {
+        let len = self.options.len();
+        let mut index = self.selected_index;
+        for _ in 0..len {
+            index = (index + step) % len;
+            if !self.disabled[index] {
+                self.selected_index = index;
+                return;
+            }
+        }
+    }
+
+    /// Moves the selection in response to the arrow keys, wrapping around at either end.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The key that was pressed.
+    ///
+    /// # Returns
+    ///
+    /// The selected option if the key was Enter and the option is enabled, or `None` otherwise.
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<T> {
+        if self.options.is_empty() {
+            return None;
+        }
+
+        match code {
+            KeyCode::Up => {
+                self.move_selection(self.options.len() - 1);
+                None
+            }
+            KeyCode::Down => {
+                self.move_selection(1);
+                None
+            }
+            KeyCode::Enter if !self.disabled[self.selected_index] => {
+                Some(self.options[self.selected_index].clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders the menu box in the middle of the terminal.
+    ///
+    /// The box is 16 columns wide unless an option needs more room, and grows in height with the
+    /// number of options. While the menu is opening or closing, only part of the empty box is
+    /// drawn.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+    /// * `message` - An optional notice drawn on the message line below the menu box.
+    /// * `focus` - How the selected option is marked.
+    /// * `colors` - The theme's interface colours.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `io::Result` that indicates success or failure.
+    pub fn render<W: io::Write>(
+        &self,
+        writer: &mut W,
+        message: Option<&str>,
+        focus: FocusIndicator,
+        colors: &UiColors,
+    ) -> io::Result<()> {
+        const MIN_MENU_BOX_WIDTH: u16 = 16;
+
+        let labels: Vec<String> = self
+            .options
+            .iter()
+            .enumerate()
+            .map(|(index, option)| focus.prefix(&option.label(), index == self.selected_index))
+            .collect();
+        let longest_label = labels
+            .iter()
+            .map(|label| label.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        let mut menu_box_width = cmp::max(MIN_MENU_BOX_WIDTH, longest_label + 4); // add room for the borders and a space on each side
+        let mut menu_box_height = labels.len() as u16 + 2; // add two for the top and bottom borders
+        let frame = match self.reveal {
+            Reveal::Opening(frame) | Reveal::Closing(frame) => frame,
+            Reveal::Open => REVEAL_FRAMES,
+            Reveal::Closed => return Ok(()),
+        };
+        if frame < REVEAL_FRAMES {
+            menu_box_width = cmp::max(2, menu_box_width * frame / REVEAL_FRAMES);
+            menu_box_height = cmp::max(2, menu_box_height * frame / REVEAL_FRAMES);
+        }
+
+        let screen = Rect::terminal()?;
+        let Some(menu_box) = layout::center_rect(screen, (menu_box_width, menu_box_height)) else {
+            return layout::render_too_small(writer, screen);
+        };
+        let menu_box_left_x = menu_box.x;
+        let menu_box_right_x = menu_box.right();
+        let menu_box_top_y = menu_box.y;
+        let menu_box_bottom_y = menu_box.bottom();
+
+        // draw box
+        let borders = Borders::current();
+        for y in menu_box_top_y..=menu_box_bottom_y {
+            for x in menu_box_left_x..=menu_box_right_x {
+                if (y == menu_box_top_y || y == menu_box_bottom_y)
+                    || (x == menu_box_left_x || x == menu_box_right_x)
+                {
+                    let printed_char: char = match (x, y) {
+                        (x, y) if (x == menu_box_left_x && y == menu_box_top_y) => borders.top_left,
+                        (x, y) if (x == menu_box_right_x && y == menu_box_top_y) => {
+                            borders.top_right
+                        }
+                        (x, y) if (x == menu_box_left_x && y == menu_box_bottom_y) => {
+                            borders.bottom_left
+                        }
+                        (x, y) if (x == menu_box_right_x && y == menu_box_bottom_y) => {
+                            borders.bottom_right
+                        }
+                        (x, _) if (x == menu_box_left_x || x == menu_box_right_x) => {
+                            borders.vertical
+                        }
+                        (_, y) if (y == menu_box_top_y || y == menu_box_bottom_y) => {
+                            borders.horizontal
+                        }
+                        _ => unreachable!(),
+                    };
+                    queue!(
+                        writer,
+                        cursor::MoveTo(x, y),
+                        style::PrintStyledContent(
+                            printed_char.with(colors.border).on(colors.text.background)
+                        )
+                    )?;
+                } else if frame < REVEAL_FRAMES {
+                    queue!(
+                        writer,
+                        cursor::MoveTo(x, y),
+                        style::PrintStyledContent(' '.on(colors.text.background))
+                    )?;
+                }
+            }
+        }
+        if frame < REVEAL_FRAMES {
+            return Ok(());
+        }
+
+        // draw text
+        for (index, label) in labels.iter().enumerate() {
+            let padded_label = get_padded_string(label, (menu_box_width - 2) as usize);
+            let styled_label = if self.disabled[index] {
+                padded_label.dark_grey()
+            } else {
+                focus.style(padded_label, index == self.selected_index, colors)
+            };
+            queue!(
+                writer,
+                cursor::MoveTo(menu_box_left_x + 1, menu_box_top_y + 1 + index as u16),
+                style::SetBackgroundColor(colors.text.background),
+                style::PrintStyledContent(styled_label),
+                style::ResetColor,
+            )?;
+        }
+
+        // draw message line
+        if let Some(message) = message {
+            queue!(
+                writer,
+                cursor::MoveTo(0, menu_box_bottom_y + 2),
+                style::SetForegroundColor(colors.text.foreground),
+                style::Print(get_padded_string(message, screen.width as usize)),
+                style::ResetColor,
+            )?;
+        }
+
+        Ok(())
+    }
+}
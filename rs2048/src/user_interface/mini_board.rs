@@ -0,0 +1,40 @@
+use crate::board::TileType;
+use crate::theme::Theme;
+use crossterm::style::{Color, Stylize};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// Renders a miniature of a board with one character per cell, coloured like the tiles on the
+/// full board, e.g. to tell save slots apart at a glance.
+///
+/// # Arguments
+///
+/// * `writer` - A mutable reference to an `io::Write` implementor for writing to the terminal.
+/// * `x` - The column of the top left corner.
+/// * `y` - The row of the top left corner.
+/// * `rows` - The tiles of the board, row by row.
+/// * `theme` - The colours of the tiles.
+///
+/// # Returns
+///
+/// Returns an `io::Result` that indicates success or failure.
+pub fn render_mini_board<W: io::Write>(
+    writer: &mut W,
+    x: u16,
+    y: u16,
+    rows: &[Vec<TileType>],
+    theme: &Theme,
+) -> io::Result<()> {
+    for (index, row) in rows.iter().enumerate() {
+        queue!(writer, cursor::MoveTo(x, y + index as u16))?;
+        for &tile in row {
+            let cell = if tile == 0 {
+                '·'.with(Color::DarkGrey).on_black()
+            } else {
+                ' '.on(theme.tile_style(tile).background)
+            };
+            queue!(writer, style::PrintStyledContent(cell))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::game::Game;
+use crate::merge::Variant;
+use crate::user_interface::game_screen::GameScreen;
+use crate::user_interface::menu::{Menu, MenuOption};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use std::io;
+
+impl MenuOption for Variant {
+    fn label(&self) -> String {
+        Variant::label(*self).to_string()
+    }
+}
+
+/// Picks the tiles a new game is played with, starting on the variant in the settings so that
+/// Enter starts the usual game. The rest of the rules come from the settings, with the winning
+/// tile and tile odds of the chosen variant.
+pub struct NewGameScreen {
+    menu: Menu<Variant>,
+}
+
+impl NewGameScreen {
+    pub fn new(context: &AppContext) -> NewGameScreen {
+        let mut menu = Menu::new(Variant::ALL.to_vec());
+        menu.select(&context.config.rules.variant);
+        NewGameScreen { menu }
+    }
+}
+
+impl<W: io::Write> Screen<W> for NewGameScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        self.menu.render(
+            writer,
+            Some("Choose the tiles  Esc: Back"),
+            context.config.focus_indicator,
+            &context.theme.ui,
+        )
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        if *code == KeyCode::Esc {
+            return Transition::Pop;
+        }
+        match self.menu.handle_key(*code) {
+            Some(variant) => {
+                let rules = if variant == context.config.rules.variant {
+                    context.config.rules.clone()
+                } else {
+                    context.config.rules.clone().with_variant(variant)
+                };
+                let game = Game::start_new_game_with(rules);
+                Transition::Replace(Box::new(GameScreen::new(game, context)))
+            }
+            None => Transition::None,
+        }
+    }
+}
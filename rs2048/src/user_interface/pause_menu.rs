@@ -0,0 +1,132 @@
+use crate::game::{now_millis, GameEvent};
+use crate::user_interface::menu::{Menu, MenuOption};
+use crate::user_interface::save_slots::{SaveSlotsScreen, SlotAction};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use std::io;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum PauseMenuOption {
+    Resume,
+    Restart,
+    SaveGame,
+    MainMenu,
+    EndGame,
+}
+
+impl MenuOption for PauseMenuOption {
+    fn label(&self) -> String {
+        match self {
+            PauseMenuOption::Resume => "Resume",
+            PauseMenuOption::Restart => "Restart",
+            PauseMenuOption::SaveGame => "Save Game",
+            PauseMenuOption::MainMenu => "Main Menu",
+            PauseMenuOption::EndGame => "End Game",
+        }
+        .to_string()
+    }
+}
+
+/// A menu shown over the board when Esc or P is pressed during a game, which is paused while it's
+/// open.
+///
+/// Restarting starts a new game by the same rules. Going back to the main menu keeps the game so
+/// it can be continued from there, while ending the game discards it. Saving opens the save slot picker on top. The menu opens out of the middle
+/// of the board and shrinks back into it when the game is resumed, unless animations are off.
+pub struct PauseScreen {
+    menu: Menu<PauseMenuOption>,
+}
+
+impl PauseScreen {
+    pub fn new() -> PauseScreen {
+        let mut menu = Menu::new(vec![
+            PauseMenuOption::Resume,
+            PauseMenuOption::Restart,
+            PauseMenuOption::SaveGame,
+            PauseMenuOption::MainMenu,
+            PauseMenuOption::EndGame,
+        ]);
+        menu.start_opening();
+        PauseScreen { menu }
+    }
+
+    /// Goes back to the game, after closing the menu if animations are on.
+    fn resume<W: io::Write>(&mut self, context: &mut AppContext) -> Transition<W> {
+        if let Some(game) = context.game.as_mut() {
+            game.resume(now_millis());
+        }
+        if !context.animating() {
+            return Transition::Pop;
+        }
+        self.menu.start_closing();
+        Transition::None
+    }
+}
+
+impl<W: io::Write> Screen<W> for PauseScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        if !context.animating() {
+            self.menu.skip_reveal();
+        }
+        self.menu.render(
+            writer,
+            None,
+            context.config.focus_indicator,
+            &context.theme.ui,
+        )
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        if matches!(code, KeyCode::Esc | KeyCode::Char('p')) {
+            return self.resume(context);
+        }
+
+        match self.menu.handle_key(*code) {
+            Some(PauseMenuOption::Resume) => self.resume(context),
+            Some(PauseMenuOption::Restart) => {
+                if let Some(game) = &context.game {
+                    // keep the paused game if a new one can't be started
+                    if let Ok(update) = game.clone().handle_event(GameEvent::NewGame) {
+                        context.game = Some(update.game);
+                    }
+                }
+                self.resume(context)
+            }
+            Some(PauseMenuOption::SaveGame) => {
+                Transition::Push(Box::new(SaveSlotsScreen::new(SlotAction::Save)))
+            }
+            Some(PauseMenuOption::MainMenu) => Transition::PopToRoot,
+            Some(PauseMenuOption::EndGame) => {
+                context.game = None;
+                Transition::PopToRoot
+            }
+            None => Transition::None,
+        }
+    }
+
+    fn frame_interval(&self, _context: &AppContext) -> Option<Duration> {
+        self.menu.reveal_frame_interval()
+    }
+
+    fn tick(&mut self, _context: &mut AppContext) -> Transition<W> {
+        self.menu.advance_reveal();
+        if self.menu.is_closed() {
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,194 @@
+use crate::board::Direction;
+use crate::game::{now_millis, Game, GameError, GameState};
+use crate::race::{Race, RaceResult};
+use crate::user_interface::game_screen::render_board_in;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::Rect;
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{cursor, queue, style};
+use std::io;
+use std::time::Duration;
+
+/// The names of the players, with the keys each one plays with.
+const PLAYER_NAMES: [&str; 2] = ["Player 1 (WASD)", "Player 2 (Arrows)"];
+
+/// Two players racing side by side on one keyboard, player one on WASD and player two on the
+/// arrow keys, with the same tiles. In a hot-seat race, whoever's turn it is can use either. The games live here rather than in the `AppContext`, so a
+/// race doesn't replace the game in progress.
+pub struct RaceScreen {
+    race: Race,
+    error: Option<GameError>,
+}
+
+impl RaceScreen {
+    /// Creates a screen for a new race.
+    ///
+    /// # Arguments
+    ///
+    /// * `race` - The race to play.
+    pub fn new(race: Race) -> RaceScreen {
+        RaceScreen { race, error: None }
+    }
+
+    /// Gets which player a key moves for, and which way.
+    fn route(code: KeyCode) -> Option<(usize, Direction)> {
+        match code {
+            KeyCode::Char('w' | 'W') => Some((0, Direction::Up)),
+            KeyCode::Char('a' | 'A') => Some((0, Direction::Left)),
+            KeyCode::Char('s' | 'S') => Some((0, Direction::Down)),
+            KeyCode::Char('d' | 'D') => Some((0, Direction::Right)),
+            KeyCode::Up => Some((1, Direction::Up)),
+            KeyCode::Left => Some((1, Direction::Left)),
+            KeyCode::Down => Some((1, Direction::Down)),
+            KeyCode::Right => Some((1, Direction::Right)),
+            _ => None,
+        }
+    }
+
+    /// Gets the line under a player's name, saying how their game stands.
+    fn standing(&self, player: usize, game: &Game, context: &AppContext) -> String {
+        let score = format!(
+            "Score: {}",
+            context.config.number_format.format(game.score() as u64)
+        );
+        match (self.race.result(), game.state()) {
+            (Some(RaceResult::MadeWinningTile(winner) | RaceResult::HigherScore(winner)), _)
+                if winner == player =>
+            {
+                format!("{}  Winner!", score)
+            }
+            (_, GameState::Lost(_)) => format!("{}  Game over", score),
+            _ => score,
+        }
+    }
+
+    /// Gets the text of the status bar, which announces the result once the race is over.
+    fn status(&self, context: &AppContext) -> String {
+        if let Some(error) = &self.error {
+            return format!(" The race couldn't go on: {:?}  Esc: Menu", error);
+        }
+        let rules = self.race.rules();
+        let winning_tile = rules
+            .variant
+            .display_value(rules.win_exponent, context.config.number_format);
+        match self.race.result() {
+            Some(RaceResult::MadeWinningTile(winner)) => format!(
+                " Player {} made {} first!  R: Rematch  Esc: Menu",
+                winner + 1,
+                winning_tile
+            ),
+            Some(RaceResult::HigherScore(winner)) => format!(
+                " Player {} wins with the higher score!  R: Rematch  Esc: Menu",
+                winner + 1
+            ),
+            Some(RaceResult::Tie) => " It's a tie!  R: Rematch  Esc: Menu".to_string(),
+            None => match self.race.turn() {
+                Some(turn) => format!(
+                    " Player {}'s turn  First to {} wins  Esc: Menu",
+                    turn + 1,
+                    winning_tile
+                ),
+                None => format!(" First to {} wins  Esc: Menu", winning_tile),
+            },
+        }
+    }
+}
+
+impl<W: io::Write> Screen<W> for RaceScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let screen = Rect::terminal()?;
+        let half_width = screen.width / 2;
+
+        for (player, game) in self.race.games().iter().enumerate() {
+            let x = half_width * player as u16;
+            let lines = [
+                PLAYER_NAMES[player].to_string(),
+                self.standing(player, game, context),
+            ];
+            for (index, line) in lines.iter().enumerate() {
+                queue!(
+                    writer,
+                    cursor::MoveTo(x, 1 + index as u16),
+                    style::SetForegroundColor(context.theme.ui.text.foreground),
+                    style::Print(get_padded_string(line, half_width as usize)),
+                    style::ResetColor,
+                )?;
+            }
+            let area = Rect {
+                x,
+                y: 3,
+                width: half_width,
+                height: screen.height.saturating_sub(4),
+            };
+            render_board_in(
+                writer,
+                area,
+                game.read_board_state(),
+                self.race.rules().variant,
+                context.config.number_format,
+                &context.theme,
+            )?;
+        }
+
+        queue!(
+            writer,
+            cursor::MoveTo(0, screen.bottom()),
+            style::SetBackgroundColor(context.theme.ui.status_bar.background),
+            style::SetForegroundColor(context.theme.ui.status_bar.foreground),
+            style::Print(format!(
+                "{:<width$}",
+                self.status(context),
+                width = screen.width as usize
+            )),
+            style::ResetColor,
+        )?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, _context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        if *code == KeyCode::Esc {
+            return Transition::Pop;
+        }
+        if self.race.result().is_some() && matches!(code, KeyCode::Char('r' | 'R')) {
+            return match self.race.rematch() {
+                Ok(race) => Transition::Replace(Box::new(RaceScreen::new(race))),
+                Err(e) => {
+                    self.error = Some(e);
+                    Transition::None
+                }
+            };
+        }
+        if let Some((player, direction)) = RaceScreen::route(*code) {
+            let player = self.race.turn().unwrap_or(player);
+            if let Err(e) = self.race.play(player, direction) {
+                self.error = Some(e);
+            }
+        }
+        Transition::None
+    }
+
+    fn frame_interval(&self, _context: &AppContext) -> Option<Duration> {
+        // redraw every second so that a game whose time runs out ends on time
+        if self.race.rules().time_limit.is_some() && self.race.result().is_none() {
+            Some(Duration::from_secs(1))
+        } else {
+            None
+        }
+    }
+
+    fn tick(&mut self, _context: &mut AppContext) -> Transition<W> {
+        self.race.check_time_limits(now_millis());
+        Transition::None
+    }
+}
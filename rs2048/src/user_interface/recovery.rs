@@ -0,0 +1,163 @@
+use crate::save;
+use crate::save::SavedGame;
+use crate::user_interface::game_screen::GameScreen;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::mini_board::render_mini_board;
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// How many columns each snapshot's line is padded to, so that shorter text fully replaces longer
+/// text when the list is redrawn.
+const LINE_WIDTH: usize = 48;
+
+/// Lists the recent snapshots of the games played, newest first, with a thumbnail of the selected
+/// one, and continues playing from the chosen snapshot. This gets a game back after an accidental
+/// restart, a bug in undo, or a crash.
+pub struct RecoveryScreen {
+    /// The snapshots as they were when the screen was opened, newest first.
+    snapshots: Vec<SavedGame>,
+    selected_index: usize,
+    message: Option<String>,
+}
+
+impl RecoveryScreen {
+    pub fn new(context: &AppContext) -> RecoveryScreen {
+        let snapshots = context
+            .snapshots
+            .lock()
+            .map(|snapshots| snapshots.newest_first().cloned().collect())
+            .unwrap_or_default();
+        RecoveryScreen {
+            snapshots,
+            selected_index: 0,
+            message: None,
+        }
+    }
+
+    /// Gets the line describing a snapshot in the list.
+    fn describe(snapshot: &SavedGame, context: &AppContext) -> String {
+        let number_format = context.config.number_format;
+        format!(
+            "Score {}, highest {}, {} moves, {}",
+            number_format.format(snapshot.score as u64),
+            snapshot
+                .rules
+                .variant
+                .display_value(snapshot.highest_tile(), number_format),
+            number_format.format(snapshot.score_history.len() as u64),
+            snapshot.age_description(save::now())
+        )
+    }
+}
+
+impl<W: io::Write> Screen<W> for RecoveryScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let screen = Rect::terminal()?;
+        let list_height = self.snapshots.len().max(1) as u16;
+        // leave room for the focus indicator and a mini board next to each line
+        let list_width = LINE_WIDTH as u16 + 8;
+        let Some(list) = layout::center_rect(screen, (list_width, list_height + 4)) else {
+            return layout::render_too_small(writer, screen);
+        };
+        let (left_x, top_y) = (list.x, list.y);
+
+        queue!(
+            writer,
+            cursor::MoveTo(0, top_y),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            style::Print(get_padded_string("Recover a Game", screen.width as usize)),
+        )?;
+
+        if self.snapshots.is_empty() {
+            queue!(
+                writer,
+                cursor::MoveTo(0, top_y + 2),
+                style::Print(get_padded_string(
+                    "No snapshots have been taken yet.",
+                    screen.width as usize
+                )),
+            )?;
+        }
+
+        let focus = context.config.focus_indicator;
+        for (index, snapshot) in self.snapshots.iter().enumerate() {
+            let selected = index == self.selected_index;
+            queue!(
+                writer,
+                cursor::MoveTo(left_x, top_y + 2 + index as u16),
+                style::PrintStyledContent(focus.style(
+                    focus.prefix(
+                        &format!(
+                            "{:<width$}",
+                            RecoveryScreen::describe(snapshot, context),
+                            width = LINE_WIDTH
+                        ),
+                        selected
+                    ),
+                    selected,
+                    &context.theme.ui,
+                )),
+            )?;
+        }
+
+        if let Some(snapshot) = self.snapshots.get(self.selected_index) {
+            render_mini_board(
+                writer,
+                left_x + LINE_WIDTH as u16 + 4,
+                top_y + 2,
+                &snapshot.board,
+                &context.theme,
+            )?;
+        }
+
+        let hint = "Enter: Continue from here  Esc: Back";
+        queue!(
+            writer,
+            cursor::MoveTo(0, top_y + list_height + 3),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            style::Print(get_padded_string(
+                self.message.as_deref().unwrap_or(hint),
+                screen.width as usize
+            )),
+            style::ResetColor,
+        )?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        let count = self.snapshots.len().max(1);
+        match code {
+            KeyCode::Up => self.selected_index = (self.selected_index + count - 1) % count,
+            KeyCode::Down => self.selected_index = (self.selected_index + 1) % count,
+            KeyCode::Enter => {
+                match self
+                    .snapshots
+                    .get(self.selected_index)
+                    .map(SavedGame::to_game)
+                {
+                    Some(Some(game)) => {
+                        return Transition::Replace(Box::new(GameScreen::new(Ok(game), context)))
+                    }
+                    Some(None) => self.message = Some("That snapshot is damaged.".to_string()),
+                    None => {}
+                }
+            }
+            KeyCode::Esc => return Transition::Pop,
+            _ => {}
+        }
+        Transition::None
+    }
+}
@@ -0,0 +1,293 @@
+use crate::game::{Game, Replay, ReplayPosition};
+use crate::rules::Rules;
+use crate::stats::ThinkingTimes;
+use crate::user_interface::game_screen::render_board;
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{cursor, queue, style, terminal};
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+/// How long each position is shown for when not replaying in real time.
+const FIXED_INTERVAL: Duration = Duration::from_millis(400);
+
+/// The shortest and longest a position is shown for when replaying in real time, so that quick
+/// moves can be followed and long pauses don't stall the replay.
+const REAL_TIME_LIMITS: (Duration, Duration) = (Duration::from_millis(60), Duration::from_secs(3));
+
+/// How many columns the analysis panel is padded to, so that shorter text fully replaces longer
+/// text when it is redrawn.
+const PANEL_WIDTH: usize = 60;
+
+/// How many of the positions before the shown one are kept for stepping back. Stepping back
+/// further replays the game from the start.
+const KEPT_POSITIONS: usize = 100;
+
+/// Plays a finished game back move by move, either at a steady pace or "as played" with the gaps
+/// the player took between moves, next to an analysis of how long they thought about each move.
+pub struct ReplayScreen {
+    /// Where the positions come from, one at a time, so that a long game's positions don't all
+    /// have to be in memory.
+    replay: Replay,
+    /// The position that is shown.
+    shown: ReplayPosition,
+    /// The position after the shown one, read ahead to time it, or `None` at the end.
+    upcoming: Option<ReplayPosition>,
+    /// Some of the positions before the shown one, oldest first, for stepping back.
+    earlier: VecDeque<ReplayPosition>,
+    /// Whether the moves stopped leading to the game's board, so the replay ends early.
+    broken: bool,
+    /// The rules the game was played by, shown so that practice games aren't mistaken for normal
+    /// ones.
+    rules: Rules,
+    thinking_times: Option<ThinkingTimes>,
+    /// How many moves into the game the shown position is.
+    index: usize,
+    playing: bool,
+    real_time: bool,
+}
+
+impl ReplayScreen {
+    /// Creates a screen that replays a game.
+    ///
+    /// # Arguments
+    ///
+    /// * `replay` - The game's moves played again, as given by `Game::replay`.
+    /// * `game` - The game being replayed, for its moves and rules.
+    ///
+    /// # Returns
+    ///
+    /// The `ReplayScreen`, or `None` if the replay has no starting position.
+    pub fn new(mut replay: Replay, game: &Game) -> Option<ReplayScreen> {
+        let shown = replay.next().flatten()?;
+        let thinking_times = game
+            .stream_moves()
+            .and_then(|moves| ThinkingTimes::from_moves(moves.map_while(|record| record)));
+        let mut screen = ReplayScreen {
+            replay,
+            shown,
+            upcoming: None,
+            earlier: VecDeque::new(),
+            broken: false,
+            rules: game.rules().clone(),
+            thinking_times,
+            index: 0,
+            playing: true,
+            real_time: false,
+        };
+        screen.upcoming = screen.read_ahead();
+        Some(screen)
+    }
+
+    /// Gets the next position from the replay.
+    ///
+    /// # Returns
+    ///
+    /// The position, or `None` at the end of the replay or if the moves stopped leading to the
+    /// game's board.
+    fn read_ahead(&mut self) -> Option<ReplayPosition> {
+        let position = self.replay.next()?;
+        self.broken |= position.is_none();
+        position
+    }
+
+    /// Shows the next position.
+    ///
+    /// # Returns
+    ///
+    /// Whether there was a next position.
+    fn advance(&mut self) -> bool {
+        let Some(next) = self.upcoming.take() else {
+            return false;
+        };
+        self.earlier
+            .push_back(std::mem::replace(&mut self.shown, next));
+        if self.earlier.len() > KEPT_POSITIONS {
+            self.earlier.pop_front();
+        }
+        self.index += 1;
+        self.upcoming = self.read_ahead();
+        true
+    }
+
+    /// Shows the position before the shown one, replaying the game from the start if it wasn't
+    /// kept.
+    fn go_back(&mut self) {
+        if self.index == 0 {
+            return;
+        }
+        if let Some(previous) = self.earlier.pop_back() {
+            self.upcoming = Some(std::mem::replace(&mut self.shown, previous));
+            self.index -= 1;
+        } else {
+            self.show_from_start(self.index - 1);
+        }
+    }
+
+    /// Replays the game from the start up to a position.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - How many moves into the game the position to show is.
+    fn show_from_start(&mut self, index: usize) {
+        if !self.replay.rewind() {
+            return;
+        }
+        let Some(start) = self.replay.next().flatten() else {
+            return;
+        };
+        self.shown = start;
+        self.earlier.clear();
+        self.broken = false;
+        self.index = 0;
+        self.upcoming = self.read_ahead();
+        while self.index < index && self.advance() {}
+    }
+
+    /// Gets how long to show the current position for before moving on to the next one.
+    fn current_interval(&self) -> Duration {
+        if !self.real_time {
+            return FIXED_INTERVAL;
+        }
+        let next = self
+            .upcoming
+            .as_ref()
+            .and_then(|position| position.last_move);
+        match (self.shown.last_move, next) {
+            (Some(shown), Some(next)) => {
+                Duration::from_millis(next.played_at.saturating_sub(shown.played_at))
+                    .clamp(REAL_TIME_LIMITS.0, REAL_TIME_LIMITS.1)
+            }
+            _ => FIXED_INTERVAL,
+        }
+    }
+
+    /// Gets the lines of text in the analysis panel.
+    fn panel_lines(&self, context: &AppContext) -> Vec<String> {
+        let number_format = context.config.number_format;
+        let seconds = |time: Duration| format!("{:.1}s", time.as_secs_f64());
+        vec![
+            format!(
+                "Replay: move {} of {}{}",
+                number_format.format(self.index as u64),
+                number_format.format(self.replay.move_count() as u64),
+                if self.broken && self.upcoming.is_none() {
+                    " (the rest doesn't replay)"
+                } else {
+                    ""
+                }
+            ),
+            format!(
+                "Timing: {}{}",
+                if self.real_time {
+                    "as played"
+                } else {
+                    "steady"
+                },
+                if self.playing { "" } else { " (paused)" }
+            ),
+            match self.thinking_times {
+                Some(times) => format!(
+                    "Thinking time over {} moves: median {}, 90% under {}, longest {}",
+                    number_format.format(times.moves as u64),
+                    seconds(times.median),
+                    seconds(times.ninetieth_percentile),
+                    seconds(times.longest)
+                ),
+                None => "Thinking time: not enough moves to tell".to_string(),
+            },
+            format!("New tiles: {}", self.rules.spawn.label()),
+        ]
+    }
+
+    /// Shows another position and pauses the replay.
+    fn step(&mut self, forwards: bool) {
+        self.playing = false;
+        if forwards {
+            self.advance();
+        } else {
+            self.go_back();
+        }
+    }
+}
+
+impl<W: io::Write> Screen<W> for ReplayScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        for (index, line) in self.panel_lines(context).iter().enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(1, 1 + index as u16),
+                style::SetForegroundColor(if index == 0 {
+                    context.theme.ui.highlight
+                } else {
+                    context.theme.ui.text.foreground
+                }),
+                style::Print(format!("{:<width$}", line, width = PANEL_WIDTH)),
+            )?;
+        }
+
+        let board = &self.shown.board;
+        render_board(
+            writer,
+            &context
+                .config
+                .board_view
+                .apply(board.get_data_for_display()),
+            self.rules.variant,
+            context.config.number_format,
+            &context.theme,
+        )?;
+
+        let size = terminal::size()?;
+        let controls = " Space: Play/Pause  Left/Right: Step  T: Timing  Esc: Back";
+        queue!(
+            writer,
+            cursor::MoveTo(0, size.1),
+            style::SetBackgroundColor(context.theme.ui.status_bar.background),
+            style::SetForegroundColor(context.theme.ui.status_bar.foreground),
+            style::Print(format!("{:<width$}", controls, width = size.0 as usize)),
+            style::ResetColor,
+        )?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, _context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        match code {
+            KeyCode::Char(' ') => {
+                // playing again from the end starts over
+                if !self.playing && self.upcoming.is_none() {
+                    self.show_from_start(0);
+                }
+                self.playing = !self.playing;
+            }
+            KeyCode::Left => self.step(false),
+            KeyCode::Right => self.step(true),
+            KeyCode::Char('t') => self.real_time = !self.real_time,
+            KeyCode::Esc | KeyCode::Char('q') => return Transition::Pop,
+            _ => {}
+        }
+        Transition::None
+    }
+
+    fn frame_interval(&self, _context: &AppContext) -> Option<Duration> {
+        self.playing.then(|| self.current_interval())
+    }
+
+    fn tick(&mut self, _context: &mut AppContext) -> Transition<W> {
+        if !self.advance() {
+            self.playing = false;
+        }
+        Transition::None
+    }
+}
@@ -0,0 +1,125 @@
+use crate::game::Game;
+use crate::save::RuleChange;
+use crate::user_interface::game_screen::GameScreen;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::save_slots::{SaveSlotsScreen, SlotAction};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// Sums up how a game saved by an older version of the game is played differently from new
+/// games, before it's loaded, so that the player isn't surprised by e.g. a board that starts
+/// with one tile.
+pub struct SaveChangesScreen {
+    /// The loaded game, which is taken when the player goes on to play it.
+    game: Option<Game>,
+    changes: Vec<RuleChange>,
+}
+
+impl SaveChangesScreen {
+    pub fn new(game: Game, changes: Vec<RuleChange>) -> SaveChangesScreen {
+        SaveChangesScreen {
+            game: Some(game),
+            changes,
+        }
+    }
+
+    /// Gets the lines of the table of changes, starting with its heading.
+    fn table(&self) -> Vec<String> {
+        let name_width = self
+            .changes
+            .iter()
+            .map(|change| change.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let saved_width = self
+            .changes
+            .iter()
+            .map(|change| change.saved.chars().count() + 1)
+            .chain(["This save".len()])
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let row = |name: &str, saved: &str, current: &str| {
+            format!("{:<name_width$}{:<saved_width$}{}", name, saved, current)
+        };
+        let mut lines = vec![row("", "This save", "New games")];
+        for change in &self.changes {
+            let marker = if change.migrated { "*" } else { "" };
+            lines.push(row(
+                &change.name,
+                &format!("{}{}", change.saved, marker),
+                &change.current,
+            ));
+        }
+        lines
+    }
+}
+
+impl<W: io::Write> Screen<W> for SaveChangesScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let mut lines = vec![
+            "This game was saved by an older version.".to_string(),
+            "It keeps its own rules:".to_string(),
+            String::new(),
+        ];
+        lines.extend(self.table());
+        if self.changes.iter().any(|change| change.migrated) {
+            lines.push(String::new());
+            lines.push("* not stored in the save, so it was filled in".to_string());
+        }
+        lines.push(String::new());
+        lines.push("Enter: Play  Esc: Back".to_string());
+
+        let screen = Rect::terminal()?;
+        let width = lines.iter().map(|line| line.chars().count()).max();
+        let Some(area) =
+            layout::center_rect(screen, (width.unwrap_or(0) as u16, lines.len() as u16))
+        else {
+            return layout::render_too_small(writer, screen);
+        };
+
+        queue!(
+            writer,
+            Clear(ClearType::All),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+        )?;
+        for (index, line) in lines.iter().enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(area.x, area.y + index as u16),
+                style::Print(line),
+            )?;
+        }
+        queue!(writer, style::ResetColor)?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        match (code, self.game.take()) {
+            (KeyCode::Enter, Some(game)) => {
+                Transition::Replace(Box::new(GameScreen::new(Ok(game), context)))
+            }
+            (KeyCode::Esc, _) => {
+                Transition::Replace(Box::new(SaveSlotsScreen::new(SlotAction::Load)))
+            }
+            (_, game) => {
+                self.game = game;
+                Transition::None
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::save;
+use crate::save::SavedGame;
+use crate::user_interface::game_screen::GameScreen;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::mini_board::render_mini_board;
+use crate::user_interface::save_changes::SaveChangesScreen;
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// How many rows each slot takes up, including the gap below it.
+const SLOT_HEIGHT: u16 = 5;
+
+/// How many columns the text next to each thumbnail is padded to, so that shorter text fully
+/// replaces longer text when the slots are redrawn.
+const DETAILS_WIDTH: usize = 28;
+
+/// Whether the slot picker saves the current game or loads a saved one.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SlotAction {
+    Save,
+    Load,
+}
+
+/// Lists the save slots with a thumbnail of each saved board next to its score, age and file size,
+/// and saves to or loads from the chosen one.
+pub struct SaveSlotsScreen {
+    action: SlotAction,
+    slots: Vec<Option<SavedGame>>,
+    /// The size of each slot's save file, which holds the whole game for replaying it.
+    file_sizes: Vec<Option<u64>>,
+    selected_index: usize,
+    message: Option<String>,
+}
+
+impl SaveSlotsScreen {
+    pub fn new(action: SlotAction) -> SaveSlotsScreen {
+        SaveSlotsScreen {
+            action,
+            slots: save::read_slots(),
+            file_sizes: save::slot_file_sizes(),
+            selected_index: 0,
+            message: None,
+        }
+    }
+
+    /// Saves to or loads from the selected slot.
+    fn choose_slot<W: io::Write>(&mut self, context: &mut AppContext) -> Transition<W> {
+        let slot = self.selected_index;
+        match self.action {
+            SlotAction::Save => {
+                let Some(game) = &context.game else {
+                    return Transition::Pop;
+                };
+                self.message = Some(match save::save_to_slot(slot, game) {
+                    Ok(_) => format!("Saved to slot {}.", slot + 1),
+                    Err(e) => format!("Couldn't save: {}", e),
+                });
+                self.slots = save::read_slots();
+                self.file_sizes = save::slot_file_sizes();
+                Transition::None
+            }
+            SlotAction::Load => match self.slots[slot]
+                .as_ref()
+                .map(|saved| (saved, saved.to_game()))
+            {
+                Some((saved, Some(game))) => {
+                    let changes = saved.rule_changes(&context.config.rules);
+                    if saved.is_outdated() && !changes.is_empty() {
+                        Transition::Replace(Box::new(SaveChangesScreen::new(game, changes)))
+                    } else {
+                        Transition::Replace(Box::new(GameScreen::new(Ok(game), context)))
+                    }
+                }
+                Some((_, None)) => {
+                    self.message = Some(format!("Slot {} is damaged.", slot + 1));
+                    Transition::None
+                }
+                None => {
+                    self.message = Some(format!("Slot {} is empty.", slot + 1));
+                    Transition::None
+                }
+            },
+        }
+    }
+
+    /// Gets the lines of text shown next to a slot's thumbnail.
+    fn details(
+        slot: usize,
+        saved: Option<&SavedGame>,
+        file_size: Option<u64>,
+        context: &AppContext,
+    ) -> Vec<String> {
+        let number_format = context.config.number_format;
+        let mut details = vec![match file_size {
+            Some(file_size) => format!("Slot {} ({})", slot + 1, save::describe_size(file_size)),
+            None => format!("Slot {}", slot + 1),
+        }];
+        match saved {
+            Some(saved) => details.extend([
+                format!("Score: {}", number_format.format(saved.score as u64)),
+                format!(
+                    "Highest tile: {}",
+                    saved
+                        .rules
+                        .variant
+                        .display_value(saved.highest_tile(), number_format)
+                ),
+                format!("Saved {}", saved.age_description(save::now())),
+            ]),
+            None => details.push("Empty".to_string()),
+        }
+        details
+    }
+}
+
+impl<W: io::Write> Screen<W> for SaveSlotsScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let (title, hint) = match self.action {
+            SlotAction::Save => ("Save Game", "Enter: Save  Esc: Back"),
+            SlotAction::Load => ("Load Game", "Enter: Load  Esc: Back"),
+        };
+
+        let screen = Rect::terminal()?;
+        let total_height = 2 + self.slots.len() as u16 * SLOT_HEIGHT + 1;
+        // leave room for the focus indicator and the mini board left of the details
+        let total_width = DETAILS_WIDTH as u16 + 10;
+        let Some(list) = layout::center_rect(screen, (total_width, total_height)) else {
+            return layout::render_too_small(writer, screen);
+        };
+        let (left_x, top_y) = (list.x, list.y);
+
+        queue!(
+            writer,
+            cursor::MoveTo(0, top_y),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            style::Print(get_padded_string(title, screen.width as usize)),
+        )?;
+
+        let focus = context.config.focus_indicator;
+        for (slot, saved) in self.slots.iter().enumerate() {
+            let y = top_y + 2 + slot as u16 * SLOT_HEIGHT;
+            let selected = slot == self.selected_index;
+
+            queue!(
+                writer,
+                cursor::MoveTo(left_x, y),
+                style::SetForegroundColor(context.theme.ui.highlight),
+                style::Print(focus.prefix("", selected)),
+            )?;
+
+            let mut details_x = left_x + focus.prefix_width() as u16;
+            if let Some(saved) = saved {
+                render_mini_board(writer, details_x, y, &saved.board, &context.theme)?;
+                let board_width = saved.board.first().map_or(0, Vec::len) as u16;
+                details_x += board_width + 2;
+            }
+
+            let file_size = self.file_sizes.get(slot).copied().flatten();
+            for (index, line) in SaveSlotsScreen::details(slot, saved.as_ref(), file_size, context)
+                .iter()
+                .enumerate()
+            {
+                queue!(
+                    writer,
+                    cursor::MoveTo(details_x, y + index as u16),
+                    style::PrintStyledContent(focus.style(
+                        format!("{:<width$}", line, width = DETAILS_WIDTH),
+                        selected && index == 0,
+                        &context.theme.ui,
+                    )),
+                )?;
+            }
+        }
+
+        queue!(
+            writer,
+            cursor::MoveTo(0, top_y + total_height),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            style::Print(get_padded_string(
+                self.message.as_deref().unwrap_or(hint),
+                screen.width as usize
+            )),
+            style::ResetColor,
+        )?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        match code {
+            KeyCode::Up => {
+                self.selected_index =
+                    (self.selected_index + self.slots.len() - 1) % self.slots.len();
+                self.message = None;
+                Transition::None
+            }
+            KeyCode::Down => {
+                self.selected_index = (self.selected_index + 1) % self.slots.len();
+                self.message = None;
+                Transition::None
+            }
+            KeyCode::Enter => self.choose_slot(context),
+            KeyCode::Esc => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::achievements::Achievements;
+use crate::config::{Config, Overrides};
+use crate::frame_pacing::FramePacer;
+use crate::game::Game;
+use crate::recovery::SnapshotRing;
+use crate::stats::Statistics;
+use crate::status_hook::StatusHook;
+use crate::theme::Theme;
+use crossterm::event::Event;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// State shared between all screens on the stack.
+pub struct AppContext {
+    pub config: Config,
+    /// The settings given on the command line, applied again whenever the config is reloaded.
+    pub overrides: Overrides,
+    /// The theme named in the config, or the default theme if there's no theme with that name.
+    pub theme: Theme,
+    /// The game in progress. This lives here rather than in the game screen so that it survives
+    /// returning to the main menu, where it can be continued.
+    pub game: Option<Game>,
+    /// The recent positions of the games played, for recovering from mistakes. It is shared with
+    /// the panic hook, which saves it when the app crashes.
+    pub snapshots: Arc<Mutex<SnapshotRing>>,
+    /// The player's record across every game, saved whenever it changes.
+    pub statistics: Statistics,
+    /// The achievements the player has unlocked, saved whenever one is unlocked.
+    pub achievements: Achievements,
+    /// When the app was started, in seconds since the Unix epoch, which tells the games of this
+    /// session apart in the statistics.
+    pub session_started_at: u64,
+    /// The hooks adding segments to the status bar, set up from the config when the app starts.
+    pub status_hooks: Vec<Box<dyn StatusHook>>,
+    /// How quickly the terminal takes frames, measured by the screen stack as it draws them.
+    pub pacing: FramePacer,
+}
+
+impl AppContext {
+    /// Gets whether screens should animate, which they don't with animations turned off in the
+    /// config or while the terminal can't keep up with the frames.
+    pub fn animating(&self) -> bool {
+        self.config.animations && !self.pacing.is_throttled()
+    }
+}
+
+/// What the screen stack should do after a screen has handled an event.
+pub enum Transition<W: io::Write> {
+    /// Stay on the current screen.
+    None,
+    /// Show a new screen on top of the current one.
+    Push(Box<dyn Screen<W>>),
+    /// Close the current screen, returning to the one below it.
+    Pop,
+    /// Close the current screen and show a new one in its place.
+    Replace(Box<dyn Screen<W>>),
+    /// Close every screen except the bottom one (the main menu).
+    PopToRoot,
+    /// Close every screen, exiting the app.
+    Quit,
+}
+
+/// A full-screen view or overlay that can be placed on the screen stack.
+pub trait Screen<W: io::Write> {
+    /// Draws the screen. The writer is flushed by the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The terminal to draw to.
+    /// * `context` - State shared between all screens.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `io::Result` that indicates success or failure.
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()>;
+
+    /// Reacts to a terminal event. Only the top screen of the stack receives events.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to handle. Resize events are handled by the stack itself.
+    /// * `context` - State shared between all screens.
+    ///
+    /// # Returns
+    ///
+    /// The `Transition` the stack should perform.
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W>;
+
+    /// How often the screen wants to be ticked and redrawn while it's on top of the stack, e.g.
+    /// for animations.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - State shared between all screens, e.g. to check whether animations are on.
+    ///
+    /// # Returns
+    ///
+    /// The time between frames, or `None` (the default) if the screen only changes in response to
+    /// events.
+    fn frame_interval(&self, _context: &AppContext) -> Option<Duration> {
+        None
+    }
+
+    /// Advances the screen by one frame. Called by the stack every `frame_interval` while no
+    /// events arrive, just before the screen is redrawn.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - State shared between all screens.
+    ///
+    /// # Returns
+    ///
+    /// The `Transition` the stack should perform, like `handle_event`.
+    fn tick(&mut self, _context: &mut AppContext) -> Transition<W> {
+        Transition::None
+    }
+
+    /// Whether the screens below this one should be drawn first, e.g. for a menu shown on top of
+    /// the board.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
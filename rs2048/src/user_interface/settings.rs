@@ -0,0 +1,243 @@
+use crate::board::TileType;
+use crate::coach::CoachingLevel;
+use crate::focus::FocusIndicator;
+use crate::merge::{MergeRule, Variant};
+use crate::rules;
+use crate::rules::Rules;
+use crate::spawn::{SpawnRule, SpawnWeights};
+use crate::theme::Theme;
+use crate::transform::DisplayTransform;
+use crate::user_interface::key_bindings::KeyBindingsScreen;
+use crate::user_interface::menu::{Menu, MenuOption};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use std::io;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum SettingsOption {
+    Animations(bool),
+    StartupSummary(bool),
+    Theme(String),
+    FocusIndicator(FocusIndicator),
+    BoardRotation(DisplayTransform),
+    MirrorBoard(bool),
+    Coaching(CoachingLevel),
+    BoardSize(usize),
+    Variant(Variant),
+    WinningTile(TileType, Variant),
+    PracticeSpawns(SpawnRule),
+    SpawnWeights(SpawnWeights, Variant),
+    MoveLimit(Option<u32>),
+    TimeLimit(Option<u64>),
+    UndoLimit(Option<u32>),
+    WrapAround(bool),
+    Gravity(bool),
+    FairSpawns(bool),
+    Zen(bool),
+    Adaptive(bool),
+    KeyBindings,
+    Back,
+}
+
+impl MenuOption for SettingsOption {
+    fn label(&self) -> String {
+        match self {
+            SettingsOption::Animations(on) => {
+                format!("Animations: {}", if *on { "On" } else { "Off" })
+            }
+            SettingsOption::StartupSummary(on) => {
+                format!("Startup Summary: {}", if *on { "On" } else { "Off" })
+            }
+            SettingsOption::Theme(name) => format!("Theme: {}", name),
+            SettingsOption::FocusIndicator(focus) => format!("Selection: {}", focus.label()),
+            SettingsOption::BoardRotation(view) => {
+                format!("Board Rotation: {}", view.rotation_label())
+            }
+            SettingsOption::MirrorBoard(on) => {
+                format!("Mirror Board: {}", if *on { "On" } else { "Off" })
+            }
+            SettingsOption::Coaching(level) => format!("Coaching: {}", level.label()),
+            SettingsOption::BoardSize(size) => format!("Board Size: {}x{}", size, size),
+            SettingsOption::Variant(variant) => format!("Tiles: {}", variant.label()),
+            SettingsOption::WinningTile(exponent, variant) => {
+                format!("Winning Tile: {}", variant.value(*exponent))
+            }
+            SettingsOption::PracticeSpawns(rule) => format!("New Tiles: {}", rule.label()),
+            SettingsOption::SpawnWeights(weights, variant) => {
+                format!("Tile Odds: {}", weights.label(*variant))
+            }
+            SettingsOption::MoveLimit(limit) => match limit {
+                Some(limit) => format!("Move Limit: {}", limit),
+                None => "Move Limit: None".to_string(),
+            },
+            SettingsOption::TimeLimit(limit) => match limit {
+                Some(limit) => format!("Time Limit: {}", rules::format_duration(*limit)),
+                None => "Time Limit: None".to_string(),
+            },
+            SettingsOption::UndoLimit(limit) => match limit {
+                Some(limit) => format!("Undo Limit: {}", limit),
+                None => "Undo Limit: None".to_string(),
+            },
+            SettingsOption::WrapAround(on) => {
+                format!("Wrap Around: {}", if *on { "On" } else { "Off" })
+            }
+            SettingsOption::Gravity(on) => format!("Gravity: {}", if *on { "On" } else { "Off" }),
+            SettingsOption::FairSpawns(on) => {
+                format!("Fair New Tiles: {}", if *on { "On" } else { "Off" })
+            }
+            SettingsOption::Zen(on) => format!("Zen Mode: {}", if *on { "On" } else { "Off" }),
+            SettingsOption::Adaptive(on) => {
+                format!("Adaptive Difficulty: {}", if *on { "On" } else { "Off" })
+            }
+            SettingsOption::KeyBindings => "Key Bindings".to_string(),
+            SettingsOption::Back => "Back".to_string(),
+        }
+    }
+}
+
+/// Lets the player change their settings without editing the config file. Every change is saved
+/// to the config file straight away.
+pub struct SettingsScreen {
+    menu: Menu<SettingsOption>,
+    message: Option<String>,
+}
+
+impl SettingsScreen {
+    pub fn new() -> SettingsScreen {
+        let mut menu = Menu::new(Vec::new());
+        menu.start_opening();
+        SettingsScreen {
+            menu,
+            message: None,
+        }
+    }
+
+    /// Builds the list of options, which show the current value of each setting.
+    fn options(context: &AppContext) -> Vec<SettingsOption> {
+        let rules = &context.config.rules;
+        vec![
+            SettingsOption::Animations(context.config.animations),
+            SettingsOption::StartupSummary(context.config.startup_summary),
+            SettingsOption::Theme(context.theme.name.clone()),
+            SettingsOption::FocusIndicator(context.config.focus_indicator),
+            SettingsOption::BoardRotation(context.config.board_view),
+            SettingsOption::MirrorBoard(context.config.board_view.mirrored),
+            SettingsOption::Coaching(context.config.coaching),
+            SettingsOption::BoardSize(context.config.rules.board_size),
+            SettingsOption::Variant(rules.variant),
+            SettingsOption::WinningTile(rules.win_exponent, rules.variant),
+            SettingsOption::PracticeSpawns(rules.spawn.clone()),
+            SettingsOption::SpawnWeights(rules.spawn_weights.clone(), rules.variant),
+            SettingsOption::MoveLimit(context.config.rules.move_limit),
+            SettingsOption::TimeLimit(context.config.rules.time_limit),
+            SettingsOption::UndoLimit(context.config.rules.undo_limit),
+            SettingsOption::WrapAround(context.config.rules.wrap_around),
+            SettingsOption::Gravity(context.config.rules.gravity),
+            SettingsOption::FairSpawns(context.config.rules.fair_spawns),
+            SettingsOption::Zen(context.config.rules.zen),
+            SettingsOption::Adaptive(context.config.rules.adaptive),
+            SettingsOption::KeyBindings,
+            SettingsOption::Back,
+        ]
+    }
+}
+
+impl<W: io::Write> Screen<W> for SettingsScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        if !context.animating() {
+            self.menu.skip_reveal();
+        }
+        self.menu.set_options(SettingsScreen::options(context));
+        self.menu.render(
+            writer,
+            self.message.as_deref(),
+            context.config.focus_indicator,
+            &context.theme.ui,
+        )
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        if *code == KeyCode::Esc {
+            return Transition::Pop;
+        }
+
+        match self.menu.handle_key(*code) {
+            Some(SettingsOption::Animations(on)) => context.config.animations = !on,
+            Some(SettingsOption::StartupSummary(on)) => context.config.startup_summary = !on,
+            Some(SettingsOption::Theme(name)) => {
+                let themes = Theme::all();
+                let index = themes.iter().position(|theme| theme.name == name);
+                let next = index.map_or(0, |index| (index + 1) % themes.len());
+                context.theme = themes[next].clone();
+                context.config.theme = context.theme.name.clone();
+            }
+            Some(SettingsOption::FocusIndicator(focus)) => {
+                context.config.focus_indicator = focus.next()
+            }
+            Some(SettingsOption::BoardRotation(view)) => {
+                context.config.board_view = view.turned_clockwise()
+            }
+            Some(SettingsOption::MirrorBoard(on)) => context.config.board_view.mirrored = !on,
+            Some(SettingsOption::Coaching(level)) => context.config.coaching = level.next(),
+            Some(SettingsOption::PracticeSpawns(rule)) => context.config.rules.spawn = rule.next(),
+            Some(SettingsOption::SpawnWeights(weights, _)) => {
+                context.config.rules.spawn_weights = weights.next()
+            }
+            Some(SettingsOption::BoardSize(size)) => {
+                context.config.rules.board_size = Rules::next_board_size(size)
+            }
+            Some(SettingsOption::Variant(variant)) => {
+                let rules = &mut context.config.rules;
+                *rules = rules.clone().with_variant(variant.next());
+            }
+            Some(SettingsOption::WinningTile(exponent, variant)) => {
+                context.config.rules.win_exponent = Rules::next_win_exponent(exponent, variant)
+            }
+            Some(SettingsOption::MoveLimit(limit)) => {
+                context.config.rules.move_limit = Rules::next_move_limit(limit)
+            }
+            Some(SettingsOption::TimeLimit(limit)) => {
+                context.config.rules.time_limit = Rules::next_time_limit(limit)
+            }
+            Some(SettingsOption::UndoLimit(limit)) => {
+                context.config.rules.undo_limit = Rules::next_undo_limit(limit)
+            }
+            Some(SettingsOption::WrapAround(on)) => context.config.rules.wrap_around = !on,
+            Some(SettingsOption::Gravity(on)) => context.config.rules.gravity = !on,
+            Some(SettingsOption::FairSpawns(on)) => context.config.rules.fair_spawns = !on,
+            Some(SettingsOption::Zen(on)) => context.config.rules.zen = !on,
+            Some(SettingsOption::Adaptive(on)) => context.config.rules.adaptive = !on,
+            Some(SettingsOption::KeyBindings) => {
+                return Transition::Push(Box::new(KeyBindingsScreen::new()))
+            }
+            Some(SettingsOption::Back) => return Transition::Pop,
+            None => return Transition::None,
+        }
+
+        self.message = context
+            .config
+            .save()
+            .err()
+            .map(|e| format!("Couldn't save the settings: {}", e));
+        Transition::None
+    }
+
+    fn frame_interval(&self, _context: &AppContext) -> Option<Duration> {
+        self.menu.reveal_frame_interval()
+    }
+
+    fn tick(&mut self, _context: &mut AppContext) -> Transition<W> {
+        self.menu.advance_reveal();
+        Transition::None
+    }
+}
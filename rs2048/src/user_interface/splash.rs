@@ -0,0 +1,152 @@
+use crate::save;
+use crate::stats;
+use crate::stats::SessionSummary;
+use crate::tile;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::main_menu::MainMenuScreen;
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyEvent, KeyEventKind};
+use crossterm::{cursor, queue, style};
+use std::io;
+use std::time::Duration;
+
+/// How long the splash is shown for if no key is pressed.
+const SHOW_FOR: Duration = Duration::from_secs(3);
+
+/// Shown briefly when the app starts, summarizing the last session before the main menu takes
+/// its place. Any key skips it, and it can be turned off in the settings.
+pub struct SplashScreen {
+    last_session: SessionSummary,
+}
+
+impl SplashScreen {
+    /// Creates the splash screen if it should be shown, i.e. if it's turned on and a game has
+    /// been finished before.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - State shared between all screens, with the statistics as they were when the
+    ///   app started.
+    ///
+    /// # Returns
+    ///
+    /// The `SplashScreen`, or `None` if the app should start at the main menu.
+    pub fn new(context: &AppContext) -> Option<SplashScreen> {
+        if !context.config.startup_summary {
+            return None;
+        }
+        let last_session = context.statistics.last_session?;
+        Some(SplashScreen { last_session })
+    }
+
+    /// Gets the lines of the summary.
+    fn lines(&self, context: &AppContext) -> Vec<String> {
+        let number_format = context.config.number_format;
+        let session = self.last_session;
+        let today = stats::day_number(save::now());
+        let streak = context.statistics.streak(today);
+        vec![
+            "2048".to_string(),
+            String::new(),
+            format!(
+                "Last session: {}",
+                save::describe_age(session.last_played_at, save::now())
+            ),
+            format!(
+                "{} {} played{}, best score {}",
+                number_format.format(session.games_played as u64),
+                if session.games_played == 1 {
+                    "game"
+                } else {
+                    "games"
+                },
+                if session.assisted_games > 0 {
+                    format!(
+                        " ({} assisted)",
+                        number_format.format(session.assisted_games as u64)
+                    )
+                } else {
+                    String::new()
+                },
+                number_format.format(session.best_score as u64)
+            ),
+            format!(
+                "Highest tile ever: {}",
+                tile::display_value(context.statistics.highest_tile, number_format)
+            ),
+            match streak {
+                0 => "No streak going. Play today to start one!".to_string(),
+                1 => "Streak: 1 day".to_string(),
+                days => format!(
+                    "Streak: {} days in a row",
+                    number_format.format(days as u64)
+                ),
+            },
+            daily_goal_line(context),
+            String::new(),
+            "Press any key".to_string(),
+        ]
+    }
+}
+
+/// Describes today's goal with a mark showing whether it has been met, e.g. "[x] Reach 512 today".
+/// The main menu shows it too.
+///
+/// # Arguments
+///
+/// * `context` - State shared between all screens, for the statistics and number format.
+pub fn daily_goal_line(context: &AppContext) -> String {
+    let today = stats::day_number(save::now());
+    let (goal, met) = context.statistics.daily_goal(today);
+    format!(
+        "[{}] {}",
+        if met { 'x' } else { ' ' },
+        goal.describe(context.config.number_format)
+    )
+}
+
+impl<W: io::Write> Screen<W> for SplashScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let screen = Rect::terminal()?;
+        let lines = self.lines(context);
+        let longest_line = lines.iter().map(|line| line.chars().count()).max();
+        let content = (longest_line.unwrap_or(0) as u16, lines.len() as u16);
+        let Some(summary) = layout::center_rect(screen, content) else {
+            return layout::render_too_small(writer, screen);
+        };
+        let top_y = summary.y;
+        for (index, line) in lines.iter().enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(0, top_y + index as u16),
+                style::SetForegroundColor(if index == 0 {
+                    context.theme.ui.highlight
+                } else {
+                    context.theme.ui.text.foreground
+                }),
+                style::Print(get_padded_string(line, screen.width as usize)),
+                style::ResetColor,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, _context: &mut AppContext) -> Transition<W> {
+        match event {
+            Event::Key(KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            }) => Transition::Replace(Box::new(MainMenuScreen::new())),
+            _ => Transition::None,
+        }
+    }
+
+    fn frame_interval(&self, _context: &AppContext) -> Option<Duration> {
+        Some(SHOW_FOR)
+    }
+
+    fn tick(&mut self, _context: &mut AppContext) -> Transition<W> {
+        Transition::Replace(Box::new(MainMenuScreen::new()))
+    }
+}
@@ -0,0 +1,142 @@
+use crate::save;
+use crate::stats;
+use crate::tile;
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// How wide the longest bar of the best tile histogram is drawn.
+const HISTOGRAM_WIDTH: u32 = 30;
+
+/// Shows what every game played adds up to: how many were played and won, the average and best
+/// scores, and how far the games got, as a histogram of the biggest tile each ended with.
+pub struct StatisticsScreen;
+
+impl StatisticsScreen {
+    pub fn new() -> StatisticsScreen {
+        StatisticsScreen
+    }
+
+    /// Gets the lines of the statistics.
+    fn lines(context: &AppContext) -> Vec<String> {
+        let number_format = context.config.number_format;
+        let statistics = &context.statistics;
+        let lifetime = &statistics.lifetime;
+        let (Some(win_rate), Some(average_score)) = (lifetime.win_rate(), lifetime.average_score())
+        else {
+            return vec!["No games played to the end yet.".to_string()];
+        };
+
+        let mut lines = vec![
+            format!("Games Played: {}", lifetime.games),
+            format!("Won: {} ({}%)", lifetime.wins, win_rate),
+            format!("Average Score: {}", number_format.format(average_score)),
+            format!(
+                "Best Score: {}",
+                number_format.format(statistics.best_score as u64)
+            ),
+        ];
+        if statistics.best_wrap_around_score > 0 {
+            lines.push(format!(
+                "Best Wrap-Around Score: {}",
+                number_format.format(statistics.best_wrap_around_score as u64)
+            ));
+        }
+        let mut other_best_scores = statistics.other_best_scores.clone();
+        other_best_scores.sort();
+        for (kind, score) in other_best_scores
+            .into_iter()
+            .filter(|&(_, score)| score > 0)
+        {
+            lines.push(format!(
+                "{}: {}",
+                kind.best_score_label(),
+                number_format.format(score as u64)
+            ));
+        }
+        let streak = statistics.streak(stats::day_number(save::now()));
+        lines.push(format!("Days Played: {}", statistics.days_played.len()));
+        if streak > 0 {
+            lines.push(format!(
+                "Streak: {} day{}",
+                streak,
+                if streak == 1 { "" } else { "s" }
+            ));
+        }
+
+        let Some(&most) = lifetime.best_tiles.values().max() else {
+            return lines;
+        };
+        let labels: Vec<String> = lifetime
+            .best_tiles
+            .keys()
+            .map(|&tile| tile::display_value(tile, number_format))
+            .collect();
+        let label_width = labels.iter().map(|label| label.chars().count()).max();
+        lines.push(String::new());
+        lines.push("Best Tile".to_string());
+        // the biggest tiles are the most interesting, so they come first
+        for (label, &count) in labels.iter().zip(lifetime.best_tiles.values()).rev() {
+            // every tile reached gets a bar, however rare
+            let bar = (count * HISTOGRAM_WIDTH / most).max(1);
+            lines.push(format!(
+                "{:>width$}  {} {}",
+                label,
+                "#".repeat(bar as usize),
+                count,
+                width = label_width.unwrap_or(0)
+            ));
+        }
+        lines
+    }
+}
+
+impl<W: io::Write> Screen<W> for StatisticsScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let mut lines = StatisticsScreen::lines(context);
+        lines.push(String::new());
+        lines.push("Esc: Back".to_string());
+
+        let screen = Rect::terminal()?;
+        let width = lines.iter().map(|line| line.chars().count()).max();
+        let Some(area) =
+            layout::center_rect(screen, (width.unwrap_or(0) as u16, lines.len() as u16 + 2))
+        else {
+            return layout::render_too_small(writer, screen);
+        };
+
+        queue!(
+            writer,
+            Clear(ClearType::All),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+            cursor::MoveTo(0, area.y),
+            style::Print(get_padded_string("Statistics", screen.width as usize)),
+        )?;
+        // the histogram is left-aligned so that its bars line up
+        for (index, line) in lines.iter().enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(area.x, area.y + 2 + index as u16),
+                style::Print(line),
+            )?;
+        }
+        queue!(writer, style::ResetColor)?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, _context: &mut AppContext) -> Transition<W> {
+        match event {
+            Event::Key(KeyEvent {
+                code: KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'),
+                kind: KeyEventKind::Press,
+                ..
+            }) => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::game::GameEvent;
+use crate::user_interface::borders::Borders;
+use crate::user_interface::end_screen::{EndScreen, Ending};
+use crate::user_interface::get_padded_string;
+use crate::user_interface::layout::{self, Rect};
+use crate::user_interface::screen::{AppContext, Screen, Transition};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{cursor, queue, style};
+use std::io;
+
+/// The lines of text shown inside the box.
+const LINES: [&str; 6] = [
+    "Undo this move?",
+    "",
+    "Undoing marks the game as assisted,",
+    "so its score won't count as a best score.",
+    "",
+    "Y: Undo  N: Keep Playing",
+];
+
+/// Asks before the first undo of a game whose rules don't limit undoing, since it marks the game
+/// as assisted. Once it's assisted, undoing doesn't ask again.
+pub struct UndoConfirmScreen {
+    /// The end screen the undo was asked for from, which is shown again if the player changes
+    /// their mind.
+    ending: Option<Ending>,
+}
+
+impl UndoConfirmScreen {
+    pub fn new(ending: Option<Ending>) -> UndoConfirmScreen {
+        UndoConfirmScreen { ending }
+    }
+}
+
+impl<W: io::Write> Screen<W> for UndoConfirmScreen {
+    fn render(&mut self, writer: &mut W, context: &AppContext) -> io::Result<()> {
+        let longest_line = LINES.iter().map(|line| line.chars().count()).max();
+        let box_width = longest_line.unwrap_or(0) as u16 + 4; // the borders and a space on each side
+        let box_height = LINES.len() as u16 + 4; // the borders and a blank line inside each
+
+        let screen = Rect::terminal()?;
+        let Some(area) = layout::center_rect(screen, (box_width, box_height)) else {
+            return layout::render_too_small(writer, screen);
+        };
+
+        let inner_width = (box_width - 2) as usize;
+        let borders = Borders::current();
+        let blank_line = "";
+        let rows = std::iter::once(borders.top(inner_width))
+            .chain(
+                std::iter::once(&blank_line)
+                    .chain(LINES.iter())
+                    .chain(std::iter::once(&blank_line))
+                    .map(|line| {
+                        let line = get_padded_string(line, inner_width);
+                        format!("{}{}{}", borders.vertical, line, borders.vertical)
+                    }),
+            )
+            .chain(std::iter::once(borders.bottom(inner_width)));
+        queue!(
+            writer,
+            style::SetBackgroundColor(context.theme.ui.text.background),
+            style::SetForegroundColor(context.theme.ui.text.foreground),
+        )?;
+        for (index, row) in rows.enumerate() {
+            queue!(
+                writer,
+                cursor::MoveTo(area.x, area.y + index as u16),
+                style::Print(row),
+            )?;
+        }
+        queue!(writer, style::ResetColor)?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event, context: &mut AppContext) -> Transition<W> {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        else {
+            return Transition::None;
+        };
+
+        match code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                if let Some(game) = context.game.take() {
+                    context.game = game
+                        .handle_event(GameEvent::Undo)
+                        .ok()
+                        .map(|update| update.game);
+                }
+                Transition::Pop
+            }
+            KeyCode::Char('n') | KeyCode::Esc => match self.ending {
+                Some(ending) => Transition::Replace(Box::new(EndScreen::new(ending))),
+                None => Transition::Pop,
+            },
+            _ => Transition::None,
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
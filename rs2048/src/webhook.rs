@@ -0,0 +1,228 @@
+use crate::game::Game;
+use crate::merge::MergeRule;
+use crate::rules::Rules;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait before each retry of a submission that failed in a way that might not happen
+/// again, e.g. the server being down. It's given up on after the last one.
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(2),
+    Duration::from_secs(10),
+    Duration::from_secs(60),
+];
+
+#[derive(Debug)]
+pub enum WebhookError {
+    /// The server turned the submission down, so trying again wouldn't help.
+    Rejected(u16),
+    /// The submission failed every attempt.
+    RequestFailed(String),
+}
+
+impl Display for WebhookError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookError::Rejected(status) => write!(f, "rejected with status {}", status),
+            WebhookError::RequestFailed(reason) => write!(f, "request failed ({})", reason),
+        }
+    }
+}
+
+/// A finished game as it's posted to the score webhook, e.g. for a leaderboard among friends.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreSubmission {
+    pub score: u32,
+    /// The seed of the game, so that the leaderboard can replay or compare games.
+    pub seed: u64,
+    /// The value of the highest tile reached, e.g. `2048`, or `2584` in the Fibonacci variant.
+    pub highest_tile: u64,
+    pub moves: usize,
+    /// The rules the game was played by, as they're written in the config file.
+    pub mode: Rules,
+    /// Whether the player had help from undoing, hints or auto-play, so that the leaderboard can
+    /// rank or leave out assisted games.
+    pub assisted: bool,
+    /// The version of the game that played it.
+    pub version: &'static str,
+}
+
+impl ScoreSubmission {
+    /// Describes a finished game.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game that just ended.
+    ///
+    /// # Returns
+    ///
+    /// The new `ScoreSubmission`.
+    pub fn new(game: &Game) -> ScoreSubmission {
+        ScoreSubmission {
+            score: game.score(),
+            seed: game.seed(),
+            highest_tile: game.rules().variant.value(game.highest_tile()),
+            moves: game.view().moves,
+            mode: game.rules().clone(),
+            assisted: game.is_assisted(),
+            version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+}
+
+/// Posts a finished game to the webhook on a thread of its own, so a slow or unreachable server
+/// never holds up the game. Failures are retried with increasing delays and then dropped, since
+/// there's nobody to tell about them.
+///
+/// # Arguments
+///
+/// * `url` - The webhook the player configured.
+/// * `submission` - The game to post.
+///
+/// # Returns
+///
+/// The thread, which finishes with the outcome of the last attempt.
+pub fn submit_in_background(
+    url: String,
+    submission: ScoreSubmission,
+) -> thread::JoinHandle<Result<(), WebhookError>> {
+    thread::spawn(move || submit(&url, &submission, &RETRY_DELAYS))
+}
+
+/// Posts a finished game to the webhook, blocking until it's accepted or every attempt failed.
+///
+/// # Arguments
+///
+/// * `url` - The webhook to post to.
+/// * `submission` - The game to post.
+/// * `retry_delays` - How long to wait before each retry.
+///
+/// # Returns
+///
+/// `Ok(())` once the server accepted the game, or a `WebhookError` if it never did.
+fn submit(
+    url: &str,
+    submission: &ScoreSubmission,
+    retry_delays: &[Duration],
+) -> Result<(), WebhookError> {
+    let mut delays = retry_delays.iter();
+    loop {
+        let error = match ureq::post(url)
+            .timeout(Duration::from_secs(10))
+            .set("User-Agent", concat!("rs2048/", env!("CARGO_PKG_VERSION")))
+            .send_json(submission)
+        {
+            Ok(_) => return Ok(()),
+            // a server that's overloaded or broken may recover, but one that refuses the game
+            // will refuse it again
+            Err(ureq::Error::Status(status, _)) if status != 429 && status < 500 => {
+                return Err(WebhookError::Rejected(status))
+            }
+            Err(e) => WebhookError::RequestFailed(e.to_string()),
+        };
+        match delays.next() {
+            Some(&delay) => thread::sleep(delay),
+            None => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Serves one HTTP request per status, in order, on a local port.
+    ///
+    /// # Returns
+    ///
+    /// The URL to post to, and the server thread, which finishes with the bodies it received.
+    fn serve(statuses: Vec<u16>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/scores", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            statuses
+                .into_iter()
+                .map(|status| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = line.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                length = value.trim().parse().unwrap();
+                            }
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    write!(
+                        stream,
+                        "HTTP/1.1 {} Whatever\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        status
+                    )
+                    .unwrap();
+                    String::from_utf8(body).unwrap()
+                })
+                .collect()
+        });
+        (url, server)
+    }
+
+    fn submission() -> ScoreSubmission {
+        let board = Board::from_notation("2048,4../..../..../....").unwrap();
+        let game = Game::restore(
+            board,
+            20000,
+            7,
+            Rules::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        ScoreSubmission::new(&game)
+    }
+
+    #[test]
+    fn failed_submissions_are_retried() {
+        let (url, server) = serve(vec![503, 200]);
+        submit(&url, &submission(), &[Duration::ZERO]).unwrap();
+
+        let bodies = server.join().unwrap();
+        assert_eq!(2, bodies.len());
+        let body: serde_json::Value = serde_json::from_str(&bodies[1]).unwrap();
+        assert_eq!(20000, body["score"]);
+        assert_eq!(7, body["seed"]);
+        assert_eq!(2048, body["highest_tile"]);
+        assert_eq!(4, body["mode"]["board_size"]);
+        assert_eq!(false, body["assisted"]);
+        assert_eq!(env!("CARGO_PKG_VERSION"), body["version"]);
+    }
+
+    #[test]
+    fn rejected_submissions_are_not_retried() {
+        let (url, server) = serve(vec![400]);
+        let result = submit(&url, &submission(), &[Duration::ZERO]);
+
+        assert!(matches!(result, Err(WebhookError::Rejected(400))));
+        assert_eq!(1, server.join().unwrap().len());
+    }
+
+    #[test]
+    fn submissions_give_up_eventually() {
+        let (url, server) = serve(vec![500, 500]);
+        let result = submit(&url, &submission(), &[Duration::ZERO]);
+
+        assert!(matches!(result, Err(WebhookError::RequestFailed(_))));
+        assert_eq!(2, server.join().unwrap().len());
+    }
+}